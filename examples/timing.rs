@@ -0,0 +1,23 @@
+use flexi_logger::{FileSpec, Logger, WriteMode};
+
+// Shows how the overhead of logging can be observed in-process.
+fn main() {
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory("log_files/timing"))
+        .write_mode(WriteMode::BufferAndFlush)
+        .sample_timings(10)
+        .start()
+        .unwrap();
+
+    for i in 0..10_000 {
+        log::info!("This is log line number {}", i);
+    }
+
+    if let Some(metrics) = logger.timing_metrics() {
+        println!(
+            "{} records measured: p50 = {:?}, p90 = {:?}, p99 = {:?}, max = {:?}",
+            metrics.sampled_records, metrics.p50, metrics.p90, metrics.p99, metrics.max
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::util::{eprint_err, ERRCODE};
+use crate::{FlexiLoggerError, LogSpecification, LoggerHandle};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::time::Duration;
+
+// A client that does not send its request within this time is disconnected.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+// Longer requests are rejected, without being read completely.
+const MAX_REQUEST_LEN: usize = 4096;
+
+/// The endpoint through which the log specification of the running program can be queried
+/// and changed.
+///
+/// Is used in [`Logger::admin_socket`](crate::Logger::admin_socket).
+///
+/// Each connection to the endpoint carries a single request, which is a single line:
+///
+/// - an empty line queries the active log specification,
+/// - any other line is parsed as log specification (see [`LogSpecification::parse`])
+///   and replaces the active log specification,
+///   like [`LoggerHandle::set_new_spec`] does.
+///
+/// The response is the active log specification, or, if the line cannot be parsed,
+/// an error message that starts with `error:`, while the previous log specification
+/// remains active.
+/// The response consists of a single line.
+/// Requests that are longer than 4 KiB are rejected with an error message.
+///
+/// ## Example
+///
+/// With
+///
+/// ```rust,no_run
+/// # use flexi_logger::{AdminSocket, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?
+///     .admin_socket(AdminSocket::Unix("/run/app-logctl.sock".into()))
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// an operator can switch on debug output for the running program with
+///
+/// ```text
+/// echo "debug, my_mod=trace" | nc -U /run/app-logctl.sock
+/// ```
+///
+/// ## Security
+///
+/// Everybody who can connect to the endpoint can change the log specification.
+/// Restrict the access to a Unix domain socket by the permissions of its directory;
+/// a TCP endpoint is only bound to the loopback interface,
+/// and thus accessible to all users of the local machine.
+#[derive(Clone, Debug)]
+pub enum AdminSocket {
+    /// A Unix domain socket at the given path.
+    ///
+    /// A socket that exists already at this path, e.g. from a previous run of the program,
+    /// is replaced.
+    ///
+    /// This variant is only available on unix.
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// A TCP socket on the given port of the loopback interface (`127.0.0.1`).
+    LocalhostTcp(u16),
+}
+
+// Binds the admin socket and serves its requests in a separate thread.
+pub(crate) fn serve(
+    admin_socket: &AdminSocket,
+    handle: LoggerHandle,
+) -> Result<(), FlexiLoggerError> {
+    match admin_socket {
+        #[cfg(unix)]
+        AdminSocket::Unix(path) => {
+            use std::os::unix::fs::FileTypeExt;
+            if let Ok(metadata) = std::fs::symlink_metadata(path) {
+                if metadata.file_type().is_socket() {
+                    std::fs::remove_file(path).map_err(FlexiLoggerError::AdminSocket)?;
+                }
+            }
+            let listener = std::os::unix::net::UnixListener::bind(path)
+                .map_err(FlexiLoggerError::AdminSocket)?;
+            spawn_server(handle, move || {
+                let (stream, _) = listener.accept()?;
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                Ok(stream)
+            })
+        }
+        AdminSocket::LocalhostTcp(port) => {
+            let listener =
+                TcpListener::bind(("127.0.0.1", *port)).map_err(FlexiLoggerError::AdminSocket)?;
+            spawn_server(handle, move || {
+                let (stream, _) = listener.accept()?;
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                Ok(stream)
+            })
+        }
+    }
+}
+
+fn spawn_server<S, F>(mut handle: LoggerHandle, mut accept: F) -> Result<(), FlexiLoggerError>
+where
+    S: Read + Write,
+    F: FnMut() -> std::io::Result<S> + Send + 'static,
+{
+    let builder = std::thread::Builder::new().name("flexi_logger-admin-socket".to_string());
+    #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+    let builder = builder.stack_size(128 * 1024);
+    builder
+        .spawn(move || loop {
+            accept()
+                .and_then(|stream| answer(&mut handle, stream))
+                .unwrap_or_else(|e| {
+                    eprint_err(ERRCODE::AdminSocket, "serving the admin socket failed", &e);
+                });
+        })
+        .map_err(FlexiLoggerError::AdminSocket)?;
+    Ok(())
+}
+
+// Reads the request from the connection and answers it.
+fn answer<S: Read + Write>(handle: &mut LoggerHandle, stream: S) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request = Vec::new();
+    reader
+        .by_ref()
+        .take(MAX_REQUEST_LEN as u64)
+        .read_until(b'\n', &mut request)?;
+
+    let response = if request.len() == MAX_REQUEST_LEN && request.last() != Some(&b'\n') {
+        format!("error: request exceeds {} bytes", MAX_REQUEST_LEN)
+    } else {
+        match std::str::from_utf8(&request).map(str::trim) {
+            Err(e) => format!("error: {}", e),
+            Ok("") => handle.current_spec().to_string(),
+            Ok(request) => match LogSpecification::parse(request) {
+                Ok(spec) => {
+                    handle.set_new_spec(spec);
+                    handle.current_spec().to_string()
+                }
+                Err(e) => format!("error: {}", e),
+            },
+        }
+    };
+    let stream = reader.get_mut();
+    stream.write_all(response.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()
+}
@@ -127,6 +127,11 @@
 //!   }
 //!   ```
 //!
+//! - with [`WriteMode::AsyncLossy`](crate::WriteMode::AsyncLossy),
+//!   the channel is bounded, and log lines are discarded rather than waiting if it is full;
+//!   so logging never blocks, and the number of discarded log lines is reported in the log
+//!   output with each flush.
+//!
 //! **Note** that with all write modes
 //! except [`WriteMode::Direct`](crate::WriteMode::Direct) (which is the default)
 //! you should keep the [`LoggerHandle`](crate::LoggerHandle) alive
@@ -199,14 +204,17 @@
 //!  - [`default_format`](crate::default_format)
 //!  - [`detailed_format`](crate::detailed_format)
 //!  - [`opt_format`](crate::opt_format)
-//!  - [`with_thread`](crate::with_thread),
+//!  - [`with_thread`](crate::with_thread)
+//!  - [`json_format`](crate::json_format),
+//!  - [`cbor_format`](crate::cbor_format) (binary output, see its documentation),
 //!
 //! or one of their coloring pendants
 //!
 //!  - [`colored_default_format`](crate::colored_default_format)
 //!  - [`colored_detailed_format`](crate::colored_detailed_format)
 //!  - [`colored_opt_format`](crate::colored_opt_format)
-//!  - [`colored_with_thread`](crate::colored_with_thread),
+//!  - [`colored_with_thread`](crate::colored_with_thread)
+//!  - [`colored_json_format`](crate::colored_json_format),
 //!
 //! or your own method.
 //!
@@ -234,6 +242,9 @@
 //! # }
 //! ```
 //!
+//! The decision honors the environment variables `NO_COLOR`, `CLICOLOR_FORCE`, and `CLICOLOR`,
+//! and can be overridden with [`Logger::color_choice`](crate::Logger::color_choice).
+//!
 //! ### Defaults
 //!
 //! `flexi_logger` initializes by default equivalently to this:
@@ -317,10 +328,13 @@
 //!      when the current log file exceeds the specified limit
 //!    - with [`Criterion::AgeOrSize`](crate::Criterion::AgeOrSize) the rotation happens
 //!      when either of the two limits is reached
+//!    - with [`Criterion::AgeAtTime`](crate::Criterion::AgeAtTime) the rotation happens
+//!      when the clock passes the given time of day, independent of when the file was created
 //!
 //! - [`Naming`](crate::Naming)<br>The current file is then renamed
 //!   - with [`Naming::Timestamps`](crate::Naming::Timestamps) to something
-//!     like `foo_r2020-11-16_08-56-52.log`
+//!     like `foo_r2020-11-16T08:56:52+01.log`, using the given offset
+//!     (or the local offset, with [`Naming::local_timestamps`](crate::Naming::local_timestamps))
 //!   - with [`Naming::Numbers`](crate::Naming::Numbers) to something like `foo_r00000.log`
 //!
 //!   and a fresh `rCURRENT` file is created.
@@ -336,6 +350,9 @@
 //!   - with [`Cleanup::KeepLogAndCompressedFiles`](crate::Cleanup::KeepLogAndCompressedFiles)
 //!     you specify the number of log files that should be
 //!     retained as is, and an additional number that are being compressed
+//!   - with [`Cleanup::MoveToDirectory`](crate::Cleanup::MoveToDirectory) you specify
+//!     the number of log files that should be retained;
+//!     if there are more, the older ones are moved to an archive directory
 //!   - with [`Cleanup::Never`](crate::Cleanup::Never) no cleanup is done, all files are retained.
 //!
 //! ```rust
@@ -349,7 +366,7 @@
 //!     )
 //!     .rotate(                      // If the program runs long enough,
 //!         Criterion::Age(Age::Day), // - create a new file every day
-//!         Naming::Timestamps,       // - let the rotated files have a timestamp in their name
+//!         Naming::local_timestamps(), // - let the rotated files have a timestamp in their name
 //!         Cleanup::KeepLogFiles(7), // - keep at most 7 log files
 //!     )
 //!     .start()?;
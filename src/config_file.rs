@@ -0,0 +1,248 @@
+use crate::writers::{FileLogWriter, FileLogWriterBuilder};
+use crate::{
+    Age, Cleanup, Criterion, Duplicate, FileSpec, FlexiLoggerError, FormatFunction, LevelFilter,
+    LogSpecification, Logger, Naming, WriteMode,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// The structure of a config file, see Logger::try_from_config_file.
+#[derive(Debug, serde_derive::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LoggerConfig {
+    spec: Option<String>,
+    log_target: Option<String>,
+    duplicate_to_stderr: Option<String>,
+    duplicate_to_stdout: Option<String>,
+    format_for_files: Option<String>,
+    write_mode: Option<String>,
+    file: Option<FileSection>,
+    writers: Option<BTreeMap<String, WriterSection>>,
+}
+
+#[derive(Debug, serde_derive::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileSection {
+    directory: Option<PathBuf>,
+    basename: Option<String>,
+    discriminant: Option<String>,
+    suffix: Option<String>,
+    use_timestamp: Option<bool>,
+    append: Option<bool>,
+    create_symlink: Option<PathBuf>,
+    rotation: Option<RotationSection>,
+}
+
+#[derive(Debug, serde_derive::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RotationSection {
+    size: Option<u64>,
+    age: Option<String>,
+    naming: Option<String>,
+    keep_log_files: Option<usize>,
+    keep_compressed_files: Option<usize>,
+}
+
+#[derive(Debug, serde_derive::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WriterSection {
+    directory: Option<PathBuf>,
+    basename: Option<String>,
+    discriminant: Option<String>,
+    suffix: Option<String>,
+    use_timestamp: Option<bool>,
+    also_send: Option<String>,
+}
+
+impl LoggerConfig {
+    pub(crate) fn read(path: &Path) -> Result<Self, FlexiLoggerError> {
+        let s = std::fs::read_to_string(path).map_err(FlexiLoggerError::ConfigFileIo)?;
+        toml::from_str(&s).map_err(FlexiLoggerError::ConfigFileToml)
+    }
+
+    pub(crate) fn log_spec(&self) -> Result<LogSpecification, FlexiLoggerError> {
+        LogSpecification::parse(self.spec.as_deref().unwrap_or("info"))
+    }
+
+    pub(crate) fn into_logger(self) -> Result<Logger, FlexiLoggerError> {
+        let mut logger = Logger::with(self.log_spec()?)
+            .duplicate_to_stderr(duplicate(self.duplicate_to_stderr.as_deref())?)
+            .duplicate_to_stdout(duplicate(self.duplicate_to_stdout.as_deref())?)
+            .format_for_files(self.format()?)
+            .write_mode(self.write_mode()?);
+        let default_target = if self.file.is_some() {
+            "file"
+        } else {
+            "stderr"
+        };
+        logger = match self.log_target.as_deref().unwrap_or(default_target) {
+            "stderr" => logger.log_to_stderr(),
+            "stdout" => logger.log_to_stdout(),
+            "file" => logger.log_to_file(self.file_spec()),
+            "nothing" => logger.do_not_log(),
+            other => return Err(invalid("log_target", other)),
+        };
+        if let Some(ref file) = self.file {
+            logger = logger
+                .o_rotate(file.rotation.as_ref().map(rotation).transpose()?)
+                .o_append(file.append.unwrap_or(false))
+                .o_create_symlink(file.create_symlink.clone());
+        }
+        for (name, writer) in self.writers.iter().flatten() {
+            let file_spec = file_spec(
+                writer.directory.as_ref(),
+                writer.basename.as_deref(),
+                writer.discriminant.as_deref(),
+                writer.suffix.as_deref(),
+                writer.use_timestamp,
+            );
+            logger = logger.add_other_file_writer(name, file_spec)?;
+            if let Some(ref level) = writer.also_send {
+                let level_filter =
+                    LevelFilter::from_str(level).map_err(|_| invalid("also_send", level))?;
+                logger = logger.also_send_to(&[name], level_filter);
+            }
+        }
+        Ok(logger)
+    }
+
+    // The builder for the file log writer, if the config logs to a file.
+    // The files are appended, because the builder is used for changing the
+    // configuration of a running logger.
+    pub(crate) fn file_log_writer_builder(
+        &self,
+    ) -> Result<Option<FileLogWriterBuilder>, FlexiLoggerError> {
+        let format = self.format()?;
+        let write_mode = self.write_mode()?;
+        let file = match self.file {
+            Some(ref file) if self.log_target.as_deref().unwrap_or("file") == "file" => file,
+            _ => return Ok(None),
+        };
+        Ok(Some(
+            FileLogWriter::builder(self.file_spec())
+                .format(format)
+                .write_mode(write_mode)
+                .o_rotate(file.rotation.as_ref().map(rotation).transpose()?)
+                .append()
+                .o_create_symlink(file.create_symlink.clone()),
+        ))
+    }
+
+    fn file_spec(&self) -> FileSpec {
+        match self.file {
+            Some(ref file) => file_spec(
+                file.directory.as_ref(),
+                file.basename.as_deref(),
+                file.discriminant.as_deref(),
+                file.suffix.as_deref(),
+                file.use_timestamp,
+            ),
+            None => FileSpec::default(),
+        }
+    }
+
+    fn format(&self) -> Result<FormatFunction, FlexiLoggerError> {
+        let format: FormatFunction = match self.format_for_files.as_deref().unwrap_or("default") {
+            "default" => crate::default_format,
+            "detailed" => crate::detailed_format,
+            "opt" => crate::opt_format,
+            "with_thread" => crate::with_thread,
+            "json" => crate::json_format,
+            "logfmt" => crate::logfmt_format,
+            other => return Err(invalid("format_for_files", other)),
+        };
+        Ok(format)
+    }
+
+    fn write_mode(&self) -> Result<WriteMode, FlexiLoggerError> {
+        Ok(match self.write_mode.as_deref().unwrap_or("direct") {
+            "direct" => WriteMode::Direct,
+            "buffer_and_flush" => WriteMode::BufferAndFlush,
+            "buffer_dont_flush" => WriteMode::BufferDontFlush,
+            #[cfg(feature = "async")]
+            "async" => WriteMode::Async,
+            other => return Err(invalid("write_mode", other)),
+        })
+    }
+}
+
+fn file_spec(
+    directory: Option<&PathBuf>,
+    basename: Option<&str>,
+    discriminant: Option<&str>,
+    suffix: Option<&str>,
+    use_timestamp: Option<bool>,
+) -> FileSpec {
+    let mut file_spec = FileSpec::default()
+        .o_directory(directory)
+        .o_basename(basename)
+        .o_discriminant(discriminant);
+    if let Some(suffix) = suffix {
+        file_spec = file_spec.suffix(suffix);
+    }
+    if let Some(use_timestamp) = use_timestamp {
+        file_spec = file_spec.use_timestamp(use_timestamp);
+    }
+    file_spec
+}
+
+fn duplicate(o_value: Option<&str>) -> Result<Duplicate, FlexiLoggerError> {
+    Ok(match o_value.unwrap_or("none") {
+        "none" => Duplicate::None,
+        "error" => Duplicate::Error,
+        "warn" => Duplicate::Warn,
+        "info" => Duplicate::Info,
+        "debug" => Duplicate::Debug,
+        "trace" => Duplicate::Trace,
+        "all" => Duplicate::All,
+        other => return Err(invalid("duplicate_to_stderr/duplicate_to_stdout", other)),
+    })
+}
+
+fn rotation(rotation: &RotationSection) -> Result<(Criterion, Naming, Cleanup), FlexiLoggerError> {
+    let o_age = match rotation.age.as_deref() {
+        None => None,
+        Some("day") => Some(Age::Day),
+        Some("hour") => Some(Age::Hour),
+        Some("minute") => Some(Age::Minute),
+        Some("second") => Some(Age::Second),
+        Some(other) => return Err(invalid("age", other)),
+    };
+    let criterion = match (o_age, rotation.size) {
+        (Some(age), Some(size)) => Criterion::AgeOrSize(age, size),
+        (Some(age), None) => Criterion::Age(age),
+        (None, Some(size)) => Criterion::Size(size),
+        (None, None) => {
+            return Err(FlexiLoggerError::ConfigFileValue(
+                "rotation needs an age or a size".to_string(),
+            ))
+        }
+    };
+    let naming = match rotation.naming.as_deref().unwrap_or("numbers") {
+        "numbers" => Naming::Numbers,
+        "timestamps" => Naming::local_timestamps(),
+        other => return Err(invalid("naming", other)),
+    };
+    let cleanup = match (rotation.keep_log_files, rotation.keep_compressed_files) {
+        (None, None) => Cleanup::Never,
+        (Some(log_files), None) => Cleanup::KeepLogFiles(log_files),
+        #[cfg(feature = "compress")]
+        (None, Some(compressed_files)) => Cleanup::KeepCompressedFiles(compressed_files),
+        #[cfg(feature = "compress")]
+        (Some(log_files), Some(compressed_files)) => {
+            Cleanup::KeepLogAndCompressedFiles(log_files, compressed_files)
+        }
+        #[cfg(not(feature = "compress"))]
+        (_, Some(_)) => {
+            return Err(FlexiLoggerError::ConfigFileValue(
+                "keep_compressed_files needs feature compress".to_string(),
+            ))
+        }
+    };
+    Ok((criterion, naming, cleanup))
+}
+
+fn invalid(key: &str, value: &str) -> FlexiLoggerError {
+    FlexiLoggerError::ConfigFileValue(format!("{} = '{}'", key, value))
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The number of log records per level.
+///
+/// Is part of [`RecordCounters`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LevelCounters {
+    /// Number of records with level `Error`.
+    pub error: u64,
+    /// Number of records with level `Warn`.
+    pub warn: u64,
+    /// Number of records with level `Info`.
+    pub info: u64,
+    /// Number of records with level `Debug`.
+    pub debug: u64,
+    /// Number of records with level `Trace`.
+    pub trace: u64,
+}
+impl LevelCounters {
+    /// Returns the number of records with the given level.
+    #[must_use]
+    pub fn get(&self, level: log::Level) -> u64 {
+        match level {
+            log::Level::Error => self.error,
+            log::Level::Warn => self.warn,
+            log::Level::Info => self.info,
+            log::Level::Debug => self.debug,
+            log::Level::Trace => self.trace,
+        }
+    }
+
+    /// Returns the number of records of all levels.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.error + self.warn + self.info + self.debug + self.trace
+    }
+}
+
+/// A snapshot of the number of log records that were handed to the writers, per level.
+///
+/// Is provided by [`LoggerHandle::counters`](crate::LoggerHandle::counters).
+/// All values are accumulated since the creation of the logger;
+/// the difference of two snapshots that were taken some time apart gives the
+/// number of records in that interval, e.g. for alerting on the rate of errors.
+///
+/// Only records that pass the log specification are counted.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RecordCounters {
+    /// The records that were handed to the primary writer.
+    pub primary: LevelCounters,
+    /// The records that were handed to the additional writers,
+    /// by their target names (see [`Logger::add_writer`](crate::Logger::add_writer)).
+    pub writers: HashMap<String, LevelCounters>,
+}
+
+// The counters behind `RecordCounters`; they are shared between the logger and its handles.
+#[derive(Debug, Default)]
+pub(crate) struct RecordCounts {
+    primary: AtomicLevelCounts,
+    writers: HashMap<String, AtomicLevelCounts>,
+}
+impl RecordCounts {
+    pub(crate) fn new<'a, I: Iterator<Item = &'a String>>(writer_names: I) -> Self {
+        Self {
+            primary: AtomicLevelCounts::default(),
+            writers: writer_names
+                .map(|name| (name.clone(), AtomicLevelCounts::default()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn count_primary(&self, level: log::Level) {
+        self.primary.count(level);
+    }
+
+    pub(crate) fn count_writer(&self, writer_name: &str, level: log::Level) {
+        if let Some(counts) = self.writers.get(writer_name) {
+            counts.count(level);
+        }
+    }
+
+    pub(crate) fn primary_snapshot(&self) -> LevelCounters {
+        self.primary.snapshot()
+    }
+
+    pub(crate) fn snapshot(&self) -> RecordCounters {
+        RecordCounters {
+            primary: self.primary.snapshot(),
+            writers: self
+                .writers
+                .iter()
+                .map(|(name, counts)| (name.clone(), counts.snapshot()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct AtomicLevelCounts {
+    // indexed by `log::Level as usize - 1`
+    counts: [AtomicU64; 5],
+}
+impl AtomicLevelCounts {
+    fn count(&self, level: log::Level) {
+        self.counts[level as usize - 1].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> LevelCounters {
+        let get = |level: log::Level| self.counts[level as usize - 1].load(Ordering::Relaxed);
+        LevelCounters {
+            error: get(log::Level::Error),
+            warn: get(log::Level::Warn),
+            info: get(log::Level::Info),
+            debug: get(log::Level::Debug),
+            trace: get(log::Level::Trace),
+        }
+    }
+}
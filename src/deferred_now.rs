@@ -1,11 +1,24 @@
-use time::{Date, formatting::Formattable, OffsetDateTime, UtcOffset};
+use crate::{timestamp_config, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+use time::{formatting::Formattable, OffsetDateTime};
+#[cfg(not(target_arch = "wasm32"))]
+use time::{Date, UtcOffset};
+
+// The source of the sequence numbers of the records.
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
 
 /// Deferred timestamp creation.
 ///
 /// Is used to ensure that a log record that is sent to multiple outputs
-/// (in maybe different formats) always uses the same timestamp.
+/// (in maybe different formats) always uses the same timestamp,
+/// and the same sequence number.
 #[derive(Debug)]
-pub struct DeferredNow(Option<OffsetDateTime>);
+#[allow(clippy::struct_field_names)]
+pub struct DeferredNow {
+    o_now: Option<OffsetDateTime>,
+    o_sequence_number: Option<u64>,
+    o_output: Option<Output>,
+}
 
 impl Default for DeferredNow {
     fn default() -> Self {
@@ -17,15 +30,69 @@ impl<'a> DeferredNow {
     /// Constructs a new instance, but does not generate the timestamp.
     #[must_use]
     pub fn new() -> Self {
-        Self(None)
+        Self {
+            o_now: None,
+            o_sequence_number: None,
+            o_output: None,
+        }
     }
 
     /// Retrieve the timestamp.
     ///
     /// Requires mutability because the first caller will generate the timestamp.
+    ///
+    /// The offset of the timestamp is determined by the
+    /// [`TimestampConfig`](crate::TimestampConfig) of the logger.
     #[allow(clippy::missing_panics_doc)]
     pub fn now(&'a mut self) -> &'a OffsetDateTime {
-        self.0.get_or_insert_with(now_local_or_utc)
+        self.o_now.get_or_insert_with(timestamp_config::now)
+    }
+
+    /// Retrieve the sequence number of the record.
+    ///
+    /// Requires mutability because the first caller will draw the number.
+    ///
+    /// The numbers are drawn from a single counter of the process, so they are
+    /// strictly increasing in the order in which the records are formatted,
+    /// across all writers and all rotated files.
+    /// This allows reconstructing exactly how records of different outputs interleave.
+    /// Records that are not formatted with a sequence number don't consume a number.
+    pub fn sequence_number(&mut self) -> u64 {
+        *self
+            .o_sequence_number
+            .get_or_insert_with(|| SEQUENCE.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Retrieve the output for which the record is currently written.
+    ///
+    /// Allows format functions and writers to adapt to the output,
+    /// e.g. to omit colors for files or to add a channel tag for an additional writer.
+    ///
+    /// Is `None` if the record is not written by the logger, e.g. if you call a format
+    /// function directly.
+    #[must_use]
+    pub fn output(&self) -> Option<&Output> {
+        self.o_output.as_ref()
+    }
+
+    pub(crate) fn set_output(&mut self, output: Output) {
+        self.o_output = Some(output);
+    }
+
+    /// Convert into a String, in the format that the provided text formats
+    /// (like [`opt_format`](crate::opt_format)) use.
+    ///
+    /// The format is determined by the [`TimestampConfig`](crate::TimestampConfig)
+    /// of the logger.
+    pub fn format_timestamp(&'a mut self) -> String {
+        timestamp_config::format_text(self.now())
+    }
+
+    /// Convert into a String, in the format that the provided machine-readable formats
+    /// (like [`json_format`](crate::json_format)) use, which is RFC 3339 unless a custom format
+    /// is set in the [`TimestampConfig`](crate::TimestampConfig) of the logger.
+    pub fn format_rfc3339(&'a mut self) -> String {
+        timestamp_config::format_rfc3339(self.now())
     }
 
     /// Convert into a formatted String.
@@ -39,18 +106,90 @@ impl<'a> DeferredNow {
 }
 
 pub(crate) fn now_local_or_utc() -> OffsetDateTime {
+    #[cfg(test)]
+    if let Some(now) = test_clock::now() {
+        return now;
+    }
     OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc())
 }
 
+pub(crate) fn now_utc() -> OffsetDateTime {
+    #[cfg(test)]
+    if let Some(now) = test_clock::now() {
+        return now.to_offset(UtcOffset::UTC);
+    }
+    OffsetDateTime::now_utc()
+}
+
+// Allows unit tests to replace the system clock of the current thread,
+// so that time-dependent logic (like rotation by age) can be tested without sleeping.
+#[cfg(test)]
+pub(crate) mod test_clock {
+    use std::cell::Cell;
+    use time::OffsetDateTime;
+
+    thread_local! {
+        static FAKE_NOW: Cell<Option<OffsetDateTime>> = Cell::new(None);
+    }
+
+    // All timestamps that are produced in this thread will be the given one,
+    // until it is changed or reset.
+    pub(crate) fn set(now: OffsetDateTime) {
+        FAKE_NOW.with(|fake_now| fake_now.set(Some(now)));
+    }
+
+    pub(crate) fn reset() {
+        FAKE_NOW.with(|fake_now| fake_now.set(None));
+    }
+
+    pub(super) fn now() -> Option<OffsetDateTime> {
+        FAKE_NOW.with(Cell::get)
+    }
+}
+
 
 /// a number of: year * 10000 + month * 100 + day
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn offset_date_time_to_year_month_day_number(offset_date_time: Date) -> i32 {
     let (year, month, day) = (offset_date_time.year(), offset_date_time.month() as i32, offset_date_time.day() as i32);
     year * 10000 + month * 100 + day
 }
 
 /// a number of: year * 10000 + month * 100 + day
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn now_as_year_month_day_number(utc_offset: UtcOffset) -> i32 {
     let now = now_local_or_utc().to_offset(utc_offset).date();
     offset_date_time_to_year_month_day_number(now)
 }
+
+/// a number of: year * 1000000 + month * 10000 + day * 100 + hour
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn offset_date_time_to_year_month_day_hour_number(
+    offset_date_time: OffsetDateTime,
+) -> i64 {
+    i64::from(offset_date_time_to_year_month_day_number(
+        offset_date_time.date(),
+    )) * 100
+        + i64::from(offset_date_time.hour())
+}
+
+/// a number of: year * 1000000 + month * 10000 + day * 100 + hour
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now_as_year_month_day_hour_number(utc_offset: UtcOffset) -> i64 {
+    offset_date_time_to_year_month_day_hour_number(now_local_or_utc().to_offset(utc_offset))
+}
+
+/// a number of: year * 100000000 + month * 1000000 + day * 10000 + hour * 100 + minute
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn offset_date_time_to_year_month_day_hour_minute_number(
+    offset_date_time: OffsetDateTime,
+) -> i64 {
+    offset_date_time_to_year_month_day_hour_number(offset_date_time) * 100
+        + i64::from(offset_date_time.minute())
+}
+
+/// a number of: year * 100000000 + month * 1000000 + day * 10000 + hour * 100 + minute
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now_as_year_month_day_hour_minute_number(utc_offset: UtcOffset) -> i64 {
+    offset_date_time_to_year_month_day_hour_minute_number(now_local_or_utc().to_offset(utc_offset))
+}
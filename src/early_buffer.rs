@@ -0,0 +1,177 @@
+use crate::FlexiLoggerError;
+use log::{Level, Log, Metadata, Record};
+use std::sync::RwLock;
+
+// The global logger that is installed with Logger::buffer_until_started().
+//
+// Until the real logger is handed over, the records are kept in memory;
+// afterwards, everything is forwarded to the real logger.
+struct EarlyBuffer(RwLock<State>);
+
+enum State {
+    Inactive,
+    Buffering {
+        records: Vec<EarlyRecord>,
+        capacity: usize,
+        discarded: usize,
+    },
+    Forwarding(Box<dyn Log>),
+}
+
+// An owned copy of the parts of a record that are replayed.
+struct EarlyRecord {
+    level: Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    message: String,
+}
+impl EarlyRecord {
+    fn new(record: &Record) -> Self {
+        Self {
+            level: record.level(),
+            target: record.target().to_string(),
+            module_path: record.module_path().map(ToString::to_string),
+            file: record.file().map(ToString::to_string),
+            line: record.line(),
+            message: record.args().to_string(),
+        }
+    }
+
+    fn replay(&self, logger: &dyn Log) {
+        logger.log(
+            &Record::builder()
+                .level(self.level)
+                .target(&self.target)
+                .module_path(self.module_path.as_deref())
+                .file(self.file.as_deref())
+                .line(self.line)
+                .args(format_args!("{}", self.message))
+                .build(),
+        );
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref EARLY_BUFFER: EarlyBuffer = EarlyBuffer(RwLock::new(State::Inactive));
+}
+
+impl Log for EarlyBuffer {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match self.0.read() {
+            Ok(state) => match *state {
+                State::Inactive => false,
+                State::Buffering { .. } => true,
+                State::Forwarding(ref logger) => logger.enabled(metadata),
+            },
+            Err(_) => false,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if let Ok(state) = self.0.read() {
+            if let State::Forwarding(ref logger) = *state {
+                logger.log(record);
+                return;
+            }
+        }
+        // the state might have changed in between, so we need to check again
+        if let Ok(mut state) = self.0.write() {
+            match *state {
+                State::Inactive => {}
+                State::Buffering {
+                    ref mut records,
+                    capacity,
+                    ref mut discarded,
+                } => {
+                    if records.len() < capacity {
+                        records.push(EarlyRecord::new(record));
+                    } else {
+                        *discarded += 1;
+                    }
+                }
+                State::Forwarding(ref logger) => logger.log(record),
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(state) = self.0.read() {
+            if let State::Forwarding(ref logger) = *state {
+                logger.flush();
+            }
+        }
+    }
+}
+
+// See Logger::buffer_until_started.
+pub(crate) fn install(capacity: usize) -> Result<(), FlexiLoggerError> {
+    let mut state = EARLY_BUFFER
+        .0
+        .write()
+        .map_err(|_| FlexiLoggerError::Poison)?;
+    if let State::Inactive = *state {
+        // the buffer must be ready before it is installed
+        *state = State::Buffering {
+            records: Vec::new(),
+            capacity,
+            discarded: 0,
+        };
+        drop(state);
+        if log::set_logger(&*EARLY_BUFFER).is_ok() {
+            log::set_max_level(log::LevelFilter::Trace);
+            return Ok(());
+        }
+        if let Ok(mut state) = EARLY_BUFFER.0.write() {
+            *state = State::Inactive;
+        }
+    }
+    Err(FlexiLoggerError::AlreadyInitialized)
+}
+
+// Makes the early buffer forward to the given logger, and replays the buffered records;
+// `prepare` is called before the logger gets any record.
+//
+// Returns the logger back if the early buffer is not in use; `prepare` is then not called.
+pub(crate) fn hand_over<F: FnOnce()>(logger: Box<dyn Log>, prepare: F) -> Result<(), Box<dyn Log>> {
+    let (records, discarded) = match EARLY_BUFFER.0.write() {
+        Ok(mut state) => match *state {
+            State::Buffering {
+                ref mut records,
+                discarded,
+                ..
+            } => {
+                // while the write lock is held, no record can reach the logger
+                prepare();
+                let records = std::mem::take(records);
+                *state = State::Forwarding(logger);
+                (records, discarded)
+            }
+            _ => return Err(logger),
+        },
+        Err(_) => return Err(logger),
+    };
+
+    // the write lock must not be held here, in case the replay produces further records
+    if let Ok(state) = EARLY_BUFFER.0.read() {
+        if let State::Forwarding(ref logger) = *state {
+            for record in &records {
+                record.replay(logger.as_ref());
+            }
+            if discarded > 0 {
+                logger.log(
+                    &Record::builder()
+                        .level(Level::Warn)
+                        .target("flexi_logger")
+                        .args(format_args!(
+                            "{} log records were discarded because the early buffer was full",
+                            discarded
+                        ))
+                        .build(),
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -3,6 +3,9 @@
 //! The following error codes are used to indicate the reason of an error.
 //! More details on them can be found here.
 //!
+//! By default, the errors are printed to stderr;
+//! [`Logger::error_channel`](crate::Logger::error_channel) allows sending them elsewhere.
+//!
 //! ## `Write`
 //!
 //! Writing the log line to the output failed.
@@ -86,6 +89,19 @@
 //!
 //! Watching the log-specification-file failed.
 //!
+//! ## `AdminSocket`
+//!
+//! This error can only occur if you use `Logger::admin_socket`.
+//!
+//! Example:
+//!
+//! ```text
+//! [flexi_logger][ERRCODE::AdminSocket] serving the admin socket failed, caused by ...
+//! ```
+//!
+//! Accepting a connection to the admin socket, or reading the request from it,
+//! or writing the response to it failed. The admin socket continues serving further connections.
+//!
 //! ## `Symlink`
 //!
 //! This error can only occur on linux systems, and when you use `Logger::create_symlink`, and
@@ -1,4 +1,5 @@
 use crate::FlexiLoggerError;
+use std::cmp::max;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use time::{format_description, OffsetDateTime};
@@ -23,6 +24,11 @@ pub struct FileSpec {
     pub(crate) o_discriminant: Option<String>,
     timestamp_cfg: TimestampCfg,
     pub(crate) o_suffix: Option<String>,
+    pub(crate) daily_subdirectories: bool,
+    // set by FileLogWriterBuilder::rotated_files_directory
+    pub(crate) o_rotated_directory: Option<PathBuf>,
+    // set by FileLogWriterBuilder::number_separator etc
+    pub(crate) numbering: Numbering,
 }
 impl Default for FileSpec {
     /// Describes a file in the current folder,
@@ -36,6 +42,9 @@ impl Default for FileSpec {
             o_discriminant: None,
             timestamp_cfg: TimestampCfg::Default,
             o_suffix: Some(String::from("log")),
+            daily_subdirectories: false,
+            o_rotated_directory: None,
+            numbering: Numbering::default(),
         }
     }
 }
@@ -67,6 +76,9 @@ impl FileSpec {
                 o_discriminant: None,
                 o_suffix: p.extension().map(|s| s.to_string_lossy().to_string()),
                 timestamp_cfg: TimestampCfg::No,
+                daily_subdirectories: false,
+                o_rotated_directory: None,
+                numbering: Numbering::default(),
             })
         }
     }
@@ -155,6 +167,21 @@ impl FileSpec {
         self
     }
 
+    /// Makes rotated log files be placed into subdirectories of the log folder,
+    /// one per day, like `2024-05-12`, rather than into the log folder itself;
+    /// the current log file stays in the log folder.
+    ///
+    /// A rotated file is placed into the subdirectory of the day when it was created.
+    /// This keeps the number of files per folder small, which helps e.g. with slow
+    /// network file systems. Subdirectories that become empty by the cleanup are removed.
+    ///
+    /// Has no effect with [`Naming::NumbersRoundRobin`](crate::Naming::NumbersRoundRobin).
+    #[must_use]
+    pub fn daily_subdirectories(mut self, daily_subdirectories: bool) -> Self {
+        self.daily_subdirectories = daily_subdirectories;
+        self
+    }
+
     // If no decison was done yet, decide now whether to include a timestamp
     // into the names of the log files.
     pub(crate) fn if_default_use_timestamp(&mut self, use_timestamp: bool) {
@@ -167,12 +194,28 @@ impl FileSpec {
         }
     }
 
+    // Appends `.gz` to the suffix, for log files that are written compressed.
+    #[cfg(feature = "compress")]
+    pub(crate) fn add_gz_suffix(&mut self) {
+        self.o_suffix = Some(match self.o_suffix.take() {
+            Some(suffix) => format!("{}.gz", suffix),
+            None => "gz".to_string(),
+        });
+    }
+
     pub(crate) fn get_directory(&self) -> PathBuf {
         self.directory.clone()
     }
 
-    // <directory>/<basename>_<discr>_<timestamp><infix>.<suffix>
-    pub(crate) fn as_pathbuf(&self, o_infix: Option<&str>) -> PathBuf {
+    // The directory into which rotated files are moved.
+    pub(crate) fn get_rotated_directory(&self) -> PathBuf {
+        self.o_rotated_directory
+            .clone()
+            .unwrap_or_else(|| self.directory.clone())
+    }
+
+    // <basename>_<discr>_<timestamp>
+    fn file_stem(&self) -> String {
         let mut filename = self.basename.clone();
         filename.reserve(50);
 
@@ -183,6 +226,71 @@ impl FileSpec {
         if let Some(timestamp) = &self.timestamp_cfg.get_timestamp() {
             filename.push_str(timestamp);
         }
+        filename
+    }
+
+    // The position in the file name where the infix of a rotated file starts.
+    pub(crate) fn infix_start(&self, filename: &str) -> Option<usize> {
+        let file_stem = self.file_stem();
+        if filename.starts_with(&file_stem) {
+            Some(file_stem.len())
+        } else {
+            None
+        }
+    }
+
+    // The infix of the numbered file with the given index.
+    pub(crate) fn number_infix(&self, idx: u32) -> String {
+        format!(
+            "{}{:0>width$}",
+            self.numbering.separator,
+            idx,
+            width = self.numbering.width
+        )
+    }
+
+    // The index of a numbered log file, or None for other files that match the glob patterns,
+    // like files with timestamps or foreign files.
+    pub(crate) fn rotate_idx(&self, file: &Path) -> Option<u32> {
+        let filename = file.file_name()?.to_str()?;
+        let infix = &filename[self.infix_start(filename)?..];
+        if !infix.starts_with(&self.numbering.separator) {
+            return None;
+        }
+        // pinned files have an additional marker behind the index
+        let digits = infix[self.numbering.separator.len()..].split('.').next()?;
+        if digits.len() >= max(self.numbering.width, 1)
+            && digits.bytes().all(|b| b.is_ascii_digit())
+        {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    // The glob patterns for the infixes of rotated files;
+    // timestamped files always use the default separator.
+    pub(crate) fn rotated_infix_patterns(&self) -> Vec<String> {
+        let mut patterns = vec![format!("{}[0-9]*", DEFAULT_NUMBER_SEPARATOR)];
+        if self.numbering.separator != DEFAULT_NUMBER_SEPARATOR {
+            patterns.push(format!(
+                "{}[0-9]*",
+                glob::Pattern::escape(&self.numbering.separator)
+            ));
+        }
+        patterns
+    }
+
+    // <directory>/<basename>_<discr>_<timestamp>.manifest.json
+    pub(crate) fn as_manifest_pathbuf(&self) -> PathBuf {
+        self.directory
+            .join(format!("{}.manifest.json", self.file_stem()))
+    }
+
+    // <directory>/<basename>_<discr>_<timestamp><infix>.<suffix>
+    pub(crate) fn as_pathbuf(&self, o_infix: Option<&str>) -> PathBuf {
+        let mut filename = self.file_stem();
+
         if let Some(infix) = o_infix {
             filename.push_str(infix);
         };
@@ -196,18 +304,50 @@ impl FileSpec {
         p_path
     }
 
+    // <rotated directory>[/<yyyy-mm-dd>]/<basename>_<discr>_<timestamp><infix>.<suffix>,
+    // where the daily subdirectory is taken from the given creation date of the file
+    pub(crate) fn as_rotated_pathbuf(&self, infix: &str, created_at: OffsetDateTime) -> PathBuf {
+        let path = self.as_pathbuf(Some(infix));
+        let mut p_path = self.get_rotated_directory();
+        if self.daily_subdirectories {
+            p_path.push(format!(
+                "{:04}-{:02}-{:02}",
+                created_at.year(),
+                u8::from(created_at.month()),
+                created_at.day()
+            ));
+        }
+        p_path.push(path.file_name().unwrap(/*ok*/));
+        p_path
+    }
+
+    // The glob patterns for rotated files, in the directory, and in the daily subdirectories
+    pub(crate) fn as_rotated_glob_patterns(
+        &self,
+        o_infix: Option<&str>,
+        o_suffix: Option<&str>,
+    ) -> Vec<String> {
+        let pattern = self.as_glob_pattern(o_infix, o_suffix);
+        let file_name = Path::new(&pattern).file_name().unwrap(/*ok*/);
+        let rotated_directory = self.get_rotated_directory();
+        let mut paths = vec![rotated_directory.join(file_name)];
+        if self.daily_subdirectories {
+            paths.push(
+                rotated_directory
+                    .join("[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]")
+                    .join(file_name),
+            );
+        }
+        paths
+            .into_iter()
+            .map(|path| path.to_str().unwrap(/* can hardly fail*/).to_string())
+            .collect()
+    }
+
     // <directory>/<basename>_<discr>_<timestamp><infix>.<suffix>
     pub(crate) fn as_glob_pattern(&self, o_infix: Option<&str>, o_suffix: Option<&str>) -> String {
-        let mut filename = self.basename.clone();
-        filename.reserve(50);
+        let mut filename = self.file_stem();
 
-        if let Some(discriminant) = &self.o_discriminant {
-            filename.push('_');
-            filename.push_str(discriminant);
-        }
-        if let Some(timestamp) = &self.timestamp_cfg.get_timestamp() {
-            filename.push_str(timestamp);
-        }
         if let Some(infix) = o_infix {
             filename.push_str(infix);
         };
@@ -230,6 +370,25 @@ impl FileSpec {
     }
 }
 
+pub(crate) const DEFAULT_NUMBER_SEPARATOR: &str = "_r";
+
+// How the index of numbered log files is formatted.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Numbering {
+    pub(crate) separator: String,
+    pub(crate) width: usize,
+    pub(crate) start: u32,
+}
+impl Default for Numbering {
+    fn default() -> Self {
+        Self {
+            separator: DEFAULT_NUMBER_SEPARATOR.to_string(),
+            width: 5,
+            start: 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum TimestampCfg {
     Default,
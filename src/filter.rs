@@ -1,6 +1,18 @@
 //! This module contains two traits which allow adding a stateful filter
 //! using [`Logger::filter`](crate::Logger::filter).
 //!
+//! A filter sees each log line after the log specification was applied,
+//! and before the line is handed over to the output channel.
+//! For each line, the filter can decide to
+//!
+//! - pass it, by forwarding the record to the provided [`LogLineWriter`],
+//! - drop it, by not forwarding it,
+//! - or adapt it, by forwarding a modified record.
+//!
+//! Several filters can be stacked by calling [`Logger::filter`](crate::Logger::filter)
+//! repeatedly; they are evaluated in the order in which they were added,
+//! and each filter forwards to the next one.
+//!
 //! # Example
 //!
 //! ```rust
@@ -35,6 +47,7 @@
 //!     Ok(())
 //! }
 //! ```
+use crate::primary_writer::PrimaryWriter;
 use crate::DeferredNow;
 use log::Record;
 
@@ -67,3 +80,35 @@ pub trait LogLineWriter {
     /// If writing to the configured output channel fails.
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()>;
 }
+
+// Hands a log line over to the next filter of the chain, and finally to the primary writer.
+pub(crate) struct FilterChain<'a> {
+    filters: &'a [Box<dyn LogLineFilter + Send + Sync>],
+    primary_writer: &'a PrimaryWriter,
+}
+impl<'a> FilterChain<'a> {
+    pub(crate) fn new(
+        filters: &'a [Box<dyn LogLineFilter + Send + Sync>],
+        primary_writer: &'a PrimaryWriter,
+    ) -> Self {
+        Self {
+            filters,
+            primary_writer,
+        }
+    }
+}
+impl LogLineWriter for FilterChain<'_> {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        match self.filters.split_first() {
+            Some((filter, filters)) => filter.write(
+                now,
+                record,
+                &FilterChain {
+                    filters,
+                    primary_writer: self.primary_writer,
+                },
+            ),
+            None => self.primary_writer.write(now, record),
+        }
+    }
+}
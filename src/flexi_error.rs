@@ -17,6 +17,22 @@ pub enum FlexiLoggerError {
     #[error("Log file cannot be written because the specified path is a directory")]
     OutputBadFile,
 
+    /// The specified path is not a rotated log file of the file log writer.
+    #[error("The specified path is not a rotated log file of the file log writer")]
+    OutputNotRotated,
+
+    /// The log files of two file log writers in the same directory overlap,
+    /// such that the cleanup of one writer would delete or compress files of the other.
+    ///
+    /// Use different basenames or discriminants to separate the writers.
+    #[error("The cleanup of a file log writer would affect the files of another writer: {0}")]
+    OutputCleanupConflict(String),
+
+    /// The time of day that is given with
+    /// [`Criterion::AgeAtTime`](crate::Criterion::AgeAtTime) is not valid.
+    #[error("The time of day for the rotation is not valid: {0:?}")]
+    OutputRotationTimeOfDay((u8, u8, u8)),
+
     /// Spawning the cleanup thread failed.
     ///
     /// This error can safely be avoided with `Logger::cleanup_in_background_thread(false)`.
@@ -39,6 +55,11 @@ pub enum FlexiLoggerError {
     #[cfg(feature = "specfile_without_notification")]
     SpecfileToml(#[from] toml::de::Error),
 
+    /// Parsing the configured logspec yaml-file failed.
+    #[error("Parsing the configured logspec yaml-file failed")]
+    #[cfg(feature = "specfile_yaml")]
+    SpecfileYaml(#[from] serde_yaml::Error),
+
     /// Specfile cannot be accessed or created.
     #[error("Specfile cannot be accessed or created")]
     #[cfg(feature = "specfile_without_notification")]
@@ -49,6 +70,31 @@ pub enum FlexiLoggerError {
     #[cfg(feature = "specfile_without_notification")]
     SpecfileExtension(&'static str),
 
+    /// The config file cannot be read.
+    #[error("The config file cannot be read")]
+    #[cfg(feature = "config_file")]
+    ConfigFileIo(#[source] std::io::Error),
+
+    /// The config file is not valid toml, or has an unexpected structure.
+    #[error("Parsing the config file failed")]
+    #[cfg(feature = "config_file")]
+    ConfigFileToml(#[source] toml::de::Error),
+
+    /// The config file contains an invalid value.
+    #[error("The config file contains an invalid value: {0}")]
+    #[cfg(feature = "config_file")]
+    ConfigFileValue(String),
+
+    /// The admin socket cannot be set up.
+    #[error("The admin socket cannot be set up")]
+    #[cfg(feature = "admin_socket")]
+    AdminSocket(#[source] std::io::Error),
+
+    /// The database of the `SqliteWriter` cannot be set up.
+    #[error("The database of the SqliteWriter cannot be set up")]
+    #[cfg(feature = "sqlite")]
+    Sqlite(#[source] rusqlite::Error),
+
     /// Invalid level filter.
     #[error("Invalid level filter")]
     LevelFilter(String),
@@ -64,6 +110,30 @@ pub enum FlexiLoggerError {
     #[error("Logger initialization failed")]
     Log(#[from] log::SetLoggerError),
 
+    /// Logger initialization failed because some logger was already initialized.
+    ///
+    /// If that logger was started by `flexi_logger`, its [`LoggerHandle`](crate::LoggerHandle)
+    /// can be retrieved with [`handle`](crate::handle).
+    #[error("Logger initialization failed because some logger was already initialized")]
+    AlreadyInitialized,
+
+    /// Some writers did not become ready in time.
+    ///
+    /// The names of these writers are contained, see
+    /// [`Logger::start_with_readiness`](crate::Logger::start_with_readiness).
+    #[error("Some writers did not become ready in time: {0:?}")]
+    NotReady(Vec<String>),
+
+    /// [`Logger::route`](crate::Logger::route) or
+    /// [`Logger::also_send_to`](crate::Logger::also_send_to) refers to a writer
+    /// that was not registered with [`Logger::add_writer`](crate::Logger::add_writer).
+    #[error("Unknown writer: {0}")]
+    UnknownWriter(String),
+
+    /// The URL of the [`WebhookWriter`](crate::writers::WebhookWriter) is not valid.
+    #[error("The webhook URL is not valid: {0}")]
+    WebhookUrl(String),
+
     /// Some synchronization object is poisoned.
     #[error("Some synchronization object is poisoned")]
     Poison,
@@ -72,6 +142,11 @@ pub enum FlexiLoggerError {
     #[error("Palette parsing failed")]
     Palette(#[from] std::num::ParseIntError),
 
+    /// Parsing the format description for timestamps failed,
+    /// see [`TimestampConfig::custom_format`](crate::TimestampConfig::custom_format).
+    #[error("Invalid format description for timestamps")]
+    TimestampFormat(#[source] time::error::InvalidFormatDescription),
+
     /// Logger is shut down.
     #[cfg(feature = "async")]
     #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
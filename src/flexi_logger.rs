@@ -1,13 +1,22 @@
-use crate::filter::LogLineFilter;
+use crate::counters::RecordCounts;
+use crate::filter::{FilterChain, LogLineFilter, LogLineWriter};
 use crate::primary_writer::PrimaryWriter;
+use crate::rate_limit::{RateLimiter, Suppressed};
+use crate::sampling::Sampler;
+use crate::shutdown_policy::ShutdownGate;
+use crate::timing::TimingSampler;
 use crate::util::{eprint_err, eprint_msg, ERRCODE};
 use crate::writers::LogWriter;
-use crate::LogSpecification;
+use crate::{DeferredNow, LogSpecification, Output};
 
 #[cfg(feature = "textfilter")]
 use regex::Regex;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+
+// The name with which the primary writer is addressed in targets like `{_Default,Alert}`,
+// and in the level filters for writers.
+pub(crate) const PRIMARY_WRITER: &str = "_Default";
 
 // Implements log::Log to plug into the log crate.
 //
@@ -18,31 +27,157 @@ use std::sync::{Arc, RwLock};
 pub(crate) struct FlexiLogger {
     log_specification: Arc<RwLock<LogSpecification>>,
     primary_writer: Arc<PrimaryWriter>,
-    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
-    filter: Option<Box<dyn LogLineFilter + Send + Sync>>,
+    // can be modified with LoggerHandle::add_writer and LoggerHandle::remove_writer
+    other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+    // the names of the initial other writers, as they are handed out with
+    // `Output::AdditionalWriter`
+    writer_outputs: HashMap<String, Arc<str>>,
+    filters: Vec<Box<dyn LogLineFilter + Send + Sync>>,
+    o_timing_sampler: Option<Arc<TimingSampler>>,
+    record_counts: Arc<RecordCounts>,
+    shutdown_gate: Arc<ShutdownGate>,
+    o_rate_limiter: Option<RateLimiter>,
+    o_sampler: Option<Sampler>,
+    // target prefixes and writer names, sorted by descending length of the prefix
+    routes: Vec<(String, String)>,
+    // writer names and level filters for the records that are also sent to other writers
+    broadcasts: Vec<(String, log::LevelFilter)>,
 }
 
 impl FlexiLogger {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         log_specification: Arc<RwLock<LogSpecification>>,
         primary_writer: Arc<PrimaryWriter>,
-        other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
-        filter: Option<Box<dyn LogLineFilter + Send + Sync>>,
+        other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+        filters: Vec<Box<dyn LogLineFilter + Send + Sync>>,
+        o_timing_sampler: Option<Arc<TimingSampler>>,
+        record_counts: Arc<RecordCounts>,
+        shutdown_gate: Arc<ShutdownGate>,
+        o_rate_limiter: Option<RateLimiter>,
+        o_sampler: Option<Sampler>,
+        routes: Vec<(String, String)>,
+        broadcasts: Vec<(String, log::LevelFilter)>,
     ) -> Self {
+        let writer_outputs = read_other_writers(&other_writers)
+            .keys()
+            .map(|name| (name.clone(), Arc::from(name.as_str())))
+            .collect();
         Self {
             log_specification,
             primary_writer,
             other_writers,
-            filter,
+            writer_outputs,
+            filters,
+            o_timing_sampler,
+            record_counts,
+            shutdown_gate,
+            o_rate_limiter,
+            o_sampler,
+            routes,
+            broadcasts,
         }
     }
 
     fn primary_enabled(&self, level: log::Level, module: &str) -> bool {
+        let spec = self
+            .log_specification
+            .read()
+            .map_err(|e| eprint_err(ERRCODE::Poison, "rwlock on log spec is poisoned", &e))
+            .unwrap();
+        // a level filter for the primary writer replaces the module filters
+        match spec.writer_filter(PRIMARY_WRITER) {
+            Some(level_filter) => level <= level_filter,
+            None => spec.enabled(level, module),
+        }
+    }
+
+    // The level filter that the log specification defines for an additional writer, if any.
+    fn writer_filter(&self, writer_name: &str) -> Option<log::LevelFilter> {
         self.log_specification
             .read()
             .map_err(|e| eprint_err(ERRCODE::Poison, "rwlock on log spec is poisoned", &e))
             .unwrap()
-            .enabled(level, module)
+            .writer_filter(writer_name)
+    }
+
+    // Lets the format functions know that the record is written to the given additional writer.
+    fn set_writer_output(&self, now: &mut DeferredNow, writer_name: &str) {
+        now.set_output(Output::AdditionalWriter(
+            self.writer_outputs
+                .get(writer_name)
+                .map_or_else(|| Arc::from(writer_name), Arc::clone),
+        ));
+    }
+
+    // The name of the writer to which records of the given target are routed, if any.
+    fn route(&self, target: &str) -> Option<&str> {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .map(|(_, writer_name)| writer_name.as_str())
+    }
+
+    // Writes a record to the writers to which records of its level are broadcast.
+    fn write_broadcast(&self, now: &mut DeferredNow, record: &log::Record) {
+        for (writer_name, level_filter) in &self.broadcasts {
+            if record.level() <= *level_filter {
+                self.write_to(now, record, writer_name);
+            }
+        }
+    }
+
+    // Writes a record to an additional writer, if the log specification admits it.
+    fn write_to(&self, now: &mut DeferredNow, record: &log::Record, writer_name: &str) {
+        let o_level_filter = self.writer_filter(writer_name);
+        if matches!(o_level_filter, Some(lf) if record.level() > lf) {
+            return;
+        }
+        match read_other_writers(&self.other_writers).get(writer_name) {
+            None => eprint_msg(
+                ERRCODE::WriterSpec,
+                &format!("bad writer spec: {}", writer_name),
+            ),
+            Some(writer) => {
+                self.record_counts.count_writer(writer_name, record.level());
+                self.set_writer_output(now, writer_name);
+                writer.write(now, record).unwrap_or_else(|e| {
+                    eprint_err(
+                        ERRCODE::Write,
+                        &format!(
+                            "writing log line to custom writer \"{}\" failed",
+                            writer_name
+                        ),
+                        &e,
+                    );
+                });
+            }
+        }
+    }
+
+    // Writes a warning about the records that were suppressed by the rate limit.
+    fn write_suppressed(&self, now: &mut DeferredNow, suppressed: &Suppressed) {
+        let target = if suppressed.target.is_empty() {
+            "flexi_logger"
+        } else {
+            &suppressed.target
+        };
+        self.primary_writer
+            .write(
+                now,
+                &log::Record::builder()
+                    .args(format_args!(
+                        "{} log records were suppressed by the rate limit",
+                        suppressed.count
+                    ))
+                    .level(log::Level::Warn)
+                    .target(target)
+                    .module_path(Some(target))
+                    .build(),
+            )
+            .unwrap_or_else(|e| {
+                eprint_err(ERRCODE::Write, "writing log line failed", &e);
+            });
     }
 }
 
@@ -64,15 +199,20 @@ impl log::Log for FlexiLogger {
         let target = metadata.target();
         let level = metadata.level();
 
-        if !self.other_writers.is_empty() && target.starts_with('{') {
+        let other_writers = read_other_writers(&self.other_writers);
+        if !other_writers.is_empty() && target.starts_with('{') {
             // at least one other writer is configured _and_ addressed
             let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
             for t in targets {
-                if t != "_Default" {
-                    match self.other_writers.get(t) {
+                if t != PRIMARY_WRITER {
+                    match other_writers.get(t) {
                         None => eprint_msg(ERRCODE::WriterSpec, &format!("bad writer spec: {}", t)),
                         Some(writer) => {
-                            if level < writer.max_log_level() {
+                            let enabled = match self.writer_filter(t) {
+                                Some(level_filter) => level <= level_filter,
+                                None => level < writer.max_log_level(),
+                            };
+                            if enabled {
                                 return true;
                             }
                         }
@@ -80,24 +220,51 @@ impl log::Log for FlexiLogger {
                 }
             }
         }
+        drop(other_writers);
 
         self.primary_enabled(level, target)
     }
 
     fn log(&self, record: &log::Record) {
+        let mut now = DeferredNow::new();
+        let o_write_in_progress = self.shutdown_gate.enter();
+        if o_write_in_progress.is_none() {
+            // the writers are shut down
+            if self.enabled(record.metadata()) {
+                self.shutdown_gate.handle_late_record(&mut now, record);
+            }
+            return;
+        }
+
         let target = record.metadata().target();
-        let mut now = crate::DeferredNow::new();
         let special_target_is_used = target.starts_with('{');
+        if let Some(ref sampler) = self.o_sampler {
+            let sampled_target = if special_target_is_used {
+                record.module_path().unwrap_or_default()
+            } else {
+                target
+            };
+            if !sampler.admit(record.level(), sampled_target) {
+                return;
+            }
+        }
         if special_target_is_used {
+            let other_writers = read_other_writers(&self.other_writers);
             let mut use_default = false;
             let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
             for t in targets {
-                if t == "_Default" {
+                if t == PRIMARY_WRITER {
                     use_default = true;
                 } else {
-                    match self.other_writers.get(t) {
+                    match other_writers.get(t) {
                         None => eprint_msg(ERRCODE::WriterSpec, &format!("bad writer spec: {}", t)),
                         Some(writer) => {
+                            let o_level_filter = self.writer_filter(t);
+                            if matches!(o_level_filter, Some(lf) if record.level() > lf) {
+                                continue;
+                            }
+                            self.record_counts.count_writer(t, record.level());
+                            self.set_writer_output(&mut now, t);
                             writer.write(&mut now, record).unwrap_or_else(|e| {
                                 eprint_err(
                                     ERRCODE::Write,
@@ -137,25 +304,66 @@ impl log::Log for FlexiLogger {
             }
         }
 
-        #[allow(clippy::option_if_let_else)]
-        if let Some(ref filter) = self.filter {
-            filter.write(&mut now, record, &(*self.primary_writer))
-        } else {
-            self.primary_writer.write(&mut now, record)
+        if !special_target_is_used {
+            self.write_broadcast(&mut now, record);
+            if let Some(writer_name) = self.route(effective_target) {
+                self.write_to(&mut now, record, writer_name);
+                return;
+            }
         }
-        .unwrap_or_else(|e| {
-            eprint_err(ERRCODE::Write, "writing log line failed", &e);
-        });
+
+        if let Some(ref rate_limiter) = self.o_rate_limiter {
+            let (admitted, o_suppressed) = rate_limiter.admit(effective_target);
+            if let Some(suppressed) = o_suppressed {
+                self.write_suppressed(&mut now, &suppressed);
+            }
+            if !admitted {
+                return;
+            }
+        }
+
+        let o_start = self
+            .o_timing_sampler
+            .as_ref()
+            .and_then(|sampler| sampler.start());
+
+        FilterChain::new(&self.filters, &self.primary_writer)
+            .write(&mut now, record)
+            .unwrap_or_else(|e| {
+                eprint_err(ERRCODE::Write, "writing log line failed", &e);
+            });
+
+        if let (Some(sampler), Some(start)) = (&self.o_timing_sampler, o_start) {
+            sampler.stop(start);
+        }
+        self.record_counts.count_primary(record.level());
     }
 
     fn flush(&self) {
+        let o_write_in_progress = self.shutdown_gate.enter();
+        if o_write_in_progress.is_none() {
+            return;
+        }
+        if let Some(ref rate_limiter) = self.o_rate_limiter {
+            for suppressed in rate_limiter.take_ended() {
+                self.write_suppressed(&mut DeferredNow::new(), &suppressed);
+            }
+        }
         self.primary_writer.flush().unwrap_or_else(|e| {
             eprint_err(ERRCODE::Flush, "flushing primary writer failed", &e);
         });
-        for writer in self.other_writers.values() {
+        for writer in read_other_writers(&self.other_writers).values() {
             writer.flush().unwrap_or_else(|e| {
                 eprint_err(ERRCODE::Flush, "flushing custom writer failed", &e);
             });
         }
     }
 }
+
+// Since the writers are only added and removed under the write lock, a panic in some writer
+// cannot leave the map in an inconsistent state, so poisoning is ignored.
+pub(crate) fn read_other_writers(
+    other_writers: &RwLock<HashMap<String, Box<dyn LogWriter>>>,
+) -> RwLockReadGuard<'_, HashMap<String, Box<dyn LogWriter>>> {
+    other_writers.read().unwrap_or_else(PoisonError::into_inner)
+}
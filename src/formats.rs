@@ -1,9 +1,11 @@
-use crate::DeferredNow;
+use crate::{mdc, DeferredNow};
 #[cfg(feature = "colors")]
 use ansi_term::{Color, Style};
 use log::Record;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
 use std::thread;
-use time::format_description;
 
 // const TS_S: &str = "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:6] \
 // [offset_hour sign:mandatory]:[offset_minute]";
@@ -12,6 +14,122 @@ use time::format_description;
 //         = format_description::parse(TS_S).unwrap(/*ok*/);
 // }
 
+/// Additional details that the provided format functions write into each log line.
+///
+/// Is used in [`Logger::format_details`](crate::Logger::format_details);
+/// by default, none of the details is written.
+///
+/// The text formats (like [`opt_format`](crate::opt_format)) write the details in front
+/// of the level, like `P[4711] TID[3] T[worker] INFO ...`,
+/// [`json_format`](crate::json_format) and [`logfmt_format`](crate::logfmt_format)
+/// write them as fields `pid`, `tid`, and `thread`.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::{opt_format, FormatDetails, Logger};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?
+///     .format(opt_format)
+///     .format_details(FormatDetails::default().process_id().thread_name())
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FormatDetails {
+    process_id: bool,
+    thread_id: bool,
+    thread_name: bool,
+}
+impl FormatDetails {
+    /// Write the id of the process.
+    #[must_use]
+    pub fn process_id(mut self) -> Self {
+        self.process_id = true;
+        self
+    }
+
+    /// Write the numeric id of the thread.
+    ///
+    /// This is the number of the thread's [`ThreadId`](std::thread::ThreadId),
+    /// which is unique within the process, but is not the id that the operating system uses.
+    #[must_use]
+    pub fn thread_id(mut self) -> Self {
+        self.thread_id = true;
+        self
+    }
+
+    /// Write the name of the thread.
+    ///
+    /// Has no effect on [`with_thread`](crate::with_thread), which writes the name anyway.
+    #[must_use]
+    pub fn thread_name(mut self) -> Self {
+        self.thread_name = true;
+        self
+    }
+
+    fn active() -> Self {
+        let bits = ACTIVE_FORMAT_DETAILS.load(Ordering::Relaxed);
+        Self {
+            process_id: bits & 0b001 != 0,
+            thread_id: bits & 0b010 != 0,
+            thread_name: bits & 0b100 != 0,
+        }
+    }
+}
+
+static ACTIVE_FORMAT_DETAILS: AtomicU8 = AtomicU8::new(0);
+
+pub(crate) fn set_format_details(details: FormatDetails) {
+    let bits = u8::from(details.process_id)
+        | u8::from(details.thread_id) << 1
+        | u8::from(details.thread_name) << 2;
+    ACTIVE_FORMAT_DETAILS.store(bits, Ordering::Relaxed);
+}
+
+// The numeric part of the current thread's ThreadId, which has no stable accessor.
+fn thread_id() -> u64 {
+    thread_local! {
+        static THREAD_ID: u64 = format!("{:?}", thread::current().id())
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0);
+    }
+    THREAD_ID.with(|id| *id)
+}
+
+// Writes the active format details, each followed by a blank.
+struct DetailsPrefix {
+    details: FormatDetails,
+    with_thread_name: bool,
+}
+impl DetailsPrefix {
+    fn new(with_thread_name: bool) -> Self {
+        Self {
+            details: FormatDetails::active(),
+            with_thread_name,
+        }
+    }
+}
+impl std::fmt::Display for DetailsPrefix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.details.process_id {
+            write!(f, "P[{}] ", std::process::id())?;
+        }
+        if self.details.thread_id {
+            write!(f, "TID[{}] ", thread_id())?;
+        }
+        if self.with_thread_name && self.details.thread_name {
+            write!(f, "T[{}] ", thread::current().name().unwrap_or("<unnamed>"))?;
+        }
+        Ok(())
+    }
+}
+
 /// A logline-formatter that produces log lines like <br>
 /// ```INFO [my_prog::some_submodule] Task successfully read from conf.json```
 ///
@@ -25,11 +143,13 @@ pub fn default_format(
 ) -> Result<(), std::io::Error> {
     write!(
         w,
-        "{} [{}] {}",
+        "{}{} [{}] {}",
+        DetailsPrefix::new(true),
         record.level(),
         record.module_path().unwrap_or("<unnamed>"),
         record.args()
-    )
+    )?;
+    write_key_values(w, record)
 }
 
 #[allow(clippy::doc_markdown)]
@@ -53,18 +173,13 @@ pub fn colored_default_format(
     let level = record.level();
     write!(
         w,
-        "{} [{}] {}",
+        "{}{} [{}] {}",
+        DetailsPrefix::new(true),
         style(level).paint(level.to_string()),
         record.module_path().unwrap_or("<unnamed>"),
         style(level).paint(record.args().to_string())
-    )
-}
-
-const TS_S: &str = "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:6] \
-                    [offset_hour sign:mandatory]:[offset_minute]";
-lazy_static::lazy_static! {
-    static ref TS: Vec<format_description::FormatItem<'static>>
-        = format_description::parse(TS_S).unwrap(/*ok*/);
+    )?;
+    write_key_values(w, record)
 }
 
 /// A logline-formatter that produces log lines with timestamp and file location, like
@@ -82,15 +197,15 @@ pub fn opt_format(
 ) -> Result<(), std::io::Error> {
     write!(
         w,
-        "[{}] {} [{}:{}] {}",
-        now.now()
-            .format(&TS)
-            .unwrap_or_else(|_| "Timestamping failed".to_string()),
+        "[{}] {}{} [{}:{}] {}",
+        now.format_timestamp(),
+        DetailsPrefix::new(true),
         record.level(),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         &record.args()
-    )
+    )?;
+    write_key_values(w, record)
 }
 
 /// A colored version of the logline-formatter `opt_format`.
@@ -110,24 +225,22 @@ pub fn colored_opt_format(
     let level = record.level();
     write!(
         w,
-        "[{}] {} [{}:{}] {}",
-        style(level).paint(
-            now.now()
-                .format(&TS)
-                .unwrap_or_else(|_| "Timestamping failed".to_string())
-        ),
+        "[{}] {}{} [{}:{}] {}",
+        style(level).paint(now.format_timestamp()),
+        DetailsPrefix::new(true),
         style(level).paint(level.to_string()),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         style(level).paint(&record.args().to_string())
-    )
+    )?;
+    write_key_values(w, record)
 }
 
 /// A logline-formatter that produces log lines like
 /// <br>
-/// ```[2016-01-13 15:25:01.640870 +01:00] INFO [foo::bar] src/foo/bar.rs:26: Task successfully read from conf.json```
+/// ```[2016-01-13 15:25:01.640870 +01:00] #42 INFO [foo::bar] src/foo/bar.rs:26: Task successfully read from conf.json```
 /// <br>
-/// i.e. with timestamp, module path and file location.
+/// i.e. with timestamp, sequence number, module path and file location.
 ///
 /// # Errors
 ///
@@ -139,16 +252,17 @@ pub fn detailed_format(
 ) -> Result<(), std::io::Error> {
     write!(
         w,
-        "[{}] {} [{}] {}:{}: {}",
-        now.now()
-            .format(&TS)
-            .unwrap_or_else(|_| "Timestamping failed".to_string()),
+        "[{}] #{} {}{} [{}] {}:{}: {}",
+        now.format_timestamp(),
+        now.sequence_number(),
+        DetailsPrefix::new(true),
         record.level(),
         record.module_path().unwrap_or("<unnamed>"),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         &record.args()
-    )
+    )?;
+    write_key_values(w, record)
 }
 
 /// A colored version of the logline-formatter `detailed_format`.
@@ -168,18 +282,17 @@ pub fn colored_detailed_format(
     let level = record.level();
     write!(
         w,
-        "[{}] {} [{}] {}:{}: {}",
-        style(level).paint(
-            now.now()
-                .format(&TS)
-                .unwrap_or_else(|_| "Timestamping failed".to_string())
-        ),
+        "[{}] #{} {}{} [{}] {}:{}: {}",
+        style(level).paint(now.format_timestamp()),
+        now.sequence_number(),
+        DetailsPrefix::new(true),
         style(level).paint(record.level().to_string()),
         record.module_path().unwrap_or("<unnamed>"),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         style(level).paint(&record.args().to_string())
-    )
+    )?;
+    write_key_values(w, record)
 }
 
 /// A logline-formatter that produces log lines like
@@ -198,16 +311,16 @@ pub fn with_thread(
 ) -> Result<(), std::io::Error> {
     write!(
         w,
-        "[{}] T[{:?}] {} [{}:{}] {}",
-        now.now()
-            .format(&TS)
-            .unwrap_or_else(|_| "Timestamping failed".to_string()),
+        "[{}] T[{:?}] {}{} [{}:{}] {}",
+        now.format_timestamp(),
         thread::current().name().unwrap_or("<unnamed>"),
+        DetailsPrefix::new(false),
         record.level(),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         &record.args()
-    )
+    )?;
+    write_key_values(w, record)
 }
 
 /// A colored version of the logline-formatter `with_thread`.
@@ -227,20 +340,491 @@ pub fn colored_with_thread(
     let level = record.level();
     write!(
         w,
-        "[{}] T[{:?}] {} [{}:{}] {}",
-        style(level).paint(
-            now.now()
-                .format(&TS)
-                .unwrap_or_else(|_| "Timestamping failed".to_string())
-        ),
+        "[{}] T[{:?}] {}{} [{}:{}] {}",
+        style(level).paint(now.format_timestamp()),
         style(level).paint(thread::current().name().unwrap_or("<unnamed>")),
+        DetailsPrefix::new(false),
         style(level).paint(level.to_string()),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         style(level).paint(&record.args().to_string())
+    )?;
+    write_key_values(w, record)
+}
+
+/// A logline-formatter that produces one JSON object per log line (ndjson), like
+/// <br>
+/// ```{"timestamp":"2016-01-13T15:25:01.640870+01:00","level":"INFO","target":"foo::bar","module":"foo::bar","file":"src/foo/bar.rs","line":26,"message":"Task successfully read from conf.json"}```
+/// <br>
+///
+/// String values are escaped properly, so that quotes or line breaks in the log message
+/// do not break the JSON structure; missing values are written as `null`.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn json_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write_json(w, now, record)
+}
+
+/// A colored version of the logline-formatter `json_format`.
+///
+/// The complete JSON object is colored, so removing the color control sequences
+/// reproduces the output of `json_format`.
+///
+/// See method [style](crate::style) if you want to influence coloring.
+///
+/// # Errors
+///
+/// See `std::write`
+#[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+#[cfg(feature = "colors")]
+pub fn colored_json_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let mut buffer = Vec::<u8>::with_capacity(200);
+    write_json(&mut buffer, now, record)?;
+    write!(
+        w,
+        "{}",
+        style(record.level()).paint(String::from_utf8_lossy(&buffer))
     )
 }
 
+fn write_json(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    w.write_all(b"{\"timestamp\":")?;
+    write_json_string(w, &now.format_rfc3339())?;
+    write!(w, ",\"level\":\"{}\",\"target\":", record.level())?;
+    write_json_string(w, record.target())?;
+    w.write_all(b",\"module\":")?;
+    write_json_opt_string(w, record.module_path())?;
+    w.write_all(b",\"file\":")?;
+    write_json_opt_string(w, record.file())?;
+    match record.line() {
+        Some(line) => write!(w, ",\"line\":{}", line)?,
+        None => w.write_all(b",\"line\":null")?,
+    }
+    let details = FormatDetails::active();
+    if details.process_id {
+        write!(w, ",\"pid\":{}", std::process::id())?;
+    }
+    if details.thread_id {
+        write!(w, ",\"tid\":{}", thread_id())?;
+    }
+    if details.thread_name {
+        w.write_all(b",\"thread\":")?;
+        write_json_opt_string(w, thread::current().name())?;
+    }
+    w.write_all(b",\"message\":")?;
+    match record.args().as_str() {
+        Some(message) => write_json_string(w, message)?,
+        None => write_json_string(w, &record.args().to_string())?,
+    }
+    write_json_mdc(w)?;
+    #[cfg(feature = "kv")]
+    write_json_key_values(w, record)?;
+    w.write_all(b"}")
+}
+
+// Writes the entries of the MDC as JSON object with name "mdc", if there are any.
+fn write_json_mdc(w: &mut dyn std::io::Write) -> std::io::Result<()> {
+    if mdc::is_empty() {
+        return Ok(());
+    }
+    w.write_all(b",\"mdc\":{")?;
+    let mut first = true;
+    mdc::try_for_each(|key, value| {
+        if !first {
+            w.write_all(b",")?;
+        }
+        first = false;
+        write_json_string(w, key)?;
+        w.write_all(b":")?;
+        write_json_string(w, value)
+    })?;
+    w.write_all(b"}")
+}
+
+// Writes the key-value pairs of the record as JSON object with name "kv", if there are any.
+#[cfg(feature = "kv")]
+fn write_json_key_values(w: &mut dyn std::io::Write, record: &Record) -> std::io::Result<()> {
+    if record.key_values().count() == 0 {
+        return Ok(());
+    }
+    w.write_all(b",\"kv\":")?;
+    write_json_key_value_object(w, record)
+}
+
+// Writes the key-value pairs of the record as JSON object.
+#[cfg(feature = "kv")]
+pub(crate) fn write_json_key_value_object(
+    w: &mut dyn std::io::Write,
+    record: &Record,
+) -> std::io::Result<()> {
+    w.write_all(b"{")?;
+    let mut first = true;
+    for_each_key_value(record, |key, value| {
+        if !first {
+            w.write_all(b",")?;
+        }
+        first = false;
+        write_json_string(w, key)?;
+        w.write_all(b":")?;
+        if let Some(b) = value.to_bool() {
+            write!(w, "{}", b)
+        } else if let Some(u) = value.to_u64() {
+            write!(w, "{}", u)
+        } else if let Some(i) = value.to_i64() {
+            write!(w, "{}", i)
+        } else if let Some(f) = value.to_f64().filter(|f| f.is_finite()) {
+            write!(w, "{}", f)
+        } else {
+            write_json_string(w, &value.to_string())
+        }
+    })?;
+    w.write_all(b"}")
+}
+
+fn write_json_opt_string(w: &mut dyn std::io::Write, o_s: Option<&str>) -> std::io::Result<()> {
+    match o_s {
+        Some(s) => write_json_string(w, s),
+        None => w.write_all(b"null"),
+    }
+}
+
+// Writes the given string as JSON string literal, with all necessary escapes.
+pub(crate) fn write_json_string(w: &mut dyn std::io::Write, s: &str) -> std::io::Result<()> {
+    w.write_all(b"\"")?;
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        let escape: &[u8] = match byte {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0..=0x1F => b"",
+            _ => continue,
+        };
+        w.write_all(&bytes[start..i])?;
+        if escape.is_empty() {
+            write!(w, "\\u{:04x}", byte)?;
+        } else {
+            w.write_all(escape)?;
+        }
+        start = i + 1;
+    }
+    w.write_all(&bytes[start..])?;
+    w.write_all(b"\"")
+}
+
+/// A logline-formatter that produces log lines in logfmt style, like
+/// <br>
+/// ```ts=2016-01-13T15:25:01.640870+01:00 level=info target=foo::bar msg="Task successfully read from conf.json"```
+/// <br>
+///
+/// Values that are empty or contain blanks, quotes, equal signs, or control characters
+/// are quoted and escaped.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn logfmt_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "ts={} level={} target=",
+        now.format_rfc3339(),
+        record.level().as_str().to_lowercase(),
+    )?;
+    write_logfmt_value(w, record.target())?;
+    let details = FormatDetails::active();
+    if details.process_id {
+        write!(w, " pid={}", std::process::id())?;
+    }
+    if details.thread_id {
+        write!(w, " tid={}", thread_id())?;
+    }
+    if details.thread_name {
+        w.write_all(b" thread=")?;
+        write_logfmt_value(w, thread::current().name().unwrap_or("<unnamed>"))?;
+    }
+    w.write_all(b" msg=")?;
+    match record.args().as_str() {
+        Some(message) => write_logfmt_value(w, message)?,
+        None => write_logfmt_value(w, &record.args().to_string())?,
+    }
+    mdc::try_for_each(|key, value| {
+        write!(w, " {}=", key)?;
+        write_logfmt_value(w, value)
+    })?;
+    #[cfg(feature = "kv")]
+    for_each_key_value(record, |key, value| {
+        write!(w, " {}=", key)?;
+        write_logfmt_value(w, &value.to_string())
+    })?;
+    Ok(())
+}
+
+fn write_logfmt_value(w: &mut dyn std::io::Write, s: &str) -> std::io::Result<()> {
+    if s.is_empty()
+        || s.bytes()
+            .any(|byte| byte <= b' ' || byte == b'"' || byte == b'=' || byte == 0x7F)
+    {
+        // the escaping rules of JSON strings serve logfmt as well
+        write_json_string(w, s)
+    } else {
+        w.write_all(s.as_bytes())
+    }
+}
+
+/// A formatter for machine-oriented output that produces each record as length-prefixed
+/// [CBOR](https://www.rfc-editor.org/rfc/rfc8949.html) map, rather than as text.
+///
+/// Each record consists of its length (4 bytes, big-endian), followed by a CBOR map with the
+/// entries `timestamp` (nanoseconds since the Unix epoch, as integer), `level`, `target`,
+/// `message`, and, if the record has key-value pairs, `kv` (a map with boolean, integer,
+/// floating point, or text values).
+///
+/// Use this format with a writer that does not append a line ending to the records,
+/// see [`FileLogWriterBuilder::without_line_ending`](crate::writers::FileLogWriterBuilder::without_line_ending)
+/// and [`Logger::without_line_ending`](crate::Logger::without_line_ending);
+/// don't combine it with [`Logger::redact`](crate::Logger::redact).
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn cbor_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let mut buffer = Vec::<u8>::with_capacity(200);
+    write_cbor(&mut buffer, now, record)?;
+    #[allow(clippy::cast_possible_truncation)]
+    w.write_all(&(buffer.len() as u32).to_be_bytes())?;
+    w.write_all(&buffer)
+}
+
+fn write_cbor(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    #[cfg(feature = "kv")]
+    let kv_count = record.key_values().count();
+    #[cfg(not(feature = "kv"))]
+    let kv_count = 0;
+    write_cbor_head(w, CBOR_MAP, if kv_count == 0 { 4 } else { 5 })?;
+
+    write_cbor_text(w, "timestamp")?;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    write_cbor_head(w, CBOR_UINT, now.now().unix_timestamp_nanos().max(0) as u64)?;
+    write_cbor_text(w, "level")?;
+    write_cbor_text(w, record.level().as_str())?;
+    write_cbor_text(w, "target")?;
+    write_cbor_text(w, record.target())?;
+    write_cbor_text(w, "message")?;
+    match record.args().as_str() {
+        Some(message) => write_cbor_text(w, message)?,
+        None => write_cbor_text(w, &record.args().to_string())?,
+    }
+
+    #[cfg(feature = "kv")]
+    if kv_count > 0 {
+        write_cbor_text(w, "kv")?;
+        write_cbor_head(w, CBOR_MAP, kv_count as u64)?;
+        for_each_key_value(record, |key, value| {
+            write_cbor_text(w, key)?;
+            if let Some(b) = value.to_bool() {
+                w.write_all(&[if b { CBOR_TRUE } else { CBOR_FALSE }])
+            } else if let Some(u) = value.to_u64() {
+                write_cbor_head(w, CBOR_UINT, u)
+            } else if let Some(i) = value.to_i64() {
+                // non-negative values were handled above
+                #[allow(clippy::cast_sign_loss)]
+                write_cbor_head(w, CBOR_NEGINT, (-1 - i) as u64)
+            } else if let Some(f) = value.to_f64() {
+                w.write_all(&[CBOR_FLOAT64])?;
+                w.write_all(&f.to_bits().to_be_bytes())
+            } else {
+                write_cbor_text(w, &value.to_string())
+            }
+        })?;
+    }
+    Ok(())
+}
+
+// CBOR major types, and the initial bytes of some simple values
+const CBOR_UINT: u8 = 0;
+#[cfg(feature = "kv")]
+const CBOR_NEGINT: u8 = 1;
+const CBOR_TEXT: u8 = 3;
+const CBOR_MAP: u8 = 5;
+#[cfg(feature = "kv")]
+const CBOR_FALSE: u8 = 0xf4;
+#[cfg(feature = "kv")]
+const CBOR_TRUE: u8 = 0xf5;
+#[cfg(feature = "kv")]
+const CBOR_FLOAT64: u8 = 0xfb;
+
+// Writes the head of a CBOR data item, i.e., the major type and the argument,
+// in its shortest form.
+fn write_cbor_head(
+    w: &mut dyn std::io::Write,
+    major_type: u8,
+    argument: u64,
+) -> std::io::Result<()> {
+    let major_type = major_type << 5;
+    if let Ok(a) = u8::try_from(argument) {
+        if a < 24 {
+            w.write_all(&[major_type | a])
+        } else {
+            w.write_all(&[major_type | 0x18, a])
+        }
+    } else if let Ok(a) = u16::try_from(argument) {
+        w.write_all(&[major_type | 0x19])?;
+        w.write_all(&a.to_be_bytes())
+    } else if let Ok(a) = u32::try_from(argument) {
+        w.write_all(&[major_type | 0x1a])?;
+        w.write_all(&a.to_be_bytes())
+    } else {
+        w.write_all(&[major_type | 0x1b])?;
+        w.write_all(&argument.to_be_bytes())
+    }
+}
+
+fn write_cbor_text(w: &mut dyn std::io::Write, s: &str) -> std::io::Result<()> {
+    write_cbor_head(w, CBOR_TEXT, s.len() as u64)?;
+    w.write_all(s.as_bytes())
+}
+
+// Appends the entries of the MDC and the key-value pairs of the record,
+// like ` key1=value1 key2=value2`.
+#[cfg(feature = "kv")]
+fn write_key_values(w: &mut dyn std::io::Write, record: &Record) -> std::io::Result<()> {
+    mdc::try_for_each(|key, value| write!(w, " {}={}", key, value))?;
+    for_each_key_value(record, |key, value| write!(w, " {}={}", key, value))
+}
+#[cfg(not(feature = "kv"))]
+fn write_key_values(w: &mut dyn std::io::Write, _record: &Record) -> std::io::Result<()> {
+    mdc::try_for_each(|key, value| write!(w, " {}={}", key, value))
+}
+
+// Calls the given function for each key-value pair of the record, until the function fails.
+#[cfg(feature = "kv")]
+fn for_each_key_value<F>(record: &Record, f: F) -> std::io::Result<()>
+where
+    F: FnMut(&str, &log::kv::Value) -> std::io::Result<()>,
+{
+    struct Visitor<F> {
+        f: F,
+        result: std::io::Result<()>,
+    }
+    impl<'kvs, F> log::kv::VisitSource<'kvs> for Visitor<F>
+    where
+        F: FnMut(&str, &log::kv::Value) -> std::io::Result<()>,
+    {
+        fn visit_pair(
+            &mut self,
+            key: log::kv::Key<'kvs>,
+            value: log::kv::Value<'kvs>,
+        ) -> Result<(), log::kv::Error> {
+            self.result = (self.f)(key.as_str(), &value);
+            match self.result {
+                Ok(()) => Ok(()),
+                Err(_) => Err(log::kv::Error::msg("writing failed")),
+            }
+        }
+    }
+
+    let mut visitor = Visitor { f, result: Ok(()) };
+    // an error from the visit is only the signal to stop, the cause is in visitor.result
+    record.key_values().visit(&mut visitor).ok();
+    visitor.result
+}
+
+/// A logline-formatter for output to stderr of services that are run by systemd;
+/// produces log lines like <br>
+/// ```<6>[my_prog::some_submodule] Task successfully read from conf.json```
+///
+/// The leading `<N>` is the
+/// [sd-daemon](https://www.freedesktop.org/software/systemd/man/sd-daemon.html)
+/// priority prefix that lets journald assign the correct priority to the record;
+/// journald removes the prefix, the timestamp is also added by journald.
+///
+/// See [`Logger::journald_aware_stderr`](crate::Logger::journald_aware_stderr)
+/// for using this format only if stderr is actually connected to the journal.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn systemd_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "<{}>[{}] {}",
+        sd_daemon_priority(record.level()),
+        record.module_path().unwrap_or("<unnamed>"),
+        record.args()
+    )
+}
+
+// Maps the log level to the syslog priority that is used in sd-daemon prefixes
+// and in the PRIORITY field of journal entries.
+pub(crate) fn sd_daemon_priority(level: log::Level) -> u8 {
+    match level {
+        log::Level::Error => 3,
+        log::Level::Warn => 4,
+        log::Level::Info => 6,
+        log::Level::Debug | log::Level::Trace => 7,
+    }
+}
+
+// systemd sets JOURNAL_STREAM to `<device>:<inode>` of the stream that connects stdout
+// or stderr of a service with the journal; the variable is inherited by child processes,
+// so stderr goes to the journal only if it is still that very stream.
+#[cfg(unix)]
+pub(crate) fn stderr_goes_to_journal() -> bool {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsFd;
+
+    std::env::var_os("JOURNAL_STREAM")
+        .and_then(|journal_stream| {
+            let (dev, ino) = journal_stream.to_str()?.split_once(':')?;
+            Some((dev.parse::<u64>().ok()?, ino.parse::<u64>().ok()?))
+        })
+        .is_some_and(|(dev, ino)| {
+            // fstat(2) on a duplicate of fd 2
+            std::io::stderr()
+                .as_fd()
+                .try_clone_to_owned()
+                .and_then(|fd| std::fs::File::from(fd).metadata())
+                .is_ok_and(|metadata| metadata.dev() == dev && metadata.ino() == ino)
+        })
+}
+#[cfg(not(unix))]
+pub(crate) fn stderr_goes_to_journal() -> bool {
+    false
+}
+
 /// Helper function that is used in the provided coloring format functions to apply
 /// colors based on the log level and the effective color palette.
 ///
@@ -337,6 +921,10 @@ fn parse_style(input: &str) -> Result<Style, std::num::ParseIntError> {
 ///
 /// This is helpful if the output is sometimes piped into other programs, which usually
 /// do not expect color control byte sequences.
+///
+/// The decision also honors the environment variables `NO_COLOR`, `CLICOLOR_FORCE`,
+/// and `CLICOLOR`, and can be overridden with
+/// [`Logger::color_choice`](crate::Logger::color_choice), see [`ColorChoice`].
 #[cfg_attr(docsrs, doc(cfg(feature = "atty")))]
 #[cfg(feature = "atty")]
 #[derive(Clone, Copy)]
@@ -361,6 +949,11 @@ pub enum AdaptiveFormat {
     #[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
     #[cfg(feature = "colors")]
     WithThread,
+    /// Chooses between [`json_format`](crate::json_format)
+    /// and [`colored_json_format`](crate::colored_json_format).
+    #[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+    #[cfg(feature = "colors")]
+    Json,
     /// Chooses between the first format function (which is supposed to be uncolored)
     /// and the second (which is supposed to be colored).
     ///
@@ -383,6 +976,8 @@ impl AdaptiveFormat {
                 Self::Opt => colored_opt_format,
                 #[cfg(feature = "colors")]
                 Self::WithThread => colored_with_thread,
+                #[cfg(feature = "colors")]
+                Self::Json => colored_json_format,
                 Self::Custom(_, colored) => colored,
             }
         } else {
@@ -395,12 +990,65 @@ impl AdaptiveFormat {
                 Self::Opt => opt_format,
                 #[cfg(feature = "colors")]
                 Self::WithThread => with_thread,
+                #[cfg(feature = "colors")]
+                Self::Json => json_format,
                 Self::Custom(uncolored, _) => uncolored,
             }
         }
     }
 }
 
+/// Decides whether an [`AdaptiveFormat`] uses its colored or its uncolored variant,
+/// see [`Logger::color_choice`](crate::Logger::color_choice).
+#[cfg_attr(docsrs, doc(cfg(feature = "atty")))]
+#[cfg(feature = "atty")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorChoice {
+    /// Uses colors if the output goes to a tty, unless the environment decides otherwise,
+    /// checked in this order:
+    ///
+    /// - if `NO_COLOR` is set to a non-empty value, no colors are used
+    ///   (see [no-color.org](https://no-color.org)),
+    /// - if `CLICOLOR_FORCE` is set to a non-empty value other than `0`,
+    ///   colors are used also if the output does not go to a tty,
+    /// - if `CLICOLOR` is set to `0`, no colors are used.
+    ///
+    /// This is the default.
+    Auto,
+    /// Always uses colors.
+    Always,
+    /// Never uses colors.
+    Never,
+}
+
+#[cfg(feature = "atty")]
+impl ColorChoice {
+    pub(crate) fn use_colors(self, stream: atty::Stream) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                if env_var_is_set("NO_COLOR") {
+                    false
+                } else if env_var_is_set("CLICOLOR_FORCE")
+                    && std::env::var_os("CLICOLOR_FORCE") != Some("0".into())
+                {
+                    true
+                } else if std::env::var_os("CLICOLOR") == Some("0".into()) {
+                    false
+                } else {
+                    atty::is(stream)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "atty")]
+fn env_var_is_set(name: &str) -> bool {
+    matches!(std::env::var_os(name), Some(value) if !value.is_empty())
+}
+
 /// Function type for format functions.
 ///
 /// If you want to write the log lines in your own format,
@@ -421,8 +1069,113 @@ impl AdaptiveFormat {
 ///
 /// - `record`: the log line's content and metadata, as provided by the log crate's macros.
 ///
+/// ## Output
+///
+/// A format function can adapt to the output it is producing for,
+/// e.g. omit colors for files or add a channel tag for an additional writer,
+/// by checking [`DeferredNow::output`]:
+///
+/// ```rust
+/// use flexi_logger::{DeferredNow, Output};
+/// use log::Record;
+///
+/// fn my_format(
+///     w: &mut dyn std::io::Write,
+///     now: &mut DeferredNow,
+///     record: &Record,
+/// ) -> std::io::Result<()> {
+///     if let Some(Output::AdditionalWriter(name)) = now.output() {
+///         write!(w, "[{}] ", name)?;
+///     }
+///     write!(w, "{} {}", record.level(), record.args())
+/// }
+/// ```
+///
+/// ## Key-value pairs
+///
+/// With the crate feature `kv`, the provided format functions write the key-value pairs
+/// that are attached to the log records (e.g. with `info!(user = "alice"; "logged in")`);
+/// the text formats append them like ` user=alice`, [`json_format`] writes them as
+/// JSON object with the name `kv`.
+///
+/// Your own format functions can access the key-value pairs with
+/// `Record::key_values()` (with the feature `kv` of the `log` crate), e.g.
+///
+/// ```rust,ignore
+/// fn my_format(
+///     w: &mut dyn std::io::Write,
+///     _now: &mut flexi_logger::DeferredNow,
+///     record: &log::Record,
+/// ) -> std::io::Result<()> {
+///     write!(w, "{}", record.args())?;
+///     if let Some(user) = record.key_values().get(log::kv::Key::from_str("user")) {
+///         write!(w, " (by {})", user)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
 pub type FormatFunction = fn(
     write: &mut dyn std::io::Write,
     now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error>;
+
+/// The output for which a log record is written, see [`DeferredNow::output`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Output {
+    /// The log file of the primary writer, see [`Logger::log_to_file`](crate::Logger::log_to_file).
+    File,
+    /// Stderr, as primary output or as duplication target.
+    Stderr,
+    /// Stdout, as primary output or as duplication target.
+    Stdout,
+    /// The custom primary writer, see [`Logger::log_to_writer`](crate::Logger::log_to_writer).
+    Writer,
+    /// The additional writer with the given name, see [`Logger::add_writer`](crate::Logger::add_writer).
+    AdditionalWriter(Arc<str>),
+    /// The console of the browser, see
+    /// [`Logger::log_to_browser_console`](crate::Logger::log_to_browser_console).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "wasm_console", target_arch = "wasm32")))
+    )]
+    #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+    BrowserConsole,
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(unix)]
+    #[test]
+    fn test_stderr_goes_to_journal() {
+        use std::os::unix::fs::MetadataExt;
+        use std::os::unix::io::AsFd;
+
+        let metadata = std::fs::File::from(std::io::stderr().as_fd().try_clone_to_owned().unwrap())
+            .metadata()
+            .unwrap();
+
+        std::env::remove_var("JOURNAL_STREAM");
+        assert!(!super::stderr_goes_to_journal());
+
+        // the variable was inherited, but stderr was redirected
+        std::env::set_var(
+            "JOURNAL_STREAM",
+            format!("{}:{}", metadata.dev(), metadata.ino() + 1),
+        );
+        assert!(!super::stderr_goes_to_journal());
+
+        std::env::set_var("JOURNAL_STREAM", "garbage");
+        assert!(!super::stderr_goes_to_journal());
+
+        std::env::set_var(
+            "JOURNAL_STREAM",
+            format!("{}:{}", metadata.dev(), metadata.ino()),
+        );
+        assert!(super::stderr_goes_to_journal());
+
+        std::env::remove_var("JOURNAL_STREAM");
+    }
+}
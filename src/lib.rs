@@ -40,17 +40,57 @@
 //! you can adapt the colors to improve readability.
 //! See the documentation of method [`Logger::set_palette`]
 //! for a description how this can be done.
+//!
+//! On `wasm32` targets, there is no file system and there are no background threads;
+//! with feature `wasm_console`, the log can be written to the console of the browser
+//! (with `Logger::log_to_browser_console`).
+
+#[cfg(all(
+    target_arch = "wasm32",
+    any(
+        feature = "admin_socket",
+        feature = "async",
+        feature = "compress",
+        feature = "config_file",
+        feature = "mmap_ring",
+        feature = "specfile_without_notification",
+        feature = "sqlite",
+        feature = "syslog_writer",
+        feature = "upload",
+    )
+))]
+compile_error!("a feature of flexi_logger needs files or threads, which wasm32 does not have");
 
+#[cfg(feature = "admin_socket")]
+mod admin_socket;
+#[cfg(feature = "config_file")]
+mod config_file;
+mod counters;
 mod deferred_now;
+mod early_buffer;
+#[cfg(not(target_arch = "wasm32"))]
 mod file_spec;
 mod flexi_error;
 mod flexi_logger;
 mod formats;
+mod lifecycle;
 mod log_specification;
 mod logger;
 mod logger_handle;
-mod parameters;
+pub mod mdc;
 mod primary_writer;
+mod rate_limit;
+#[cfg(not(target_arch = "wasm32"))]
+mod retention_policy;
+#[cfg(not(target_arch = "wasm32"))]
+mod rotation_policy;
+mod sampling;
+mod shutdown_policy;
+mod timestamp_config;
+mod timing;
+#[cfg(feature = "tracing_bridge")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing_bridge")))]
+pub mod tracing_bridge;
 #[cfg(feature = "trc")]
 #[cfg_attr(docsrs, doc(cfg(feature = "trc")))]
 pub mod trc;
@@ -63,20 +103,36 @@ pub mod writers;
 
 pub mod error_info;
 
+#[cfg(feature = "admin_socket")]
+#[cfg_attr(docsrs, doc(cfg(feature = "admin_socket")))]
+pub use crate::admin_socket::AdminSocket;
+pub use crate::counters::{LevelCounters, RecordCounters};
 pub use crate::deferred_now::DeferredNow;
+#[cfg(not(target_arch = "wasm32"))]
 pub use crate::file_spec::FileSpec;
 pub use crate::flexi_error::FlexiLoggerError;
 pub use crate::formats::*;
 pub use crate::log_specification::{LogSpecBuilder, LogSpecification, ModuleFilter};
 pub use crate::logger::{Duplicate, Logger};
-pub use crate::logger_handle::LoggerHandle;
-pub use crate::parameters::{Age, Cleanup, Criterion, Naming};
+pub use crate::logger_handle::{existing_handle, handle, LoggerHandle, SpecGuard};
+pub use crate::rate_limit::RateLimit;
+#[cfg(feature = "upload")]
+#[cfg_attr(docsrs, doc(cfg(feature = "upload")))]
+pub use crate::retention_policy::Uploader;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::retention_policy::{Cleanup, CleanupEvent};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::rotation_policy::{Age, Criterion, Naming};
+pub use crate::shutdown_policy::ShutdownPolicy;
+pub use crate::timestamp_config::TimestampConfig;
+pub use crate::timing::TimingMetrics;
+pub use crate::util::{ErrCode, ErrorCallback, ErrorChannel};
 pub(crate) use crate::write_mode::EffectiveWriteMode;
 pub use crate::write_mode::WriteMode;
 
-pub use crate::write_mode::{DEFAULT_BUFFER_CAPACITY, DEFAULT_FLUSH_INTERVAL};
 #[cfg(feature = "async")]
-pub use crate::write_mode::{DEFAULT_MESSAGE_CAPA, DEFAULT_POOL_CAPA};
+pub use crate::write_mode::{OverflowPolicy, DEFAULT_MESSAGE_CAPA, DEFAULT_POOL_CAPA};
+pub use crate::write_mode::{DEFAULT_BUFFER_CAPACITY, DEFAULT_FLUSH_INTERVAL};
 
 /// Re-exports from log crate
 pub use log::{Level, LevelFilter, Record};
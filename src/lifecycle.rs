@@ -0,0 +1,88 @@
+use crate::primary_writer::PrimaryWriter;
+use crate::util::{eprint_err, ERRCODE};
+use crate::{DeferredNow, LevelCounters, LogSpecification};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// The target of the startup and the shutdown record.
+const TARGET: &str = "flexi_logger";
+
+// Makes sure that the shutdown record is written only once, see Logger::shutdown_summary.
+#[derive(Debug, Default)]
+pub(crate) struct ShutdownSummary {
+    written: AtomicBool,
+}
+impl ShutdownSummary {
+    // Returns the text of the shutdown record, but only once.
+    pub(crate) fn take(&self, counters: &LevelCounters, o_bytes: Option<u64>) -> Option<String> {
+        if self.written.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        let mut summary = "shutdown:".to_string();
+        for level in &[
+            log::Level::Error,
+            log::Level::Warn,
+            log::Level::Info,
+            log::Level::Debug,
+            log::Level::Trace,
+        ] {
+            write!(
+                summary,
+                " {}={}",
+                level.to_string().to_lowercase(),
+                counters.get(*level)
+            )
+            .ok();
+        }
+        if let Some(bytes) = o_bytes {
+            write!(summary, " bytes={}", bytes).ok();
+        }
+        Some(summary)
+    }
+}
+
+// The text of the startup record, see Logger::startup_banner.
+pub(crate) fn startup_banner(version: &str, spec: &LogSpecification) -> String {
+    let binary = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "<unknown_binary>".to_string());
+    format!(
+        "startup: binary={} version={} pid={} hostname={} spec=\"{}\"",
+        binary,
+        version,
+        std::process::id(),
+        hostname(),
+        spec.to_string()
+    )
+}
+
+#[cfg(feature = "hostname")]
+fn hostname() -> String {
+    hostname::get().map_or_else(
+        |_| "<unknown_hostname>".to_string(),
+        |hostname| hostname.to_string_lossy().to_string(),
+    )
+}
+#[cfg(not(feature = "hostname"))]
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "<unknown_hostname>".to_string())
+}
+
+// Writes the startup or the shutdown record directly with the primary writer,
+// independent of the log specification.
+pub(crate) fn write_lifecycle_record(primary_writer: &PrimaryWriter, text: &str) {
+    primary_writer
+        .write(
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target(TARGET)
+                .module_path(Some(TARGET))
+                .args(format_args!("{}", text))
+                .build(),
+        )
+        .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing lifecycle record failed", &e));
+}
@@ -3,7 +3,7 @@ use crate::LevelFilter;
 
 #[cfg(feature = "textfilter")]
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fmt::Write;
 
@@ -19,7 +19,8 @@ use std::fmt::Write;
 ///
 /// ```text
 /// <log_level_spec> ::= single_log_level_spec[{,single_log_level_spec}][/<text_filter>]
-/// <single_log_level_spec> ::= <path_to_module>|<log_level>|<path_to_module>=<log_level>
+/// <single_log_level_spec> ::= <module_filter>|<log_level>|<module_filter>=<log_level>
+/// <module_filter> ::= <path_to_module>|/<regex>/
 /// <text_filter> ::= <regex>
 /// ```
 ///
@@ -45,6 +46,14 @@ use std::fmt::Write;
 ///   * `foobaz` (!)
 ///   * `foobaz::bar` (!)
 ///
+/// * A module filter that is enclosed in slashes is a regular expression, which is matched
+///   against the module path, e.g. `"info, /^my_app::(db|cache)/=trace"`.
+///   The regular expression must not contain a slash.
+///   Regular expressions take precedence over the other module filters;
+///   if several regular expressions match, the longest one wins.
+///   Regular expressions in module filters are only supported if the default feature
+///   `textfilter` is not switched off.
+///
 /// The optional text filter is applied for all modules.
 ///
 /// Note that external module names are to be specified like in ```"extern crate ..."```, i.e.,
@@ -57,6 +66,11 @@ use std::fmt::Write;
 #[derive(Clone, Debug, Default)]
 pub struct LogSpecification {
     module_filters: Vec<ModuleFilter>,
+    // the level filters for the additional writers, by writer name
+    writer_filters: BTreeMap<String, LevelFilter>,
+    // the compiled regular expressions of the module filters, with the same indices
+    #[cfg(feature = "textfilter")]
+    module_regexes: Vec<Option<Regex>>,
     #[cfg(feature = "textfilter")]
     textfilter: Option<Box<Regex>>,
 }
@@ -64,6 +78,10 @@ pub struct LogSpecification {
 /// Defines which loglevel filter to use for the specified module.
 ///
 /// A `ModuleFilter`, whose `module_name` is not set, describes the default loglevel filter.
+///
+/// A `module_name` that is enclosed in slashes, like `/^my_app::(db|cache)/`,
+/// is a regular expression (see [`LogSpecification`]); if it is not a valid regular expression,
+/// the filter matches no module.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ModuleFilter {
     /// The module name.
@@ -75,17 +93,68 @@ pub struct ModuleFilter {
 impl LogSpecification {
     pub(crate) fn update_from(&mut self, other: Self) {
         self.module_filters = other.module_filters;
+        self.writer_filters = other.writer_filters;
 
         #[cfg(feature = "textfilter")]
         {
+            self.module_regexes = other.module_regexes;
             self.textfilter = other.textfilter;
         }
     }
 
+    // Compiles the regular expressions of the module filters, which must be sorted already.
+    fn from_parts(
+        module_filters: Vec<ModuleFilter>,
+        #[cfg(feature = "textfilter")] textfilter: Option<Box<Regex>>,
+        parse_errs: &mut String,
+    ) -> Self {
+        #[cfg(feature = "textfilter")]
+        let module_regexes = module_filters
+            .iter()
+            .map(|module_filter| {
+                module_filter
+                    .module_name
+                    .as_deref()
+                    .and_then(module_regex_pattern)
+                    .and_then(|pattern| match Regex::new(pattern) {
+                        Ok(regex) => Some(regex),
+                        Err(e) => {
+                            push_err(&format!("invalid regex module filter - {}", e), parse_errs);
+                            None
+                        }
+                    })
+            })
+            .collect();
+
+        #[cfg(not(feature = "textfilter"))]
+        if module_filters.iter().any(|module_filter| {
+            module_filter
+                .module_name
+                .as_deref()
+                .and_then(module_regex_pattern)
+                .is_some()
+        }) {
+            push_err(
+                "regex module filters require feature textfilter",
+                parse_errs,
+            );
+        }
+
+        Self {
+            module_filters,
+            writer_filters: BTreeMap::new(),
+            #[cfg(feature = "textfilter")]
+            module_regexes,
+            #[cfg(feature = "textfilter")]
+            textfilter,
+        }
+    }
+
     pub(crate) fn max_level(&self) -> log::LevelFilter {
         self.module_filters
             .iter()
             .map(|d| d.level_filter)
+            .chain(self.writer_filters.values().copied())
             .max()
             .unwrap_or(log::LevelFilter::Off)
     }
@@ -104,6 +173,9 @@ impl LogSpecification {
                 module_name: None,
                 level_filter: LevelFilter::Info,
             }],
+            writer_filters: BTreeMap::new(),
+            #[cfg(feature = "textfilter")]
+            module_regexes: vec![None],
             #[cfg(feature = "textfilter")]
             textfilter: None,
         }
@@ -118,73 +190,89 @@ impl LogSpecification {
         let mut parse_errs = String::new();
         let mut dirs = Vec::<ModuleFilter>::new();
         let spec = spec.as_ref();
-        let mut parts = spec.split('/');
-        let mods = parts.next();
-        #[cfg(feature = "textfilter")]
-        let filter = parts.next();
-        if parts.next().is_some() {
+        let (mods, filter) = split_spec(spec);
+        if matches!(filter, Some(filter) if filter.contains('/')) {
             push_err(
                 &format!("invalid log spec '{}' (too many '/'s), ignoring it", spec),
                 &mut parse_errs,
             );
             return parse_err(parse_errs, Self::off());
         }
-        if let Some(m) = mods {
-            for s in m.split(',') {
-                let s = s.trim();
-                if s.is_empty() {
-                    continue;
-                }
-                let mut parts = s.split('=');
-                let (log_level, name) = match (
-                    parts.next().map(str::trim),
-                    parts.next().map(str::trim),
-                    parts.next(),
-                ) {
-                    (Some(part_0), None, None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
+        for s in mods {
+            let s = s.trim();
+            if s.is_empty() {
+                continue;
+            }
+            if let Some(len) = regex_part_len(s) {
+                // a regular expression, optionally followed by `=<log_level>`
+                let level = s[len..].trim_start();
+                let level = level.strip_prefix('=').unwrap_or(level).trim();
+                let level_filter = if level.is_empty() {
+                    LevelFilter::max()
+                } else {
+                    match parse_level_filter(level) {
+                        Ok(level_filter) => level_filter,
+                        Err(e) => {
+                            push_err(&e.to_string(), &mut parse_errs);
                             continue;
                         }
-                        // if the single argument is a log-level string or number,
-                        // treat that as a global fallback setting
-                        match parse_level_filter(part_0.trim()) {
-                            Ok(num) => (num, None),
-                            Err(_) => (LevelFilter::max(), Some(part_0)),
-                        }
                     }
+                };
+                dirs.push(ModuleFilter {
+                    module_name: Some(s[..len].to_string()),
+                    level_filter,
+                });
+                continue;
+            }
+            let mut parts = s.split('=');
+            let (log_level, name) = match (
+                parts.next().map(str::trim),
+                parts.next().map(str::trim),
+                parts.next(),
+            ) {
+                (Some(part_0), None, None) => {
+                    if contains_whitespace(part_0, &mut parse_errs) {
+                        continue;
+                    }
+                    // if the single argument is a log-level string or number,
+                    // treat that as a global fallback setting
+                    match parse_level_filter(part_0.trim()) {
+                        Ok(num) => (num, None),
+                        Err(_) => (LevelFilter::max(), Some(part_0)),
+                    }
+                }
 
-                    (Some(part_0), Some(""), None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
-                            continue;
-                        }
-                        (LevelFilter::max(), Some(part_0))
+                (Some(part_0), Some(""), None) => {
+                    if contains_whitespace(part_0, &mut parse_errs) {
+                        continue;
                     }
+                    (LevelFilter::max(), Some(part_0))
+                }
 
-                    (Some(part_0), Some(part_1), None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
+                (Some(part_0), Some(part_1), None) => {
+                    if contains_whitespace(part_0, &mut parse_errs) {
+                        continue;
+                    }
+                    match parse_level_filter(part_1.trim()) {
+                        Ok(num) => (num, Some(part_0.trim())),
+                        Err(e) => {
+                            push_err(&e.to_string(), &mut parse_errs);
                             continue;
                         }
-                        match parse_level_filter(part_1.trim()) {
-                            Ok(num) => (num, Some(part_0.trim())),
-                            Err(e) => {
-                                push_err(&e.to_string(), &mut parse_errs);
-                                continue;
-                            }
-                        }
                     }
-                    _ => {
-                        push_err(
-                            &format!("invalid part in log spec '{}', ignoring it", s),
-                            &mut parse_errs,
-                        );
-                        continue;
-                    }
-                };
-                dirs.push(ModuleFilter {
-                    module_name: name.map(ToString::to_string),
-                    level_filter: log_level,
-                });
-            }
+                }
+                _ => {
+                    push_err(
+                        &format!("invalid part in log spec '{}', ignoring it", s),
+                        &mut parse_errs,
+                    );
+                    continue;
+                }
+            };
+            dirs.push(ModuleFilter {
+                module_name: name.map(ToString::to_string),
+                level_filter: log_level,
+            });
         }
 
         #[cfg(feature = "textfilter")]
@@ -196,11 +284,12 @@ impl LogSpecification {
             }
         });
 
-        let logspec = Self {
-            module_filters: dirs.level_sort(),
+        let logspec = Self::from_parts(
+            dirs.level_sort(),
             #[cfg(feature = "textfilter")]
             textfilter,
-        };
+            &mut parse_errs,
+        );
 
         if parse_errs.is_empty() {
             Ok(logspec)
@@ -250,14 +339,27 @@ impl LogSpecification {
     /// [`FlexiLoggerError::Parse`] if the input is malformed.
     #[cfg(feature = "specfile_without_notification")]
     pub fn from_toml<S: AsRef<str>>(s: S) -> Result<Self, FlexiLoggerError> {
-        #[derive(Clone, Debug, serde_derive::Deserialize)]
-        struct LogSpecFileFormat {
-            pub global_level: Option<String>,
-            pub global_pattern: Option<String>,
-            pub modules: Option<std::collections::BTreeMap<String, String>>,
-        }
-        let s = s.as_ref();
-        let logspec_ff: LogSpecFileFormat = toml::from_str(s)?;
+        Self::from_file_format(toml::from_str(s.as_ref())?)
+    }
+
+    /// Reads a log specification from an appropriate yaml document.
+    ///
+    /// The document has the same structure as the toml document that is accepted by
+    /// [`LogSpecification::from_toml`].
+    ///
+    /// This method is only avaible with feature `specfile_yaml`.
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::Parse`] if the input is malformed.
+    #[cfg_attr(docsrs, doc(cfg(feature = "specfile_yaml")))]
+    #[cfg(feature = "specfile_yaml")]
+    pub fn from_yaml<S: AsRef<str>>(s: S) -> Result<Self, FlexiLoggerError> {
+        Self::from_file_format(serde_yaml::from_str(s.as_ref())?)
+    }
+
+    #[cfg(feature = "specfile_without_notification")]
+    fn from_file_format(logspec_ff: LogSpecFileFormat) -> Result<Self, FlexiLoggerError> {
         let mut parse_errs = String::new();
         let mut module_filters = Vec::<ModuleFilter>::new();
 
@@ -275,6 +377,11 @@ impl LogSpecification {
             });
         }
 
+        let mut writer_filters = BTreeMap::new();
+        for (k, v) in logspec_ff.writers.unwrap_or_default() {
+            writer_filters.insert(k, parse_level_filter(v)?);
+        }
+
         #[cfg(feature = "textfilter")]
         let textfilter = match logspec_ff.global_pattern {
             None => None,
@@ -287,11 +394,13 @@ impl LogSpecification {
             },
         };
 
-        let logspec = Self {
-            module_filters: module_filters.level_sort(),
+        let mut logspec = Self::from_parts(
+            module_filters.level_sort(),
             #[cfg(feature = "textfilter")]
             textfilter,
-        };
+            &mut parse_errs,
+        );
+        logspec.writer_filters = writer_filters;
         if parse_errs.is_empty() {
             Ok(logspec)
         } else {
@@ -308,54 +417,89 @@ impl LogSpecification {
     /// [`FlexiLoggerError::SpecfileIo`] if writing fails.
     #[cfg(feature = "specfile_without_notification")]
     pub fn to_toml(&self, w: &mut dyn std::io::Write) -> Result<(), FlexiLoggerError> {
-        self.to_toml_impl(w).map_err(FlexiLoggerError::SpecfileIo)
+        SpecfileFormat::Toml.write(self, w)
+    }
+
+    /// Serializes itself in yaml format.
+    ///
+    /// This method is only avaible with feature `specfile_yaml`.
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::SpecfileIo`] if writing fails.
+    #[cfg_attr(docsrs, doc(cfg(feature = "specfile_yaml")))]
+    #[cfg(feature = "specfile_yaml")]
+    pub fn to_yaml(&self, w: &mut dyn std::io::Write) -> Result<(), FlexiLoggerError> {
+        SpecfileFormat::Yaml.write(self, w)
     }
 
     #[cfg(feature = "specfile_without_notification")]
-    fn to_toml_impl(&self, w: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+    fn to_specfile_impl(
+        &self,
+        w: &mut dyn std::io::Write,
+        format: SpecfileFormat,
+    ) -> Result<(), std::io::Error> {
         w.write_all(b"### Optional: Default log level\n")?;
         let last = self.module_filters.last();
         match last {
             Some(last_v) if last_v.module_name.is_none() => {
                 w.write_all(
-                    format!(
-                        "global_level = '{}'\n",
-                        last_v.level_filter.to_string().to_lowercase()
-                    )
-                    .as_bytes(),
+                    format
+                        .pair(
+                            "global_level",
+                            &last_v.level_filter.to_string().to_lowercase(),
+                        )
+                        .as_bytes(),
                 )?;
             }
             _ => {
-                w.write_all(b"#global_level = 'info'\n")?;
+                w.write_all(format!("#{}", format.pair("global_level", "info")).as_bytes())?;
             }
         }
 
         w.write_all(
             b"\n### Optional: specify a regular expression to suppress all messages that don't match\n",
         )?;
-        w.write_all(b"#global_pattern = 'foo'\n")?;
+        w.write_all(format!("#{}", format.pair("global_pattern", "foo")).as_bytes())?;
 
         w.write_all(
             b"\n### Specific log levels per module are optionally defined in this section\n",
         )?;
-        w.write_all(b"[modules]\n")?;
+        w.write_all(format.section("modules").as_bytes())?;
         if self.module_filters.is_empty() || self.module_filters[0].module_name.is_none() {
-            w.write_all(b"#'mod1' = 'warn'\n")?;
-            w.write_all(b"#'mod2' = 'debug'\n")?;
-            w.write_all(b"#'mod2::mod3' = 'trace'\n")?;
+            for (name, level) in &[
+                ("mod1", "warn"),
+                ("mod2", "debug"),
+                ("mod2::mod3", "trace"),
+                ("/^mod4::(db|cache)/", "trace"),
+            ] {
+                w.write_all(format!("#{}", format.entry(name, level)).as_bytes())?;
+            }
         }
         for mf in &self.module_filters {
             if let Some(ref name) = mf.module_name {
                 w.write_all(
-                    format!(
-                        "'{}' = '{}'\n",
-                        name,
-                        mf.level_filter.to_string().to_lowercase()
-                    )
-                    .as_bytes(),
+                    format
+                        .entry(name, &mf.level_filter.to_string().to_lowercase())
+                        .as_bytes(),
                 )?;
             }
         }
+
+        w.write_all(
+            b"\n### Log levels for the additional writers are optionally defined in this section\n",
+        )?;
+        w.write_all(format.section("writers").as_bytes())?;
+        if self.writer_filters.is_empty() {
+            w.write_all(format!("#{}", format.entry("Alert", "warn")).as_bytes())?;
+        }
+        for (name, level_filter) in &self.writer_filters {
+            w.write_all(
+                format
+                    .entry(name, &level_filter.to_string().to_lowercase())
+                    .as_bytes(),
+            )?;
+        }
         Ok(())
     }
 
@@ -363,10 +507,10 @@ impl LogSpecification {
     #[must_use]
     pub fn enabled(&self, level: log::Level, writing_module: &str) -> bool {
         // Search for the longest match, the vector is assumed to be pre-sorted.
-        for module_filter in &self.module_filters {
+        for (index, module_filter) in self.module_filters.iter().enumerate() {
             match module_filter.module_name {
                 Some(ref module_name) => {
-                    if writing_module.starts_with(module_name) {
+                    if self.module_matches(index, module_name, writing_module) {
                         return level <= module_filter.level_filter;
                     }
                 }
@@ -376,12 +520,57 @@ impl LogSpecification {
         false
     }
 
+    #[cfg(feature = "textfilter")]
+    fn module_matches(&self, index: usize, module_name: &str, writing_module: &str) -> bool {
+        if module_regex_pattern(module_name).is_some() {
+            matches!(self.module_regexes[index], Some(ref regex) if regex.is_match(writing_module))
+        } else {
+            writing_module.starts_with(module_name)
+        }
+    }
+
+    #[cfg(not(feature = "textfilter"))]
+    fn module_matches(&self, _index: usize, module_name: &str, writing_module: &str) -> bool {
+        module_regex_pattern(module_name).is_none() && writing_module.starts_with(module_name)
+    }
+
     /// Provides a reference to the module filters.
     #[must_use]
     pub fn module_filters(&self) -> &Vec<ModuleFilter> {
         &self.module_filters
     }
 
+    /// Returns the level filter that is defined for the additional writer with the given name.
+    ///
+    /// Level filters for additional writers can be defined in a specfile,
+    /// or with [`LoggerHandle::set_writer_level`](crate::LoggerHandle::set_writer_level),
+    /// and replace the [`LogWriter::max_log_level`](crate::writers::LogWriter::max_log_level)
+    /// of the writer.
+    /// A level filter for the name `_Default` applies to the primary writer
+    /// and replaces the module filters for it.
+    #[must_use]
+    pub fn writer_filter(&self, writer_name: &str) -> Option<LevelFilter> {
+        self.writer_filters.get(writer_name).copied()
+    }
+
+    // Sets the level filter for the additional writer with the given name,
+    // or removes it if None is given.
+    pub(crate) fn set_writer_filter(
+        &mut self,
+        writer_name: &str,
+        o_level_filter: Option<LevelFilter>,
+    ) {
+        match o_level_filter {
+            Some(level_filter) => {
+                self.writer_filters
+                    .insert(writer_name.to_string(), level_filter);
+            }
+            None => {
+                self.writer_filters.remove(writer_name);
+            }
+        }
+    }
+
     /// Provides a reference to the text filter.
     ///
     /// This method is only avaible if the default feature `textfilter` is not switched off.
@@ -437,6 +626,135 @@ impl std::convert::TryFrom<&String> for LogSpecification {
     }
 }
 
+// Splits a log spec into its module filters and the optional text filter.
+//
+// A '/' that starts a module filter is the begin of a regular expression, if it is closed
+// by a further '/' that is followed by '=', ',', or the end of the spec;
+// other '/'s separate the text filter.
+fn split_spec(spec: &str) -> (Vec<&str>, Option<&str>) {
+    let mut parts = Vec::new();
+    let mut rest = spec;
+    loop {
+        let start = rest.len() - rest.trim_start().len();
+        let skip = regex_part_len(&rest[start..]).map_or(0, |len| start + len);
+        let end = rest[skip..]
+            .find(&[',', '/'][..])
+            .map_or(rest.len(), |idx| skip + idx);
+        parts.push(&rest[..end]);
+        rest = &rest[end..];
+        match rest.chars().next() {
+            None => return (parts, None),
+            Some(',') => rest = &rest[1..],
+            Some(_) => return (parts, Some(&rest[1..])),
+        }
+    }
+}
+
+// Returns the length of the leading regex module filter (including its slashes), if any.
+fn regex_part_len(s: &str) -> Option<usize> {
+    if !s.starts_with('/') {
+        return None;
+    }
+    let len = s[1..].find('/')? + 2;
+    let after = s[len..].trim_start();
+    if after.is_empty() || after.starts_with('=') || after.starts_with(',') {
+        Some(len)
+    } else {
+        None
+    }
+}
+
+// Returns the pattern if the module name is a regular expression, like `/^my_app::db/`.
+fn module_regex_pattern(module_name: &str) -> Option<&str> {
+    if module_name.len() > 2 && module_name.starts_with('/') && module_name.ends_with('/') {
+        Some(&module_name[1..module_name.len() - 1])
+    } else {
+        None
+    }
+}
+
+// The structure of a specfile, in toml or yaml format.
+#[cfg(feature = "specfile_without_notification")]
+#[derive(Clone, Debug, serde_derive::Deserialize)]
+struct LogSpecFileFormat {
+    global_level: Option<String>,
+    global_pattern: Option<String>,
+    modules: Option<BTreeMap<String, String>>,
+    writers: Option<BTreeMap<String, String>>,
+}
+
+// The format of a specfile, which is determined by the extension of the file.
+#[cfg(feature = "specfile_without_notification")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum SpecfileFormat {
+    Toml,
+    #[cfg(feature = "specfile_yaml")]
+    Yaml,
+}
+#[cfg(feature = "specfile_without_notification")]
+impl SpecfileFormat {
+    pub(crate) fn of(specfile: &std::path::Path) -> Result<Self, FlexiLoggerError> {
+        match specfile.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("toml") => Ok(Self::Toml),
+            #[cfg(feature = "specfile_yaml")]
+            Some(ext) if ext == "yaml" || ext == "yml" => Ok(Self::Yaml),
+            #[cfg(not(feature = "specfile_yaml"))]
+            _ => Err(FlexiLoggerError::SpecfileExtension(
+                "only spec files with extension toml are supported",
+            )),
+            #[cfg(feature = "specfile_yaml")]
+            _ => Err(FlexiLoggerError::SpecfileExtension(
+                "only spec files with extension toml, yaml, or yml are supported",
+            )),
+        }
+    }
+
+    pub(crate) fn parse(self, s: &str) -> Result<LogSpecification, FlexiLoggerError> {
+        match self {
+            Self::Toml => LogSpecification::from_toml(s),
+            #[cfg(feature = "specfile_yaml")]
+            Self::Yaml => LogSpecification::from_yaml(s),
+        }
+    }
+
+    pub(crate) fn write(
+        self,
+        logspec: &LogSpecification,
+        w: &mut dyn std::io::Write,
+    ) -> Result<(), FlexiLoggerError> {
+        logspec
+            .to_specfile_impl(w, self)
+            .map_err(FlexiLoggerError::SpecfileIo)
+    }
+
+    // A top-level key-value pair.
+    fn pair(self, key: &str, value: &str) -> String {
+        match self {
+            Self::Toml => format!("{} = '{}'\n", key, value),
+            #[cfg(feature = "specfile_yaml")]
+            Self::Yaml => format!("{}: '{}'\n", key, value),
+        }
+    }
+
+    // The header of a section.
+    fn section(self, name: &str) -> String {
+        match self {
+            Self::Toml => format!("[{}]\n", name),
+            #[cfg(feature = "specfile_yaml")]
+            Self::Yaml => format!("{}:\n", name),
+        }
+    }
+
+    // A key-value pair within a section.
+    fn entry(self, key: &str, value: &str) -> String {
+        match self {
+            Self::Toml => format!("'{}' = '{}'\n", key, value),
+            #[cfg(feature = "specfile_yaml")]
+            Self::Yaml => format!("  '{}': '{}'\n", key, value),
+        }
+    }
+}
+
 fn push_err(s: &str, parse_errs: &mut String) {
     if !parse_errs.is_empty() {
         parse_errs.push_str("; ");
@@ -579,11 +897,12 @@ impl LogSpecBuilder {
     /// Creates a log specification without text filter.
     #[must_use]
     pub fn finalize(self) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.into_vec_module_filter(),
+        LogSpecification::from_parts(
+            self.module_filters.into_vec_module_filter(),
             #[cfg(feature = "textfilter")]
-            textfilter: None,
-        }
+            None,
+            &mut String::new(),
+        )
     }
 
     /// Creates a log specification with text filter.
@@ -592,20 +911,22 @@ impl LogSpecBuilder {
     #[cfg(feature = "textfilter")]
     #[must_use]
     pub fn finalize_with_textfilter(self, tf: Regex) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.into_vec_module_filter(),
-            textfilter: Some(Box::new(tf)),
-        }
+        LogSpecification::from_parts(
+            self.module_filters.into_vec_module_filter(),
+            Some(Box::new(tf)),
+            &mut String::new(),
+        )
     }
 
     /// Creates a log specification without being consumed.
     #[must_use]
     pub fn build(&self) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.clone().into_vec_module_filter(),
+        LogSpecification::from_parts(
+            self.module_filters.clone().into_vec_module_filter(),
             #[cfg(feature = "textfilter")]
-            textfilter: None,
-        }
+            None,
+            &mut String::new(),
+        )
     }
 
     /// Creates a log specification without being consumed, optionally with a text filter.
@@ -614,10 +935,11 @@ impl LogSpecBuilder {
     #[cfg(feature = "textfilter")]
     #[must_use]
     pub fn build_with_textfilter(&self, tf: Option<Regex>) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.clone().into_vec_module_filter(),
-            textfilter: tf.map(Box::new),
-        }
+        LogSpecification::from_parts(
+            self.module_filters.clone().into_vec_module_filter(),
+            tf.map(Box::new),
+            &mut String::new(),
+        )
     }
 }
 
@@ -641,13 +963,17 @@ trait LevelSort {
     fn level_sort(self) -> Vec<ModuleFilter>;
 }
 impl LevelSort for Vec<ModuleFilter> {
-    /// Sort the module filters by length of their name,
+    /// Sort the module filters by length of their name, after the regular expressions,
     /// this allows a little more efficient lookup at runtime.
     fn level_sort(mut self) -> Vec<ModuleFilter> {
         self.sort_by(|a, b| {
-            let a_len = a.module_name.as_ref().map_or(0, String::len);
-            let b_len = b.module_name.as_ref().map_or(0, String::len);
-            b_len.cmp(&a_len)
+            let a_key = a.module_name.as_deref().map_or((false, 0), |name| {
+                (module_regex_pattern(name).is_some(), name.len())
+            });
+            let b_key = b.module_name.as_deref().map_or((false, 0), |name| {
+                (module_regex_pattern(name).is_some(), name.len())
+            });
+            b_key.cmp(&a_key)
         });
         self
     }
@@ -782,6 +1108,43 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn parse_logging_spec_regex_module_filters() {
+        let spec =
+            LogSpecification::parse("info, my_app=warn, /^my_app::(db|cache)/=trace, /^a{1,3}$/")
+                .unwrap();
+        assert_eq!(
+            spec.to_string(),
+            "info,/^my_app::(db|cache)/=trace,/^a{1,3}$/=trace,my_app=warn"
+        );
+        assert!(spec.enabled(Level::Trace, "my_app::db"));
+        assert!(spec.enabled(Level::Trace, "my_app::cache::lru"));
+        assert!(!spec.enabled(Level::Info, "my_app::web"));
+        assert!(spec.enabled(Level::Warn, "my_app::web"));
+        assert!(spec.enabled(Level::Trace, "aaa"));
+        assert!(!spec.enabled(Level::Debug, "aaaa"));
+        assert!(spec.enabled(Level::Info, "other"));
+        assert!(spec.text_filter().is_none());
+
+        // regex module filters and text filter
+        let spec = LogSpecification::parse("/db$/=debug/a.c").unwrap();
+        assert_eq!(
+            spec.module_filters()[0].module_name,
+            Some("/db$/".to_string())
+        );
+        assert!(spec.enabled(Level::Debug, "my_app::db"));
+        assert!(!spec.enabled(Level::Debug, "my_app::db::pool"));
+        assert_eq!(spec.text_filter().unwrap().to_string(), "a.c");
+
+        // a slash that does not enclose a module filter still starts the text filter
+        let spec = LogSpecification::parse("info,/a.c").unwrap();
+        assert_eq!(spec.module_filters().len(), 1);
+        assert_eq!(spec.text_filter().unwrap().to_string(), "a.c");
+
+        assert!(LogSpecification::parse("info, /my_app::(db/=trace").is_err());
+    }
+
     #[test]
     fn reuse_logspec_builder() {
         let mut builder = crate::LogSpecBuilder::new();
@@ -990,6 +1353,58 @@ mod test_with_specfile {
         );
     }
 
+    #[test]
+    fn specfile_writers() {
+        let ls = LogSpecification::from_toml(
+            "global_level = 'info'\n\
+             \n\
+             [writers]\n\
+             'Alert' = 'warn'\n\
+             'Sec' = 'trace'\n\
+             ",
+        )
+        .unwrap();
+        assert_eq!(ls.writer_filter("Alert"), Some(log::LevelFilter::Warn));
+        assert_eq!(ls.writer_filter("Sec"), Some(log::LevelFilter::Trace));
+        assert_eq!(ls.writer_filter("Other"), None);
+        assert_eq!(ls.max_level(), log::LevelFilter::Trace);
+
+        let mut toml = Vec::new();
+        ls.to_toml(&mut toml).unwrap();
+        let ls2 = LogSpecification::from_toml(String::from_utf8(toml).unwrap()).unwrap();
+        assert_eq!(ls2.module_filters, ls.module_filters);
+        assert_eq!(ls2.writer_filters, ls.writer_filters);
+    }
+
+    #[cfg(feature = "specfile_yaml")]
+    #[test]
+    fn specfile_yaml() {
+        let ls_yaml = LogSpecification::from_yaml(
+            "global_level: 'info'\n\
+             modules:\n  \
+               'mod1::mod2': 'debug'\n  \
+               '/^mod4::(db|cache)/': 'trace'\n\
+             writers:\n  \
+               'Alert': 'warn'\n\
+             ",
+        )
+        .unwrap();
+        let ls_spec =
+            LogSpecification::parse("info, mod1::mod2 = debug, /^mod4::(db|cache)/ = trace")
+                .unwrap();
+        assert_eq!(ls_yaml.module_filters, ls_spec.module_filters);
+        assert_eq!(ls_yaml.writer_filter("Alert"), Some(log::LevelFilter::Warn));
+
+        // the generated file is valid yaml, also if all sections are commented out
+        for ls in &[ls_yaml, LogSpecification::off()] {
+            let mut yaml = Vec::new();
+            ls.to_yaml(&mut yaml).unwrap();
+            let ls2 = LogSpecification::from_yaml(String::from_utf8(yaml).unwrap()).unwrap();
+            assert_eq!(ls2.module_filters, ls.module_filters);
+            assert_eq!(ls2.writer_filters, ls.writer_filters);
+        }
+    }
+
     #[cfg(feature = "specfile_without_notification")]
     fn compare_specs(toml: &str, spec_string: &str) {
         let ls_toml = LogSpecification::from_toml(toml).unwrap();
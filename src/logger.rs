@@ -1,18 +1,36 @@
+use crate::counters::RecordCounts;
 use crate::filter::LogLineFilter;
-use crate::flexi_logger::FlexiLogger;
-use crate::formats::default_format;
+use crate::flexi_logger::{read_other_writers, FlexiLogger};
+use crate::formats::{default_format, stderr_goes_to_journal, systemd_format};
 #[cfg(feature = "atty")]
-use crate::formats::AdaptiveFormat;
+use crate::formats::{AdaptiveFormat, ColorChoice};
+use crate::lifecycle::{startup_banner, write_lifecycle_record, ShutdownSummary};
+#[cfg(feature = "specfile_without_notification")]
+use crate::log_specification::SpecfileFormat;
+use crate::logger_handle::register_active_handle;
 #[cfg(feature = "specfile_without_notification")]
 use crate::logger_handle::LogSpecSubscriber;
-use crate::primary_writer::PrimaryWriter;
+use crate::primary_writer::{PrimaryWriter, StderrDuplication};
+use crate::rate_limit::RateLimiter;
+use crate::sampling::Sampler;
+use crate::shutdown_policy::ShutdownGate;
+use crate::timing::TimingSampler;
 #[cfg(feature = "specfile")]
-use crate::util::{eprint_err, ERRCODE};
-use crate::writers::{FileLogWriter, FileLogWriterBuilder, LogWriter};
+use crate::util::eprint_err;
+use crate::util::{
+    eprint_msg, Redaction, ERRCODE, PLATFORM_LINE_ENDING, UNIX_LINE_ENDING, WINDOWS_LINE_ENDING,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::writers::{FileLogWriter, FileLogWriterBuilder, LogFileSystem, RetryPolicy};
+use crate::writers::{IoWriter, LogWriter};
+#[cfg(feature = "admin_socket")]
+use crate::AdminSocket;
 use crate::WriteMode;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{Cleanup, CleanupEvent, Criterion, FileSpec, Naming};
 use crate::{
-    Cleanup, Criterion, FileSpec, FlexiLoggerError, FormatFunction, LogSpecification, LoggerHandle,
-    Naming,
+    ErrorChannel, FlexiLoggerError, FormatDetails, FormatFunction, LevelFilter, LogSpecification,
+    LoggerHandle, RateLimit, ShutdownPolicy, TimestampConfig,
 };
 
 #[cfg(feature = "specfile")]
@@ -20,12 +38,23 @@ use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
 use std::collections::HashMap;
 #[cfg(feature = "specfile_without_notification")]
 use std::io::Read;
-#[cfg(feature = "specfile_without_notification")]
+#[cfg(any(feature = "config_file", feature = "specfile_without_notification"))]
 use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
 use std::path::PathBuf;
+#[cfg(feature = "specfile_without_notification")]
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "specfile")]
+use std::sync::atomic::Ordering;
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+// The name of the additional writer that is registered by `also_log_errors_to`.
+#[cfg(not(target_arch = "wasm32"))]
+const ERRORS_WRITER: &str = "_Errors";
 
 /// The entry-point for using `flexi_logger`.
 ///
@@ -58,23 +87,58 @@ pub struct Logger {
     spec: LogSpecification,
     log_target: LogTarget,
     duplicate_err: Duplicate,
+    o_duplicate_err_spec: Option<LogSpecification>,
+    o_duplicate_err_rate_limit: Option<RateLimit>,
     duplicate_out: Duplicate,
+    #[cfg(not(target_arch = "wasm32"))]
     format_for_file: FormatFunction,
     format_for_stderr: FormatFunction,
     format_for_stdout: FormatFunction,
     format_for_writer: FormatFunction,
+    // are resolved to format_for_stderr and format_for_stdout when the logger is built
+    #[cfg(feature = "atty")]
+    o_adaptive_format_for_stderr: Option<AdaptiveFormat>,
+    #[cfg(feature = "atty")]
+    o_adaptive_format_for_stdout: Option<AdaptiveFormat>,
+    #[cfg(feature = "atty")]
+    color_choice: ColorChoice,
     #[cfg(feature = "colors")]
     o_palette: Option<String>,
+    timestamp_config: TimestampConfig,
+    format_details: FormatDetails,
+    #[cfg(not(target_arch = "wasm32"))]
     flush_interval: std::time::Duration,
+    #[cfg(not(target_arch = "wasm32"))]
     flwb: FileLogWriterBuilder,
     other_writers: HashMap<String, Box<dyn LogWriter>>,
-    filter: Option<Box<dyn LogLineFilter + Send + Sync>>,
+    formats_for_other_writers: HashMap<String, FormatFunction>,
+    routes: Vec<(String, String)>,
+    broadcasts: Vec<(String, LevelFilter)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    o_errors_file_spec: Option<FileSpec>,
+    filters: Vec<Box<dyn LogLineFilter + Send + Sync>>,
+    o_sample_timings: Option<usize>,
+    shutdown_policy: ShutdownPolicy,
+    o_rate_limit: Option<RateLimit>,
+    sampler: Sampler,
+    o_redaction: Option<Arc<Redaction>>,
+    o_max_record_len: Option<usize>,
+    // for the output to stdout and stderr; the file log writer has its own
+    std_streams_line_ending: &'static [u8],
+    error_channel: ErrorChannel,
+    log_panics: bool,
+    o_startup_banner: Option<String>,
+    shutdown_summary: bool,
+    #[cfg(feature = "admin_socket")]
+    o_admin_socket: Option<AdminSocket>,
 }
 
 enum LogTarget {
     StdErr,
     StdOut,
     Multi(bool, Option<Box<dyn LogWriter>>),
+    #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+    BrowserConsole,
 }
 
 /// Create a Logger instance and define how to access the (initial)
@@ -118,6 +182,70 @@ impl Logger {
         Ok(Self::from_spec_and_errs(LogSpecification::env_or_parse(s)?))
     }
 
+    /// Creates a Logger that is configured with the given toml file,
+    /// so that the logging behavior of a deployment can be changed without recompiling.
+    ///
+    /// All entries are optional, the following example shows the available settings:
+    ///
+    /// ```toml
+    /// # the log specification, see LogSpecification; default: "info"
+    /// spec = "info, my_crate::db = debug"
+    ///
+    /// # "stderr", "stdout", "file", or "nothing";
+    /// # default: "file" if there is a section [file], otherwise "stderr"
+    /// log_target = "file"
+    ///
+    /// # "none", "error", "warn", "info", "debug", "trace", or "all"; default: "none"
+    /// duplicate_to_stderr = "warn"
+    /// duplicate_to_stdout = "none"
+    ///
+    /// # "default", "detailed", "opt", "with_thread", "json", or "logfmt"; default: "default"
+    /// format_for_files = "detailed"
+    ///
+    /// # "direct", "buffer_and_flush", "buffer_dont_flush", or "async"; default: "direct"
+    /// write_mode = "buffer_and_flush"
+    ///
+    /// [file]
+    /// directory = "log_files"
+    /// basename = "my_prog"
+    /// discriminant = "instance_1"
+    /// suffix = "log"
+    /// use_timestamp = false
+    /// append = true
+    /// create_symlink = "current_log"
+    ///
+    /// [file.rotation]
+    /// size = 10_000_000       # rotate when the file has this size (in bytes),
+    /// age = "day"             # or when a new "day", "hour", "minute", or "second" begins
+    /// naming = "numbers"      # or "timestamps"
+    /// keep_log_files = 7      # default: all files are kept
+    /// keep_compressed_files = 30
+    ///
+    /// # additional file writers, which use the rotation and write mode of the [file] section;
+    /// # log lines are sent to them with targets like "{alerts}", see Logger::add_writer
+    /// [writers.alerts]
+    /// directory = "log_files"
+    /// discriminant = "alerts"
+    /// use_timestamp = false
+    /// also_send = "warn"      # records of this level and above, see Logger::also_send_to
+    /// ```
+    ///
+    /// The Logger can be further configured with its methods before it is started.
+    /// See [`LoggerHandle::reload_config`] for changing the configuration at runtime.
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::ConfigFileIo`] if the file cannot be read,
+    /// [`FlexiLoggerError::ConfigFileToml`] if it is not valid toml or has unknown entries,
+    /// [`FlexiLoggerError::ConfigFileValue`] if it contains an invalid value,
+    /// [`FlexiLoggerError::Parse`] if the log specification is malformed,
+    /// and `FlexiLoggerError::OutputIo` if an additional writer cannot be created.
+    #[cfg_attr(docsrs, doc(cfg(feature = "config_file")))]
+    #[cfg(feature = "config_file")]
+    pub fn try_from_config_file<P: AsRef<Path>>(path: P) -> Result<Self, FlexiLoggerError> {
+        crate::config_file::LoggerConfig::read(path.as_ref())?.into_logger()
+    }
+
     fn from_spec_and_errs(spec: LogSpecification) -> Self {
         #[cfg(feature = "colors")]
         #[cfg(windows)]
@@ -129,34 +257,55 @@ impl Logger {
             spec,
             log_target: LogTarget::StdErr,
             duplicate_err: Duplicate::None,
+            o_duplicate_err_spec: None,
+            o_duplicate_err_rate_limit: None,
             duplicate_out: Duplicate::None,
+            #[cfg(not(target_arch = "wasm32"))]
             format_for_file: default_format,
 
-            #[cfg(feature = "colors")]
-            format_for_stdout: AdaptiveFormat::Default.format_function(if cfg!(feature = "atty") {
-                atty::is(atty::Stream::Stdout)
-            } else {
-                false
-            }),
-            #[cfg(feature = "colors")]
-            format_for_stderr: AdaptiveFormat::Default.format_function(if cfg!(feature = "atty") {
-                atty::is(atty::Stream::Stderr)
-            } else {
-                false
-            }),
-
-            #[cfg(not(feature = "colors"))]
             format_for_stdout: default_format,
-            #[cfg(not(feature = "colors"))]
             format_for_stderr: default_format,
-
             format_for_writer: default_format,
+
+            #[cfg(feature = "colors")]
+            o_adaptive_format_for_stderr: Some(AdaptiveFormat::Default),
+            #[cfg(feature = "colors")]
+            o_adaptive_format_for_stdout: Some(AdaptiveFormat::Default),
+            #[cfg(all(feature = "atty", not(feature = "colors")))]
+            o_adaptive_format_for_stderr: None,
+            #[cfg(all(feature = "atty", not(feature = "colors")))]
+            o_adaptive_format_for_stdout: None,
+            #[cfg(feature = "atty")]
+            color_choice: ColorChoice::Auto,
+
             #[cfg(feature = "colors")]
             o_palette: None,
+            timestamp_config: TimestampConfig::default(),
+            format_details: FormatDetails::default(),
+            #[cfg(not(target_arch = "wasm32"))]
             flush_interval: Duration::from_secs(0),
+            #[cfg(not(target_arch = "wasm32"))]
             flwb: FileLogWriter::builder(FileSpec::default()),
             other_writers: HashMap::<String, Box<dyn LogWriter>>::new(),
-            filter: None,
+            formats_for_other_writers: HashMap::<String, FormatFunction>::new(),
+            routes: Vec::new(),
+            broadcasts: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            o_errors_file_spec: None,
+            filters: Vec::new(),
+            o_sample_timings: None,
+            shutdown_policy: ShutdownPolicy::DropWithCounter,
+            o_rate_limit: None,
+            sampler: Sampler::default(),
+            o_redaction: None,
+            o_max_record_len: None,
+            std_streams_line_ending: UNIX_LINE_ENDING,
+            error_channel: ErrorChannel::StdErr,
+            log_panics: false,
+            o_startup_banner: None,
+            shutdown_summary: false,
+            #[cfg(feature = "admin_socket")]
+            o_admin_socket: None,
         }
     }
 }
@@ -184,6 +333,7 @@ impl Logger {
     ///  e.g. `myprog_2015-07-08_10-44-11.log`.
     ///
     /// You can duplicate to stdout and stderr, and you can add additional writers.
+    #[cfg(not(target_arch = "wasm32"))]
     #[must_use]
     pub fn log_to_file(mut self, file_spec: FileSpec) -> Self {
         self.log_target = LogTarget::Multi(true, None);
@@ -200,10 +350,55 @@ impl Logger {
         self
     }
 
+    /// Log is written to the console of the browser, via `web_sys::console`.
+    ///
+    /// The log lines are formatted with the format for writers
+    /// (see [`Logger::format_for_writer`]); the level of each record selects the console
+    /// method (`error`, `warn`, `info`, or `debug`).
+    ///
+    /// Only available on `wasm32`, where the file log writer and the background threads
+    /// are not available.
+    #[cfg_attr(docsrs, doc(cfg(feature = "wasm_console")))]
+    #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+    #[must_use]
+    pub fn log_to_browser_console(mut self) -> Self {
+        self.log_target = LogTarget::BrowserConsole;
+        self
+    }
+
+    /// Log is written to the provided implementation of [`std::io::Write`],
+    /// e.g. a text pane of a GUI or a stream to a remote service,
+    /// without the need to implement [`LogWriter`].
+    ///
+    /// The log lines are formatted with the format for writers
+    /// (see [`Logger::format_for_writer`]) and handed over by an [`IoWriter`];
+    /// the output is flushed like the output of other writers,
+    /// e.g. with [`LoggerHandle::flush`], or regularly if the chosen [`WriteMode`]
+    /// has a flush interval.
+    ///
+    /// You can duplicate to stdout and stderr, and you can add additional writers.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let _logger = Logger::try_with_str("info")?
+    ///     .log_to_io_writer(Box::new(std::io::sink()))
+    ///     .start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn log_to_io_writer(self, w: Box<dyn std::io::Write + Send>) -> Self {
+        self.log_to_writer(Box::new(IoWriter::new(w)))
+    }
+
     /// Log is written to a file, as with [`Logger::log_to_file`], _and_ to an alternative
     /// [`LogWriter`] implementation.
     ///
     /// And you can duplicate to stdout and stderr, and you can add additional writers.
+    #[cfg(not(target_arch = "wasm32"))]
     #[must_use]
     pub fn log_to_file_and_writer(mut self, file_spec: FileSpec, w: Box<dyn LogWriter>) -> Self {
         self.log_target = LogTarget::Multi(true, Some(w));
@@ -230,6 +425,7 @@ impl Logger {
 
     /// Makes the logger print an info message to stdout with the name of the logfile
     /// when a logfile is opened for writing.
+    #[cfg(not(target_arch = "wasm32"))]
     #[must_use]
     pub fn print_message(mut self) -> Self {
         self.flwb = self.flwb.print_message();
@@ -238,6 +434,9 @@ impl Logger {
 
     /// Makes the logger write messages with the specified minimum severity additionally to stderr.
     ///
+    /// The duplicated messages are formatted independently of the messages that are written
+    /// to files, see [`Logger::format_for_stderr`].
+    ///
     /// Does not work with [`Logger::log_to_stdout`] or [`Logger::log_to_stderr`].
     #[must_use]
     pub fn duplicate_to_stderr(mut self, dup: Duplicate) -> Self {
@@ -245,8 +444,43 @@ impl Logger {
         self
     }
 
+    /// Makes the logger write the messages that the given log specification lets pass
+    /// additionally to stderr.
+    ///
+    /// Other than with [`Logger::duplicate_to_stderr`], the levels can be chosen per module,
+    /// e.g. with `warn, my_app::api = info`, so that the console is not flooded by chatty
+    /// dependencies whose messages are still wanted in the log file.
+    /// Only the module filters of the given log specification are evaluated, after the
+    /// messages have passed the log specification of the logger.
+    ///
+    /// This setting overrides [`Logger::duplicate_to_stderr`].
+    ///
+    /// Does not work with [`Logger::log_to_stdout`] or [`Logger::log_to_stderr`].
+    #[must_use]
+    pub fn duplicate_to_stderr_with_spec(mut self, spec: LogSpecification) -> Self {
+        self.o_duplicate_err_spec = Some(spec);
+        self
+    }
+
+    /// Limits the number of messages that are duplicated to stderr per second,
+    /// to protect interactive terminals during log storms.
+    ///
+    /// The limit applies only to the duplication (see [`Logger::duplicate_to_stderr`]
+    /// and [`Logger::duplicate_to_stderr_with_spec`]); the other outputs, like the log file,
+    /// still receive all messages.
+    /// Like with [`Logger::rate_limit`], a single warning on stderr tells how many messages
+    /// were not duplicated, when the second in which the limit was exceeded has ended.
+    #[must_use]
+    pub fn rate_limit_duplication_to_stderr(mut self, rate_limit: RateLimit) -> Self {
+        self.o_duplicate_err_rate_limit = Some(rate_limit);
+        self
+    }
+
     /// Makes the logger write messages with the specified minimum severity additionally to stdout.
     ///
+    /// The duplicated messages are formatted independently of the messages that are written
+    /// to files, see [`Logger::format_for_stdout`].
+    ///
     /// Does not work with [`Logger::log_to_stdout`] or [`Logger::log_to_stderr`].
     #[must_use]
     pub fn duplicate_to_stdout(mut self, dup: Duplicate) -> Self {
@@ -271,11 +505,20 @@ impl Logger {
     /// By default, [`default_format`] is used for output to files and to custom writers,
     /// and [`AdaptiveFormat::Default`] is used for output to `stderr` and `stdout`.
     /// If the feature `colors` is switched off, [`default_format`] is used for all outputs.
+    #[must_use]
     pub fn format(mut self, format: FormatFunction) -> Self {
-        self.format_for_file = format;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.format_for_file = format;
+        }
         self.format_for_stderr = format;
         self.format_for_stdout = format;
         self.format_for_writer = format;
+        #[cfg(feature = "atty")]
+        {
+            self.o_adaptive_format_for_stderr = None;
+            self.o_adaptive_format_for_stdout = None;
+        }
         self
     }
 
@@ -283,6 +526,7 @@ impl Logger {
     /// that are written to files.
     ///
     /// Regarding the default, see [`Logger::format`].
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn format_for_files(mut self, format: FormatFunction) -> Self {
         self.format_for_file = format;
         self
@@ -292,20 +536,44 @@ impl Logger {
     /// that are written to stderr.
     ///
     /// Regarding the default, see [`Logger::format`].
+    #[must_use]
     pub fn format_for_stderr(mut self, format_function: FormatFunction) -> Self {
         self.format_for_stderr = format_function;
+        #[cfg(feature = "atty")]
+        {
+            self.o_adaptive_format_for_stderr = None;
+        }
         self
     }
 
     /// Makes the logger use the specified format for messages that are written to `stderr`.
-    /// Coloring is used if `stderr` is a tty.
+    /// Coloring is used if `stderr` is a tty, see [`Logger::color_choice`].
     ///
     /// Regarding the default, see [`Logger::format`].
     #[cfg_attr(docsrs, doc(cfg(feature = "atty")))]
     #[cfg(feature = "atty")]
     #[must_use]
     pub fn adaptive_format_for_stderr(mut self, adaptive_format: AdaptiveFormat) -> Self {
-        self.format_for_stderr = adaptive_format.format_function(atty::is(atty::Stream::Stderr));
+        self.o_adaptive_format_for_stderr = Some(adaptive_format);
+        self
+    }
+
+    /// Makes the logger use [`systemd_format`](crate::systemd_format)
+    /// for messages that are written to `stderr`,
+    /// if the program runs as a systemd service with `stderr` being connected to the journal
+    /// (which is detected by comparing the device and inode number in the environment variable
+    /// `JOURNAL_STREAM` with those of `stderr`; a child process that inherits the variable,
+    /// but not the stream, is thus not affected).
+    /// On non-unix platforms the format for `stderr` is never changed.
+    ///
+    /// This way journald assigns the correct priorities to the log records,
+    /// without the need for a native journal writer.
+    /// Otherwise the format for `stderr` remains unchanged.
+    #[must_use]
+    pub fn journald_aware_stderr(mut self) -> Self {
+        if stderr_goes_to_journal() {
+            self = self.format_for_stderr(systemd_format);
+        }
         self
     }
 
@@ -313,33 +581,77 @@ impl Logger {
     /// that are written to stdout.
     ///
     /// Regarding the default, see [`Logger::format`].
+    #[must_use]
     pub fn format_for_stdout(mut self, format_function: FormatFunction) -> Self {
         self.format_for_stdout = format_function;
+        #[cfg(feature = "atty")]
+        {
+            self.o_adaptive_format_for_stdout = None;
+        }
         self
     }
 
     /// Makes the logger use the specified format for messages that are written to `stdout`.
-    /// Coloring is used if `stdout` is a tty.
+    /// Coloring is used if `stdout` is a tty, see [`Logger::color_choice`].
     ///
     /// Regarding the default, see [`Logger::format`].
     #[cfg_attr(docsrs, doc(cfg(feature = "atty")))]
     #[cfg(feature = "atty")]
     #[must_use]
     pub fn adaptive_format_for_stdout(mut self, adaptive_format: AdaptiveFormat) -> Self {
-        self.format_for_stdout = adaptive_format.format_function(atty::is(atty::Stream::Stdout));
+        self.o_adaptive_format_for_stdout = Some(adaptive_format);
+        self
+    }
+
+    /// Decides whether the adaptive formats for `stderr` and `stdout`
+    /// (see [`Logger::adaptive_format_for_stderr`] and [`Logger::adaptive_format_for_stdout`],
+    /// and the default formats if the feature `colors` is used) use colors.
+    ///
+    /// By default, [`ColorChoice::Auto`] is used, which honors the environment variables
+    /// `NO_COLOR`, `CLICOLOR_FORCE`, and `CLICOLOR`, and otherwise uses colors only
+    /// if the output goes to a tty.
+    /// On windows, if the feature `colors` is used, the processing of ANSI escape sequences
+    /// is switched on for the console, so that the colors are also shown in `cmd` and PowerShell.
+    #[cfg_attr(docsrs, doc(cfg(feature = "atty")))]
+    #[cfg(feature = "atty")]
+    #[must_use]
+    pub fn color_choice(mut self, color_choice: ColorChoice) -> Self {
+        self.color_choice = color_choice;
         self
     }
 
-    /// Allows specifying a format function for an additional writer.
-    /// Note that it is up to the implementation of the additional writer
+    /// Allows specifying a format function for the writer that is given to
+    /// [`Logger::log_to_writer`] or [`Logger::log_to_file_and_writer`].
+    /// Note that it is up to the implementation of the writer
     /// whether it evaluates this setting or not.
     ///
+    /// For the writers that are registered with [`Logger::add_writer`],
+    /// use [`Logger::format_for_additional_writer`].
+    ///
     /// Regarding the default, see [`Logger::format`].
     pub fn format_for_writer(mut self, format: FormatFunction) -> Self {
         self.format_for_writer = format;
         self
     }
 
+    /// Allows specifying a format function for the writer that is registered
+    /// with [`Logger::add_writer`] under the given target name.
+    /// Note that it is up to the implementation of the writer
+    /// whether it evaluates this setting or not.
+    ///
+    /// Writers for which no format function is specified here keep the format
+    /// they were created with.
+    #[must_use]
+    pub fn format_for_additional_writer<S: Into<String>>(
+        mut self,
+        target_name: S,
+        format: FormatFunction,
+    ) -> Self {
+        self.formats_for_other_writers
+            .insert(target_name.into(), format);
+        self
+    }
+
     /// Sets the color palette for function [`style`](crate::style), which is used in the
     /// provided coloring format functions.
     ///
@@ -371,6 +683,27 @@ impl Logger {
         self
     }
 
+    /// Defines how the provided format functions write timestamps, and which offset the
+    /// timestamps of [`DeferredNow`](crate::DeferredNow) have, see [`TimestampConfig`].
+    ///
+    /// The config is applied to all loggers, the last built logger wins.
+    /// It has no effect on the timestamps that are used for file rotation and in file names.
+    #[must_use]
+    pub fn timestamp_config(mut self, timestamp_config: TimestampConfig) -> Self {
+        self.timestamp_config = timestamp_config;
+        self
+    }
+
+    /// Makes the provided format functions write additional details into each log line,
+    /// like the process id or the thread name, see [`FormatDetails`].
+    ///
+    /// The details are applied to all loggers, the last built logger wins.
+    #[must_use]
+    pub fn format_details(mut self, format_details: FormatDetails) -> Self {
+        self.format_details = format_details;
+        self
+    }
+
     /// Prevent indefinite growth of the log file by applying file rotation
     /// and a clean-up strategy for older log files.
     ///
@@ -401,15 +734,21 @@ impl Logger {
     /// ## Parameters
     ///
     /// `criterion` defines *when* the log file should be rotated, based on its size or age.
-    /// See [`Criterion`] for details.
+    /// See [`Criterion`] for details; an [`Age`](crate::Age) can be used directly.
     ///
     /// `naming` defines the naming convention for the rotated log files.
     /// See [`Naming`] for details.
     ///
     /// `cleanup` defines the strategy for dealing with older files.
     /// See [`Cleanup`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
     #[must_use]
-    pub fn rotate(mut self, criterion: Criterion, naming: Naming, cleanup: Cleanup) -> Self {
+    pub fn rotate<C: Into<Criterion>>(
+        mut self,
+        criterion: C,
+        naming: Naming,
+        cleanup: Cleanup,
+    ) -> Self {
         self.flwb = self.flwb.rotate(criterion, naming, cleanup);
         self
     }
@@ -423,6 +762,7 @@ impl Logger {
     ///
     /// If you call this method with `use_background_thread = false`,
     /// the cleanup is done synchronously.
+    #[cfg(not(target_arch = "wasm32"))]
     #[must_use]
     pub fn cleanup_in_background_thread(mut self, use_background_thread: bool) -> Self {
         self.flwb = self
@@ -431,12 +771,449 @@ impl Logger {
         self
     }
 
+    /// Makes the cleanup additionally run periodically, with the given interval,
+    /// and not only when a rotation happens.
+    ///
+    /// See [`FileLogWriterBuilder::cleanup_interval`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn cleanup_interval(mut self, interval: Duration) -> Self {
+        self.flwb = self.flwb.cleanup_interval(interval);
+        self
+    }
+
+    /// Makes a background thread check periodically, with the given interval,
+    /// if a rotation is due, and do it.
+    ///
+    /// See [`FileLogWriterBuilder::rotation_check_interval`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn rotation_check_interval(mut self, interval: Duration) -> Self {
+        self.flwb = self.flwb.rotation_check_interval(interval);
+        self
+    }
+
+    /// Determines how rotation by age handles a clock that is set backwards.
+    ///
+    /// See [`FileLogWriterBuilder::backwards_clock_tolerance`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn backwards_clock_tolerance(mut self, tolerance: Duration) -> Self {
+        self.flwb = self.flwb.backwards_clock_tolerance(tolerance);
+        self
+    }
+
+    /// Makes rotated log files be moved into the given directory.
+    ///
+    /// See [`FileLogWriterBuilder::rotated_files_directory`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn rotated_files_directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.flwb = self.flwb.rotated_files_directory(directory);
+        self
+    }
+
+    /// Sets the string that separates the file stem from the index in the names
+    /// of numbered log files.
+    ///
+    /// See [`FileLogWriterBuilder::number_separator`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn number_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.flwb = self.flwb.number_separator(separator);
+        self
+    }
+
+    /// Sets the minimal number of digits of the index in the names of numbered log files.
+    ///
+    /// See [`FileLogWriterBuilder::number_width`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn number_width(mut self, width: usize) -> Self {
+        self.flwb = self.flwb.number_width(width);
+        self
+    }
+
+    /// Sets the index of the first numbered log file.
+    ///
+    /// See [`FileLogWriterBuilder::first_number`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn first_number(mut self, start: u32) -> Self {
+        self.flwb = self.flwb.first_number(start);
+        self
+    }
+
+    /// Makes failed writes to the log file be retried according to the given [`RetryPolicy`].
+    ///
+    /// See [`FileLogWriterBuilder::retry_policy`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.flwb = self.flwb.retry_policy(retry_policy);
+        self
+    }
+
+    /// Limits the disk space that the log files may use.
+    ///
+    /// See [`FileLogWriterBuilder::max_total_bytes_on_disk`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn max_total_bytes_on_disk(mut self, max_bytes: u64) -> Self {
+        self.flwb = self.flwb.max_total_bytes_on_disk(max_bytes);
+        self
+    }
+
+    /// Makes log records be written to stderr while writing to the log file is suspended.
+    ///
+    /// See [`FileLogWriterBuilder::duplicate_to_stderr_while_suspended`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn duplicate_to_stderr_while_suspended(mut self, duplicate: bool) -> Self {
+        self.flwb = self.flwb.duplicate_to_stderr_while_suspended(duplicate);
+        self
+    }
+
+    /// Makes the log file be created only when the first record with the given level
+    /// or a more severe level needs to be written.
+    ///
+    /// See [`FileLogWriterBuilder::create_file_lazily`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn create_file_lazily(mut self, level: LevelFilter) -> Self {
+        self.flwb = self.flwb.create_file_lazily(level);
+        self
+    }
+
+    /// Makes disk space be reserved for each log file when it is created.
+    ///
+    /// See [`FileLogWriterBuilder::preallocate`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "preallocate")))]
+    #[cfg(feature = "preallocate")]
+    #[must_use]
+    pub fn preallocate(mut self, bytes: u64) -> Self {
+        self.flwb = self.flwb.preallocate(bytes);
+        self
+    }
+
+    /// Makes the log files be managed with the given [`LogFileSystem`].
+    ///
+    /// See [`FileLogWriterBuilder::file_system`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn file_system(mut self, file_system: Arc<dyn LogFileSystem>) -> Self {
+        self.flwb = self.flwb.file_system(file_system);
+        self
+    }
+
+    /// Sets the level with which older log files are compressed.
+    ///
+    /// See [`FileLogWriterBuilder::compression_level`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.flwb = self.flwb.compression_level(level);
+        self
+    }
+
+    /// Sets the maximal number of threads that compress older log files concurrently.
+    ///
+    /// See [`FileLogWriterBuilder::compression_threads`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compression_threads(mut self, threads: usize) -> Self {
+        self.flwb = self.flwb.compression_threads(threads);
+        self
+    }
+
+    /// Makes the shutdown rotate the current log file and compress it immediately.
+    ///
+    /// See [`FileLogWriterBuilder::compress_on_shutdown`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compress_on_shutdown(mut self) -> Self {
+        self.flwb = self.flwb.compress_on_shutdown();
+        self
+    }
+
+    /// Makes the log files be written compressed, with gzip.
+    ///
+    /// See [`FileLogWriterBuilder::compress_while_writing`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compress_while_writing(mut self) -> Self {
+        self.flwb = self.flwb.compress_while_writing();
+        self
+    }
+
+    /// Registers a callback that is called whenever the cleanup compresses, deletes,
+    /// or moves an older log file.
+    ///
+    /// The callback is also used for the files of additional writers that share the settings
+    /// of the main log file, like the one of [`Logger::also_log_errors_to`].
+    /// See [`FileLogWriterBuilder::on_cleanup`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn on_cleanup<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&CleanupEvent) + Send + Sync + 'static,
+    {
+        self.flwb = self.flwb.on_cleanup(callback);
+        self
+    }
+
     /// Apply the provided filter before really writing log lines.
     ///
+    /// Can be called repeatedly to stack several filters;
+    /// they are applied in the order in which they were added.
+    ///
     /// See the documentation of module [`filter`](crate::filter) for a usage example.
     #[must_use]
     pub fn filter(mut self, filter: Box<dyn LogLineFilter + Send + Sync>) -> Self {
-        self.filter = Some(filter);
+        self.filters.push(filter);
+        self
+    }
+
+    /// Measures for every `every_nth` log record the time that is spent
+    /// in formatting and writing it to the primary output.
+    ///
+    /// The collected statistics can be retrieved with
+    /// [`LoggerHandle::timing_metrics`](crate::LoggerHandle::timing_metrics).
+    ///
+    /// With asynchronous [`WriteMode`]s only the hand-over to the output thread is measured.
+    #[must_use]
+    pub fn sample_timings(mut self, every_nth: usize) -> Self {
+        self.o_sample_timings = Some(every_nth);
+        self
+    }
+
+    /// Defines what happens with log records that are emitted after the writers were shut down
+    /// with [`LoggerHandle::shutdown`], see [`ShutdownPolicy`].
+    ///
+    /// Defaults to [`ShutdownPolicy::DropWithCounter`].
+    #[must_use]
+    pub fn shutdown_policy(mut self, shutdown_policy: ShutdownPolicy) -> Self {
+        self.shutdown_policy = shutdown_policy;
+        self
+    }
+
+    /// Limits the number of records that are written per second, see [`RateLimit`].
+    ///
+    /// By default, no limit is applied.
+    #[must_use]
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.o_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Keeps only a random fraction of the records with the given level and all
+    /// more verbose levels, and discards the others.
+    ///
+    /// This allows leaving e.g. trace logging enabled in production at a tolerable volume.
+    /// The decision is cheap, and is taken before a record is formatted;
+    /// it applies to all writers, and only to the records that pass the log specification.
+    ///
+    /// `fraction` is the share of the records that are kept, from `0.0` (none) to `1.0` (all).
+    ///
+    /// ```rust
+    /// # use flexi_logger::{Logger, LevelFilter};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // keep all records up to level info, and 1% of the debug and trace records
+    /// let _logger = Logger::try_with_str("trace")?
+    ///     .sample(LevelFilter::Debug, 0.01)
+    ///     .start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// By default, no records are discarded.
+    #[must_use]
+    pub fn sample(mut self, level: LevelFilter, fraction: f64) -> Self {
+        self.sampler.add_rule(String::new(), level, fraction);
+        self
+    }
+
+    /// Like [`Logger::sample`], but applies only to the records whose target starts with
+    /// the given prefix.
+    ///
+    /// For each record, only the rule with the longest matching target prefix is applied,
+    /// so a rule for a target overrides the rule from [`Logger::sample`].
+    #[must_use]
+    pub fn sample_target<S: Into<String>>(
+        mut self,
+        target: S,
+        level: LevelFilter,
+        fraction: f64,
+    ) -> Self {
+        self.sampler.add_rule(target.into(), level, fraction);
+        self
+    }
+
+    /// Registers a function that is applied to every formatted log line before it is written,
+    /// e.g. to mask secrets, tokens, or personal data.
+    ///
+    /// The function gets the formatted log line, without line ending, and can modify it in place.
+    ///
+    /// The redaction is applied uniformly to the output of the provided writers: to files
+    /// (also those of additional [`FileLogWriter`]s), to stdout and stderr (also when
+    /// log lines are duplicated there), and to the output of
+    /// [`NetworkWriter`](crate::writers::NetworkWriter).
+    /// Other writers that do their own formatting,
+    /// like your own implementations of [`LogWriter`], are not affected.
+    ///
+    /// The redaction is applied to all loggers, the last started (or built) logger wins;
+    /// a logger that fails to start does not change it.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let _logger = Logger::try_with_str("info")?
+    ///     .redact(|line: &mut Vec<u8>| {
+    ///         if let Some(pos) = line.windows(9).position(|w| w == b"password=") {
+    ///             line.truncate(pos + 9);
+    ///             line.extend_from_slice(b"***");
+    ///         }
+    ///     })
+    ///     .start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn redact<F>(mut self, redaction: F) -> Self
+    where
+        F: Fn(&mut Vec<u8>) + Send + Sync + 'static,
+    {
+        self.o_redaction = Some(Arc::new(redaction));
+        self
+    }
+
+    /// Limits the length of the formatted log lines, e.g. to protect the log files against
+    /// a dependency that logs a huge blob in a single record.
+    ///
+    /// Longer log lines are cut after `max_len` bytes (at a character boundary),
+    /// and get the suffix `…[truncated N bytes]`, where `N` is the number of bytes that
+    /// were cut off; the suffix is not included in `max_len`.
+    /// The limit is applied after the redaction (see [`Logger::redact`]),
+    /// and to the same outputs as the redaction.
+    ///
+    /// The limit is applied to all loggers, the last started (or built) logger wins;
+    /// a logger that fails to start does not change it.
+    #[must_use]
+    pub fn max_record_len(mut self, max_len: usize) -> Self {
+        self.o_max_record_len = Some(max_len);
+        self
+    }
+
+    /// Determines where `flexi_logger` reports its internal errors,
+    /// like failures to write log lines, to rotate log files, or to parse
+    /// an updated log specification.
+    ///
+    /// By default, these errors are written to stderr, which is invisible e.g. for daemons.
+    /// With [`ErrorChannel::Callback`] they can be captured programmatically.
+    ///
+    /// The error channel applies to all loggers, the last started (or built) logger wins;
+    /// a logger that fails to start does not change it.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::{ErrorChannel, Logger};
+    /// # use std::sync::Arc;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let _logger = Logger::try_with_str("info")?
+    ///     .error_channel(ErrorChannel::Callback(Arc::new(|errcode, msg| {
+    ///         // e.g. increment a metric
+    ///         println!("logging failed: {:?}, {}", errcode, msg);
+    ///     })))
+    ///     .start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn error_channel(mut self, error_channel: ErrorChannel) -> Self {
+        self.error_channel = error_channel;
+        self
+    }
+
+    /// Installs a panic hook that logs panics, and then flushes all writers.
+    ///
+    /// The panic is written as an `error!` record with target `panic`,
+    /// through the normal pipeline, i.e., subject to the log specification and to all filters.
+    /// The record contains the name of the panicking thread, the panic message, its location,
+    /// and, with rust 1.65 or newer, a backtrace.
+    ///
+    /// Flushing the writers ensures that buffered output is written before the program
+    /// terminates, even with `panic = "abort"`.
+    /// With the asynchronous write modes, the flush is only handed over to the output thread,
+    /// right behind the panic record, and is executed as long as the process is alive.
+    ///
+    /// The hook replaces the panic hook that was installed before (by default,
+    /// the one that prints the panic to stderr).
+    ///
+    /// By default, panics are not logged.
+    #[must_use]
+    pub fn log_panics(mut self, log_panics: bool) -> Self {
+        self.log_panics = log_panics;
+        self
+    }
+
+    /// Makes the logger write a startup record when it is started,
+    /// with the name of the binary, the given version of the program
+    /// (e.g. `env!("CARGO_PKG_VERSION")`), the process id, the hostname,
+    /// and the active log specification, like
+    ///
+    /// `startup: binary=myapp version=1.2.3 pid=4711 hostname=myhost spec="info"`.
+    ///
+    /// The record has level `Info` and target `flexi_logger`;
+    /// it is written with the primary writer, independent of the log specification.
+    ///
+    /// By default, no startup record is written.
+    #[must_use]
+    pub fn startup_banner<S: Into<String>>(mut self, version: S) -> Self {
+        self.o_startup_banner = Some(version.into());
+        self
+    }
+
+    /// Makes the logger write a shutdown record when it is shut down,
+    /// with the number of records per level that were handed to the primary writer
+    /// (see [`LoggerHandle::counters`]),
+    /// and, if the primary writer writes to a file, the total number of bytes written, like
+    ///
+    /// `shutdown: error=0 warn=2 info=125 debug=0 trace=0 bytes=9472`.
+    ///
+    /// The record has level `Info` and target `flexi_logger`;
+    /// it is written with the primary writer, independent of the log specification.
+    ///
+    /// By default, no shutdown record is written.
+    #[must_use]
+    pub fn shutdown_summary(mut self, shutdown_summary: bool) -> Self {
+        self.shutdown_summary = shutdown_summary;
+        self
+    }
+
+    /// Opens an endpoint through which the log specification of the running program
+    /// can be queried and changed, see [`AdminSocket`].
+    ///
+    /// The endpoint is set up when the logger is built;
+    /// if that fails, the logger initialization fails with [`FlexiLoggerError::AdminSocket`].
+    ///
+    /// By default, no admin socket is used.
+    #[cfg_attr(docsrs, doc(cfg(feature = "admin_socket")))]
+    #[cfg(feature = "admin_socket")]
+    #[must_use]
+    pub fn admin_socket(mut self, admin_socket: AdminSocket) -> Self {
+        self.o_admin_socket = Some(admin_socket);
         self
     }
 
@@ -445,6 +1222,7 @@ impl Logger {
     ///
     /// This option only has an effect if logs are written to files, but
     /// it will hardly make an effect if [`FileSpec::suppress_timestamp`] is not used.
+    #[cfg(not(target_arch = "wasm32"))]
     #[must_use]
     pub fn append(mut self) -> Self {
         self.flwb = self.flwb.append();
@@ -468,6 +1246,7 @@ impl Logger {
     /// tail --follow=name --max-unchanged-stats=1 --retry link_to_log_file
     /// ```
     ///
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn create_symlink<P: Into<PathBuf>>(mut self, symlink: P) -> Self {
         self.flwb = self.flwb.create_symlink(symlink);
         self
@@ -486,9 +1265,143 @@ impl Logger {
         self
     }
 
+    /// Routes the log records, whose target starts with the given prefix,
+    /// to the writer that was registered with the given name
+    /// (see [`Logger::add_writer`]), instead of to the primary output.
+    ///
+    /// This allows e.g. writing the logs of some crates into separate files,
+    /// each with its own rotation settings, without the need to use the
+    /// `{Name}` syntax for targets in the log calls.
+    ///
+    /// Since the target is by default the module path, the prefix is compared like
+    /// the module names in the [`LogSpecification`].
+    /// If several prefixes match, the longest one wins.
+    ///
+    /// Routed records are subject to the log specification like all records of the primary output,
+    /// but they are not affected by a [`RateLimit`] or a [`LogLineFilter`].
+    /// Records whose target addresses writers explicitly are not routed.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::{writers::FileLogWriter, FileSpec, Logger};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let db_writer = FileLogWriter::builder(
+    ///     FileSpec::default()
+    /// #       .directory("log_files/route_docu")
+    ///         .discriminant("db"),
+    /// )
+    /// .try_build()?;
+    /// let _logger = Logger::try_with_str("info, sqlx=debug")?
+    ///     .log_to_file(FileSpec::default())
+    /// #   .log_to_file(FileSpec::default().directory("log_files/route_docu"))
+    ///     .add_writer("db", Box::new(db_writer))
+    ///     .route("sqlx", "db")
+    ///     .start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn route<P: Into<String>, W: Into<String>>(
+        mut self,
+        target_prefix: P,
+        writer_name: W,
+    ) -> Self {
+        self.routes.push((target_prefix.into(), writer_name.into()));
+        self
+    }
+
+    /// Sends the records of the primary output, whose level is admitted by the given
+    /// level filter, additionally to the writers that were registered with the given names
+    /// (see [`Logger::add_writer`]).
+    ///
+    /// This allows e.g. copying all warnings and errors into an alert log and an audit log,
+    /// without the need to use the `{Name}` syntax for targets in the log calls.
+    ///
+    /// The records are sent to the writers before a [`RateLimit`] or a [`LogLineFilter`]
+    /// is applied, and also if they are routed with [`Logger::route`].
+    /// Records whose target addresses writers explicitly are not sent.
+    ///
+    /// Can be called repeatedly; a writer that is named in several calls gets the records
+    /// that are admitted by any of the level filters.
+    ///
+    /// See also [`TeeWriter`](crate::writers::TeeWriter) for a writer that hands its records
+    /// over to several other writers.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::{writers::FileLogWriter, FileSpec, LevelFilter, Logger};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let alerts = FileLogWriter::builder(
+    /// #     FileSpec::default().directory("log_files/also_send_to_docu").discriminant("alerts"),
+    /// # ).try_build()?;
+    /// # let audit = FileLogWriter::builder(
+    /// #     FileSpec::default().directory("log_files/also_send_to_docu").discriminant("audit"),
+    /// # ).try_build()?;
+    /// let _logger = Logger::try_with_str("info")?
+    ///     .add_writer("alerts", Box::new(alerts))
+    ///     .add_writer("audit", Box::new(audit))
+    ///     .also_send_to(&["alerts", "audit"], LevelFilter::Warn)
+    ///     .start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn also_send_to(mut self, writer_names: &[&str], level_filter: LevelFilter) -> Self {
+        for writer_name in writer_names {
+            self.broadcasts
+                .push(((*writer_name).to_string(), level_filter));
+        }
+        self
+    }
+
+    /// Writes warnings and errors additionally into separate files, which are specified
+    /// by the given [`FileSpec`].
+    ///
+    /// This allows watching a small, quiet error file, while the main log file carries
+    /// the full output.
+    ///
+    /// The error files are written by an additional [`FileLogWriter`] that uses the format
+    /// for files and the settings that apply to the main log file, like
+    /// the [rotation](Logger::rotate), [`WriteMode`], and [`Logger::append`];
+    /// the error files are rotated and cleaned up on their own.
+    /// Make sure that the file spec differs from the one of the main log file,
+    /// e.g. by using a discriminant.
+    ///
+    /// The writer is registered as additional writer with name `_Errors`, and receives
+    /// copies of the records of the primary output as with [`Logger::also_send_to`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::{FileSpec, Logger};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let _logger = Logger::try_with_str("debug")?
+    ///     .log_to_file(
+    ///         FileSpec::default()
+    /// #           .directory("log_files/also_log_errors_to_docu")
+    ///     )
+    ///     .also_log_errors_to(
+    ///         FileSpec::default()
+    /// #           .directory("log_files/also_log_errors_to_docu")
+    ///             .discriminant("errors"),
+    ///     )
+    ///     .start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn also_log_errors_to(mut self, file_spec: FileSpec) -> Self {
+        self.o_errors_file_spec = Some(file_spec);
+        self
+    }
+
     /// Sets the write mode for the logger.
     ///
     /// See [`WriteMode`] for more (important!) details.
+    #[cfg(not(target_arch = "wasm32"))]
     #[must_use]
     pub fn write_mode(mut self, write_mode: WriteMode) -> Self {
         self.flwb = self.flwb.write_mode(write_mode.without_flushing());
@@ -496,10 +1409,62 @@ impl Logger {
         self
     }
 
-    /// Use Windows line endings, rather than just `\n`.
+    /// Makes each rotation link the rotated log file and the new log file
+    /// with a line in each of them.
+    ///
+    /// See [`FileLogWriterBuilder::rotation_breadcrumbs`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn rotation_breadcrumbs(mut self) -> Self {
+        self.flwb = self.flwb.rotation_breadcrumbs();
+        self
+    }
+
+    /// Makes a manifest of the rotated log files be maintained.
+    ///
+    /// See [`FileLogWriterBuilder::write_manifest`] for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn write_manifest(mut self) -> Self {
+        self.flwb = self.flwb.write_manifest();
+        self
+    }
+
+    /// Use Windows line endings, rather than just `\n`,
+    /// for the output to files, stdout, and stderr.
+    ///
+    /// See [`FileLogWriterBuilder::use_windows_line_ending`] for details.
     #[must_use]
     pub fn use_windows_line_ending(mut self) -> Self {
-        self.flwb = self.flwb.use_windows_line_ending();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.flwb = self.flwb.use_windows_line_ending();
+        }
+        self.std_streams_line_ending = WINDOWS_LINE_ENDING;
+        self
+    }
+
+    /// Use the line ending that is usual on the target platform,
+    /// i.e., `\r\n` on windows and `\n` elsewhere,
+    /// for the output to files, stdout, and stderr.
+    #[must_use]
+    pub fn use_platform_line_ending(mut self) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.flwb = self.flwb.use_platform_line_ending();
+        }
+        self.std_streams_line_ending = PLATFORM_LINE_ENDING;
+        self
+    }
+
+    /// Don't append a line ending to the records that are written to files.
+    ///
+    /// This is useful with formats that produce binary output,
+    /// like [`cbor_format`](crate::cbor_format).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn without_line_ending(mut self) -> Self {
+        self.flwb = self.flwb.without_line_ending();
         self
     }
 }
@@ -507,6 +1472,7 @@ impl Logger {
 /// Alternative set of methods to control the behavior of the Logger.
 /// Use these methods when you want to control the settings flexibly,
 /// e.g. with commandline arguments via `docopts` or `clap`.
+#[cfg(not(target_arch = "wasm32"))]
 impl Logger {
     /// With true, makes the logger print an info message to stdout, each time
     /// when a new file is used for log-output.
@@ -556,6 +1522,36 @@ impl Logger {
 
 /// Finally, start logging, optionally with a spec-file.
 impl Logger {
+    /// Installs a preliminary global logger that keeps all log records in memory
+    /// until a `Logger` is started, e.g. to not lose the records that libraries emit during
+    /// their initialization, before the configuration of the logger is known.
+    ///
+    /// The buffer takes at most `capacity` records; further records are discarded,
+    /// and a warning with their number is written after the replay.
+    ///
+    /// When the logger is started (with [`Logger::start`] or one of its variants),
+    /// the buffered records are replayed into it, such that they are filtered, formatted,
+    /// and written like all later records. Note that the replayed records get the
+    /// timestamp of the replay, and that their key-value pairs are not retained.
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Logger::buffer_until_started(1_000)?;
+    /// log::info!("this record is emitted before the configuration is known");
+    ///
+    /// let _logger = Logger::try_with_str("info")?.start()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::AlreadyInitialized`] if a logger was already installed.
+    pub fn buffer_until_started(capacity: usize) -> Result<(), FlexiLoggerError> {
+        crate::early_buffer::install(capacity)
+    }
+
     /// Consumes the Logger object and initializes `flexi_logger`.
     ///
     /// **Keep the [`LoggerHandle`] alive up to the very end of your program!**
@@ -587,10 +1583,48 @@ impl Logger {
     ///
     /// # Errors
     ///
-    /// Several variants of [`FlexiLoggerError`] can occur.
+    /// [`FlexiLoggerError::AlreadyInitialized`] if a logger was already installed;
+    /// several other variants of [`FlexiLoggerError`] can occur as well.
     pub fn start(self) -> Result<LoggerHandle, FlexiLoggerError> {
-        let (boxed_logger, handle) = self.build()?;
-        log::set_boxed_logger(boxed_logger)?;
+        let (boxed_logger, handle, global_settings) = self.build_without_global_settings()?;
+        install(boxed_logger, &handle, &global_settings)?;
+        Ok(handle)
+    }
+
+    /// Like [`Logger::start`], but waits until all writers are ready to write
+    /// (see [`LogWriter::ready`]) before the logger is installed.
+    ///
+    /// This is useful with writers that need some time to become operational,
+    /// like a [`NetworkWriter`](crate::writers::NetworkWriter),
+    /// and which would otherwise drop or delay the records that are written early.
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::NotReady`], with the names of the writers that are not ready,
+    /// if not all writers became ready within the given time;
+    /// the logger is then not installed, and its writers are shut down.
+    /// The file writer is named `file`, the writer given to [`Logger::log_to_writer`]
+    /// is named `writer`, and the writers given to [`Logger::add_writer`] are named
+    /// by their target names.
+    ///
+    /// [`FlexiLoggerError::AlreadyInitialized`] if a logger was already installed;
+    /// several other variants of [`FlexiLoggerError`] can occur as well.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_with_readiness(self, timeout: Duration) -> Result<LoggerHandle, FlexiLoggerError> {
+        let (boxed_logger, handle, global_settings) = self.build_without_global_settings()?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let unready_writers = handle.unready_writers();
+            if unready_writers.is_empty() {
+                break;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(FlexiLoggerError::NotReady(unready_writers));
+            }
+            std::thread::sleep(std::cmp::min(deadline - now, Duration::from_millis(10)));
+        }
+        install(boxed_logger, &handle, &global_settings)?;
         Ok(handle)
     }
 
@@ -599,6 +1633,8 @@ impl Logger {
     ///
     /// The returned boxed logger implements the [`Log`](log::Log) trait
     /// and can be installed manually or nested within another logger.
+    /// Note that the settings that apply to all loggers, like the
+    /// [error channel](Logger::error_channel), take effect already here.
     ///
     /// **Keep the [`LoggerHandle`] alive up to the very end of your program!**
     /// See [`Logger::start`] for more details.
@@ -607,21 +1643,54 @@ impl Logger {
     ///
     /// Several variants of [`FlexiLoggerError`] can occur.
     pub fn build(self) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
+        let (boxed_logger, handle, global_settings) = self.build_without_global_settings()?;
+        global_settings.apply(&handle)?;
+        Ok((boxed_logger, handle))
+    }
+
+    // Like `build`, but the settings that affect the whole process, and thus also a logger
+    // that might already be running, are returned rather than applied.
+    fn build_without_global_settings(
+        mut self,
+    ) -> Result<(Box<dyn log::Log>, LoggerHandle, GlobalSettings), FlexiLoggerError> {
+        #[cfg(feature = "atty")]
+        self.resolve_adaptive_formats();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.add_errors_file_writer()?;
+        let routes = sorted_routes(self.routes, &self.other_writers)?;
+        let broadcasts = merged_broadcasts(self.broadcasts, &self.other_writers)?;
         #[cfg(feature = "colors")]
         crate::formats::set_palette(&self.o_palette)?;
+        crate::timestamp_config::set_timestamp_config(&self.timestamp_config)?;
+        crate::formats::set_format_details(self.format_details);
 
+        #[cfg(not(target_arch = "wasm32"))]
+        let write_mode = self.flwb.get_write_mode();
+        #[cfg(target_arch = "wasm32")]
+        let write_mode = &WriteMode::Direct;
         let a_primary_writer = Arc::new(match self.log_target {
-            LogTarget::StdOut => {
-                PrimaryWriter::stdout(self.format_for_stdout, self.flwb.get_write_mode())
-            }
-            LogTarget::StdErr => {
-                PrimaryWriter::stderr(self.format_for_stderr, self.flwb.get_write_mode())
-            }
+            LogTarget::StdOut => PrimaryWriter::stdout(
+                self.format_for_stdout,
+                write_mode,
+                self.std_streams_line_ending,
+            ),
+            LogTarget::StdErr => PrimaryWriter::stderr(
+                self.format_for_stderr,
+                write_mode,
+                self.std_streams_line_ending,
+            ),
+            #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
             LogTarget::Multi(use_file, mut o_writer) => PrimaryWriter::multi(
-                self.duplicate_err,
+                StderrDuplication {
+                    duplicate: self.duplicate_err,
+                    o_spec: self.o_duplicate_err_spec,
+                    o_rate_limiter: self.o_duplicate_err_rate_limit.map(RateLimiter::new),
+                },
                 self.duplicate_out,
                 self.format_for_stderr,
                 self.format_for_stdout,
+                self.std_streams_line_ending,
+                #[cfg(not(target_arch = "wasm32"))]
                 if use_file {
                     Some(Box::new(
                         self.flwb.format(self.format_for_file).try_build()?,
@@ -636,10 +1705,23 @@ impl Logger {
                     o_writer
                 },
             ),
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            LogTarget::BrowserConsole => PrimaryWriter::browser_console(self.format_for_writer),
         });
 
-        let a_other_writers = Arc::new(self.other_writers);
+        let mut other_writers = self.other_writers;
+        for (target_name, format) in self.formats_for_other_writers {
+            match other_writers.get_mut(&target_name) {
+                Some(writer) => writer.format(format),
+                None => eprint_msg(
+                    ERRCODE::WriterSpec,
+                    &format!("format for unknown writer: {}", target_name),
+                ),
+            }
+        }
+        let a_other_writers = Arc::new(RwLock::new(other_writers));
 
+        #[cfg(not(target_arch = "wasm32"))]
         if self.flush_interval != Duration::from_secs(0) {
             let flush_interval = self.flush_interval;
             let pw = Arc::clone(&a_primary_writer);
@@ -652,7 +1734,7 @@ impl Logger {
                 loop {
                     receiver.recv_timeout(flush_interval).ok();
                     pw.flush().ok();
-                    for w in ows.values() {
+                    for w in read_other_writers(&ows).values() {
                         w.flush().ok();
                     }
                 }
@@ -661,17 +1743,113 @@ impl Logger {
 
         let max_level = self.spec.max_level();
         let a_l_spec = Arc::new(RwLock::new(self.spec));
+        let o_timing_sampler = self
+            .o_sample_timings
+            .map(|every_nth| Arc::new(TimingSampler::new(every_nth)));
+        let a_shutdown_gate = Arc::new(ShutdownGate::new(
+            self.shutdown_policy,
+            self.format_for_stderr,
+            self.std_streams_line_ending,
+        ));
+
+        let a_record_counts = Arc::new(RecordCounts::new(
+            read_other_writers(&a_other_writers).keys(),
+        ));
+        let o_shutdown_summary = if self.shutdown_summary {
+            Some(Arc::new(ShutdownSummary::default()))
+        } else {
+            None
+        };
+        if let Some(ref version) = self.o_startup_banner {
+            if let Ok(spec) = a_l_spec.read() {
+                write_lifecycle_record(&a_primary_writer, &startup_banner(version, &spec));
+            }
+        }
 
         let flexi_logger = FlexiLogger::new(
             Arc::clone(&a_l_spec),
             Arc::clone(&a_primary_writer),
             Arc::clone(&a_other_writers),
-            self.filter,
+            self.filters,
+            o_timing_sampler.clone(),
+            Arc::clone(&a_record_counts),
+            Arc::clone(&a_shutdown_gate),
+            self.o_rate_limit.map(RateLimiter::new),
+            if self.sampler.is_empty() {
+                None
+            } else {
+                Some(self.sampler)
+            },
+            routes,
+            broadcasts,
+        );
+
+        let handle = LoggerHandle::new(
+            a_l_spec,
+            a_primary_writer,
+            a_other_writers,
+            o_timing_sampler,
+            a_record_counts,
+            o_shutdown_summary,
+            a_shutdown_gate,
         );
+        #[cfg(feature = "admin_socket")]
+        if let Some(ref admin_socket) = self.o_admin_socket {
+            handle.serve_admin_socket(admin_socket)?;
+        }
+        let global_settings = GlobalSettings {
+            error_channel: self.error_channel,
+            o_redaction: self.o_redaction,
+            o_max_record_len: self.o_max_record_len,
+            max_level,
+            log_panics: self.log_panics,
+        };
+        Ok((Box::new(flexi_logger), handle, global_settings))
+    }
+
+    // Registers the writer for `also_log_errors_to`, and sends it copies of the warnings
+    // and errors.
+    // Adds a writer for other files, with the settings of the file log writer.
+    #[cfg(feature = "config_file")]
+    pub(crate) fn add_other_file_writer(
+        self,
+        name: &str,
+        file_spec: FileSpec,
+    ) -> Result<Self, FlexiLoggerError> {
+        let writer = self
+            .flwb
+            .for_other_files(file_spec, LevelFilter::Trace)
+            .format(self.format_for_file)
+            .try_build()?;
+        Ok(self.add_writer(name, Box::new(writer)))
+    }
+
+    #[cfg(feature = "atty")]
+    fn resolve_adaptive_formats(&mut self) {
+        if let Some(adaptive_format) = self.o_adaptive_format_for_stderr {
+            let use_colors = self.color_choice.use_colors(atty::Stream::Stderr);
+            self.format_for_stderr = adaptive_format.format_function(use_colors);
+        }
+        if let Some(adaptive_format) = self.o_adaptive_format_for_stdout {
+            let use_colors = self.color_choice.use_colors(atty::Stream::Stdout);
+            self.format_for_stdout = adaptive_format.format_function(use_colors);
+        }
+    }
 
-        let handle = LoggerHandle::new(a_l_spec, a_primary_writer, a_other_writers);
-        handle.reconfigure(max_level);
-        Ok((Box::new(flexi_logger), handle))
+    #[cfg(not(target_arch = "wasm32"))]
+    fn add_errors_file_writer(&mut self) -> Result<(), FlexiLoggerError> {
+        if let Some(file_spec) = self.o_errors_file_spec.take() {
+            let writer = self
+                .flwb
+                .for_other_files(file_spec, LevelFilter::Warn)
+                .format(self.format_for_file)
+                .try_build()?;
+            self.other_writers
+                .insert(ERRORS_WRITER.to_string(), Box::new(writer));
+            self.broadcasts
+                .push((ERRORS_WRITER.to_string(), LevelFilter::Warn));
+        }
+        Ok(())
     }
 
     /// Consumes the Logger object and initializes `flexi_logger` in a way that
@@ -720,12 +1898,23 @@ impl Logger {
     /// #'mod1' = 'warn'
     /// #'mod2' = 'debug'
     /// #'mod2::mod3' = 'trace'
+    /// #'/^mod4::(db|cache)/' = 'trace'
+    ///
+    /// ### Log levels for the additional writers are optionally defined in this section
+    /// [writers]
+    /// #'Alert' = 'warn'
     /// ```
     ///
     /// You can subsequently edit and modify the file according to your needs,
     /// while the program is running, and it will immediately take your changes into account.
     ///
-    /// Currently only toml-files are supported, the file suffix thus must be `.toml`.
+    /// The level of an additional writer, if specified in section `writers`,
+    /// replaces the writer's [`LogWriter::max_log_level`].
+    ///
+    /// The format of the file is determined by its extension:
+    /// files with extension `toml` are read and written in toml format.
+    /// With feature `specfile_yaml`, files with extension `yaml` or `yml` are
+    /// read and written in yaml format, with the same structure.
     ///
     /// The initial spec remains valid if the file cannot be read.
     ///
@@ -745,9 +1934,9 @@ impl Logger {
         specfile: P,
     ) -> Result<LoggerHandle, FlexiLoggerError> {
         // Make logging work, before caring for the specfile
-        let (boxed_logger, handle) = self.build()?;
-        log::set_boxed_logger(boxed_logger)?;
-        subscribe_to_specfile(specfile, handle.clone())?;
+        let (boxed_logger, handle, global_settings) = self.build_without_global_settings()?;
+        install(boxed_logger, &handle, &global_settings)?;
+        handle.watch_specfile(specfile.as_ref())?;
         Ok(handle)
     }
 
@@ -766,18 +1955,134 @@ impl Logger {
         specfile: P,
     ) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
         let (boxed_log, handle) = self.build()?;
-        subscribe_to_specfile(specfile, handle.clone())?;
+        handle.watch_specfile(specfile.as_ref())?;
         Ok((boxed_log, handle))
     }
 }
 
+// The settings of a logger that are kept in statics, and which thus must only be applied
+// when the logger is really used, e.g. not if its installation failed.
+struct GlobalSettings {
+    error_channel: ErrorChannel,
+    o_redaction: Option<Arc<Redaction>>,
+    o_max_record_len: Option<usize>,
+    max_level: LevelFilter,
+    log_panics: bool,
+}
+impl GlobalSettings {
+    // Applies the settings; the log macros pass records only after this.
+    fn apply(&self, handle: &LoggerHandle) -> Result<(), FlexiLoggerError> {
+        crate::util::set_error_channel(self.error_channel.clone())?;
+        crate::util::set_redaction(self.o_redaction.clone())?;
+        crate::util::set_max_record_len(self.o_max_record_len);
+        handle.reconfigure(self.max_level);
+        if self.log_panics {
+            handle.log_panics();
+        }
+        Ok(())
+    }
+}
+
+// Installs the logger as the global logger and registers its handle.
+//
+// The global settings are applied only if the installation succeeds,
+// and before the logger gets to see any record.
+fn install(
+    boxed_logger: Box<dyn log::Log>,
+    handle: &LoggerHandle,
+    global_settings: &GlobalSettings,
+) -> Result<(), FlexiLoggerError> {
+    let mut o_applied = None;
+    match crate::early_buffer::hand_over(boxed_logger, || {
+        o_applied = Some(global_settings.apply(handle));
+    }) {
+        Ok(()) => o_applied.unwrap_or(Ok(()))?,
+        Err(boxed_logger) => {
+            log::set_boxed_logger(boxed_logger)
+                .map_err(|_| FlexiLoggerError::AlreadyInitialized)?;
+            global_settings.apply(handle)?;
+        }
+    }
+    register_active_handle(handle);
+    Ok(())
+}
+
+// Checks that the routes refer to registered writers, and sorts them such that
+// the longest matching prefix comes first.
+fn sorted_routes(
+    mut routes: Vec<(String, String)>,
+    other_writers: &HashMap<String, Box<dyn LogWriter>>,
+) -> Result<Vec<(String, String)>, FlexiLoggerError> {
+    if let Some((_, writer_name)) = routes
+        .iter()
+        .find(|(_, writer_name)| !other_writers.contains_key(writer_name))
+    {
+        return Err(FlexiLoggerError::UnknownWriter(writer_name.clone()));
+    }
+    routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+    Ok(routes)
+}
+
+// Checks that the broadcasts refer to registered writers, and merges the level filters
+// of each writer.
+fn merged_broadcasts(
+    broadcasts: Vec<(String, LevelFilter)>,
+    other_writers: &HashMap<String, Box<dyn LogWriter>>,
+) -> Result<Vec<(String, LevelFilter)>, FlexiLoggerError> {
+    let mut merged = Vec::<(String, LevelFilter)>::new();
+    for (writer_name, level_filter) in broadcasts {
+        if !other_writers.contains_key(&writer_name) {
+            return Err(FlexiLoggerError::UnknownWriter(writer_name));
+        }
+        match merged.iter_mut().find(|(name, _)| *name == writer_name) {
+            Some((_, merged_filter)) => {
+                *merged_filter = std::cmp::max(*merged_filter, level_filter);
+            }
+            None => merged.push((writer_name, level_filter)),
+        }
+    }
+    Ok(merged)
+}
+
 #[cfg(feature = "specfile_without_notification")]
 pub(crate) fn subscribe_to_specfile<P: AsRef<Path>, H: LogSpecSubscriber>(
     specfile: P,
-    mut subscriber: H,
+    subscriber: H,
 ) -> Result<(), FlexiLoggerError> {
+    subscribe_to_specfile_while_active(specfile, subscriber, Arc::new(AtomicBool::new(true)))
+        .map(|_wakeup| ())
+}
+
+// Wakes up the thread that watches a specfile, so that it finds out without delay
+// that it is no longer active, and terminates, which also ends the watch.
+#[cfg(feature = "specfile_without_notification")]
+#[derive(Debug)]
+pub(crate) struct SpecfileWatcherWakeup {
+    #[cfg(feature = "specfile")]
+    sender: std::sync::mpsc::Sender<DebouncedEvent>,
+}
+#[cfg(feature = "specfile_without_notification")]
+impl SpecfileWatcherWakeup {
+    pub(crate) fn wake_up(&self) {
+        #[cfg(feature = "specfile")]
+        self.sender.send(DebouncedEvent::Rescan).ok();
+    }
+}
+
+// Like `subscribe_to_specfile`, but the watcher stops applying changes
+// (and terminates with the next file system event) once `active` is set to false;
+// the returned wakeup produces such an event.
+#[cfg(feature = "specfile_without_notification")]
+#[cfg_attr(not(feature = "specfile"), allow(clippy::needless_pass_by_value))]
+pub(crate) fn subscribe_to_specfile_while_active<P: AsRef<Path>, H: LogSpecSubscriber>(
+    specfile: P,
+    mut subscriber: H,
+    #[cfg_attr(not(feature = "specfile"), allow(unused_variables))] active: Arc<AtomicBool>,
+) -> Result<SpecfileWatcherWakeup, FlexiLoggerError> {
     let specfile = specfile.as_ref();
     synchronize_subscriber_with_specfile(&mut subscriber, specfile)?;
+    #[cfg(feature = "specfile")]
+    let format = SpecfileFormat::of(specfile)?;
 
     #[cfg(feature = "specfile")]
     {
@@ -788,6 +2093,7 @@ pub(crate) fn subscribe_to_specfile<P: AsRef<Path>, H: LogSpecSubscriber>(
 
         // Watch the parent folder of the specfile, using debounced events
         let (tx, rx) = std::sync::mpsc::channel();
+        let wakeup = SpecfileWatcherWakeup { sender: tx.clone() };
         let debouncing_delay = std::time::Duration::from_millis(1000);
         let mut watcher = watcher(tx, debouncing_delay)?;
         watcher.watch(
@@ -802,13 +2108,17 @@ pub(crate) fn subscribe_to_specfile<P: AsRef<Path>, H: LogSpecSubscriber>(
         builder.spawn(move || {
             let _anchor_for_watcher = watcher; // keep it alive!
             loop {
-                match rx.recv() {
+                let received = rx.recv();
+                if !active.load(Ordering::Relaxed) {
+                    break;
+                }
+                match received {
                     Ok(debounced_event) => match debounced_event {
                         DebouncedEvent::Create(ref path) | DebouncedEvent::Write(ref path) => {
                             if path.canonicalize().map(|x| x == specfile).unwrap_or(false) {
                                 log_spec_string_from_file(&specfile)
                                     .map_err(FlexiLoggerError::SpecfileIo)
-                                    .and_then(|s| LogSpecification::from_toml(&s))
+                                    .and_then(|s| format.parse(&s))
                                     .and_then(|spec| subscriber.set_new_spec(spec))
                                     .map_err(|e| {
                                         eprint_err(
@@ -833,8 +2143,10 @@ pub(crate) fn subscribe_to_specfile<P: AsRef<Path>, H: LogSpecSubscriber>(
                 }
             }
         })?;
+        Ok(wakeup)
     }
-    Ok(())
+    #[cfg(not(feature = "specfile"))]
+    Ok(SpecfileWatcherWakeup {})
 }
 
 // If the specfile exists, read the file and update the log_spec from it;
@@ -844,21 +2156,11 @@ pub(crate) fn synchronize_subscriber_with_specfile<H: LogSpecSubscriber>(
     subscriber: &mut H,
     specfile: &Path,
 ) -> Result<(), FlexiLoggerError> {
-    if specfile
-        .extension()
-        .unwrap_or_else(|| std::ffi::OsStr::new(""))
-        .to_str()
-        .unwrap_or("")
-        != "toml"
-    {
-        return Err(FlexiLoggerError::SpecfileExtension(
-            "only spec files with extension toml are supported",
-        ));
-    }
+    let format = SpecfileFormat::of(specfile)?;
 
     if Path::is_file(specfile) {
         let s = log_spec_string_from_file(specfile).map_err(FlexiLoggerError::SpecfileIo)?;
-        subscriber.set_new_spec(LogSpecification::from_toml(&s)?)?;
+        subscriber.set_new_spec(format.parse(&s)?)?;
     } else {
         if let Some(specfolder) = specfile.parent() {
             std::fs::DirBuilder::new()
@@ -872,7 +2174,7 @@ pub(crate) fn synchronize_subscriber_with_specfile<H: LogSpecSubscriber>(
             .open(specfile)
             .map_err(FlexiLoggerError::SpecfileIo)?;
 
-        subscriber.initial_spec()?.to_toml(&mut file)?;
+        format.write(&subscriber.initial_spec()?, &mut file)?;
     }
     Ok(())
 }
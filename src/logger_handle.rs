@@ -1,9 +1,38 @@
+use crate::counters::{RecordCounters, RecordCounts};
+use crate::flexi_logger::{read_other_writers, PRIMARY_WRITER};
+use crate::lifecycle::{write_lifecycle_record, ShutdownSummary};
 use crate::primary_writer::PrimaryWriter;
+use crate::shutdown_policy::ShutdownGate;
+use crate::timing::{TimingMetrics, TimingSampler};
 use crate::util::{eprint_err, ERRCODE};
-use crate::writers::{FileLogWriterBuilder, LogWriter};
-use crate::{FlexiLoggerError, LogSpecification};
+use crate::writers::LogWriter;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::writers::{FileLogWriterBuilder, WriterStats};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{Cleanup, Criterion, Naming};
+use crate::{ErrCode, FlexiLoggerError, FormatFunction, LogSpecification};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{BufRead, BufReader, Read};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::pin::Pin;
+#[cfg(feature = "specfile_without_notification")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context, Poll, Waker};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// See LoggerHandle::is_healthy.
+const HEALTH_PERIOD: Duration = Duration::from_secs(60);
 
 /// Shuts down the logger when dropped, and allows reconfiguring the logger programmatically.
 ///
@@ -84,32 +113,102 @@ use std::sync::{Arc, RwLock};
 pub struct LoggerHandle {
     spec: Arc<RwLock<LogSpecification>>,
     spec_stack: Vec<LogSpecification>,
+    // the specs that were replaced by the active SpecGuards, shared by all clones
+    am_spec_guards: Arc<Mutex<SpecGuards>>,
     primary_writer: Arc<PrimaryWriter>,
-    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+    o_timing_sampler: Option<Arc<TimingSampler>>,
+    record_counts: Arc<RecordCounts>,
+    o_shutdown_summary: Option<Arc<ShutdownSummary>>,
+    shutdown_gate: Arc<ShutdownGate>,
+    #[cfg(feature = "specfile_without_notification")]
+    o_specfile_watch: Arc<Mutex<Option<SpecfileWatch>>>,
+    // is false for the handles that are provided by `handle()`
+    shutdown_on_drop: bool,
+}
+
+// The specfile that is currently used, the flag that keeps its watcher alive,
+// and the wakeup that lets the watcher terminate promptly when the flag is reset.
+#[cfg(feature = "specfile_without_notification")]
+struct SpecfileWatch {
+    specfile: PathBuf,
+    active: Arc<AtomicBool>,
+    wakeup: crate::logger::SpecfileWatcherWakeup,
 }
 
 impl LoggerHandle {
     pub(crate) fn new(
         spec: Arc<RwLock<LogSpecification>>,
         primary_writer: Arc<PrimaryWriter>,
-        other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+        other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+        o_timing_sampler: Option<Arc<TimingSampler>>,
+        record_counts: Arc<RecordCounts>,
+        o_shutdown_summary: Option<Arc<ShutdownSummary>>,
+        shutdown_gate: Arc<ShutdownGate>,
     ) -> Self {
         Self {
             spec,
             spec_stack: Vec::default(),
+            am_spec_guards: Arc::new(Mutex::new(SpecGuards::default())),
             primary_writer,
             other_writers,
+            o_timing_sampler,
+            record_counts,
+            o_shutdown_summary,
+            shutdown_gate,
+            #[cfg(feature = "specfile_without_notification")]
+            o_specfile_watch: Arc::new(Mutex::new(None)),
+            shutdown_on_drop: true,
+        }
+    }
+
+    // Produces a clone that does not shut down the writers when it is dropped.
+    fn non_owning_clone(&self) -> Self {
+        Self {
+            spec: Arc::clone(&self.spec),
+            spec_stack: Vec::default(),
+            am_spec_guards: Arc::clone(&self.am_spec_guards),
+            primary_writer: Arc::clone(&self.primary_writer),
+            other_writers: Arc::clone(&self.other_writers),
+            o_timing_sampler: self.o_timing_sampler.clone(),
+            record_counts: Arc::clone(&self.record_counts),
+            o_shutdown_summary: self.o_shutdown_summary.clone(),
+            shutdown_gate: Arc::clone(&self.shutdown_gate),
+            #[cfg(feature = "specfile_without_notification")]
+            o_specfile_watch: Arc::clone(&self.o_specfile_watch),
+            shutdown_on_drop: false,
         }
     }
 
     //
     pub(crate) fn reconfigure(&self, mut max_level: log::LevelFilter) {
-        for w in self.other_writers.as_ref().values() {
+        for w in read_other_writers(&self.other_writers).values() {
             max_level = std::cmp::max(max_level, w.max_log_level());
         }
         log::set_max_level(max_level);
     }
 
+    // Names of the writers that are not yet ready to write;
+    // additional writers are named by their target name.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn unready_writers(&self) -> Vec<String> {
+        let mut unready: Vec<String> = self
+            .primary_writer
+            .unready_writers()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        let other_writers = read_other_writers(&self.other_writers);
+        let mut unready_others: Vec<&String> = other_writers
+            .iter()
+            .filter(|(_, writer)| !writer.ready())
+            .map(|(name, _)| name)
+            .collect();
+        unready_others.sort();
+        unready.extend(unready_others.into_iter().cloned());
+        unready
+    }
+
     /// Replaces the active `LogSpecification`.
     #[allow(clippy::missing_panics_doc)]
     pub fn set_new_spec(&mut self, new_spec: LogSpecification) {
@@ -132,6 +231,33 @@ impl LoggerHandle {
         Ok(())
     }
 
+    /// Applies those settings of the given config file (see
+    /// [`Logger::try_from_config_file`](crate::Logger::try_from_config_file))
+    /// that can safely be changed at runtime.
+    ///
+    /// These are the log specification, and, if the logger writes to a file,
+    /// the settings of the `[file]` section and the write mode (see [`LoggerHandle::reset_flw`]).
+    /// The current file is continued, i.e., `append` is always assumed.
+    ///
+    /// The log target, the duplication, the format, and the additional writers
+    /// are not changed.
+    ///
+    /// # Errors
+    ///
+    /// Like [`Logger::try_from_config_file`](crate::Logger::try_from_config_file),
+    /// and like [`LoggerHandle::reset_flw`]. If an error occurs, nothing is changed.
+    #[cfg_attr(docsrs, doc(cfg(feature = "config_file")))]
+    #[cfg(feature = "config_file")]
+    pub fn reload_config<P: AsRef<Path>>(&mut self, path: P) -> Result<(), FlexiLoggerError> {
+        let config = crate::config_file::LoggerConfig::read(path.as_ref())?;
+        let new_spec = config.log_spec()?;
+        if let Some(flwb) = config.file_log_writer_builder()? {
+            self.reset_flw(&flwb)?;
+        }
+        self.set_new_spec(new_spec);
+        Ok(())
+    }
+
     /// Replaces the active `LogSpecification` and pushes the previous one to a Stack.
     #[allow(clippy::missing_panics_doc)]
     pub fn push_temp_spec(&mut self, new_spec: LogSpecification) {
@@ -167,18 +293,301 @@ impl LoggerHandle {
         }
     }
 
+    /// Replaces the active `LogSpecification` temporarily, e.g. with a more verbose one,
+    /// until the returned guard is dropped.
+    ///
+    /// Dropping the guard restores the `LogSpecification` that was active before,
+    /// also if the scope is left with a panic.
+    /// Guards can be nested; if they are not dropped in reverse order of their creation,
+    /// dropping a guard lets the remaining guards restore the right specs eventually.
+    ///
+    /// Note that a `LogSpecification` that is set while a guard is alive,
+    /// e.g. with [`LoggerHandle::set_new_spec`] or by an update of the specfile,
+    /// is replaced when the guard is dropped.
+    ///
+    /// ```rust
+    /// # use flexi_logger::{LogSpecification, Logger};
+    /// # let logger = Logger::try_with_str("info").unwrap().log_to_stdout().start().unwrap();
+    /// {
+    ///     let _guard = logger.raise_level_scoped(LogSpecification::parse("debug").unwrap());
+    ///     // debug output is written here
+    /// }
+    /// // the original spec is active again
+    /// ```
+    pub fn raise_level_scoped(&self, spec: LogSpecification) -> SpecGuard {
+        let mut spec_guards = self
+            .am_spec_guards
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let max_level = spec.max_level();
+        let mut active_spec = self.spec.write().unwrap_or_else(PoisonError::into_inner);
+        let previous_spec = active_spec.clone();
+        active_spec.update_from(spec);
+        drop(active_spec);
+        let id = spec_guards.next_id;
+        spec_guards.next_id += 1;
+        spec_guards.replaced.push((id, previous_spec));
+        drop(spec_guards);
+        self.reconfigure(max_level);
+
+        SpecGuard {
+            handle: self.non_owning_clone(),
+            id,
+        }
+    }
+
+    /// Adds an additional writer while the program is running,
+    /// e.g. for a plugin that is loaded later and brings its own log sink.
+    ///
+    /// The writer is used like the writers that were added with
+    /// [`Logger::add_writer`](crate::Logger::add_writer):
+    /// log records are sent to it if their target addresses it with `{name}`.
+    /// If a writer with the same name exists already, it is replaced,
+    /// and the replaced writer is flushed and shut down.
+    ///
+    /// Note that the format from [`Logger::format_for_writer`](crate::Logger::format_for_writer)
+    /// is not applied, and that the records that are written to the writer
+    /// are not included in [`LoggerHandle::counters`].
+    pub fn add_writer<S: Into<String>>(&self, name: S, writer: Box<dyn LogWriter>) {
+        let o_replaced = self
+            .other_writers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name.into(), writer);
+        if let Some(replaced) = o_replaced {
+            replaced.flush().ok();
+            replaced.shutdown();
+        }
+        if let Ok(spec) = self.spec.read() {
+            self.reconfigure(spec.max_level());
+        }
+    }
+
+    /// Removes an additional writer while the program is running,
+    /// after the records that are currently written to it are done;
+    /// the removed writer is flushed and shut down.
+    ///
+    /// Records whose target addresses the removed writer are reported as errors
+    /// (see [`ErrCode::WriterSpec`](crate::ErrCode::WriterSpec)).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::UnknownWriter` if no writer with the given name exists.
+    pub fn remove_writer(&self, name: &str) -> Result<(), FlexiLoggerError> {
+        let removed = self
+            .other_writers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name)
+            .ok_or_else(|| FlexiLoggerError::UnknownWriter(name.to_string()))?;
+        removed.flush().ok();
+        removed.shutdown();
+        Ok(())
+    }
+
+    /// Replaces the format function of the additional writer with the given name,
+    /// like [`Logger::format_for_additional_writer`](crate::Logger::format_for_additional_writer)
+    /// does at initialization.
+    ///
+    /// Note that it is up to the implementation of the writer
+    /// whether it evaluates this setting or not.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::UnknownWriter` if no writer with the given name exists.
+    pub fn set_format_for_additional_writer(
+        &self,
+        name: &str,
+        format: FormatFunction,
+    ) -> Result<(), FlexiLoggerError> {
+        self.other_writers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+            .ok_or_else(|| FlexiLoggerError::UnknownWriter(name.to_string()))?
+            .format(format);
+        Ok(())
+    }
+
+    /// Sets the level filter of the additional writer with the given name,
+    /// e.g. to raise the verbosity of a single writer temporarily, during an investigation.
+    ///
+    /// The level filter replaces the
+    /// [`LogWriter::max_log_level`](crate::writers::LogWriter::max_log_level) of the writer.
+    /// With the name `_Default`, it applies to the primary writer
+    /// (e.g. the file log writer), and replaces the module filters of the log specification
+    /// for it.
+    ///
+    /// The level filter is stored in the active log specification
+    /// (see [`LogSpecification::writer_filter`]), so it is dropped when the log specification
+    /// is replaced, e.g. with [`LoggerHandle::set_new_spec`] or by an update of the specfile.
+    /// Use [`LoggerHandle::reset_writer_level`] to return to the original level.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::UnknownWriter` if no writer with the given name exists.
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn set_writer_level(
+        &self,
+        name: &str,
+        level_filter: log::LevelFilter,
+    ) -> Result<(), FlexiLoggerError> {
+        self.set_writer_filter(name, Some(level_filter))
+    }
+
+    /// Removes the level filter that was set with [`LoggerHandle::set_writer_level`]
+    /// (or in the specfile) for the writer with the given name.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::UnknownWriter` if no writer with the given name exists.
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn reset_writer_level(&self, name: &str) -> Result<(), FlexiLoggerError> {
+        self.set_writer_filter(name, None)
+    }
+
+    fn set_writer_filter(
+        &self,
+        name: &str,
+        o_level_filter: Option<log::LevelFilter>,
+    ) -> Result<(), FlexiLoggerError> {
+        if name != PRIMARY_WRITER && !read_other_writers(&self.other_writers).contains_key(name) {
+            return Err(FlexiLoggerError::UnknownWriter(name.to_string()));
+        }
+        let max_level = {
+            let mut spec = self.spec.write().map_err(|_| FlexiLoggerError::Poison)?;
+            spec.set_writer_filter(name, o_level_filter);
+            spec.max_level()
+        };
+        self.reconfigure(max_level);
+        Ok(())
+    }
+
     /// Flush all writers.
+    ///
+    /// With the asynchronous write modes, the flush is only triggered,
+    /// see [`LoggerHandle::flush_and_wait`] for a flush with a completion guarantee.
     pub fn flush(&self) {
         self.primary_writer.flush().ok();
-        for writer in self.other_writers.values() {
+        for writer in read_other_writers(&self.other_writers).values() {
             writer.flush().ok();
         }
     }
 
+    /// Flush all writers, and wait until all log lines that were logged before
+    /// are written out, but not longer than the given timeout.
+    ///
+    /// With the asynchronous write modes, this waits until the output threads
+    /// have processed the flush request.
+    ///
+    /// Returns `false` if the timeout expired.
+    #[must_use]
+    pub fn flush_and_wait(&self, timeout: Duration) -> bool {
+        flush_and_wait(&self.primary_writer, &self.other_writers, timeout)
+    }
+
+    /// Like [`LoggerHandle::flush_and_wait`], but does not block the caller;
+    /// the returned future resolves when the flush is complete or the timeout expired.
+    ///
+    /// The future can be used with any async runtime; the waiting is done in an extra thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn flush_and_wait_async(&self, timeout: Duration) -> impl Future<Output = bool> + Send {
+        let completion = Arc::new(Mutex::new(FlushCompletionState::default()));
+        let t_completion = Arc::clone(&completion);
+        let primary_writer = Arc::clone(&self.primary_writer);
+        let other_writers = Arc::clone(&self.other_writers);
+        let builder = std::thread::Builder::new().name("flexi_logger-flush".to_string());
+        #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+        let builder = builder.stack_size(128 * 1024);
+        let spawned = builder.spawn(move || {
+            let complete = flush_and_wait(&primary_writer, &other_writers, timeout);
+            if let Ok(mut state) = t_completion.lock() {
+                state.o_complete = Some(complete);
+                if let Some(waker) = state.o_waker.take() {
+                    waker.wake();
+                }
+            }
+        });
+        if spawned.is_err() {
+            if let Ok(mut state) = completion.lock() {
+                state.o_complete = Some(false);
+            }
+        }
+        FlushCompletion(completion)
+    }
+
+    /// Spawns a thread that reads the given input line by line,
+    /// e.g. the stdout or stderr of a child process,
+    /// and logs each line with the given level and target.
+    ///
+    /// The lines are handed to the logger that was installed with
+    /// [`Logger::start`](crate::Logger::start), so they are filtered, formatted, written,
+    /// rotated, and cleaned up like all other log lines.
+    /// Line breaks are removed from the end of the lines, invalid UTF-8 is replaced.
+    ///
+    /// The thread ends when the input is exhausted, or when reading from it fails;
+    /// the returned `JoinHandle` then provides the result of the reading.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use flexi_logger::Logger;
+    /// # use std::process::{Command, Stdio};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let logger = Logger::try_with_str("info")?.start()?;
+    /// let mut child = Command::new("ls").stdout(Stdio::piped()).spawn()?;
+    /// let pipe = logger.pipe_reader(log::Level::Info, "ls", child.stdout.take().unwrap())?;
+    /// child.wait()?;
+    /// pipe.join().unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the thread cannot be spawned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pipe_reader<R: Read + Send + 'static>(
+        &self,
+        level: log::Level,
+        target: &str,
+        reader: R,
+    ) -> Result<JoinHandle<std::io::Result<()>>, FlexiLoggerError> {
+        let target = target.to_string();
+        let builder = std::thread::Builder::new().name(format!("flexi_logger-pipe-{}", target));
+        #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+        let builder = builder.stack_size(128 * 1024);
+        Ok(builder.spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                if reader.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(());
+                }
+                while line.ends_with(b"\n") || line.ends_with(b"\r") {
+                    line.pop();
+                }
+                log::logger().log(
+                    &log::Record::builder()
+                        .level(level)
+                        .target(&target)
+                        .args(format_args!("{}", String::from_utf8_lossy(&line)))
+                        .build(),
+                );
+            }
+        })?)
+    }
+
     /// Replaces parts of the configuration of the file log writer.
     ///
-    /// Note that neither the write mode nor the format function can be reset and
-    /// that the provided `FileLogWriterBuilder` must have the same values for these as the
+    /// The write mode can be switched between [`WriteMode::Direct`](crate::WriteMode::Direct)
+    /// and the buffered write modes, but not from or to an asynchronous write mode.
+    ///
+    /// Note that the format function cannot be reset and
+    /// that the provided `FileLogWriterBuilder` must have the same value for it as the
     /// currently used `FileLogWriter`.
     ///
     /// # Example
@@ -188,11 +597,12 @@ impl LoggerHandle {
     /// # Errors
     ///
     /// `FlexiLoggerError::Reset` if no file log writer is configured,
-    ///  or if a reset was tried with a different write mode.
+    ///  or if a reset was tried from or to an asynchronous write mode.
     ///
     /// `FlexiLoggerError::Io` if the specified path doesn't work.
     ///
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn reset_flw(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
         if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
             mw.reset_file_log_writer(flwb)
@@ -201,6 +611,379 @@ impl LoggerHandle {
         }
     }
 
+    /// Returns the number of log lines that were discarded so far because the channel
+    /// to the output thread was full.
+    ///
+    /// Is always zero, unless [`WriteMode::AsyncWith`](crate::WriteMode::AsyncWith)
+    /// is used with a bounded channel and an
+    /// [`OverflowPolicy`](crate::OverflowPolicy) that discards log lines.
+    #[must_use]
+    pub fn dropped_records(&self) -> usize {
+        self.primary_writer.dropped_records()
+    }
+
+    /// Moves the file logging to the given directory.
+    ///
+    /// The current log file is flushed and closed, subsequent log lines are written to
+    /// a new file in the given directory, which is created if necessary.
+    /// Rotation starts afresh in the new directory, and the symlink (if configured)
+    /// is redirected to the new log file.
+    /// Log files that were written before remain in the previous directory.
+    ///
+    /// This is useful for programs that migrate their data directory at runtime,
+    /// e.g. after mounting some encrypted storage.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Reset` if no file log writer is configured.
+    ///
+    /// `FlexiLoggerError::OutputIo` or `FlexiLoggerError::OutputBadDirectory`
+    /// if the specified path doesn't work.
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn relocate<P: AsRef<Path>>(&self, directory: P) -> Result<(), FlexiLoggerError> {
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            mw.relocate_file_log_writer(directory.as_ref())
+        } else {
+            Err(FlexiLoggerError::Reset)
+        }
+    }
+
+    /// Exempts a rotated log file of the file log writer from the cleanup.
+    ///
+    /// See [`FileLogWriter::pin`](crate::writers::FileLogWriter::pin) for details.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Reset` if no file log writer is configured.
+    ///
+    /// `FlexiLoggerError::OutputNotRotated` if the path does not denote a rotated log file.
+    ///
+    /// `FlexiLoggerError::OutputIo` if renaming the file fails.
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pin<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, FlexiLoggerError> {
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            mw.pin_file(path.as_ref())
+        } else {
+            Err(FlexiLoggerError::Reset)
+        }
+    }
+
+    /// Returns the paths of the log files of the file log writer that currently exist.
+    ///
+    /// See [`FileLogWriter::existing_log_files`](crate::writers::FileLogWriter::existing_log_files)
+    /// for details.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Reset` if no file log writer is configured.
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn existing_log_files(&self) -> Result<Vec<PathBuf>, FlexiLoggerError> {
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            mw.existing_log_files()
+        } else {
+            Err(FlexiLoggerError::Reset)
+        }
+    }
+
+    /// Replaces the rotation configuration of the file log writer at runtime,
+    /// e.g. to tighten the rotation or the cleanup during an incident.
+    ///
+    /// See [`FileLogWriter::set_rotation`](crate::writers::FileLogWriter::set_rotation)
+    /// for details.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Reset` if no file log writer is configured.
+    ///
+    /// `FlexiLoggerError::OutputCleanupConflict` if the new cleanup would affect the files
+    /// of another writer.
+    ///
+    /// `FlexiLoggerError::OutputIo` if rotating or opening the file fails.
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_rotation<C: Into<Criterion>>(
+        &self,
+        criterion: C,
+        naming: Naming,
+        cleanup: Cleanup,
+    ) -> Result<(), FlexiLoggerError> {
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            mw.set_file_rotation(criterion.into(), naming, cleanup)
+        } else {
+            Err(FlexiLoggerError::Reset)
+        }
+    }
+
+    /// Replaces the format function for all messages that are written to files,
+    /// stderr, or stdout, e.g. to switch between human-readable output and JSON
+    /// when an operator asks for machine-readable logs.
+    ///
+    /// This is the runtime counterpart of [`Logger::format`](crate::Logger::format),
+    /// with the exception that the writer that was given to
+    /// [`Logger::log_to_writer`](crate::Logger::log_to_writer) or
+    /// [`Logger::log_to_file_and_writer`](crate::Logger::log_to_file_and_writer)
+    /// keeps its format.
+    /// Use [`LoggerHandle::set_format_for_additional_writer`] for the additional writers.
+    ///
+    /// The new format function is used for all log lines that are written after this call;
+    /// with an asynchronous [`WriteMode`](crate::WriteMode), lines that were already handed over
+    /// to the output thread are not affected.
+    pub fn set_format(&self, format: FormatFunction) {
+        self.primary_writer.set_format_for_files(format);
+        self.primary_writer.set_format_for_stderr(format);
+        self.primary_writer.set_format_for_stdout(format);
+    }
+
+    /// Replaces the format function for messages that are written to files.
+    ///
+    /// See [`LoggerHandle::set_format`].
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Reset` if no file log writer is configured.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_format_for_files(&self, format: FormatFunction) -> Result<(), FlexiLoggerError> {
+        if self.primary_writer.set_format_for_files(format) {
+            Ok(())
+        } else {
+            Err(FlexiLoggerError::Reset)
+        }
+    }
+
+    /// Replaces the format function for messages that are written,
+    /// or duplicated, to stderr.
+    ///
+    /// See [`LoggerHandle::set_format`].
+    pub fn set_format_for_stderr(&self, format: FormatFunction) {
+        self.primary_writer.set_format_for_stderr(format);
+    }
+
+    /// Replaces the format function for messages that are written,
+    /// or duplicated, to stdout.
+    ///
+    /// See [`LoggerHandle::set_format`].
+    pub fn set_format_for_stdout(&self, format: FormatFunction) {
+        self.primary_writer.set_format_for_stdout(format);
+    }
+
+    /// Returns the last `n` log lines that the file log writer wrote, oldest first.
+    ///
+    /// See [`FileLogWriter::read_back`](crate::writers::FileLogWriter::read_back) for details.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Reset` if no file log writer is configured.
+    ///
+    /// `FlexiLoggerError::OutputIo` if reading a log file fails.
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_back(&self, n: usize) -> Result<Vec<String>, FlexiLoggerError> {
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            mw.read_back(n)
+        } else {
+            Err(FlexiLoggerError::Reset)
+        }
+    }
+
+    /// Like [`LoggerHandle::relocate`], and additionally moves the specfile that was provided
+    /// to [`Logger::start_with_specfile`](crate::Logger::start_with_specfile)
+    /// into the given directory, and watches it there.
+    ///
+    /// If the directory already contains a file with the name of the specfile,
+    /// that file is used, and the previous specfile remains untouched.
+    ///
+    /// # Errors
+    ///
+    /// See [`LoggerHandle::relocate`].
+    ///
+    /// `FlexiLoggerError::SpecfileIo` if the specfile cannot be moved.
+    ///
+    /// Several other variants of [`FlexiLoggerError`] can occur when the specfile
+    /// is read and watched in the new directory.
+    #[cfg_attr(docsrs, doc(cfg(feature = "specfile_without_notification")))]
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn relocate_with_specfile<P: AsRef<Path>>(
+        &self,
+        directory: P,
+    ) -> Result<(), FlexiLoggerError> {
+        let directory = directory.as_ref();
+        self.relocate(directory)?;
+
+        let o_old_specfile = self
+            .o_specfile_watch
+            .lock()
+            .map_err(|_| FlexiLoggerError::Poison)?
+            .as_ref()
+            .map(|watch| watch.specfile.clone());
+        if let Some((old_specfile, file_name)) = o_old_specfile
+            .as_ref()
+            .and_then(|old_specfile| Some((old_specfile, old_specfile.file_name()?)))
+        {
+            let new_specfile = directory.join(file_name);
+            if !new_specfile.exists() && old_specfile.exists() {
+                // rename does not work across file systems, then we fall back to copying
+                std::fs::rename(old_specfile, &new_specfile)
+                    .or_else(|_| std::fs::copy(old_specfile, &new_specfile).map(|_| ()))
+                    .map_err(FlexiLoggerError::SpecfileIo)?;
+            }
+            self.watch_specfile(&new_specfile)?;
+        }
+        Ok(())
+    }
+
+    // Installs a panic hook that logs the panic and flushes all writers.
+    pub(crate) fn log_panics(&self) {
+        let handle = self.non_owning_clone();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            let payload = panic_info.payload();
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("Box<dyn Any>");
+            let location = panic_info.location().map_or_else(String::new, |location| {
+                format!(
+                    ", {}:{}:{}",
+                    location.file(),
+                    location.line(),
+                    location.column()
+                )
+            });
+            log::error!(
+                target: "panic",
+                "thread '{}' panicked at '{}'{}{}",
+                std::thread::current().name().unwrap_or("<unnamed>"),
+                message,
+                location,
+                backtrace()
+            );
+            handle.flush();
+        }));
+    }
+
+    // Binds the admin socket and serves its requests.
+    #[cfg(feature = "admin_socket")]
+    pub(crate) fn serve_admin_socket(
+        &self,
+        admin_socket: &crate::AdminSocket,
+    ) -> Result<(), FlexiLoggerError> {
+        crate::admin_socket::serve(admin_socket, self.non_owning_clone())
+    }
+
+    // The currently active log specification.
+    #[cfg(feature = "admin_socket")]
+    pub(crate) fn current_spec(&self) -> LogSpecification {
+        self.spec
+            .read()
+            .map_err(|e| eprint_err(ERRCODE::Poison, "rwlock on log spec is poisoned", &e))
+            .unwrap(/*ok*/)
+            .clone()
+    }
+
+    // Synchronizes the log specification with the given specfile and watches it for changes;
+    // a previously watched specfile is no longer watched.
+    #[cfg(feature = "specfile_without_notification")]
+    pub(crate) fn watch_specfile(&self, specfile: &Path) -> Result<(), FlexiLoggerError> {
+        let mut guard = self
+            .o_specfile_watch
+            .lock()
+            .map_err(|_| FlexiLoggerError::Poison)?;
+        if let Some(previous) = guard.take() {
+            previous.active.store(false, Ordering::Relaxed);
+            previous.wakeup.wake_up();
+        }
+        let active = Arc::new(AtomicBool::new(true));
+        let wakeup = crate::logger::subscribe_to_specfile_while_active(
+            specfile,
+            self.non_owning_clone(),
+            Arc::clone(&active),
+        )?;
+        *guard = Some(SpecfileWatch {
+            specfile: specfile.to_path_buf(),
+            active,
+            wakeup,
+        });
+        Ok(())
+    }
+
+    /// Returns a snapshot of the number of log records per level that were handed to the
+    /// primary writer and to each of the additional writers.
+    ///
+    /// See [`RecordCounters`] for details.
+    #[must_use]
+    pub fn counters(&self) -> RecordCounters {
+        self.record_counts.snapshot()
+    }
+
+    /// Returns a snapshot of the counters of the file log writer.
+    ///
+    /// Returns `None` if no file log writer is configured.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn writer_stats(&self) -> Option<WriterStats> {
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            mw.file_writer_stats()
+        } else {
+            None
+        }
+    }
+
+    /// Returns statistics about the time that was spent in formatting and writing log records.
+    ///
+    /// Returns `None` if the logger was not configured with
+    /// [`Logger::sample_timings`](crate::Logger::sample_timings),
+    /// or if no record was measured yet.
+    #[must_use]
+    pub fn timing_metrics(&self) -> Option<TimingMetrics> {
+        self.o_timing_sampler
+            .as_ref()
+            .and_then(|sampler| sampler.metrics())
+    }
+
+    /// Returns the number of internal errors that occurred so far, per error code;
+    /// error codes without errors are omitted.
+    ///
+    /// The counts cover all loggers of the process, since the start of the process.
+    /// See [`error_info`](crate::error_info) for the meaning of the error codes.
+    #[must_use]
+    pub fn error_counts(&self) -> HashMap<ErrCode, usize> {
+        crate::util::error_counts()
+    }
+
+    /// Returns `false` if writing or flushing log lines failed within the last minute.
+    ///
+    /// This allows e.g. the health endpoint of a service to report
+    /// when logging is silently failing.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        !crate::util::write_error_within(HEALTH_PERIOD)
+    }
+
+    /// Returns the number of log records that were emitted after [`LoggerHandle::shutdown`]
+    /// was called.
+    ///
+    /// See [`ShutdownPolicy`](crate::ShutdownPolicy).
+    #[must_use]
+    pub fn records_after_shutdown(&self) -> usize {
+        self.shutdown_gate.records_after_shutdown()
+    }
+
+    /// Returns the log records that were emitted after [`LoggerHandle::shutdown`] was called,
+    /// and that were queued due to
+    /// [`ShutdownPolicy::QueueAndAbandon`](crate::ShutdownPolicy::QueueAndAbandon).
+    #[must_use]
+    pub fn abandoned_records(&self) -> Vec<String> {
+        self.shutdown_gate.abandoned_records()
+    }
+
     /// Shutdown all participating writers.
     ///
     /// This method is supposed to be called at the very end of your program, if
@@ -211,14 +994,71 @@ impl LoggerHandle {
     ///   which could leave unexpected files in the filesystem
     /// - you use your own writer(s), and they need to clean up resources
     ///
+    /// Log records that are emitted after the shutdown are handled according to the
+    /// [`ShutdownPolicy`](crate::ShutdownPolicy)
+    /// (see [`Logger::shutdown_policy`](crate::Logger::shutdown_policy)).
+    ///
     /// See also [`writers::LogWriter::shutdown`](crate::writers::LogWriter::shutdown).
     pub fn shutdown(&self) {
+        self.write_shutdown_summary();
+        self.shutdown_gate.close();
         self.primary_writer.shutdown();
-        for writer in self.other_writers.values() {
+        for writer in read_other_writers(&self.other_writers).values() {
             writer.shutdown();
         }
     }
 
+    /// Like [`LoggerHandle::shutdown`], but waits at most for the given duration,
+    /// which is important if the program must terminate before some deadline.
+    ///
+    /// Returns `true` if the shutdown was completed in time, i.e., if all buffered
+    /// log lines were written. If the shutdown takes longer, e.g. because some output hangs,
+    /// it is left to a background thread, and `false` is returned.
+    /// Dropping the `LoggerHandle` then does not try to shut down the writers again.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn shutdown_with_timeout(&self, timeout: Duration) -> bool {
+        self.write_shutdown_summary();
+        let (sender, receiver) = channel();
+        let shutdown_gate = Arc::clone(&self.shutdown_gate);
+        let primary_writer = Arc::clone(&self.primary_writer);
+        let other_writers = Arc::clone(&self.other_writers);
+        let spawned = std::thread::Builder::new()
+            .name("flexi_logger-shutdown".to_string())
+            .spawn(move || {
+                shutdown_gate.close();
+                primary_writer.shutdown();
+                for writer in read_other_writers(&other_writers).values() {
+                    writer.shutdown();
+                }
+                sender.send(()).ok();
+            });
+        if spawned.is_ok() {
+            receiver.recv_timeout(timeout).is_ok()
+        } else {
+            // without a separate thread, the duration of the shutdown cannot be bounded
+            self.shutdown();
+            true
+        }
+    }
+
+    // See Logger::shutdown_summary.
+    fn write_shutdown_summary(&self) {
+        if let Some(ref shutdown_summary) = self.o_shutdown_summary {
+            if self.shutdown_gate.is_closed() {
+                return;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            let o_bytes = self.writer_stats().map(|stats| stats.bytes);
+            #[cfg(target_arch = "wasm32")]
+            let o_bytes = None;
+            let counters = self.record_counts.primary_snapshot();
+            if let Some(summary) = shutdown_summary.take(&counters, o_bytes) {
+                write_lifecycle_record(&self.primary_writer, &summary);
+            }
+        }
+    }
+
     // Allows checking the logs written so far to the writer
     #[doc(hidden)]
     pub fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
@@ -226,15 +1066,114 @@ impl LoggerHandle {
     }
 }
 
+// Flushes all writers, and waits until they have written out everything,
+// but not longer than the timeout.
+fn flush_and_wait(
+    primary_writer: &PrimaryWriter,
+    other_writers: &RwLock<HashMap<String, Box<dyn LogWriter>>>,
+    timeout: Duration,
+) -> bool {
+    let deadline = Instant::now() + timeout;
+    let mut complete = primary_writer.flush_and_wait(timeout);
+    for writer in read_other_writers(other_writers).values() {
+        complete &= writer.flush_and_wait(deadline.saturating_duration_since(Instant::now()));
+    }
+    complete
+}
+
+// The result of a flush that is done in an extra thread, see LoggerHandle::flush_and_wait_async.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct FlushCompletionState {
+    o_complete: Option<bool>,
+    o_waker: Option<Waker>,
+}
+#[cfg(not(target_arch = "wasm32"))]
+struct FlushCompletion(Arc<Mutex<FlushCompletionState>>);
+#[cfg(not(target_arch = "wasm32"))]
+impl Future for FlushCompletion {
+    type Output = bool;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<bool> {
+        if let Ok(mut state) = self.0.lock() {
+            if let Some(complete) = state.o_complete {
+                return Poll::Ready(complete);
+            }
+            state.o_waker = Some(cx.waker().clone());
+            Poll::Pending
+        } else {
+            Poll::Ready(false)
+        }
+    }
+}
+
+#[rustversion::since(1.65)]
+fn backtrace() -> String {
+    format!("\n{}", std::backtrace::Backtrace::force_capture())
+}
+#[rustversion::before(1.65)]
+fn backtrace() -> String {
+    String::new()
+}
+
 impl Drop for LoggerHandle {
     fn drop(&mut self) {
-        self.primary_writer.shutdown();
-        for writer in self.other_writers.values() {
-            writer.shutdown();
+        // after an explicit shutdown, which might still be in progress, nothing is to be done
+        if self.shutdown_on_drop && !self.shutdown_gate.is_closed() {
+            self.write_shutdown_summary();
+            self.primary_writer.shutdown();
+            for writer in read_other_writers(&self.other_writers).values() {
+                writer.shutdown();
+            }
+        }
+    }
+}
+
+/// Restores the previous log specification when it is dropped.
+///
+/// Is returned by [`LoggerHandle::raise_level_scoped`].
+#[must_use = "the previous log specification is restored when the guard is dropped"]
+pub struct SpecGuard {
+    handle: LoggerHandle,
+    id: usize,
+}
+impl Drop for SpecGuard {
+    fn drop(&mut self) {
+        let mut spec_guards = self
+            .handle
+            .am_spec_guards
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = spec_guards
+            .replaced
+            .iter()
+            .position(|(id, _)| *id == self.id)
+        {
+            let (_, previous_spec) = spec_guards.replaced.remove(index);
+            if index < spec_guards.replaced.len() {
+                // a later guard is still alive, and will restore the spec that was active
+                // before this guard
+                spec_guards.replaced[index].1 = previous_spec;
+            } else {
+                let max_level = previous_spec.max_level();
+                self.handle
+                    .spec
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .update_from(previous_spec);
+                drop(spec_guards);
+                self.handle.reconfigure(max_level);
+            }
         }
     }
 }
 
+// The specs that were replaced by the SpecGuards that are alive, oldest first.
+#[derive(Default)]
+struct SpecGuards {
+    next_id: usize,
+    replaced: Vec<(usize, LogSpecification)>,
+}
+
 /// Trait that allows to register for changes to the log specification.
 #[cfg(feature = "specfile_without_notification")]
 pub trait LogSpecSubscriber: 'static + Send {
@@ -264,3 +1203,54 @@ impl LogSpecSubscriber for LoggerHandle {
         Ok((*self.spec.read().map_err(|_e| FlexiLoggerError::Poison)?).clone())
     }
 }
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_HANDLE: RwLock<Option<LoggerHandle>> = RwLock::new(None);
+}
+
+// Remembers the handle of the logger that was successfully installed as the global logger.
+pub(crate) fn register_active_handle(handle: &LoggerHandle) {
+    if let Ok(mut guard) = ACTIVE_HANDLE.write() {
+        *guard = Some(handle.non_owning_clone());
+    }
+}
+
+/// Provides a handle to the active logger, i.e., the logger that was installed with
+/// [`Logger::start`](crate::Logger::start) or
+/// [`Logger::start_with_specfile`](crate::Logger::start_with_specfile), if any.
+///
+/// This allows deeply nested code to flush the logger or to modify the log specification
+/// without the need to pass the [`LoggerHandle`] through all layers of the application.
+///
+/// Dropping the returned handle does _not_ shut down the logger;
+/// this remains the job of the handle that was returned from the logger initialization.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = flexi_logger::Logger::try_with_str("info")?.start()?;
+/// // ...
+/// if let Some(mut handle) = flexi_logger::handle() {
+///     handle.parse_new_spec("debug")?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+pub fn handle() -> Option<LoggerHandle> {
+    ACTIVE_HANDLE
+        .read()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(LoggerHandle::non_owning_clone))
+}
+
+/// Equivalent to [`handle`].
+///
+/// Is useful if the logger initialization failed with
+/// [`FlexiLoggerError::AlreadyInitialized`], e.g. in plugin-style architectures
+/// where several components try to initialize the logger.
+#[must_use]
+pub fn existing_handle() -> Option<LoggerHandle> {
+    handle()
+}
@@ -0,0 +1,117 @@
+//! Mapped diagnostic context (MDC): key-value pairs that belong to the current thread
+//! and are written into every log line that the thread produces.
+//!
+//! This allows adding request-scoped context, like a request id or a user name,
+//! to all log lines without passing it through all call sites.
+//!
+//! The provided text format functions (like [`opt_format`](crate::opt_format)) append the
+//! entries to the log line, like ` request_id=4711 user=anna`,
+//! [`json_format`](crate::json_format) writes them as object with name `mdc`,
+//! and [`logfmt_format`](crate::logfmt_format) writes them as additional fields.
+//! Own format functions can query the entries with [`get`] or [`for_each`].
+//!
+//! The entries are written in the order of their keys.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use flexi_logger::mdc;
+//!
+//! fn handle_request(request_id: u64) {
+//!     let _guard = mdc::insert_scoped("request_id", request_id);
+//!     log::info!("processing"); // is written with ` request_id=...`
+//! } // the entry is removed here
+//! ```
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+thread_local! {
+    #[allow(clippy::missing_const_for_thread_local)] // const initializers need rustc 1.59
+    static MDC: RefCell<BTreeMap<String, String>> = RefCell::new(BTreeMap::new());
+}
+
+/// Adds an entry to the MDC of the current thread,
+/// and returns the value that the key had before, if any.
+#[allow(clippy::needless_pass_by_value)]
+pub fn insert<K: Into<String>, V: ToString>(key: K, value: V) -> Option<String> {
+    MDC.with(|mdc| mdc.borrow_mut().insert(key.into(), value.to_string()))
+}
+
+/// Adds an entry to the MDC of the current thread, for as long as the returned guard lives.
+///
+/// When the guard is dropped, the previous value of the key is restored,
+/// or the entry is removed if the key had no value before.
+#[must_use = "the entry is removed when the guard is dropped"]
+pub fn insert_scoped<K: Into<String>, V: ToString>(key: K, value: V) -> MdcGuard {
+    let key = key.into();
+    let o_previous = insert(key.clone(), value);
+    MdcGuard { key, o_previous }
+}
+
+/// Returns the value of the given key in the MDC of the current thread.
+#[must_use]
+pub fn get(key: &str) -> Option<String> {
+    MDC.with(|mdc| mdc.borrow().get(key).cloned())
+}
+
+/// Removes an entry from the MDC of the current thread, and returns its value.
+#[allow(clippy::must_use_candidate)]
+pub fn remove(key: &str) -> Option<String> {
+    MDC.with(|mdc| mdc.borrow_mut().remove(key))
+}
+
+/// Removes all entries from the MDC of the current thread.
+pub fn clear() {
+    MDC.with(|mdc| mdc.borrow_mut().clear());
+}
+
+/// Calls the given function for each entry of the MDC of the current thread,
+/// in the order of the keys.
+///
+/// The MDC must not be modified from within the function; such modifications
+/// are not possible while the MDC is read, and would panic.
+pub fn for_each<F: FnMut(&str, &str)>(mut f: F) {
+    MDC.with(|mdc| {
+        if let Ok(mdc) = mdc.try_borrow() {
+            for (key, value) in mdc.iter() {
+                f(key, value);
+            }
+        }
+    });
+}
+
+// Like for_each, but stops at the first error.
+pub(crate) fn try_for_each<F>(mut f: F) -> std::io::Result<()>
+where
+    F: FnMut(&str, &str) -> std::io::Result<()>,
+{
+    MDC.with(|mdc| match mdc.try_borrow() {
+        Ok(mdc) => mdc.iter().try_for_each(|(key, value)| f(key, value)),
+        Err(_) => Ok(()),
+    })
+}
+
+pub(crate) fn is_empty() -> bool {
+    MDC.with(|mdc| mdc.try_borrow().map_or(true, |mdc| mdc.is_empty()))
+}
+
+/// Restores the previous state of an entry of the MDC when it is dropped.
+///
+/// Is returned by [`insert_scoped`].
+#[derive(Debug)]
+pub struct MdcGuard {
+    key: String,
+    o_previous: Option<String>,
+}
+impl Drop for MdcGuard {
+    fn drop(&mut self) {
+        match self.o_previous.take() {
+            Some(previous) => {
+                insert(std::mem::take(&mut self.key), previous);
+            }
+            None => {
+                remove(&self.key);
+            }
+        }
+    }
+}
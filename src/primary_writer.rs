@@ -3,31 +3,40 @@ mod stderr_writer;
 mod stdout_writer;
 
 use self::multi_writer::MultiWriter;
+pub(crate) use self::multi_writer::StderrDuplication;
 use self::stderr_writer::StdErrWriter;
 use self::stdout_writer::StdOutWriter;
 use crate::deferred_now::DeferredNow;
 use crate::filter::LogLineWriter;
 use crate::logger::Duplicate;
-use crate::writers::{FileLogWriter, LogWriter};
-use crate::{FormatFunction, WriteMode};
+#[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+use crate::writers::BrowserConsoleWriter;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::writers::FileLogWriter;
+use crate::writers::LogWriter;
+use crate::{FormatFunction, Output, WriteMode};
 use log::Record;
 
 // Writes either to stdout, or to stderr,
 // or to a file (with optional duplication to stderr),
-// or to nowhere (with optional "duplication" to stderr).
+// or to nowhere (with optional "duplication" to stderr),
+// or, in the browser, to its console.
 #[allow(clippy::large_enum_variant)]
 pub(crate) enum PrimaryWriter {
     StdOut(StdOutWriter),
     StdErr(StdErrWriter),
     Multi(MultiWriter),
+    #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+    BrowserConsole(Box<BrowserConsoleWriter>),
 }
 impl PrimaryWriter {
     pub fn multi(
-        duplicate_stderr: Duplicate,
+        duplicate_stderr: StderrDuplication,
         duplicate_stdout: Duplicate,
         format_for_stderr: FormatFunction,
         format_for_stdout: FormatFunction,
-        o_file_writer: Option<Box<FileLogWriter>>,
+        line_ending: &'static [u8],
+        #[cfg(not(target_arch = "wasm32"))] o_file_writer: Option<Box<FileLogWriter>>,
         o_other_writer: Option<Box<dyn LogWriter>>,
     ) -> Self {
         Self::Multi(MultiWriter::new(
@@ -35,24 +44,52 @@ impl PrimaryWriter {
             duplicate_stdout,
             format_for_stderr,
             format_for_stdout,
+            line_ending,
+            #[cfg(not(target_arch = "wasm32"))]
             o_file_writer,
             o_other_writer,
         ))
     }
-    pub fn stderr(format: FormatFunction, write_mode: &WriteMode) -> Self {
-        Self::StdErr(StdErrWriter::new(format, write_mode))
+    pub fn stderr(
+        format: FormatFunction,
+        write_mode: &WriteMode,
+        line_ending: &'static [u8],
+    ) -> Self {
+        Self::StdErr(StdErrWriter::new(format, write_mode, line_ending))
     }
 
-    pub fn stdout(format: FormatFunction, write_mode: &WriteMode) -> Self {
-        Self::StdOut(StdOutWriter::new(format, write_mode))
+    pub fn stdout(
+        format: FormatFunction,
+        write_mode: &WriteMode,
+        line_ending: &'static [u8],
+    ) -> Self {
+        Self::StdOut(StdOutWriter::new(format, write_mode, line_ending))
+    }
+
+    #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+    pub fn browser_console(format: FormatFunction) -> Self {
+        let mut writer = BrowserConsoleWriter::new(log::LevelFilter::Trace);
+        writer.format(format);
+        Self::BrowserConsole(writer)
     }
 
     // Write out a log line.
     pub fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
         match *self {
-            Self::StdErr(ref w) => w.write(now, record),
-            Self::StdOut(ref w) => w.write(now, record),
+            Self::StdErr(ref w) => {
+                now.set_output(Output::Stderr);
+                w.write(now, record)
+            }
+            Self::StdOut(ref w) => {
+                now.set_output(Output::Stdout);
+                w.write(now, record)
+            }
             Self::Multi(ref w) => w.write(now, record),
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(ref w) => {
+                now.set_output(Output::BrowserConsole);
+                w.write(now, record)
+            }
         }
     }
 
@@ -62,6 +99,82 @@ impl PrimaryWriter {
             Self::StdErr(ref w) => w.flush(),
             Self::StdOut(ref w) => w.flush(),
             Self::Multi(ref w) => w.flush(),
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(ref w) => w.flush(),
+        }
+    }
+
+    // Flushes, and waits until the flushed log lines are written, but not longer than the timeout.
+    pub fn flush_and_wait(&self, timeout: std::time::Duration) -> bool {
+        match *self {
+            Self::StdErr(ref w) => w.flush_and_wait(timeout),
+            Self::StdOut(ref w) => w.flush_and_wait(timeout),
+            Self::Multi(ref w) => w.flush_and_wait(timeout),
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(ref w) => w.flush_and_wait(timeout),
+        }
+    }
+
+    // Replaces the format function for the output to files; returns false if there is none.
+    pub fn set_format_for_files(&self, format: FormatFunction) -> bool {
+        match self {
+            Self::StdErr(_) | Self::StdOut(_) => false,
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Multi(w) => w.set_format_for_file(format),
+            #[cfg(target_arch = "wasm32")]
+            Self::Multi(_) => {
+                let _ = format;
+                false
+            }
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(_) => false,
+        }
+    }
+
+    // Replaces the format function for the output, or the duplication, to stderr.
+    pub fn set_format_for_stderr(&self, format: FormatFunction) {
+        match self {
+            Self::StdErr(w) => w.set_format(format),
+            Self::StdOut(_) => {}
+            Self::Multi(w) => w.set_format_for_stderr(format),
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(_) => {}
+        }
+    }
+
+    // Replaces the format function for the output, or the duplication, to stdout.
+    pub fn set_format_for_stdout(&self, format: FormatFunction) {
+        match self {
+            Self::StdErr(_) => {}
+            Self::StdOut(w) => w.set_format(format),
+            Self::Multi(w) => w.set_format_for_stdout(format),
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(_) => {}
+        }
+    }
+
+    // Number of log lines that were discarded due to the overflow policy of the async mode.
+    pub fn dropped_records(&self) -> usize {
+        match self {
+            Self::StdErr(w) => w.dropped_records(),
+            Self::StdOut(w) => w.dropped_records(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Multi(w) => w.dropped_records(),
+            #[cfg(target_arch = "wasm32")]
+            Self::Multi(_) => 0,
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(_) => 0,
+        }
+    }
+
+    // Names of the writers that are not yet ready to write.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn unready_writers(&self) -> Vec<&'static str> {
+        match self {
+            Self::StdErr(_) | Self::StdOut(_) => Vec::new(),
+            Self::Multi(w) => w.unready_writers(),
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(_) => Vec::new(),
         }
     }
 
@@ -77,6 +190,10 @@ impl PrimaryWriter {
             Self::Multi(writer) => {
                 writer.validate_logs(expected);
             }
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(writer) => {
+                writer.validate_logs(expected);
+            }
         }
     }
 
@@ -92,6 +209,10 @@ impl PrimaryWriter {
             Self::Multi(writer) => {
                 writer.shutdown();
             }
+            #[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+            Self::BrowserConsole(writer) => {
+                writer.shutdown();
+            }
         }
     }
 }
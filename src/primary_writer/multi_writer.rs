@@ -1,40 +1,64 @@
 use crate::deferred_now::DeferredNow;
 use crate::logger::Duplicate;
-use crate::util::write_buffered;
-use crate::writers::{FileLogWriter, FileLogWriterBuilder, LogWriter};
-use crate::{FlexiLoggerError, FormatFunction};
+use crate::rate_limit::{RateLimiter, Suppressed};
+use crate::util::{write_buffered, LockedStderr, SwappableFormat};
+use crate::writers::LogWriter;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::writers::{FileLogWriter, FileLogWriterBuilder, WriterStats};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{Cleanup, Criterion, FlexiLoggerError, Naming};
+use crate::{FormatFunction, LogSpecification, Output};
 use log::Record;
 use std::io::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// Decides which messages are duplicated to stderr, see Logger::duplicate_to_stderr,
+// Logger::duplicate_to_stderr_with_spec, and Logger::rate_limit_duplication_to_stderr.
+pub(crate) struct StderrDuplication {
+    pub(crate) duplicate: Duplicate,
+    // overrides duplicate
+    pub(crate) o_spec: Option<LogSpecification>,
+    pub(crate) o_rate_limiter: Option<RateLimiter>,
+}
 
 // The `MultiWriter` writes logs to stderr or to a set of `Writer`s, and in the latter case
 // can duplicate messages to stderr.
 pub(crate) struct MultiWriter {
-    duplicate_stderr: Duplicate,
+    duplicate_stderr: StderrDuplication,
     duplicate_stdout: Duplicate,
-    format_for_stderr: FormatFunction,
-    format_for_stdout: FormatFunction,
+    format_for_stderr: SwappableFormat,
+    format_for_stdout: SwappableFormat,
+    // for the duplication to stderr and stdout
+    line_ending: &'static [u8],
+    #[cfg(not(target_arch = "wasm32"))]
     o_file_writer: Option<Box<FileLogWriter>>,
     o_other_writer: Option<Box<dyn LogWriter>>,
 }
 
 impl MultiWriter {
     pub(crate) fn new(
-        duplicate_stderr: Duplicate,
+        duplicate_stderr: StderrDuplication,
         duplicate_stdout: Duplicate,
         format_for_stderr: FormatFunction,
         format_for_stdout: FormatFunction,
-        o_file_writer: Option<Box<FileLogWriter>>,
+        line_ending: &'static [u8],
+        #[cfg(not(target_arch = "wasm32"))] o_file_writer: Option<Box<FileLogWriter>>,
         o_other_writer: Option<Box<dyn LogWriter>>,
     ) -> Self {
         MultiWriter {
             duplicate_stderr,
             duplicate_stdout,
-            format_for_stderr,
-            format_for_stdout,
+            format_for_stderr: SwappableFormat::new(format_for_stderr),
+            format_for_stdout: SwappableFormat::new(format_for_stdout),
+            line_ending,
+            #[cfg(not(target_arch = "wasm32"))]
             o_file_writer,
             o_other_writer,
         }
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn reset_file_log_writer(
         &self,
         flwb: &FileLogWriterBuilder,
@@ -43,10 +67,158 @@ impl MultiWriter {
             .as_ref()
             .map_or(Err(FlexiLoggerError::Reset), |flw| flw.reset(flwb))
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn relocate_file_log_writer(
+        &self,
+        directory: &Path,
+    ) -> Result<(), FlexiLoggerError> {
+        self.o_file_writer
+            .as_ref()
+            .map_or(Err(FlexiLoggerError::Reset), |flw| flw.relocate(directory))
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn pin_file(&self, path: &Path) -> Result<PathBuf, FlexiLoggerError> {
+        self.o_file_writer
+            .as_ref()
+            .map_or(Err(FlexiLoggerError::Reset), |flw| flw.pin(path))
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn existing_log_files(&self) -> Result<Vec<PathBuf>, FlexiLoggerError> {
+        self.o_file_writer
+            .as_ref()
+            .map_or(Err(FlexiLoggerError::Reset), |flw| flw.existing_log_files())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn set_file_rotation(
+        &self,
+        criterion: Criterion,
+        naming: Naming,
+        cleanup: Cleanup,
+    ) -> Result<(), FlexiLoggerError> {
+        self.o_file_writer
+            .as_ref()
+            .map_or(Err(FlexiLoggerError::Reset), |flw| {
+                flw.set_rotation(criterion, naming, cleanup)
+            })
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn set_format_for_file(&self, format: FormatFunction) -> bool {
+        if let Some(ref flw) = self.o_file_writer {
+            flw.set_format(format);
+            true
+        } else {
+            false
+        }
+    }
+    pub(crate) fn set_format_for_stderr(&self, format: FormatFunction) {
+        self.format_for_stderr.set(format);
+    }
+    pub(crate) fn set_format_for_stdout(&self, format: FormatFunction) {
+        self.format_for_stdout.set(format);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn read_back(&self, n: usize) -> Result<Vec<String>, FlexiLoggerError> {
+        self.o_file_writer
+            .as_ref()
+            .map_or(Err(FlexiLoggerError::Reset), |flw| flw.read_back(n))
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn file_writer_stats(&self) -> Option<WriterStats> {
+        self.o_file_writer.as_ref().map(|flw| flw.stats())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn dropped_records(&self) -> usize {
+        self.o_file_writer
+            .as_ref()
+            .map_or(0, |flw| flw.dropped_records())
+    }
+    fn duplicating_to_stderr(&self) -> bool {
+        self.duplicate_stderr.o_spec.is_some()
+            || !matches!(self.duplicate_stderr.duplicate, Duplicate::None)
+    }
+    fn duplicate_to_stderr(&self, record: &Record) -> bool {
+        if let Some(ref spec) = self.duplicate_stderr.o_spec {
+            return spec.enabled(record.level(), effective_module(record));
+        }
+        match self.duplicate_stderr.duplicate {
+            Duplicate::Error => record.level() == log::Level::Error,
+            Duplicate::Warn => record.level() <= log::Level::Warn,
+            Duplicate::Info => record.level() <= log::Level::Info,
+            Duplicate::Debug => record.level() <= log::Level::Debug,
+            Duplicate::Trace | Duplicate::All => true,
+            Duplicate::None => false,
+        }
+    }
+    // Applies the rate limit, if any, to the duplication of the record to stderr,
+    // and writes the warning about the messages that were suppressed before.
+    fn write_to_stderr(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        if let Some(ref rate_limiter) = self.duplicate_stderr.o_rate_limiter {
+            let (admitted, o_suppressed) = rate_limiter.admit(effective_module(record));
+            if let Some(suppressed) = o_suppressed {
+                self.write_suppressed(now, &suppressed)?;
+            }
+            if !admitted {
+                return Ok(());
+            }
+        }
+        self.write_to_stderr_unlimited(now, record)
+    }
+    fn write_suppressed(
+        &self,
+        now: &mut DeferredNow,
+        suppressed: &Suppressed,
+    ) -> std::io::Result<()> {
+        let target = if suppressed.target.is_empty() {
+            "flexi_logger"
+        } else {
+            &suppressed.target
+        };
+        self.write_to_stderr_unlimited(
+            now,
+            &Record::builder()
+                .args(format_args!(
+                    "{} log records were not duplicated to stderr due to the rate limit",
+                    suppressed.count
+                ))
+                .level(log::Level::Warn)
+                .target(target)
+                .module_path(Some(target))
+                .build(),
+        )
+    }
+    fn write_to_stderr_unlimited(
+        &self,
+        now: &mut DeferredNow,
+        record: &Record,
+    ) -> std::io::Result<()> {
+        now.set_output(Output::Stderr);
+        write_buffered(
+            self.format_for_stderr.get(),
+            now,
+            record,
+            self.line_ending,
+            &mut LockedStderr,
+            #[cfg(test)]
+            None,
+        )
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn unready_writers(&self) -> Vec<&'static str> {
+        let mut unready = Vec::new();
+        #[cfg(not(target_arch = "wasm32"))]
+        if matches!(self.o_file_writer, Some(ref writer) if !writer.ready()) {
+            unready.push("file");
+        }
+        if matches!(self.o_other_writer, Some(ref writer) if !writer.ready()) {
+            unready.push("writer");
+        }
+        unready
+    }
 }
 
 impl LogWriter for MultiWriter {
     fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        #[cfg(not(target_arch = "wasm32"))]
         if let Some(ref writer) = self.o_file_writer {
             (*writer).validate_logs(expected);
         }
@@ -56,22 +228,8 @@ impl LogWriter for MultiWriter {
     }
 
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        if match self.duplicate_stderr {
-            Duplicate::Error => record.level() == log::Level::Error,
-            Duplicate::Warn => record.level() <= log::Level::Warn,
-            Duplicate::Info => record.level() <= log::Level::Info,
-            Duplicate::Debug => record.level() <= log::Level::Debug,
-            Duplicate::Trace | Duplicate::All => true,
-            Duplicate::None => false,
-        } {
-            write_buffered(
-                self.format_for_stderr,
-                now,
-                record,
-                &mut std::io::stderr(),
-                #[cfg(test)]
-                None,
-            )?;
+        if self.duplicate_to_stderr(record) {
+            self.write_to_stderr(now, record)?;
         }
 
         if match self.duplicate_stdout {
@@ -82,20 +240,25 @@ impl LogWriter for MultiWriter {
             Duplicate::Trace | Duplicate::All => true,
             Duplicate::None => false,
         } {
+            now.set_output(Output::Stdout);
             write_buffered(
-                self.format_for_stdout,
+                self.format_for_stdout.get(),
                 now,
                 record,
+                self.line_ending,
                 &mut std::io::stdout(),
                 #[cfg(test)]
                 None,
             )?;
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
         if let Some(ref writer) = self.o_file_writer {
+            now.set_output(Output::File);
             writer.write(now, record)?;
         }
         if let Some(ref writer) = self.o_other_writer {
+            now.set_output(Output::Writer);
             writer.write(now, record)?;
         }
         Ok(())
@@ -103,10 +266,11 @@ impl LogWriter for MultiWriter {
 
     /// Provides the maximum log level that is to be written.
     fn max_log_level(&self) -> log::LevelFilter {
-        *self
-            .o_file_writer
-            .as_ref()
-            .map(|w| w.max_log_level())
+        #[cfg(not(target_arch = "wasm32"))]
+        let o_file_level = self.o_file_writer.as_ref().map(|w| w.max_log_level());
+        #[cfg(target_arch = "wasm32")]
+        let o_file_level = None;
+        *o_file_level
             .iter()
             .chain(
                 self.o_other_writer
@@ -119,6 +283,7 @@ impl LogWriter for MultiWriter {
     }
 
     fn flush(&self) -> std::io::Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
         if let Some(ref writer) = self.o_file_writer {
             writer.flush()?;
         }
@@ -126,7 +291,12 @@ impl LogWriter for MultiWriter {
             writer.flush()?;
         }
 
-        if !matches!(self.duplicate_stderr, Duplicate::None) {
+        if self.duplicating_to_stderr() {
+            if let Some(ref rate_limiter) = self.duplicate_stderr.o_rate_limiter {
+                for suppressed in rate_limiter.take_ended() {
+                    self.write_suppressed(&mut DeferredNow::new(), &suppressed)?;
+                }
+            }
             std::io::stderr().flush()?;
         }
         if !matches!(self.duplicate_stdout, Duplicate::None) {
@@ -135,7 +305,27 @@ impl LogWriter for MultiWriter {
         Ok(())
     }
 
+    fn flush_and_wait(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut complete = true;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ref writer) = self.o_file_writer {
+            complete &= writer.flush_and_wait(timeout);
+        }
+        if let Some(ref writer) = self.o_other_writer {
+            complete &= writer.flush_and_wait(deadline.saturating_duration_since(Instant::now()));
+        }
+        if self.duplicating_to_stderr() {
+            complete &= std::io::stderr().flush().is_ok();
+        }
+        if !matches!(self.duplicate_stdout, Duplicate::None) {
+            complete &= std::io::stdout().flush().is_ok();
+        }
+        complete
+    }
+
     fn shutdown(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
         if let Some(ref writer) = self.o_file_writer {
             writer.shutdown();
         }
@@ -144,3 +334,13 @@ impl LogWriter for MultiWriter {
         }
     }
 }
+
+// Like with the log specification of the logger, special targets are not
+// matched against module filters, or counted by a rate limit.
+fn effective_module<'a>(record: &Record<'a>) -> &'a str {
+    if record.target().starts_with('{') {
+        record.module_path().unwrap_or_default()
+    } else {
+        record.target()
+    }
+}
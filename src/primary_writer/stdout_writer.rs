@@ -1,18 +1,17 @@
 use crate::deferred_now::DeferredNow;
 #[cfg(feature = "async")]
 use crate::util::{eprint_err, ERRCODE};
-use crate::util::{io_err, write_buffered};
+use crate::util::{io_err, write_buffered, write_direct, SwappableFormat};
 #[cfg(feature = "async")]
-use crate::util::{ASYNC_FLUSH, ASYNC_SHUTDOWN};
+use crate::util::{sanitize, AsyncSender, ASYNC_FLUSH, ASYNC_SHUTDOWN};
+#[cfg(feature = "async")]
+use crate::OverflowPolicy;
 use crate::{writers::LogWriter, EffectiveWriteMode, FormatFunction, WriteMode};
 #[cfg(test)]
 use std::io::Cursor;
 
 #[cfg(feature = "async")]
-use crossbeam::{
-    channel::{self, SendError, Sender},
-    queue::ArrayQueue,
-};
+use crossbeam::{channel::SendError, queue::ArrayQueue};
 use log::Record;
 use std::io::{BufWriter, Write};
 #[cfg(any(feature = "async", test))]
@@ -20,10 +19,12 @@ use std::sync::Arc;
 use std::sync::Mutex;
 #[cfg(feature = "async")]
 use std::thread::JoinHandle;
+use std::time::Duration;
 
 // `StdOutWriter` writes logs to stdout.
 pub(crate) struct StdOutWriter {
-    format: FormatFunction,
+    format: SwappableFormat,
+    line_ending: &'static [u8],
     writer: OutWriter,
     #[cfg(test)]
     validation_buffer: Arc<Mutex<Cursor<Vec<u8>>>>,
@@ -37,7 +38,7 @@ enum OutWriter {
 #[cfg(feature = "async")]
 #[derive(Debug)]
 struct AsyncHandle {
-    sender: Sender<Vec<u8>>,
+    sender: AsyncSender,
     mo_thread_handle: Mutex<Option<JoinHandle<()>>>,
     a_pool: Arc<ArrayQueue<Vec<u8>>>,
     msg_capa: usize,
@@ -48,11 +49,23 @@ impl AsyncHandle {
         _bufsize: usize,
         pool_capa: usize,
         msg_capa: usize,
+        channel_capa: Option<usize>,
+        overflow_policy: OverflowPolicy,
+        format: FormatFunction,
+        line_ending: &'static [u8],
         #[cfg(test)] validation_buffer: &Arc<Mutex<Cursor<Vec<u8>>>>,
     ) -> Self {
-        let (sender, receiver) = channel::unbounded::<Vec<u8>>();
         let a_pool = Arc::new(ArrayQueue::new(pool_capa));
+        let (sender, mut receiver) = AsyncSender::new(
+            channel_capa,
+            overflow_policy,
+            format,
+            line_ending,
+            &a_pool,
+            msg_capa,
+        );
         let t_pool = Arc::clone(&a_pool);
+        let t_flushes = sender.flush_tracker();
         #[cfg(test)]
         let t_validation_buffer = Arc::clone(validation_buffer);
 
@@ -72,6 +85,7 @@ impl AsyncHandle {
                                             .unwrap_or_else(
                                                 |e| eprint_err(ERRCODE::Flush, "flushing failed", &e)
                                             );
+                                        t_flushes.processed();
                                     }
                                     ASYNC_SHUTDOWN => {
                                         break;
@@ -88,6 +102,8 @@ impl AsyncHandle {
                                         }
                                     }
                                 }
+                                // buffers that had to grow beyond the configured capacity are freed
+                                // rather than pooled, to keep the memory consumption of the pool bounded
                                 if message.capacity() <= msg_capa {
                                     message.clear();
                                     t_pool.push(message).ok();
@@ -112,13 +128,21 @@ impl AsyncHandle {
             .unwrap_or_else(|| Vec::with_capacity(self.msg_capa))
     }
 
-    fn send(&self, buffer: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
-        self.sender.send(buffer)
+    fn send_line(&self, buffer: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
+        self.sender.send_line(buffer)
+    }
+
+    fn send_control(&self, buffer: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
+        self.sender.send_control(buffer)
     }
 }
 
 impl StdOutWriter {
-    pub(crate) fn new(format: FormatFunction, write_mode: &WriteMode) -> Self {
+    pub(crate) fn new(
+        format: FormatFunction,
+        write_mode: &WriteMode,
+        line_ending: &'static [u8],
+    ) -> Self {
         #[cfg(test)]
         let validation_buffer = Arc::new(Mutex::new(Cursor::new(Vec::<u8>::new())));
 
@@ -136,6 +160,8 @@ impl StdOutWriter {
                 pool_capa,
                 message_capa,
                 flush_interval,
+                channel_capa,
+                overflow_policy,
             } => {
                 assert_eq!(
                     flush_interval,
@@ -146,19 +172,38 @@ impl StdOutWriter {
                     bufsize,
                     pool_capa,
                     message_capa,
+                    channel_capa,
+                    overflow_policy,
+                    format,
+                    line_ending,
                     #[cfg(test)]
                     &validation_buffer,
                 ))
             }
         };
         Self {
-            format,
+            format: SwappableFormat::new(format),
+            line_ending,
             writer,
             #[cfg(test)]
             validation_buffer,
         }
     }
 }
+impl StdOutWriter {
+    pub(crate) fn set_format(&self, format: FormatFunction) {
+        self.format.set(format);
+    }
+
+    // Number of log lines that were discarded due to the overflow policy of the async mode.
+    pub(crate) fn dropped_records(&self) -> usize {
+        match &self.writer {
+            #[cfg(feature = "async")]
+            OutWriter::Async(handle) => handle.sender.dropped(),
+            _ => 0,
+        }
+    }
+}
 impl LogWriter for StdOutWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
@@ -167,9 +212,10 @@ impl LogWriter for StdOutWriter {
                 let mut w = stdout.lock();
 
                 write_buffered(
-                    self.format,
+                    self.format.get(),
                     now,
                     record,
+                    self.line_ending,
                     &mut w,
                     #[cfg(test)]
                     Some(&self.validation_buffer),
@@ -177,10 +223,13 @@ impl LogWriter for StdOutWriter {
             }
             OutWriter::Buffered(mbuf_w) => {
                 let mut w = mbuf_w.lock().map_err(|_e| io_err("Poison"))?;
-                write_buffered(
-                    self.format,
+                let stdout = std::io::stdout();
+                let _stdout_lock = stdout.lock();
+                write_direct(
+                    self.format.get(),
                     now,
                     record,
+                    self.line_ending,
                     &mut *w,
                     #[cfg(test)]
                     Some(&self.validation_buffer),
@@ -189,12 +238,10 @@ impl LogWriter for StdOutWriter {
             #[cfg(feature = "async")]
             OutWriter::Async(handle) => {
                 let mut buffer = handle.pop_buffer();
-                (self.format)(&mut buffer, now, record)
+                (self.format.get())(&mut buffer, now, record)
                     .unwrap_or_else(|e| eprint_err(ERRCODE::Format, "formatting failed", &e));
-                buffer
-                    .write_all(b"\n")
-                    .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing failed", &e));
-                handle.send(buffer).map_err(|_e| io_err("Send"))?;
+                sanitize(&mut buffer);
+                handle.send_line(buffer).map_err(|_e| io_err("Send"))?;
                 Ok(())
             }
         }
@@ -215,18 +262,31 @@ impl LogWriter for StdOutWriter {
             OutWriter::Async(handle) => {
                 let mut buffer = handle.pop_buffer();
                 buffer.extend(ASYNC_FLUSH);
-                handle.send(buffer).ok();
+                handle.send_control(buffer).ok();
                 Ok(())
             }
         }
     }
 
+    #[cfg_attr(not(feature = "async"), allow(unused_variables))]
+    fn flush_and_wait(&self, timeout: Duration) -> bool {
+        match &self.writer {
+            #[cfg(feature = "async")]
+            OutWriter::Async(handle) => {
+                let mut buffer = handle.pop_buffer();
+                buffer.extend(ASYNC_FLUSH);
+                handle.sender.flush_and_wait(buffer, timeout)
+            }
+            _ => self.flush().is_ok(),
+        }
+    }
+
     fn shutdown(&self) {
         #[cfg(feature = "async")]
         if let OutWriter::Async(handle) = &self.writer {
             let mut buffer = handle.pop_buffer();
             buffer.extend(ASYNC_SHUTDOWN);
-            handle.send(buffer).ok();
+            handle.send_control(buffer).ok();
             if let Ok(ref mut o_th) = handle.mo_thread_handle.lock() {
                 o_th.take().and_then(|th| th.join().ok());
             }
@@ -267,7 +327,7 @@ mod test {
 
     #[test]
     fn test_with_validation() {
-        let writer = StdOutWriter::new(opt_format, &WriteMode::Direct);
+        let writer = StdOutWriter::new(opt_format, &WriteMode::Direct, b"\n");
         let mut rb = log::Record::builder();
         rb.target("myApp")
             .file(Some("stdout_writer.rs"))
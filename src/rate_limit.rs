@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Limits the number of log records that are written per second.
+///
+/// Is used in [`Logger::rate_limit`](crate::Logger::rate_limit).
+///
+/// Records that exceed the limit are discarded before they are formatted.
+/// When the second in which records were discarded has ended,
+/// a single warning is written that tells how many records were suppressed.
+/// This warning is written together with the next record that passes the limit,
+/// or when the logger is flushed.
+///
+/// The limit applies to the records that are written by the primary writer
+/// (i.e., to the log file, stdout, or stderr, including the duplication to stdout or stderr);
+/// records that are directed explicitly to additional writers are not limited.
+///
+/// ## Example
+///
+/// Write at most 1000 records per second for each target:
+///
+/// ```rust
+/// # use flexi_logger::{FileSpec, Logger, RateLimit};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?
+///     .log_to_file(FileSpec::default())
+///     .rate_limit(RateLimit::per_second(1000).per_target())
+/// #   .log_to_stdout()
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimit {
+    max_records: usize,
+    per_target: bool,
+}
+impl RateLimit {
+    /// Allows at most the given number of records per second, for all targets together.
+    #[must_use]
+    pub fn per_second(max_records: usize) -> Self {
+        Self {
+            max_records,
+            per_target: false,
+        }
+    }
+
+    /// Applies the limit to each target separately.
+    #[must_use]
+    pub fn per_target(mut self) -> Self {
+        self.per_target = true;
+        self
+    }
+}
+
+const WINDOW: Duration = Duration::from_secs(1);
+// Key for counting the records of all targets together.
+const GLOBAL: &str = "";
+
+// Number of records that were suppressed within a window that has ended.
+pub(crate) struct Suppressed {
+    // is empty if the limit is not applied per target
+    pub(crate) target: String,
+    pub(crate) count: usize,
+}
+
+struct Window {
+    start: Instant,
+    passed: usize,
+    suppressed: usize,
+}
+impl Window {
+    fn new(start: Instant) -> Self {
+        Self {
+            start,
+            passed: 0,
+            suppressed: 0,
+        }
+    }
+
+    // Starts a new window if the current one has ended,
+    // and returns the number of records that were suppressed in the ended window.
+    fn renew_if_ended(&mut self, now: Instant) -> Option<usize> {
+        if now.duration_since(self.start) < WINDOW {
+            return None;
+        }
+        let suppressed = self.suppressed;
+        *self = Self::new(now);
+        if suppressed > 0 {
+            Some(suppressed)
+        } else {
+            None
+        }
+    }
+}
+
+// Counts the records per window, and decides which records can be written.
+pub(crate) struct RateLimiter {
+    rate_limit: RateLimit,
+    windows: Mutex<HashMap<String, Window>>,
+}
+impl RateLimiter {
+    pub(crate) fn new(rate_limit: RateLimit) -> Self {
+        Self {
+            rate_limit,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Returns true if a record of the given target can be written,
+    // and the suppressed records of the ended window, if any.
+    pub(crate) fn admit(&self, target: &str) -> (bool, Option<Suppressed>) {
+        let key = if self.rate_limit.per_target {
+            target
+        } else {
+            GLOBAL
+        };
+        let now = Instant::now();
+        self.windows.lock().map_or((true, None), |mut windows| {
+            if !windows.contains_key(key) {
+                windows.insert(key.to_string(), Window::new(now));
+            }
+            let window = windows.get_mut(key).unwrap(/*ok*/);
+
+            let o_suppressed = window.renew_if_ended(now).map(|count| Suppressed {
+                target: key.to_string(),
+                count,
+            });
+            if window.passed < self.rate_limit.max_records {
+                window.passed += 1;
+                (true, o_suppressed)
+            } else {
+                window.suppressed += 1;
+                (false, o_suppressed)
+            }
+        })
+    }
+
+    // Returns the suppressed records of all windows that have ended.
+    pub(crate) fn take_ended(&self) -> Vec<Suppressed> {
+        let now = Instant::now();
+        self.windows.lock().map_or_else(
+            |_| Vec::new(),
+            |mut windows| {
+                windows
+                    .iter_mut()
+                    .filter_map(|(key, window)| {
+                        window.renew_if_ended(now).map(|count| Suppressed {
+                            target: key.clone(),
+                            count,
+                        })
+                    })
+                    .collect()
+            },
+        )
+    }
+}
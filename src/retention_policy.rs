@@ -0,0 +1,206 @@
+#[cfg(feature = "upload")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Defines the strategy for handling older log files.
+///
+/// Is used in [`Logger::rotate`](crate::Logger::rotate).
+///
+/// Note that if you use a strategy other than `Cleanup::Never`, then the cleanup work is
+/// by default done in an extra thread, to minimize the impact on the program.
+///
+/// See [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown)
+/// to avoid interrupting a currently active cleanup when your program terminates.
+///
+/// See
+/// [`Logger::cleanup_in_background_thread`](crate::Logger::cleanup_in_background_thread)
+/// if you want to control whether this extra thread is created and used.
+///
+/// Rotated files that are pinned with [`LoggerHandle::pin`](crate::LoggerHandle::pin)
+/// are exempt from the cleanup.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Cleanup {
+    /// Older log files are not touched - they remain for ever.
+    Never,
+
+    /// The specified number of rotated log files are kept.
+    /// Older files are deleted, if necessary.
+    KeepLogFiles(usize),
+
+    /// The specified number of rotated log files are compressed and kept.
+    /// Older files are deleted, if necessary.
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    KeepCompressedFiles(usize),
+
+    /// Allows keeping some files as text files and some as compressed files.
+    ///
+    /// ## Example
+    ///
+    /// `KeepLogAndCompressedFiles(5,30)` ensures that the youngest five log files are
+    /// kept as text files, the next 30 are kept as compressed files with additional suffix `.gz`,
+    /// and older files are removed.
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    KeepLogAndCompressedFiles(usize, usize),
+
+    /// The specified number of rotated log files are kept,
+    /// older files are moved into the given archive directory, rather than being deleted.
+    ///
+    /// The archive directory is created if necessary, and can be on another volume.
+    /// Files in the archive directory are never overwritten or removed;
+    /// if a file with the same name exists already, the moved file gets an additional
+    /// infix `.restart-<number>`.
+    /// Files that were compressed with an earlier configuration are moved as they are.
+    ///
+    /// ## Example
+    ///
+    /// `MoveToDirectory(PathBuf::from("/mnt/archive/logs"), 5)` ensures that the youngest five
+    /// log files are kept in the log directory, and older files are moved to `/mnt/archive/logs`.
+    MoveToDirectory(PathBuf, usize),
+
+    /// The specified number of rotated log files are kept,
+    /// older files are handed to the given [`Uploader`], and are deleted locally
+    /// only after the upload succeeded.
+    ///
+    /// A failed upload is reported as an internal error, and the file is kept;
+    /// the upload is retried with the next cleanup.
+    /// Compressed files are uploaded as they are.
+    ///
+    /// The uploads run in the cleanup thread (see
+    /// [`Logger::cleanup_in_background_thread`](crate::Logger::cleanup_in_background_thread)),
+    /// so slow uploads do not delay the logging.
+    ///
+    /// ## Example
+    ///
+    /// `UploadThenDelete(Uploader::new(upload_to_s3), 2)` ensures that the youngest two
+    /// log files are kept in the log directory, and older files are uploaded and then deleted.
+    #[cfg_attr(docsrs, doc(cfg(feature = "upload")))]
+    #[cfg(feature = "upload")]
+    UploadThenDelete(Uploader, usize),
+}
+
+impl Cleanup {
+    // Returns true if some cleanup is to be done.
+    #[must_use]
+    #[allow(clippy::match_like_matches_macro)]
+    pub(crate) fn do_cleanup(&self) -> bool {
+        !matches!(self, Self::Never)
+    }
+}
+
+/// Describes what the cleanup did with an older log file.
+///
+/// Is handed to the callback that is registered with
+/// [`FileLogWriterBuilder::on_cleanup`](crate::writers::FileLogWriterBuilder::on_cleanup)
+/// or [`Logger::on_cleanup`](crate::Logger::on_cleanup).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CleanupEvent {
+    /// The file was compressed into a new file, and then removed.
+    Compressed {
+        /// The path of the removed file.
+        path: PathBuf,
+        /// The path of the compressed file.
+        compressed_path: PathBuf,
+    },
+
+    /// The file was deleted.
+    Deleted {
+        /// The path of the deleted file.
+        path: PathBuf,
+    },
+
+    /// The file was moved to the archive directory, see [`Cleanup::MoveToDirectory`].
+    Moved {
+        /// The path the file had before.
+        path: PathBuf,
+        /// The path of the file in the archive directory.
+        new_path: PathBuf,
+    },
+
+    /// The file was uploaded and then deleted, see [`Cleanup::UploadThenDelete`].
+    #[cfg_attr(docsrs, doc(cfg(feature = "upload")))]
+    #[cfg(feature = "upload")]
+    Uploaded {
+        /// The path of the deleted file.
+        path: PathBuf,
+    },
+}
+
+/// Uploads rotated log files to some remote storage, see [`Cleanup::UploadThenDelete`].
+///
+/// The function is called with the path of a closed log file,
+/// and has to return only after the upload is complete.
+/// Asynchronous clients can e.g. block on the upload with the executor of their choice.
+///
+/// ## Example
+///
+/// ```rust
+/// # use flexi_logger::{Cleanup, Criterion, Logger, Naming, Uploader};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let uploader = Uploader::new(|path| {
+///     // send the file to the remote storage, e.g. with the client of the object store
+///     println!("uploading {}", path.display());
+///     Ok(())
+/// });
+/// let _logger = Logger::try_with_str("info")?
+///     .log_to_file(flexi_logger::FileSpec::default())
+///     .rotate(
+///         Criterion::Size(10_000_000),
+///         Naming::Numbers,
+///         Cleanup::UploadThenDelete(uploader, 2),
+///     )
+/// #   .log_to_stdout()
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "upload")))]
+#[cfg(feature = "upload")]
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct Uploader(Arc<dyn Fn(&Path) -> std::io::Result<()> + Send + Sync>);
+#[cfg(feature = "upload")]
+impl Uploader {
+    /// Wraps the function that uploads a file.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&Path) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn upload(&self, path: &Path) -> std::io::Result<()> {
+        (self.0)(path)
+    }
+}
+#[cfg(feature = "upload")]
+impl std::fmt::Debug for Uploader {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("Uploader")
+    }
+}
+
+// The callback that is registered with FileLogWriterBuilder::on_cleanup.
+#[derive(Clone)]
+pub(crate) struct CleanupListener(Arc<dyn Fn(&CleanupEvent) + Send + Sync>);
+impl CleanupListener {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: Fn(&CleanupEvent) + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn notify(&self, event: &CleanupEvent) {
+        (self.0)(event);
+    }
+}
+impl std::fmt::Debug for CleanupListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("CleanupListener")
+    }
+}
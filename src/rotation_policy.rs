@@ -0,0 +1,272 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI32, AtomicI64};
+
+// use time::macros::offset;
+use time::UtcOffset;
+
+use crate::deferred_now::{
+    now_as_year_month_day_hour_minute_number, now_as_year_month_day_hour_number,
+    now_as_year_month_day_number, now_local_or_utc,
+};
+
+/// Criterion when to rotate the log file.
+///
+/// Used in [`Logger::rotate`](crate::Logger::rotate).
+///
+/// An [`Age`] converts into `Criterion::Age`,
+/// and a tuple `(Age, u64)` into `Criterion::AgeOrSize`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Criterion {
+    /// Rotate the log file when it exceeds the specified size in bytes.
+    Size(u64),
+    /// Rotate the log file when it has become older than the specified age.
+    ///
+    /// ## Restarts with `Logger::append()`
+    ///
+    /// With `Logger::append()`, a restarted program continues writing into an existing
+    /// rCURRENT file. To apply the age criterion correctly also to such a continued file,
+    /// `flexi_logger` needs to know when the file was created.
+    ///
+    /// The file system cannot be asked reliably for that:
+    /// on Linux, `std::fs::metadata.created()` returns `Err`, because linux does not maintain a
+    /// created-at-timestamp; and on Windows, for compatibility with DOS (sic!),
+    /// the created_at-info of a file that is deleted (or renamed) is magically transferred
+    /// to its successor, when the recreation happens within some seconds [\[1\]](#ref-1).
+    ///
+    /// With `Logger::append()`, `flexi_logger` therefore persists the creation time
+    /// of the rCURRENT file in a small sidecar file next to it, which has the additional
+    /// suffix `.created_at`. Only if this file is missing (e.g., because the rCURRENT file
+    /// was written by an older version of `flexi_logger`), the time of the restart is used
+    /// instead, and a left-over rCURRENT file is then used longer than it should be.
+    ///
+    /// <a name="ref-1">\[1\]</a> [https://superuser.com/questions/966490/windows-7-what-is-date-created-file-property-referring-to](https://superuser.com/questions/966490/windows-7-what-is-date-created-file-property-referring-to).
+    ///
+    Age(Age),
+    /// Rotate the file when it has either become older than the specified age, or when it has
+    /// exceeded the specified size in bytes.
+    ///
+    /// See documentation for Age and Size.
+    AgeOrSize(Age, u64),
+    /// Rotate the log file when the clock passes the given time of day,
+    /// like `(0, 0, 0)` for midnight, or `(6, 30, 0)` for 06:30.
+    ///
+    /// Unlike with [`Criterion::Age`], the rotation does not depend on when the current file
+    /// was created (e.g., when the program was started), but only on the clock:
+    /// a file that is created at 06:29 is rotated at 06:30.
+    ///
+    /// This is the recommended way for daily rotation, it generalizes [`Age::EveryNewDay`].
+    ///
+    /// The time of day must be valid, i.e., hour < 24, minute < 60, second < 60,
+    /// otherwise building the file log writer fails.
+    AgeAtTime {
+        /// The time of day (hour, minute, second) at which the rotation happens.
+        time_of_day: (u8, u8, u8),
+        /// The offset in which the clock is read.
+        offset: UtcOffset,
+    },
+    /// Rotate the log file when the free space on the volume of the log files
+    /// falls below the specified number of bytes.
+    ///
+    /// After such a rotation, and after the regular cleanup, the oldest rotated log files
+    /// are deleted, regardless of the [`Cleanup`](crate::Cleanup) setting,
+    /// until the free space is above the limit again, or until no rotated log file is left.
+    /// Pinned files are not deleted.
+    ///
+    /// The free space is checked at most once per second.
+    /// It can only be determined on unix; on other platforms, this criterion never rotates.
+    #[cfg_attr(docsrs, doc(cfg(feature = "disk_free")))]
+    #[cfg(feature = "disk_free")]
+    DiskFree(u64),
+}
+impl From<Age> for Criterion {
+    fn from(age: Age) -> Self {
+        Self::Age(age)
+    }
+}
+impl From<(Age, u64)> for Criterion {
+    fn from((age, size): (Age, u64)) -> Self {
+        Self::AgeOrSize(age, size)
+    }
+}
+//
+// #[derive(PartialEq, Eq)]
+// pub struct Date {
+//     year: i32,
+//     month: i32,
+//     day: i32,
+// }
+//
+// impl Date {
+//     pub fn new(year: i32, month: i32, day: i32) -> Self{
+//         Self {
+//             year,
+//             month,
+//             day
+//         }
+//     }
+// }
+
+#[derive(Clone, Debug)]
+pub struct SplitAtEveryNewDay {
+    pub(crate) atomic_day_number: Arc<AtomicI32>,
+    pub utc_offset: UtcOffset,
+}
+
+impl SplitAtEveryNewDay {
+    pub fn new(utc_offset: UtcOffset) -> Self {
+        Self {
+            atomic_day_number: Arc::new(AtomicI32::new(now_as_year_month_day_number(utc_offset))),
+            utc_offset,
+        }
+    }
+
+    /// utc_offset_hour should be -12 <= and <= +12
+    pub fn new_by_hour(utc_offset_hour: i8) -> Self {
+        Self::new(UtcOffset::from_hms(utc_offset_hour, 0, 0)
+            .map_err(|err| {
+                println!("could not make utc offset by param: {}, err: {:?}", utc_offset_hour, err);
+                err
+            })
+            .unwrap()
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SplitAtEveryNewHour {
+    pub(crate) atomic_hour_number: Arc<AtomicI64>,
+    pub utc_offset: UtcOffset,
+}
+
+impl SplitAtEveryNewHour {
+    pub fn new(utc_offset: UtcOffset) -> Self {
+        Self {
+            atomic_hour_number: Arc::new(AtomicI64::new(now_as_year_month_day_hour_number(
+                utc_offset,
+            ))),
+            utc_offset,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SplitAtEveryNewMinute {
+    pub(crate) atomic_minute_number: Arc<AtomicI64>,
+    pub utc_offset: UtcOffset,
+}
+
+impl SplitAtEveryNewMinute {
+    pub fn new(utc_offset: UtcOffset) -> Self {
+        Self {
+            atomic_minute_number: Arc::new(AtomicI64::new(
+                now_as_year_month_day_hour_minute_number(utc_offset),
+            )),
+            utc_offset,
+        }
+    }
+}
+
+/// The age after which a log file rotation will be triggered,
+/// when [`Criterion::Age`] is chosen.
+///
+/// The "local clock" is read in the offset that results from the chosen [`Naming`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Age {
+    /// Rotate the log file when the local clock has started a new day since the
+    /// current file had been created.
+    Day,
+    /// Rotate the log file when the local clock has started a new hour since the
+    /// current file had been created.
+    Hour,
+    /// Rotate the log file when the local clock has started a new minute since the
+    /// current file had been created.
+    Minute,
+    /// Rotate the log file when the local clock has started a new second since the
+    /// current file had been created.
+    Second,
+
+    // find a more performant solution than RwLock.
+    /// Rotate the log file when a new day comes(when mid-night comes( 00:00:00 ) or after)
+    ///
+    /// [`Criterion::AgeAtTime`] allows choosing other times of day and is recommended instead.
+    EveryNewDay(SplitAtEveryNewDay),
+
+    /// Rotate the log file when a new hour comes (at hh:00:00 or after),
+    /// independent of when the current file had been created.
+    EveryNewHour(SplitAtEveryNewHour),
+
+    /// Rotate the log file when a new minute comes (at hh:mm:00 or after),
+    /// independent of when the current file had been created.
+    EveryNewMinute(SplitAtEveryNewMinute),
+}
+
+impl Age {
+    /// new with a offset
+    pub fn new_with_splitting_at_every_new_day(utc_offset: UtcOffset) -> Self {
+        Age::EveryNewDay(SplitAtEveryNewDay::new(utc_offset))
+    }
+
+    /// new with a hour number
+    pub fn new_with_splitting_at_every_new_day_by_offset_hour(utc_offset_hour: i8) -> Self {
+        Age::EveryNewDay(SplitAtEveryNewDay::new_by_hour(utc_offset_hour))
+    }
+
+    /// new with a offset, rotating at every new hour
+    #[must_use]
+    pub fn new_with_splitting_at_every_new_hour(utc_offset: UtcOffset) -> Self {
+        Age::EveryNewHour(SplitAtEveryNewHour::new(utc_offset))
+    }
+
+    /// new with a offset, rotating at every new minute
+    #[must_use]
+    pub fn new_with_splitting_at_every_new_minute(utc_offset: UtcOffset) -> Self {
+        Age::EveryNewMinute(SplitAtEveryNewMinute::new(utc_offset))
+    }
+}
+
+/// The naming convention for rotated log files.
+///
+/// With file rotation, the logs are written to a file with infix `_rCURRENT`.
+/// When rotation happens, the CURRENT log file will be renamed to a file with
+/// another infix of the form `"_r..."`. `Naming` defines which other infix will be used.
+///
+/// Used in [`Logger::rotate`](crate::Logger::rotate).
+///
+/// A `UtcOffset` converts into `Naming::Timestamps`.
+///
+/// The age criterion of file rotation is evaluated in the offset
+/// that is given with `Naming::Timestamps`, and in the local offset with `Naming::Numbers`
+/// and `Naming::NumbersRoundRobin`.
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub enum Naming {
+    /// File rotation rotates to files with a timestamp-infix, like `"r2020-01-27T14:41:08+01"`,
+    /// where the timestamp is expressed in the given offset.
+    Timestamps(UtcOffset),
+    /// File rotation rotates to files with a number-infix.
+    Numbers,
+    /// File rotation rotates to files with a number-infix, like with `Numbers`,
+    /// but cycles through the given number of indexes (starting with 0),
+    /// and overwrites the oldest of these files when all indexes are in use.
+    ///
+    /// The disk usage is thus strictly bounded, without a cleanup being necessary.
+    /// A value of 0 is treated as 1.
+    NumbersRoundRobin(u32),
+}
+impl Naming {
+    /// File rotation rotates to files with a timestamp-infix, in the local offset.
+    ///
+    /// The local offset is determined when this method is called;
+    /// if it cannot be determined, UTC is used.
+    #[must_use]
+    pub fn local_timestamps() -> Self {
+        Self::Timestamps(now_local_or_utc().offset())
+    }
+}
+impl From<UtcOffset> for Naming {
+    fn from(utc_offset: UtcOffset) -> Self {
+        Self::Timestamps(utc_offset)
+    }
+}
@@ -0,0 +1,87 @@
+use log::{Level, LevelFilter};
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+// A sampling rule, see Logger::sample and Logger::sample_target.
+struct SamplingRule {
+    // is empty for the rule that applies to all targets
+    target: String,
+    level: LevelFilter,
+    // a record is kept if a random number is below the threshold
+    threshold: u64,
+}
+
+// Decides randomly for the records with the configured levels (and targets) if they are kept.
+#[derive(Default)]
+pub(crate) struct Sampler {
+    // sorted by descending length of the target prefix
+    rules: Vec<SamplingRule>,
+}
+impl Sampler {
+    pub(crate) fn add_rule(&mut self, target: String, level: LevelFilter, fraction: f64) {
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let threshold = if fraction >= 1.0 {
+            u64::MAX
+        } else if fraction > 0.0 {
+            (fraction * u64::MAX as f64) as u64
+        } else {
+            0
+        };
+        self.rules.retain(|rule| rule.target != target);
+        self.rules.push(SamplingRule {
+            target,
+            level,
+            threshold,
+        });
+        self.rules
+            .sort_by_key(|rule| std::cmp::Reverse(rule.target.len()));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // Returns false if a record with the given level and target is to be discarded.
+    //
+    // The rule with the longest matching target prefix decides.
+    pub(crate) fn admit(&self, level: Level, target: &str) -> bool {
+        match self
+            .rules
+            .iter()
+            .find(|rule| target.starts_with(rule.target.as_str()))
+        {
+            Some(rule) if rule.level != LevelFilter::Off && level >= rule.level => {
+                rule.threshold == u64::MAX || random() < rule.threshold
+            }
+            _ => true,
+        }
+    }
+}
+
+// A fast pseudo-random number generator (xorshift64*) per thread,
+// which is good enough for sampling.
+fn random() -> u64 {
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(seed());
+    }
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    })
+}
+
+// Uses the randomly seeded hasher of the standard library; the seed must not be zero.
+fn seed() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0x9E37_79B9_7F4A_7C15);
+    hasher.finish() | 1
+}
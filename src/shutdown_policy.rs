@@ -0,0 +1,162 @@
+use crate::deferred_now::DeferredNow;
+use crate::util::{sanitize, write_buffered, LockedStderr};
+use crate::{FormatFunction, Output};
+use log::Record;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+// Upper limit for the time the shutdown waits for the writes that are in progress.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Describes what happens with log records that are emitted after the writers were shut down
+/// with [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown).
+///
+/// Is used in [`Logger::shutdown_policy`](crate::Logger::shutdown_policy).
+///
+/// Records whose output is already in progress when the shutdown begins are written completely
+/// before the writers are shut down. All later records are handled according to the policy,
+/// and they are counted, see
+/// [`LoggerHandle::records_after_shutdown`](crate::LoggerHandle::records_after_shutdown).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ShutdownPolicy {
+    /// The records are discarded.
+    ///
+    /// This is the default.
+    DropWithCounter,
+
+    /// The records are written to stderr, using the format for stderr
+    /// (see [`Logger::format_for_stderr`](crate::Logger::format_for_stderr)).
+    RouteToStderr,
+
+    /// The records are formatted like with `RouteToStderr`, but rather than being written,
+    /// they are queued in memory, until the queue holds the given number of records;
+    /// further records are discarded.
+    ///
+    /// The queued records can be retrieved with
+    /// [`LoggerHandle::abandoned_records`](crate::LoggerHandle::abandoned_records).
+    QueueAndAbandon(usize),
+}
+
+// Separates the records that are written before the shutdown of the writers
+// from those that come too late, and handles the latter according to the policy.
+pub(crate) struct ShutdownGate {
+    policy: ShutdownPolicy,
+    format: FormatFunction,
+    line_ending: &'static [u8],
+    closed: AtomicBool,
+    writes_in_progress: AtomicUsize,
+    // signals the closing thread when the last write in progress is done
+    m_writes_done: Mutex<()>,
+    writes_done: Condvar,
+    records_after_shutdown: AtomicUsize,
+    abandoned_records: Mutex<Vec<String>>,
+}
+
+// Marks a write as in progress as long as it lives.
+pub(crate) struct WriteInProgress<'a>(&'a ShutdownGate);
+impl Drop for WriteInProgress<'_> {
+    fn drop(&mut self) {
+        let gate = self.0;
+        if gate.writes_in_progress.fetch_sub(1, Ordering::SeqCst) == 1
+            && gate.closed.load(Ordering::SeqCst)
+        {
+            // taking the lock ensures that the closing thread is either waiting already,
+            // or has not yet checked the number of writes in progress
+            let _guard = gate.m_writes_done.lock();
+            gate.writes_done.notify_all();
+        }
+    }
+}
+
+impl ShutdownGate {
+    pub(crate) fn new(
+        policy: ShutdownPolicy,
+        format: FormatFunction,
+        line_ending: &'static [u8],
+    ) -> Self {
+        Self {
+            policy,
+            format,
+            line_ending,
+            closed: AtomicBool::new(false),
+            writes_in_progress: AtomicUsize::new(0),
+            m_writes_done: Mutex::new(()),
+            writes_done: Condvar::new(),
+            records_after_shutdown: AtomicUsize::new(0),
+            abandoned_records: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Returns None if the gate is closed;
+    // otherwise the writers must not be shut down before the returned value is dropped.
+    pub(crate) fn enter(&self) -> Option<WriteInProgress<'_>> {
+        self.writes_in_progress.fetch_add(1, Ordering::SeqCst);
+        let write_in_progress = WriteInProgress(self);
+        if self.closed.load(Ordering::SeqCst) {
+            None
+        } else {
+            Some(write_in_progress)
+        }
+    }
+
+    // Closes the gate, and waits until the writes that are in progress are done,
+    // but not longer than CLOSE_TIMEOUT.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        if let Ok(guard) = self.m_writes_done.lock() {
+            self.writes_done
+                .wait_timeout_while(guard, CLOSE_TIMEOUT, |()| {
+                    self.writes_in_progress.load(Ordering::SeqCst) > 0
+                })
+                .ok();
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn handle_late_record(&self, now: &mut DeferredNow, record: &Record) {
+        self.records_after_shutdown.fetch_add(1, Ordering::Relaxed);
+        match self.policy {
+            ShutdownPolicy::DropWithCounter => {}
+            ShutdownPolicy::RouteToStderr => {
+                // errors are reported by write_buffered
+                now.set_output(Output::Stderr);
+                write_buffered(
+                    self.format,
+                    now,
+                    record,
+                    self.line_ending,
+                    &mut LockedStderr,
+                    #[cfg(test)]
+                    None,
+                )
+                .ok();
+            }
+            ShutdownPolicy::QueueAndAbandon(capacity) => {
+                if let Ok(mut abandoned_records) = self.abandoned_records.lock() {
+                    if abandoned_records.len() < capacity {
+                        let mut buffer = Vec::<u8>::with_capacity(200);
+                        (self.format)(&mut buffer, now, record).ok();
+                        sanitize(&mut buffer);
+                        abandoned_records.push(String::from_utf8_lossy(&buffer).into_owned());
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn records_after_shutdown(&self) -> usize {
+        self.records_after_shutdown.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn abandoned_records(&self) -> Vec<String> {
+        self.abandoned_records
+            .lock()
+            .map(|abandoned_records| abandoned_records.clone())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,329 @@
+use crate::deferred_now::{now_local_or_utc, now_utc};
+use crate::FlexiLoggerError;
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, RwLock};
+use time::format_description::{self, OwnedFormatItem};
+use time::{OffsetDateTime, UtcOffset};
+
+/// Controls how the provided format functions write timestamps,
+/// and which offset the timestamps of [`DeferredNow`](crate::DeferredNow) have.
+///
+/// Is used in [`Logger::timestamp_config`](crate::Logger::timestamp_config).
+///
+/// By default, the text formats (like [`opt_format`](crate::opt_format)) write timestamps like
+/// `2016-01-13 15:25:01.640870 +01:00`, the machine-readable formats
+/// (like [`json_format`](crate::json_format)) write RFC 3339 timestamps like
+/// `2016-01-13T15:25:01.640870+01:00`,
+/// and the local time is used, if it can be determined, otherwise UTC.
+///
+/// ## Example
+///
+/// Use RFC 3339 timestamps in UTC with millisecond precision in all log lines:
+///
+/// ```rust
+/// use flexi_logger::{opt_format, FileSpec, Logger, TimestampConfig};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?
+///     .log_to_file(FileSpec::default())
+///     .format(opt_format)
+///     .timestamp_config(
+///         TimestampConfig::default()
+///             .rfc3339()
+///             .subsecond_digits(3)
+///             .utc(),
+///     )
+/// #   .log_to_stdout()
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimestampConfig {
+    layout: Layout,
+    subsecond_digits: u8,
+    offset: Offset,
+    cache_seconds: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Layout {
+    Default,
+    Rfc3339,
+    Custom(OwnedFormatItem),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Offset {
+    Local,
+    Utc,
+    Fixed(UtcOffset),
+}
+
+impl Default for TimestampConfig {
+    fn default() -> Self {
+        Self {
+            layout: Layout::Default,
+            subsecond_digits: 6,
+            offset: Offset::Local,
+            cache_seconds: false,
+        }
+    }
+}
+
+impl TimestampConfig {
+    /// Makes all provided format functions write RFC 3339 timestamps,
+    /// like `2016-01-13T15:25:01.640870+01:00`.
+    #[must_use]
+    pub fn rfc3339(mut self) -> Self {
+        self.layout = Layout::Rfc3339;
+        self
+    }
+
+    /// Makes all provided format functions write the timestamps according to the given
+    /// format description, see the
+    /// [documentation of the `time` crate](https://time-rs.github.io/book/api/format-description.html)
+    /// for the syntax.
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::TimestampFormat`] if the format description is not valid.
+    pub fn custom_format(mut self, format_description: &str) -> Result<Self, FlexiLoggerError> {
+        self.layout = Layout::Custom(
+            format_description::parse_owned::<1>(format_description)
+                .map_err(FlexiLoggerError::TimestampFormat)?,
+        );
+        Ok(self)
+    }
+
+    /// Sets the number of subsecond digits (at most 9), defaults to 6.
+    ///
+    /// With 0, the subseconds are omitted. Has no effect on custom formats.
+    #[must_use]
+    pub fn subsecond_digits(mut self, digits: u8) -> Self {
+        self.subsecond_digits = std::cmp::min(digits, 9);
+        self
+    }
+
+    /// Renders the part of the timestamps that does not change within a second
+    /// (date, time up to the seconds, and offset) only once per second and thread,
+    /// and only the subseconds for every log line.
+    ///
+    /// Formatting the timestamps can dominate the CPU usage of high-throughput logging;
+    /// this option reduces the effort considerably, without changing the output.
+    /// It can be combined with [`TimestampConfig::subsecond_digits`],
+    /// e.g. with `3` to truncate the timestamps to milliseconds.
+    ///
+    /// Has no effect on custom formats.
+    #[must_use]
+    pub fn cache_seconds(mut self) -> Self {
+        self.cache_seconds = true;
+        self
+    }
+
+    /// Uses the local time, if it can be determined, otherwise UTC. This is the default.
+    #[must_use]
+    pub fn local(mut self) -> Self {
+        self.offset = Offset::Local;
+        self
+    }
+
+    /// Uses UTC.
+    #[must_use]
+    pub fn utc(mut self) -> Self {
+        self.offset = Offset::Utc;
+        self
+    }
+
+    /// Uses the given fixed offset.
+    #[must_use]
+    pub fn fixed_offset(mut self, offset: UtcOffset) -> Self {
+        self.offset = Offset::Fixed(offset);
+        self
+    }
+}
+
+// The config in the form that is used for producing and formatting timestamps.
+struct ActiveConfig {
+    text: OwnedFormatItem,
+    rfc3339: OwnedFormatItem,
+    offset: Offset,
+    o_cached: Option<CachedLayout>,
+}
+impl ActiveConfig {
+    fn from_config(config: &TimestampConfig) -> Self {
+        let subseconds = match config.subsecond_digits {
+            0 => String::new(),
+            digits => format!(".[subsecond digits:{digits}]"),
+        };
+        let rfc3339 = parse_builtin(&format!(
+            "[year]-[month]-[day]T{SECONDS}{subseconds}{RFC3339_OFFSET}"
+        ));
+        let o_cached = if config.cache_seconds {
+            CachedLayout::new(&config.layout, config.subsecond_digits)
+        } else {
+            None
+        };
+        match config.layout {
+            Layout::Default => Self {
+                text: parse_builtin(&format!(
+                    "[year]-[month]-[day] {SECONDS}{subseconds} {RFC3339_OFFSET}"
+                )),
+                rfc3339,
+                offset: config.offset,
+                o_cached,
+            },
+            Layout::Rfc3339 => Self {
+                text: rfc3339.clone(),
+                rfc3339,
+                offset: config.offset,
+                o_cached,
+            },
+            Layout::Custom(ref custom) => Self {
+                text: custom.clone(),
+                rfc3339: custom.clone(),
+                offset: config.offset,
+                o_cached,
+            },
+        }
+    }
+}
+
+const SECONDS: &str = "[hour]:[minute]:[second]";
+const RFC3339_OFFSET: &str = "[offset_hour sign:mandatory]:[offset_minute]";
+
+// Distinguishes the cached layouts of consecutive configs.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// The builtin layouts, split into the parts before and after the subseconds,
+// which are rendered only when the second or the offset changes.
+struct CachedLayout {
+    generation: u64,
+    text: (OwnedFormatItem, OwnedFormatItem),
+    rfc3339: (OwnedFormatItem, OwnedFormatItem),
+    subsecond_digits: u8,
+}
+impl CachedLayout {
+    fn new(layout: &Layout, subsecond_digits: u8) -> Option<Self> {
+        let rfc3339 = (
+            parse_builtin(&format!("[year]-[month]-[day]T{SECONDS}")),
+            parse_builtin(RFC3339_OFFSET),
+        );
+        let text = match layout {
+            Layout::Default => (
+                parse_builtin(&format!("[year]-[month]-[day] {SECONDS}")),
+                parse_builtin(&format!(" {RFC3339_OFFSET}")),
+            ),
+            Layout::Rfc3339 => rfc3339.clone(),
+            Layout::Custom(_) => return None,
+        };
+        Some(Self {
+            generation: GENERATION.fetch_add(1, Ordering::Relaxed),
+            text,
+            rfc3339,
+            subsecond_digits,
+        })
+    }
+
+    fn format(&self, timestamp: &OffsetDateTime, rfc3339: bool) -> Option<String> {
+        thread_local! {
+            static CACHE: RefCell<Option<SecondCache>> = const { RefCell::new(None) };
+        }
+        CACHE.with(|cache| {
+            let mut o_cache = cache.borrow_mut();
+            let second = (
+                self.generation,
+                timestamp.unix_timestamp(),
+                timestamp.offset(),
+            );
+            if !matches!(*o_cache, Some(ref cache) if cache.second == second) {
+                *o_cache = Some(SecondCache {
+                    second,
+                    o_text: None,
+                    o_rfc3339: None,
+                });
+            }
+            let cache = o_cache.as_mut()?;
+            let (items, o_rendered) = if rfc3339 {
+                (&self.rfc3339, &mut cache.o_rfc3339)
+            } else {
+                (&self.text, &mut cache.o_text)
+            };
+            if o_rendered.is_none() {
+                *o_rendered = Some((
+                    timestamp.format(&items.0).ok()?,
+                    timestamp.format(&items.1).ok()?,
+                ));
+            }
+            let (head, tail) = o_rendered.as_ref()?;
+
+            let mut result = String::with_capacity(head.len() + 10 + tail.len());
+            result.push_str(head);
+            if self.subsecond_digits > 0 {
+                let digits = u32::from(self.subsecond_digits);
+                let subseconds = timestamp.nanosecond() / 10_u32.pow(9 - digits);
+                write!(result, ".{subseconds:0width$}", width = digits as usize).ok()?;
+            }
+            result.push_str(tail);
+            Some(result)
+        })
+    }
+}
+
+// The parts of the timestamps of one second that were rendered in the current thread.
+struct SecondCache {
+    // generation, unix timestamp, and offset
+    second: (u64, i64, UtcOffset),
+    o_text: Option<(String, String)>,
+    o_rfc3339: Option<(String, String)>,
+}
+
+fn parse_builtin(format_description: &str) -> OwnedFormatItem {
+    format_description::parse_owned::<1>(format_description).unwrap(/*ok*/)
+}
+
+static ACTIVE_CONFIG: LazyLock<RwLock<ActiveConfig>> =
+    LazyLock::new(|| RwLock::new(ActiveConfig::from_config(&TimestampConfig::default())));
+
+pub(crate) fn set_timestamp_config(config: &TimestampConfig) -> Result<(), FlexiLoggerError> {
+    *(ACTIVE_CONFIG
+        .write()
+        .map_err(|_| FlexiLoggerError::Poison)?) = ActiveConfig::from_config(config);
+    Ok(())
+}
+
+// Produces the current timestamp, with the configured offset.
+pub(crate) fn now() -> OffsetDateTime {
+    let offset = ACTIVE_CONFIG
+        .read()
+        .map_or(Offset::Local, |active_config| active_config.offset);
+    match offset {
+        Offset::Local => now_local_or_utc(),
+        Offset::Utc => now_utc(),
+        Offset::Fixed(offset) => now_utc().to_offset(offset),
+    }
+}
+
+pub(crate) fn format_text(timestamp: &OffsetDateTime) -> String {
+    ACTIVE_CONFIG
+        .read()
+        .ok()
+        .and_then(|active_config| match active_config.o_cached {
+            Some(ref cached) => cached.format(timestamp, false),
+            None => timestamp.format(&active_config.text).ok(),
+        })
+        .unwrap_or_else(|| "Timestamping failed".to_string())
+}
+
+pub(crate) fn format_rfc3339(timestamp: &OffsetDateTime) -> String {
+    ACTIVE_CONFIG
+        .read()
+        .ok()
+        .and_then(|active_config| match active_config.o_cached {
+            Some(ref cached) => cached.format(timestamp, true),
+            None => timestamp.format(&active_config.rfc3339).ok(),
+        })
+        .unwrap_or_else(|| "Timestamping failed".to_string())
+}
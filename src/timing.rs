@@ -0,0 +1,127 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Number of most recent samples that are kept for computing the percentiles.
+const SAMPLE_CAPA: usize = 1024;
+
+/// Statistics about the time that was spent in formatting and writing log records.
+///
+/// Is provided by [`LoggerHandle::timing_metrics`](crate::LoggerHandle::timing_metrics)
+/// if the logger was configured with [`Logger::sample_timings`](crate::Logger::sample_timings).
+///
+/// The percentiles are computed from the most recent samples (at most 1024).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TimingMetrics {
+    /// Total number of records that were measured so far.
+    pub sampled_records: usize,
+    /// Median duration.
+    pub p50: Duration,
+    /// 90th percentile.
+    pub p90: Duration,
+    /// 99th percentile.
+    pub p99: Duration,
+    /// Maximal duration.
+    pub max: Duration,
+}
+
+// Measures the duration of formatting and writing for every n-th record.
+pub(crate) struct TimingSampler {
+    every_nth: usize,
+    counter: AtomicUsize,
+    samples: Mutex<Samples>,
+}
+
+#[derive(Default)]
+struct Samples {
+    total: usize,
+    ring: Vec<Duration>,
+}
+
+impl TimingSampler {
+    pub(crate) fn new(every_nth: usize) -> Self {
+        Self {
+            every_nth: std::cmp::max(every_nth, 1),
+            counter: AtomicUsize::new(0),
+            samples: Mutex::new(Samples::default()),
+        }
+    }
+
+    // Returns the start time if the current record is to be measured.
+    pub(crate) fn start(&self) -> Option<Instant> {
+        match self.counter.fetch_add(1, Ordering::Relaxed) % self.every_nth {
+            0 => Some(Instant::now()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn stop(&self, start: Instant) {
+        let elapsed = start.elapsed();
+        if let Ok(mut samples) = self.samples.lock() {
+            let idx = samples.total % SAMPLE_CAPA;
+            if samples.ring.len() < SAMPLE_CAPA {
+                samples.ring.push(elapsed);
+            } else {
+                samples.ring[idx] = elapsed;
+            }
+            samples.total += 1;
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> Option<TimingMetrics> {
+        let (sampled_records, mut sorted) = {
+            let samples = self.samples.lock().ok()?;
+            (samples.total, samples.ring.clone())
+        };
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[(sorted.len() - 1) * p / 100];
+        Some(TimingMetrics {
+            sampled_records,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimingSampler;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sampling_and_percentiles() {
+        let sampler = TimingSampler::new(3);
+        assert!(sampler.metrics().is_none());
+
+        let mut measured = 0;
+        for _ in 0..30 {
+            if let Some(start) = sampler.start() {
+                measured += 1;
+                sampler.stop(start);
+            }
+        }
+        assert_eq!(measured, 10);
+
+        let metrics = sampler.metrics().unwrap();
+        assert_eq!(metrics.sampled_records, 10);
+        assert!(metrics.p50 <= metrics.p90);
+        assert!(metrics.p90 <= metrics.p99);
+        assert!(metrics.p99 <= metrics.max);
+        assert!(metrics.max < Duration::from_secs(1));
+
+        // only the most recent samples are kept
+        for _ in 0..3 * 2000 {
+            if let Some(start) = sampler.start() {
+                sampler.stop(start - Duration::from_millis(5));
+            }
+        }
+        let metrics = sampler.metrics().unwrap();
+        assert_eq!(metrics.sampled_records, 2010);
+        assert!(metrics.p50 >= Duration::from_millis(5));
+    }
+}
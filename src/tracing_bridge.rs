@@ -0,0 +1,142 @@
+//! Forward events of [`tracing`](https://docs.rs/tracing/latest/tracing/)
+//! into `flexi_logger`.
+//!
+//! [`FlexiLoggerLayer`] is a [`Layer`] for `tracing-subscriber` that converts each `tracing`
+//! event into a log record and hands it over to `flexi_logger`, so that the events are
+//! filtered with the [`LogSpecification`](crate::LogSpecification) of the logger,
+//! and are written by its writers, with all rotation and cleanup functionality.
+//!
+//! ### Example
+//!
+//! Precondition: add these entries to your `Cargo.toml`:
+//! ```toml
+//! flexi_logger = {version = "0.20", features = ["tracing_bridge"]}
+//! tracing = "0.1"
+//! tracing-subscriber = "0.3"
+//! ```
+//!
+//! ```rust,ignore
+//! use flexi_logger::{tracing_bridge::FlexiLoggerLayer, FileSpec, Logger};
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! let _logger = Logger::try_with_str("info")?
+//!     .log_to_file(FileSpec::default())
+//!     .start()?;
+//!
+//! tracing::subscriber::set_global_default(
+//!     tracing_subscriber::registry().with(FlexiLoggerLayer::new()),
+//! )?;
+//!
+//! tracing::info!(user = "alice", "logged in"); // is written as `logged in user=alice`
+//! ```
+//!
+//! Spans are not forwarded, only events.
+use log::Log;
+use std::fmt::Write;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// A [`Layer`] that forwards `tracing` events into `flexi_logger`.
+///
+/// The fields of the events, apart from `message`, are appended to the message,
+/// like ` key1=value1 key2=value2`.
+pub struct FlexiLoggerLayer {
+    o_logger: Option<Box<dyn Log>>,
+}
+impl FlexiLoggerLayer {
+    /// Creates a layer that forwards to the logger that was
+    /// installed with [`Logger::start`](crate::Logger::start).
+    #[must_use]
+    pub fn new() -> Self {
+        Self { o_logger: None }
+    }
+
+    /// Creates a layer that forwards to the given logger,
+    /// which was e.g. created with [`Logger::build`](crate::Logger::build).
+    #[must_use]
+    pub fn with_logger(logger: Box<dyn Log>) -> Self {
+        Self {
+            o_logger: Some(logger),
+        }
+    }
+
+    fn logger(&self) -> &dyn Log {
+        match self.o_logger {
+            Some(ref logger) => logger.as_ref(),
+            None => log::logger(),
+        }
+    }
+}
+impl Default for FlexiLoggerLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for FlexiLoggerLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = log_level(*metadata.level());
+        let logger = self.logger();
+        if !logger.enabled(
+            &log::Metadata::builder()
+                .level(level)
+                .target(metadata.target())
+                .build(),
+        ) {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        logger.log(
+            &log::Record::builder()
+                .level(level)
+                .target(metadata.target())
+                .module_path(metadata.module_path())
+                .file(metadata.file())
+                .line(metadata.line())
+                .args(format_args!("{}{}", visitor.message, visitor.fields))
+                .build(),
+        );
+    }
+}
+
+fn log_level(level: Level) -> log::Level {
+    if level == Level::ERROR {
+        log::Level::Error
+    } else if level == Level::WARN {
+        log::Level::Warn
+    } else if level == Level::INFO {
+        log::Level::Info
+    } else if level == Level::DEBUG {
+        log::Level::Debug
+    } else {
+        log::Level::Trace
+    }
+}
+
+// Collects the message and the other fields of an event.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+}
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            write!(self.fields, " {}={}", field.name(), value).ok();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            write!(self.message, "{:?}", value).ok();
+        } else {
+            write!(self.fields, " {}={:?}", field.name(), value).ok();
+        }
+    }
+}
@@ -46,7 +46,7 @@
 //!     .rotate(
 //!         // If the program runs long enough,
 //!         Criterion::Age(Age::Day), // - create a new file every day
-//!         Naming::Timestamps,       // - let the rotated files have a timestamp in their name
+//!         Naming::local_timestamps(), // - let the rotated files have a timestamp in their name
 //!         Cleanup::KeepLogFiles(7), // - keep at most seven log files
 //!     )
 //!     .write_mode(WriteMode::Async)
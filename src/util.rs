@@ -1,31 +1,367 @@
-use crate::{deferred_now::DeferredNow, FormatFunction};
+#[cfg(feature = "async")]
+use crate::OverflowPolicy;
+use crate::{deferred_now::DeferredNow, FlexiLoggerError, FormatFunction};
+#[cfg(feature = "async")]
+use crossbeam::{
+    channel::{self, Receiver, RecvError, SendError, Sender, TrySendError},
+    queue::ArrayQueue,
+};
 use log::Record;
 use std::cell::RefCell;
-use std::io::Write;
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 #[cfg(test)]
 use std::io::Cursor;
-#[cfg(test)]
-use std::sync::{Arc, Mutex};
+#[cfg(feature = "async")]
+use std::sync::Condvar;
+#[cfg(any(feature = "async", test))]
+use std::sync::Mutex;
+use std::sync::{Arc, RwLock};
+
+pub(crate) const WINDOWS_LINE_ENDING: &[u8] = b"\r\n";
+pub(crate) const UNIX_LINE_ENDING: &[u8] = b"\n";
+// The line ending that is usual on the target platform.
+pub(crate) const PLATFORM_LINE_ENDING: &[u8] = if cfg!(windows) {
+    WINDOWS_LINE_ENDING
+} else {
+    UNIX_LINE_ENDING
+};
 
 #[cfg(feature = "async")]
 pub(crate) const ASYNC_FLUSH: &[u8] = b"F";
 #[cfg(feature = "async")]
 pub(crate) const ASYNC_SHUTDOWN: &[u8] = b"S";
+// Hard limit for the length of a log line in async mode, unless message_capa is bigger.
+#[cfg(feature = "async")]
+pub(crate) const ASYNC_MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+// Sending side of the channels to the output thread of an async writer.
+//
+// Log lines are sent according to the configured `OverflowPolicy`, control messages
+// (`ASYNC_FLUSH`, `ASYNC_SHUTDOWN`) use a separate unbounded channel, so they are neither
+// discarded nor do they ever block the sender.
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub(crate) struct AsyncSender {
+    sender: Sender<Vec<u8>>,
+    control_sender: Sender<Vec<u8>>,
+    // allows discarding the oldest log line, is only used with DropOldestWithCounter
+    o_receiver: Option<Receiver<Vec<u8>>>,
+    // discarded buffers are returned to the message pool
+    a_pool: Arc<ArrayQueue<Vec<u8>>>,
+    message_capa: usize,
+    overflow_policy: OverflowPolicy,
+    dropped: Arc<AtomicUsize>,
+    // is used to format the reports of DropNewestWithReport
+    format: FormatFunction,
+    line_ending: &'static [u8],
+    reported: Arc<AtomicUsize>,
+    flushes: Arc<FlushTracker>,
+}
+#[cfg(feature = "async")]
+impl AsyncSender {
+    pub(crate) fn new(
+        channel_capa: Option<usize>,
+        overflow_policy: OverflowPolicy,
+        format: FormatFunction,
+        line_ending: &'static [u8],
+        a_pool: &Arc<ArrayQueue<Vec<u8>>>,
+        message_capa: usize,
+    ) -> (Self, AsyncReceiver) {
+        let (sender, receiver) = match channel_capa {
+            Some(capa) => channel::bounded::<Vec<u8>>(capa),
+            None => channel::unbounded::<Vec<u8>>(),
+        };
+        let (control_sender, control_receiver) = channel::unbounded::<Vec<u8>>();
+        let o_receiver = if let OverflowPolicy::DropOldestWithCounter = overflow_policy {
+            Some(receiver.clone())
+        } else {
+            None
+        };
+        (
+            Self {
+                sender,
+                control_sender,
+                o_receiver,
+                a_pool: Arc::clone(a_pool),
+                message_capa,
+                overflow_policy,
+                dropped: Arc::new(AtomicUsize::new(0)),
+                format,
+                line_ending,
+                reported: Arc::new(AtomicUsize::new(0)),
+                flushes: Arc::new(FlushTracker::default()),
+            },
+            AsyncReceiver {
+                receiver,
+                control_receiver,
+                o_pending_control: None,
+            },
+        )
+    }
+
+    // Sends a formatted log line, after terminating it with the line ending.
+    //
+    // A log line that exceeds the hard length limit is cut, with a marker that tells how many
+    // bytes were cut off.
+    pub(crate) fn send_line(&self, mut buffer: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
+        truncate(&mut buffer, ASYNC_MAX_MESSAGE_LEN.max(self.message_capa));
+        buffer.extend_from_slice(self.line_ending);
+        self.send(buffer)
+    }
+
+    // Sends a log line, applying the overflow policy if the channel is full.
+    pub(crate) fn send(&self, buffer: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
+        match self.overflow_policy {
+            OverflowPolicy::Block => self.sender.send(buffer),
+            OverflowPolicy::DropNewest | OverflowPolicy::DropNewestWithReport => {
+                match self.sender.try_send(buffer) {
+                    Ok(()) => Ok(()),
+                    Err(TrySendError::Full(buffer)) => {
+                        self.discard(buffer);
+                        Ok(())
+                    }
+                    Err(TrySendError::Disconnected(buffer)) => Err(SendError(buffer)),
+                }
+            }
+            OverflowPolicy::DropOldestWithCounter => {
+                let mut buffer = buffer;
+                loop {
+                    match self.sender.try_send(buffer) {
+                        Ok(()) => return Ok(()),
+                        Err(TrySendError::Full(returned)) => {
+                            buffer = returned;
+                            if let Some(oldest) =
+                                self.o_receiver.as_ref().and_then(|r| r.try_recv().ok())
+                            {
+                                self.discard(oldest);
+                            }
+                        }
+                        Err(TrySendError::Disconnected(buffer)) => return Err(SendError(buffer)),
+                    }
+                }
+            }
+        }
+    }
+
+    // Counts a discarded log line and returns its buffer to the message pool.
+    fn discard(&self, mut buffer: Vec<u8>) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        if buffer.capacity() <= self.message_capa {
+            buffer.clear();
+            self.a_pool.push(buffer).ok();
+        }
+    }
+
+    // Sends a control message;
+    // a flush is preceded by the report of the discarded log lines, if required.
+    pub(crate) fn send_control(&self, buffer: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
+        if buffer == ASYNC_FLUSH {
+            self.send_flush(buffer).map(|_| ())
+        } else {
+            self.control_sender.send(buffer)
+        }
+    }
+
+    // Sends a flush request and waits until the output thread has processed it,
+    // or until the timeout expires; returns false in the latter case.
+    pub(crate) fn flush_and_wait(&self, buffer: Vec<u8>, timeout: Duration) -> bool {
+        match self.send_flush(buffer) {
+            Ok(request) => self.flushes.wait_for(request, timeout),
+            Err(_) => false,
+        }
+    }
+
+    // The output thread has to report each processed flush request to the tracker.
+    pub(crate) fn flush_tracker(&self) -> Arc<FlushTracker> {
+        Arc::clone(&self.flushes)
+    }
+
+    // Sends a flush request, and returns its number.
+    //
+    // The report of the discarded log lines must not block the flush; if the channel is full,
+    // the discarded log lines are reported with the next flush.
+    fn send_flush(&self, buffer: Vec<u8>) -> Result<u64, SendError<Vec<u8>>> {
+        if let Some((report, count)) = self.drop_report() {
+            match self.sender.try_send(report) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    self.reported.fetch_sub(count, Ordering::Relaxed);
+                }
+                Err(TrySendError::Disconnected(report)) => return Err(SendError(report)),
+            }
+        }
+        let request = self.flushes.request();
+        self.control_sender.send(buffer)?;
+        Ok(request)
+    }
+
+    // With DropNewestWithReport, produces a formatted log line about the log lines that were
+    // discarded since the previous report, if there are any, together with their number.
+    fn drop_report(&self) -> Option<(Vec<u8>, usize)> {
+        if self.overflow_policy != OverflowPolicy::DropNewestWithReport {
+            return None;
+        }
+        let dropped = self.dropped();
+        let reported = self.reported.fetch_max(dropped, Ordering::Relaxed);
+        if dropped <= reported {
+            return None;
+        }
+        let mut buffer = Vec::<u8>::with_capacity(200);
+        (self.format)(
+            &mut buffer,
+            &mut DeferredNow::new(),
+            &Record::builder()
+                .level(log::Level::Warn)
+                .target("flexi_logger")
+                .module_path(Some("flexi_logger"))
+                .args(format_args!(
+                    "{} log lines were discarded because the output could not keep up",
+                    dropped - reported
+                ))
+                .build(),
+        )
+        .map_err(|e| eprint_err(ERRCODE::Format, "formatting failed", &e))
+        .ok()?;
+        buffer.extend_from_slice(self.line_ending);
+        Some((buffer, dropped - reported))
+    }
+
+    // Number of log lines that were discarded due to the overflow policy.
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+#[cfg(feature = "async")]
+impl std::fmt::Debug for AsyncSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("AsyncSender")
+            .field("sender", &self.sender)
+            .field("control_sender", &self.control_sender)
+            .field("o_receiver", &self.o_receiver)
+            .field("a_pool", &self.a_pool)
+            .field("message_capa", &self.message_capa)
+            .field("overflow_policy", &self.overflow_policy)
+            .field("dropped", &self.dropped)
+            .field("format", &"<..>")
+            .field("line_ending", &self.line_ending)
+            .field("reported", &self.reported)
+            .field("flushes", &self.flushes)
+            .finish()
+    }
+}
+
+// Receiving side of the channels to the output thread of an async writer.
+//
+// A control message is only handed out after the log lines that were sent before it,
+// and thus might wait in the channel for log lines, were handed out.
+// Only the log lines that are in the channel when the control message arrives are handed
+// out first, so that a control message does not wait forever while other threads keep logging.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub(crate) struct AsyncReceiver {
+    receiver: Receiver<Vec<u8>>,
+    control_receiver: Receiver<Vec<u8>>,
+    // the pending control message, and the number of log lines to hand out before it
+    o_pending_control: Option<(Vec<u8>, usize)>,
+}
+#[cfg(feature = "async")]
+impl AsyncReceiver {
+    pub(crate) fn recv(&mut self) -> Result<Vec<u8>, RecvError> {
+        loop {
+            if let Some((_, ref mut ahead)) = self.o_pending_control {
+                if *ahead > 0 {
+                    *ahead -= 1;
+                    if let Ok(message) = self.receiver.try_recv() {
+                        return Ok(message);
+                    }
+                }
+                return self
+                    .o_pending_control
+                    .take()
+                    .map(|(control, _)| control)
+                    .ok_or(RecvError);
+            }
+            channel::select! {
+                recv(self.receiver) -> message => return message,
+                recv(self.control_receiver) -> control => {
+                    self.o_pending_control = Some((control?, self.receiver.len()));
+                }
+            }
+        }
+    }
+}
+
+// Counts the flush requests that were sent to the output thread of an async writer,
+// and those that the output thread has processed.
+//
+// Since the channel preserves the order, all log lines that were sent before
+// the n-th flush request are written when n flush requests are processed.
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+pub(crate) struct FlushTracker {
+    requested: AtomicU64,
+    processed: Mutex<u64>,
+    condvar: Condvar,
+}
+#[cfg(feature = "async")]
+impl FlushTracker {
+    fn request(&self) -> u64 {
+        self.requested.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub(crate) fn processed(&self) {
+        if let Ok(mut processed) = self.processed.lock() {
+            *processed += 1;
+            self.condvar.notify_all();
+        }
+    }
+
+    fn wait_for(&self, request: u64, timeout: Duration) -> bool {
+        if let Ok(processed) = self.processed.lock() {
+            if let Ok((_, wait_result)) =
+                self.condvar
+                    .wait_timeout_while(processed, timeout, |processed| *processed < request)
+            {
+                return !wait_result.timed_out();
+            }
+        }
+        false
+    }
+}
 
-#[derive(Copy, Clone, Debug)]
-pub(crate) enum ERRCODE {
+/// Classifies the internal errors of `flexi_logger`, see [`error_info`](crate::error_info)
+/// for details on the individual codes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ErrCode {
+    /// Writing a log line failed.
     Write,
+    /// Flushing buffered log lines failed.
     Flush,
+    /// Formatting a log line failed.
     Format,
+    /// A lock was poisoned.
     Poison,
+    /// Opening, rotating or cleaning up log files failed.
     LogFile,
+    /// A log line was addressed to a writer that does not exist.
     WriterSpec,
+    /// The specfile could not be read or watched.
     #[cfg(feature = "specfile")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "specfile")))]
     LogSpecFile,
+    /// The symbolic link to the log file could not be created.
     #[cfg(target_os = "linux")]
     Symlink,
+    /// The admin socket failed.
+    #[cfg(feature = "admin_socket")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "admin_socket")))]
+    AdminSocket,
 }
+pub(crate) use self::ErrCode as ERRCODE;
 impl ERRCODE {
     fn as_index(self) -> &'static str {
         match self {
@@ -39,44 +375,302 @@ impl ERRCODE {
             Self::LogSpecFile => "logspecfile",
             #[cfg(target_os = "linux")]
             Self::Symlink => "symlink",
+            #[cfg(feature = "admin_socket")]
+            Self::AdminSocket => "adminsocket",
+        }
+    }
+
+    // The position of the error code in ERROR_COUNTS.
+    fn as_number(self) -> usize {
+        match self {
+            Self::Write => 0,
+            Self::Flush => 1,
+            Self::Format => 2,
+            Self::Poison => 3,
+            Self::LogFile => 4,
+            Self::WriterSpec => 5,
+            #[cfg(feature = "specfile")]
+            Self::LogSpecFile => 6,
+            #[cfg(target_os = "linux")]
+            Self::Symlink => 7,
+            #[cfg(feature = "admin_socket")]
+            Self::AdminSocket => 8,
+        }
+    }
+
+    fn all() -> &'static [Self] {
+        &[
+            Self::Write,
+            Self::Flush,
+            Self::Format,
+            Self::Poison,
+            Self::LogFile,
+            Self::WriterSpec,
+            #[cfg(feature = "specfile")]
+            Self::LogSpecFile,
+            #[cfg(target_os = "linux")]
+            Self::Symlink,
+            #[cfg(feature = "admin_socket")]
+            Self::AdminSocket,
+        ]
+    }
+}
+
+// The number of internal errors per error code, see ErrCode::as_number.
+static ERROR_COUNTS: [AtomicUsize; 9] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+// The time of the latest write or flush error, in milliseconds since ERROR_CLOCK_START, plus 1;
+// 0 if there was no such error yet.
+static LAST_WRITE_ERROR: AtomicU64 = AtomicU64::new(0);
+lazy_static::lazy_static! {
+    static ref ERROR_CLOCK_START: Instant = Instant::now();
+}
+
+fn count_error(errcode: ERRCODE) {
+    ERROR_COUNTS[errcode.as_number()].fetch_add(1, Ordering::Relaxed);
+    if let ERRCODE::Write | ERRCODE::Flush = errcode {
+        #[allow(clippy::cast_possible_truncation)]
+        let millis = ERROR_CLOCK_START.elapsed().as_millis() as u64;
+        LAST_WRITE_ERROR.store(millis + 1, Ordering::Relaxed);
+    }
+}
+
+// The number of internal errors so far, per error code; codes without errors are omitted.
+pub(crate) fn error_counts() -> HashMap<ErrCode, usize> {
+    ErrCode::all()
+        .iter()
+        .map(|errcode| {
+            (
+                *errcode,
+                ERROR_COUNTS[errcode.as_number()].load(Ordering::Relaxed),
+            )
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+// Checks if a write or flush error was reported within the given period.
+pub(crate) fn write_error_within(period: Duration) -> bool {
+    match LAST_WRITE_ERROR.load(Ordering::Relaxed) {
+        0 => false,
+        millis => {
+            let last_error = *ERROR_CLOCK_START + Duration::from_millis(millis - 1);
+            last_error.elapsed() <= period
+        }
+    }
+}
+
+/// Function that is called with the internal errors of `flexi_logger`,
+/// see [`ErrorChannel::Callback`].
+pub type ErrorCallback = dyn Fn(ErrCode, String) + Send + Sync;
+
+/// Describes where `flexi_logger` reports its internal errors,
+/// like failures to write log lines or to rotate log files,
+/// see [`Logger::error_channel`](crate::Logger::error_channel).
+#[derive(Clone)]
+pub enum ErrorChannel {
+    /// Errors are written to stderr (default).
+    StdErr,
+    /// Errors are ignored.
+    DevNull,
+    /// Errors are appended to the given file.
+    File(std::path::PathBuf),
+    /// Errors are handed over to the given function,
+    /// together with a message that describes the error.
+    ///
+    /// The function must not log, since logging can cause further errors.
+    Callback(Arc<ErrorCallback>),
+}
+impl std::fmt::Debug for ErrorChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::StdErr => f.write_str("StdErr"),
+            Self::DevNull => f.write_str("DevNull"),
+            Self::File(path) => f.debug_tuple("File").field(path).finish(),
+            Self::Callback(_) => f.write_str("Callback(<..>)"),
         }
     }
 }
 
+lazy_static::lazy_static! {
+    static ref ERROR_CHANNEL: RwLock<ErrorChannel> = RwLock::new(ErrorChannel::StdErr);
+}
+
+pub(crate) fn set_error_channel(error_channel: ErrorChannel) -> Result<(), FlexiLoggerError> {
+    *(ERROR_CHANNEL
+        .write()
+        .map_err(|_| FlexiLoggerError::Poison)?) = error_channel;
+    Ok(())
+}
+
 pub(crate) fn eprint_err(errcode: ERRCODE, msg: &str, err: &dyn std::error::Error) {
-    let s = format!(
-        "[flexi_logger][ERRCODE::{code:?}] {msg}, caused by {err}\n\
-         See https://docs.rs/flexi_logger/latest/flexi_logger/error_info/index.html#{code_lc}",
-        msg = msg,
-        err = err,
-        code = errcode,
-        code_lc = errcode.as_index(),
-    );
-    try_to_write(&s);
+    try_to_write(errcode, &format!("{}, caused by {}", msg, err));
 }
 
 pub(crate) fn eprint_msg(errcode: ERRCODE, msg: &str) {
-    let s = format!(
-        "[flexi_logger][ERRCODE::{code:?}] {msg}\n\
-         See https://docs.rs/flexi_logger/latest/flexi_logger/error_info/index.html#{code_lc}",
-        msg = msg,
-        code = errcode,
-        code_lc = errcode.as_index(),
-    );
-    try_to_write(&s);
+    try_to_write(errcode, msg);
+}
+
+fn try_to_write(errcode: ERRCODE, msg: &str) {
+    count_error(errcode);
+    let error_channel = match ERROR_CHANNEL.read() {
+        Ok(error_channel) => error_channel.clone(),
+        Err(_) => ErrorChannel::StdErr,
+    };
+    let full_message = || {
+        format!(
+            "[flexi_logger][ERRCODE::{code:?}] {msg}\n\
+             See https://docs.rs/flexi_logger/latest/flexi_logger/error_info/index.html#{code_lc}",
+            msg = msg,
+            code = errcode,
+            code_lc = errcode.as_index(),
+        )
+    };
+    match error_channel {
+        ErrorChannel::StdErr => eprintln!("{}", full_message()),
+        ErrorChannel::DevNull => {}
+        ErrorChannel::File(path) => {
+            // there is no better place to report a failure here
+            if let Ok(mut file) = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+            {
+                writeln!(file, "{}", full_message()).ok();
+            }
+        }
+        ErrorChannel::Callback(callback) => callback(errcode, msg.to_string()),
+    }
+}
+
+pub(crate) fn io_err<E>(e: E) -> std::io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    std::io::Error::new(std::io::ErrorKind::Other, e)
+}
+
+// Writes each buffer completely while holding the lock of stderr.
+//
+// `Stderr::write` acquires the lock for each call, and may accept only a part of the buffer;
+// writers like `BufWriter` would then write the rest with a separate call, so that other
+// output can sneak in between and tear the line apart.
+pub(crate) struct LockedStderr;
+impl Write for LockedStderr {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let stderr = std::io::stderr();
+        let mut locked = stderr.lock();
+        locked.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stderr().lock().flush()
+    }
+}
+
+// Function that is applied to every formatted log line, see `Logger::redact`.
+pub(crate) type Redaction = dyn Fn(&mut Vec<u8>) + Send + Sync;
+
+lazy_static::lazy_static! {
+    static ref REDACTION: RwLock<Option<Arc<Redaction>>> = RwLock::new(None);
+}
+
+pub(crate) fn set_redaction(o_redaction: Option<Arc<Redaction>>) -> Result<(), FlexiLoggerError> {
+    *(REDACTION.write().map_err(|_| FlexiLoggerError::Poison)?) = o_redaction;
+    Ok(())
+}
+
+// Applies the configured redaction, if any, and then the configured maximum length,
+// if any, to a formatted log line (without line ending).
+pub(crate) fn sanitize(buffer: &mut Vec<u8>) {
+    if let Ok(o_redaction) = REDACTION.read() {
+        if let Some(ref redaction) = *o_redaction {
+            redaction(buffer);
+        }
+    }
+    truncate(buffer, MAX_RECORD_LEN.load(Ordering::Relaxed));
+}
+
+fn redaction_is_set() -> bool {
+    matches!(REDACTION.read().as_deref(), Ok(Some(_)))
+}
+
+// Maximum length of a formatted log line, see `Logger::max_record_len`.
+static MAX_RECORD_LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+pub(crate) fn set_max_record_len(o_max_len: Option<usize>) {
+    MAX_RECORD_LEN.store(o_max_len.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+fn record_len_is_limited() -> bool {
+    MAX_RECORD_LEN.load(Ordering::Relaxed) != usize::MAX
+}
+
+// Cuts the line to max_len bytes, at a character boundary, and appends a marker
+// that tells how many bytes were cut off.
+fn truncate(buffer: &mut Vec<u8>, max_len: usize) {
+    if buffer.len() <= max_len {
+        return;
+    }
+    let mut len = max_len;
+    // don't split a UTF-8 encoded character
+    while len > 0 && (buffer[len] & 0b1100_0000) == 0b1000_0000 {
+        len -= 1;
+    }
+    let cut_off = buffer.len() - len;
+    buffer.truncate(len);
+    write!(buffer, "…[truncated {} bytes]", cut_off).ok();
+}
+
+// A format function that can be replaced at runtime, see `LoggerHandle::set_format`.
+pub(crate) struct SwappableFormat(RwLock<FormatFunction>);
+impl SwappableFormat {
+    pub(crate) fn new(format: FormatFunction) -> Self {
+        Self(RwLock::new(format))
+    }
+
+    pub(crate) fn get(&self) -> FormatFunction {
+        match self.0.read() {
+            Ok(format) => *format,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
+    pub(crate) fn set(&self, format: FormatFunction) {
+        match self.0.write() {
+            Ok(mut f) => *f = format,
+            Err(poisoned) => *poisoned.into_inner() = format,
+        }
+    }
 }
 
-fn try_to_write(s: &str) {
-    eprintln!("{}", s);
-    // TODO DOES THIS MAKE SENSE (for issue#75)? NEEDS SOME TESTING
-    // let w = std::io::stderr();
-    // let mut wl = w.lock();
-    // let result = wl.write(s.as_bytes());
-    // result.ok();
+// Notes if the BufWriter had to write out a part of its content.
+struct OverflowTracker<'a, W: Write> {
+    w: &'a mut BufWriter<W>,
+    overflowed: bool,
 }
+impl<W: Write> Write for OverflowTracker<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.w.buffer().len() + buf.len() > self.w.capacity() {
+            self.overflowed = true;
+        }
+        self.w.write(buf)
+    }
 
-pub(crate) fn io_err(s: &'static str) -> std::io::Error {
-    std::io::Error::new(std::io::ErrorKind::Other, s)
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.w.flush()
+    }
 }
 
 // Thread-local buffer
@@ -95,6 +689,7 @@ pub(crate) fn write_buffered(
     format_function: FormatFunction,
     now: &mut DeferredNow,
     record: &Record,
+    line_ending: &[u8],
     w: &mut dyn Write,
     #[cfg(test)] o_validation_buffer: Option<&Arc<Mutex<Cursor<Vec<u8>>>>>,
 ) -> Result<(), std::io::Error> {
@@ -104,8 +699,9 @@ pub(crate) fn write_buffered(
         Ok(mut buffer) => {
             (format_function)(&mut *buffer, now, record)
                 .unwrap_or_else(|e| eprint_err(ERRCODE::Format, "formatting failed", &e));
+            sanitize(&mut buffer);
             buffer
-                .write_all(b"\n")
+                .write_all(line_ending)
                 .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing failed", &e));
 
             result = w.write_all(&*buffer).map_err(|e| {
@@ -127,8 +723,9 @@ pub(crate) fn write_buffered(
             let mut tmp_buf = Vec::<u8>::with_capacity(200);
             (format_function)(&mut tmp_buf, now, record)
                 .unwrap_or_else(|e| eprint_err(ERRCODE::Format, "formatting failed", &e));
+            sanitize(&mut tmp_buf);
             tmp_buf
-                .write_all(b"\n")
+                .write_all(line_ending)
                 .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing failed", &e));
 
             result = w.write_all(&tmp_buf).map_err(|e| {
@@ -144,3 +741,54 @@ pub(crate) fn write_buffered(
     });
     result
 }
+
+// Lets the format function write directly into the BufWriter, and thus avoids copying
+// the formatted log line.
+//
+// If the log line does not fit into the buffer, the BufWriter writes out a part of it;
+// the rest is then written out immediately, so the caller must hold the lock of the
+// underlying output to prevent other output from tearing the log line.
+// Uses write_buffered if a redaction or a maximum length is configured,
+// because they need the complete log line.
+pub(crate) fn write_direct<W: Write>(
+    format_function: FormatFunction,
+    now: &mut DeferredNow,
+    record: &Record,
+    line_ending: &[u8],
+    w: &mut BufWriter<W>,
+    #[cfg(test)] o_validation_buffer: Option<&Arc<Mutex<Cursor<Vec<u8>>>>>,
+) -> Result<(), std::io::Error> {
+    if redaction_is_set() || record_len_is_limited() {
+        return write_buffered(
+            format_function,
+            now,
+            record,
+            line_ending,
+            w,
+            #[cfg(test)]
+            o_validation_buffer,
+        );
+    }
+
+    let mut line_writer = OverflowTracker {
+        w,
+        overflowed: false,
+    };
+    (format_function)(&mut line_writer, now, record)
+        .unwrap_or_else(|e| eprint_err(ERRCODE::Format, "formatting failed", &e));
+    let mut result = line_writer.write_all(line_ending);
+    if result.is_ok() && line_writer.overflowed {
+        result = line_writer.w.flush();
+    }
+    if let Err(ref e) = result {
+        eprint_err(ERRCODE::Write, "writing failed", e);
+    }
+
+    #[cfg(test)]
+    if let Some(valbuf) = o_validation_buffer {
+        let mut valbuf = valbuf.lock().unwrap();
+        (format_function)(&mut *valbuf, now, record).ok();
+        valbuf.write_all(line_ending).ok();
+    }
+    result
+}
@@ -16,6 +16,40 @@ pub const DEFAULT_POOL_CAPA: usize = 50;
 #[cfg(feature = "async")]
 pub const DEFAULT_MESSAGE_CAPA: usize = 200;
 
+/// Describes what happens with a log line if the channel to the output thread of an
+/// async writer is bounded and full.
+///
+/// Is used in [`WriteMode::AsyncWith`].
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[cfg(feature = "async")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// The logging thread waits until the output thread has made room.
+    ///
+    /// No log line is lost, but logging can become slow if the output device is slow.
+    Block,
+
+    /// The new log line is discarded and counted.
+    ///
+    /// Logging never waits for the output thread.
+    DropNewest,
+
+    /// The oldest log line in the channel is discarded and counted, to make room for the new one.
+    ///
+    /// Logging never waits for the output thread, and the most recent log lines are kept.
+    DropOldestWithCounter,
+
+    /// The new log line is discarded and counted, like with `DropNewest`;
+    /// additionally, whenever the output is flushed, a single warning is written
+    /// with the number of log lines that were discarded since the previous report.
+    /// If the channel is full when the output is flushed, the warning is deferred
+    /// to the next flush.
+    ///
+    /// Logging and flushing never wait for the output thread.
+    /// Use a write mode with a flush interval to get the reports regularly.
+    DropNewestWithReport,
+}
+
 /// Describes whether the log output should be written synchronously or asynchronously,
 /// and if and how I/O should be buffered and flushed.
 ///
@@ -78,9 +112,32 @@ pub enum WriteMode {
     #[cfg(feature = "async")]
     Async,
 
-    /// Log lines are sent through an unbounded channel to an output thread, which
+    /// Like `AsyncWith` with a bounded channel and [`OverflowPolicy::DropNewestWithReport`],
+    /// and with default values for the other parameters.
+    ///
+    /// Logging never blocks, neither on I/O nor on a full channel, which is essential e.g.
+    /// for real-time threads; if the output thread cannot keep up, log lines are discarded.
+    /// The number of discarded log lines can be retrieved with
+    /// [`LoggerHandle::dropped_records`](crate::LoggerHandle::dropped_records),
+    /// and is reported in the log output with each flush.
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    #[cfg(feature = "async")]
+    AsyncLossy {
+        /// Maximal number of log lines that can wait in the channel to the output thread.
+        channel_capa: usize,
+        /// The interval for flushing the output and for reporting discarded log lines.
+        ///
+        /// With `Duration::ZERO` flushing is suppressed.
+        flush_interval: Duration,
+    },
+
+    /// Log lines are sent through a channel to an output thread, which
     /// does the I/O, and, if `log_to_file()` is chosen, also the rotation and the cleanup.
     ///
+    /// The channel is unbounded, unless `channel_capa` is set; for a bounded channel,
+    /// the `overflow_policy` decides whether logging waits or log lines are discarded
+    /// if the output thread cannot keep up.
+    ///
     /// Uses buffered output to reduce overhead, and a bounded message pool to reduce allocations.
     /// The log output is flushed regularly with the given interval.
     ///
@@ -93,11 +150,25 @@ pub enum WriteMode {
         /// Capacity of the pool for the message buffers.
         pool_capa: usize,
         /// Capacity of an individual message buffer.
+        ///
+        /// Longer log lines are not split: their buffer grows as needed
+        /// and is freed, rather than returned to the pool, after the log line was written.
+        /// The growth is bounded by a hard limit of 1 MiB, or `message_capa` if that is bigger;
+        /// a longer log line is cut at the limit and ends with a marker that tells
+        /// how many bytes were cut off.
         message_capa: usize,
         /// The interval for flushing the output.
         ///
         /// With `Duration::ZERO` flushing is suppressed.
         flush_interval: Duration,
+        /// Maximal number of log lines that can wait in the channel to the output thread;
+        /// with `None` the channel is unbounded.
+        channel_capa: Option<usize>,
+        /// What to do with a log line if the bounded channel is full.
+        ///
+        /// The number of discarded log lines can be retrieved with
+        /// [`LoggerHandle::dropped_records`](crate::LoggerHandle::dropped_records).
+        overflow_policy: OverflowPolicy,
     },
 }
 
@@ -117,6 +188,10 @@ pub(crate) enum EffectiveWriteMode {
         ///
         /// With `Duration::ZERO` flushing is suppressed.
         flush_interval: Duration,
+        /// Capacity of the channel to the output thread, `None` means unbounded.
+        channel_capa: Option<usize>,
+        /// Behavior if the bounded channel is full.
+        overflow_policy: OverflowPolicy,
     },
     BufferDontFlushWith(usize),
 }
@@ -144,6 +219,20 @@ impl WriteMode {
                 pool_capa: DEFAULT_POOL_CAPA,
                 message_capa: DEFAULT_MESSAGE_CAPA,
                 flush_interval: DEFAULT_FLUSH_INTERVAL,
+                channel_capa: None,
+                overflow_policy: OverflowPolicy::Block,
+            },
+            #[cfg(feature = "async")]
+            Self::AsyncLossy {
+                channel_capa,
+                flush_interval,
+            } => EffectiveWriteMode::AsyncWith {
+                bufsize: DEFAULT_BUFFER_CAPACITY,
+                pool_capa: DEFAULT_POOL_CAPA,
+                message_capa: DEFAULT_MESSAGE_CAPA,
+                flush_interval,
+                channel_capa: Some(channel_capa),
+                overflow_policy: OverflowPolicy::DropNewestWithReport,
             },
             #[cfg(feature = "async")]
             Self::AsyncWith {
@@ -151,14 +240,19 @@ impl WriteMode {
                 pool_capa,
                 message_capa,
                 flush_interval,
+                channel_capa,
+                overflow_policy,
             } => EffectiveWriteMode::AsyncWith {
                 bufsize,
                 pool_capa,
                 message_capa,
                 flush_interval,
+                channel_capa,
+                overflow_policy,
             },
         }
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn without_flushing(&self) -> WriteMode {
         match self {
             Self::Direct | Self::BufferDontFlush | Self::BufferDontFlushWith(_) => *self,
@@ -170,6 +264,16 @@ impl WriteMode {
                 pool_capa: DEFAULT_POOL_CAPA,
                 message_capa: DEFAULT_MESSAGE_CAPA,
                 flush_interval: Duration::from_secs(0),
+                channel_capa: None,
+                overflow_policy: OverflowPolicy::Block,
+            },
+            #[cfg(feature = "async")]
+            Self::AsyncLossy {
+                channel_capa,
+                flush_interval: _,
+            } => Self::AsyncLossy {
+                channel_capa: *channel_capa,
+                flush_interval: Duration::from_secs(0),
             },
             #[cfg(feature = "async")]
             Self::AsyncWith {
@@ -177,14 +281,19 @@ impl WriteMode {
                 pool_capa,
                 message_capa,
                 flush_interval: _,
+                channel_capa,
+                overflow_policy,
             } => Self::AsyncWith {
                 bufsize: *bufsize,
                 pool_capa: *pool_capa,
                 message_capa: *message_capa,
                 flush_interval: Duration::from_secs(0),
+                channel_capa: *channel_capa,
+                overflow_policy: *overflow_policy,
             },
         }
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn buffersize(&self) -> Option<usize> {
         #[allow(clippy::match_same_arms)]
         match self.inner() {
@@ -197,9 +306,26 @@ impl WriteMode {
                 pool_capa: _,
                 message_capa: _,
                 flush_interval: _,
+                channel_capa: _,
+                overflow_policy: _,
             } => Some(bufsize),
         }
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn is_async(&self) -> bool {
+        #[cfg(feature = "async")]
+        {
+            matches!(
+                self,
+                Self::Async | Self::AsyncLossy { .. } | Self::AsyncWith { .. }
+            )
+        }
+        #[cfg(not(feature = "async"))]
+        {
+            false
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn get_flush_interval(&self) -> Duration {
         #[allow(clippy::match_same_arms)]
         match self {
@@ -211,11 +337,18 @@ impl WriteMode {
             Self::Async => DEFAULT_FLUSH_INTERVAL,
             Self::BufferAndFlushWith(_, flush_interval) => *flush_interval,
             #[cfg(feature = "async")]
+            Self::AsyncLossy {
+                channel_capa: _,
+                flush_interval,
+            } => *flush_interval,
+            #[cfg(feature = "async")]
             Self::AsyncWith {
                 bufsize: _,
                 pool_capa: _,
                 message_capa: _,
                 flush_interval,
+                channel_capa: _,
+                overflow_policy: _,
             } => *flush_interval,
         }
     }
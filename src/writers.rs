@@ -1,8 +1,18 @@
 //! Contains the trait [`LogWriter`] for extending `flexi_logger`
 //! with additional log writers,
-//! and two concrete implementations
-//! for writing to files ([`FileLogWriter`])
-//! or to the syslog ([`SyslogWriter`]).
+//! and concrete implementations
+//! for writing to files ([`FileLogWriter`]),
+//! to one file per log level ([`LevelSplitWriter`]),
+//! to a network endpoint ([`NetworkWriter`]),
+//! to a unix domain socket or a windows named pipe ([`DatagramWriter`]),
+//! to an arbitrary implementation of `std::io::Write` ([`IoWriter`]),
+//! to the syslog ([`SyslogWriter`]),
+//! to the systemd journal (`JournalWriter`),
+//! to an `SQLite` database (`SqliteWriter`),
+//! to a memory-mapped ring buffer that survives crashes (`MmapRingWriter`),
+//! to a webhook, e.g. of Slack ([`WebhookWriter`]),
+//! or to the console of the browser (`BrowserConsoleWriter`),
+//! and the [`TeeWriter`], which combines several log writers.
 //! You can also use your own implementations of [`LogWriter`].
 //!
 //! Such log writers can be used in two ways:
@@ -14,6 +24,7 @@
 //!   * [`Logger::log_to_stdout`](crate::Logger::log_to_stdout)
 //!   * [`Logger::log_to_file`](crate::Logger::log_to_file)
 //!   * [`Logger::log_to_writer`](crate::Logger::log_to_writer)
+//!   * [`Logger::log_to_io_writer`](crate::Logger::log_to_io_writer)
 //!   * [`Logger::log_to_file_and_writer`](crate::Logger::log_to_file_and_writer)
 //!   * [`Logger::do_not_log`](crate::Logger::do_not_log)
 //!
@@ -35,6 +46,11 @@
 //!   These log calls will not be affected by the value of `flexi_logger`'s log specification;
 //!   they will always be written, as you might want it for alerts or auditing.
 //!
+//!   Alternatively, you can use [`Logger::route()`](crate::Logger::route) to send the log calls
+//!   of some modules to one of these log writers, without specifying its target name
+//!   in the log calls, and [`Logger::also_send_to()`](crate::Logger::also_send_to)
+//!   to send copies of the normal log calls to some of these log writers.
+//!
 //!   In the following example we define an alert writer, and a macro to facilitate using it
 //!   (and avoid using the explicit target specification in the macro call), and
 //!   show some example calls.
@@ -98,18 +114,76 @@
 //!   ```
 //!
 
+#[cfg(any(unix, windows))]
+mod datagram_writer;
+#[cfg(not(target_arch = "wasm32"))]
 mod file_log_writer;
+mod io_writer;
+#[cfg(not(target_arch = "wasm32"))]
+mod level_split_writer;
 mod log_writer;
+#[cfg(not(target_arch = "wasm32"))]
+mod network_writer;
+mod tee_writer;
+#[cfg(not(target_arch = "wasm32"))]
+mod webhook_writer;
 
 #[cfg(feature = "syslog_writer")]
 mod syslog_writer;
 
+#[cfg(all(feature = "journald", target_os = "linux"))]
+mod journal_writer;
+
+#[cfg(feature = "mmap_ring")]
+mod mmap_ring_writer;
+
+#[cfg(feature = "sqlite")]
+mod sqlite_writer;
+
+#[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+mod browser_console_writer;
+
 #[cfg(feature = "syslog_writer")]
 pub use self::syslog_writer::{
     LevelToSyslogSeverity, SyslogConnector, SyslogFacility, SyslogSeverity, SyslogWriter,
 };
 
+#[cfg_attr(docsrs, doc(cfg(any(unix, windows))))]
+#[cfg(any(unix, windows))]
+pub use self::datagram_writer::{DatagramWriter, DatagramWriterBuilder};
+#[cfg(not(target_arch = "wasm32"))]
 pub use self::file_log_writer::{
-    ArcFileLogWriter, FileLogWriter, FileLogWriterBuilder, FileLogWriterHandle,
+    ArcFileLogWriter, FileLogWriter, FileLogWriterBuilder, FileLogWriterHandle, FileMetadata,
+    LogFileSystem, RetryExhausted, RetryPolicy, StdFileSystem, WriterStats,
+    DEFAULT_BACKWARDS_CLOCK_TOLERANCE,
 };
+pub use self::io_writer::IoWriter;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::level_split_writer::LevelSplitWriter;
 pub use self::log_writer::LogWriter;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::network_writer::{NetworkWriter, NetworkWriterBuilder, StreamWrapper};
+pub use self::tee_writer::TeeWriter;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::webhook_writer::{
+    HttpStream, HttpStreamWrapper, WebhookPayload, WebhookWriter, WebhookWriterBuilder,
+};
+
+#[cfg_attr(docsrs, doc(cfg(all(feature = "journald", target_os = "linux"))))]
+#[cfg(all(feature = "journald", target_os = "linux"))]
+pub use self::journal_writer::JournalWriter;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap_ring")))]
+#[cfg(feature = "mmap_ring")]
+pub use self::mmap_ring_writer::{MmapRingWriter, MmapRingWriterBuilder};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
+#[cfg(feature = "sqlite")]
+pub use self::sqlite_writer::{SqliteWriter, SqliteWriterBuilder};
+
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "wasm_console", target_arch = "wasm32")))
+)]
+#[cfg(all(feature = "wasm_console", target_arch = "wasm32"))]
+pub use self::browser_console_writer::BrowserConsoleWriter;
@@ -0,0 +1,78 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::log_writer::LogWriter;
+use crate::{default_format, FormatFunction};
+use std::io::Result as IoResult;
+
+/// A [`LogWriter`] implementation that writes log lines to the console of the browser,
+/// with the methods `console.error`, `console.warn`, `console.info`, and `console.debug`,
+/// so that the browser's developer tools can filter them by level
+/// (trace records are written with `console.debug`).
+///
+/// Only available on `wasm32` targets, with optional crate feature `wasm_console`.
+///
+/// The log lines are formatted with [`default_format`] by default;
+/// this can be changed with [`Logger::format_for_writer`](crate::Logger::format_for_writer).
+///
+/// In the browser, there is no file system and there are no threads,
+/// so the logger should be used with the default [`WriteMode::Direct`](crate::WriteMode::Direct),
+/// and without features that need files or background threads.
+///
+/// Usually you will not need this writer directly: use
+/// [`Logger::log_to_browser_console`](crate::Logger::log_to_browser_console)
+/// to make the browser console the primary log target.
+/// The writer can be used as an additional writer, too;
+/// see the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// use flexi_logger::Logger;
+///
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .log_to_browser_console()
+///     .start()
+///     .unwrap();
+/// ```
+pub struct BrowserConsoleWriter {
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+}
+impl BrowserConsoleWriter {
+    /// Returns a boxed instance.
+    #[must_use]
+    pub fn new(max_log_level: log::LevelFilter) -> Box<Self> {
+        Box::new(Self {
+            format: default_format,
+            max_log_level,
+        })
+    }
+}
+
+impl LogWriter for BrowserConsoleWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut buffer = Vec::<u8>::with_capacity(200);
+        (self.format)(&mut buffer, now, record)?;
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.as_ref().into();
+        match record.level() {
+            log::Level::Error => web_sys::console::error_1(&line),
+            log::Level::Warn => web_sys::console::warn_1(&line),
+            log::Level::Info => web_sys::console::info_1(&line),
+            log::Level::Debug | log::Level::Trace => web_sys::console::debug_1(&line),
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+}
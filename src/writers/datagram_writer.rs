@@ -0,0 +1,166 @@
+use crate::deferred_now::DeferredNow;
+use crate::util::{io_err, sanitize};
+use crate::writers::log_writer::LogWriter;
+use crate::{default_format, FlexiLoggerError, FormatFunction};
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A [`LogWriter`] implementation that sends each formatted log record as a single message
+/// to a local collector, like `vector` or `fluent-bit`,
+/// without touching the file system or the network stack.
+///
+/// On unix, the records are sent as datagrams to a unix domain socket,
+/// on windows they are written to a named pipe (e.g. `\\.\pipe\my_collector`),
+/// which should be created in message mode.
+///
+/// The records are formatted without a trailing line break, since each message
+/// is a record of its own.
+/// If sending fails, e.g. because the collector was restarted,
+/// the writer connects anew and tries once more; if that fails too,
+/// the record is lost and the write fails with an I/O error.
+/// Records that exceed the maximum message size of the socket are not sent either.
+///
+/// Use [`DatagramWriter::builder`] to create an instance,
+/// and see the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::DatagramWriter, Logger};
+///
+/// let datagram_writer = DatagramWriter::builder("/run/vector/logs.socket")
+///     .max_log_level(log::LevelFilter::Info)
+///     .try_build()
+///     .unwrap();
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .add_writer("Collector", Box::new(datagram_writer))
+///     .start()
+///     .unwrap();
+/// ```
+pub struct DatagramWriter {
+    path: PathBuf,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    // is None after a failed send, until the next successful reconnect
+    mo_endpoint: Mutex<Option<Endpoint>>,
+}
+impl DatagramWriter {
+    /// Returns a builder for a `DatagramWriter` that sends to the given socket path,
+    /// or, on windows, to the given named pipe.
+    #[must_use]
+    pub fn builder<P: Into<PathBuf>>(path: P) -> DatagramWriterBuilder {
+        DatagramWriterBuilder {
+            path: path.into(),
+            format: default_format,
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+}
+
+impl LogWriter for DatagramWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut message = Vec::<u8>::with_capacity(200);
+        (self.format)(&mut message, now, record)?;
+        sanitize(&mut message);
+
+        let mut o_endpoint = self
+            .mo_endpoint
+            .lock()
+            .map_err(|_| io_err("datagram writer is poisoned"))?;
+        if let Some(ref mut endpoint) = *o_endpoint {
+            if endpoint.send(&message).is_ok() {
+                return Ok(());
+            }
+        }
+        *o_endpoint = None;
+        let mut endpoint = Endpoint::connect(&self.path)?;
+        endpoint.send(&message)?;
+        *o_endpoint = Some(endpoint);
+        Ok(())
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+}
+
+/// Builder for [`DatagramWriter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct DatagramWriterBuilder {
+    path: PathBuf,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+}
+impl DatagramWriterBuilder {
+    /// Sets the format function, defaults to [`default_format`](crate::default_format).
+    ///
+    /// Note that [`Logger::format_for_writer`](crate::Logger::format_for_writer)
+    /// overrides this value.
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the maximum log level that is to be written, defaults to `Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Produces the `DatagramWriter` and connects it to the socket or named pipe.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the socket or named pipe cannot be connected,
+    /// e.g. because the collector is not running.
+    pub fn try_build(self) -> Result<DatagramWriter, FlexiLoggerError> {
+        let endpoint = Endpoint::connect(&self.path)?;
+        Ok(DatagramWriter {
+            path: self.path,
+            format: self.format,
+            max_log_level: self.max_log_level,
+            mo_endpoint: Mutex::new(Some(endpoint)),
+        })
+    }
+}
+
+#[cfg(unix)]
+struct Endpoint(std::os::unix::net::UnixDatagram);
+#[cfg(unix)]
+impl Endpoint {
+    fn connect(path: &Path) -> IoResult<Self> {
+        let socket = std::os::unix::net::UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(Self(socket))
+    }
+
+    fn send(&mut self, message: &[u8]) -> IoResult<()> {
+        self.0.send(message).map(|_| ())
+    }
+}
+
+// In message mode, each write to the pipe is delivered as one message.
+#[cfg(windows)]
+struct Endpoint(std::fs::File);
+#[cfg(windows)]
+impl Endpoint {
+    fn connect(path: &Path) -> IoResult<Self> {
+        std::fs::OpenOptions::new().write(true).open(path).map(Self)
+    }
+
+    fn send(&mut self, message: &[u8]) -> IoResult<()> {
+        std::io::Write::write_all(&mut self.0, message)
+    }
+}
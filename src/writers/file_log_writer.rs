@@ -1,24 +1,34 @@
 #![allow(clippy::module_name_repetitions)]
 mod builder;
+mod cleanup_domain;
 mod config;
+mod file_system;
+mod lazy_creation;
+mod manifest;
+mod retry_policy;
 mod state;
 mod state_handle;
+mod stats;
 
-pub use self::builder::{ArcFileLogWriter, FileLogWriterBuilder, FileLogWriterHandle};
+pub use self::builder::{
+    ArcFileLogWriter, FileLogWriterBuilder, FileLogWriterHandle, DEFAULT_BACKWARDS_CLOCK_TOLERANCE,
+};
+pub use self::file_system::{FileMetadata, LogFileSystem, StdFileSystem};
+pub use self::retry_policy::{RetryExhausted, RetryPolicy};
+pub use self::stats::WriterStats;
 
 use self::{
-    config::{Config, RotationConfig},
+    config::{CompressionConfig, Config, RotationConfig},
+    lazy_creation::LazyCreation,
     state::State,
     state_handle::StateHandle,
 };
 use crate::{
-    writers::LogWriter, DeferredNow, EffectiveWriteMode, FileSpec, FlexiLoggerError, FormatFunction,
+    writers::LogWriter, Cleanup, Criterion, DeferredNow, EffectiveWriteMode, FileSpec,
+    FlexiLoggerError, FormatFunction, Naming,
 };
 use log::Record;
-use std::path::PathBuf;
-
-const WINDOWS_LINE_ENDING: &[u8] = b"\r\n";
-const UNIX_LINE_ENDING: &[u8] = b"\n";
+use std::path::{Path, PathBuf};
 
 /// A configurable [`LogWriter`] implementation that writes to a file or a sequence of files.
 ///
@@ -30,6 +40,7 @@ pub struct FileLogWriter {
     // we need internal mutability and thread-safety.
     state_handle: StateHandle,
     max_log_level: log::LevelFilter,
+    o_lazy_creation: Option<LazyCreation>,
 }
 impl FileLogWriter {
     pub(crate) fn new(
@@ -37,6 +48,11 @@ impl FileLogWriter {
         max_log_level: log::LevelFilter,
         format_function: FormatFunction,
     ) -> FileLogWriter {
+        let line_ending = state.config().line_ending;
+        let o_lazy_creation = state
+            .config()
+            .o_create_file_lazily
+            .map(|level| LazyCreation::new(level, line_ending));
         let state_handle = match state.config().write_mode.inner() {
             EffectiveWriteMode::Direct
             | EffectiveWriteMode::BufferAndFlushWith(_, _)
@@ -50,12 +66,22 @@ impl FileLogWriter {
                 pool_capa,
                 message_capa,
                 flush_interval: _,
-            } => StateHandle::new_async(pool_capa, message_capa, state, format_function),
+                channel_capa,
+                overflow_policy,
+            } => StateHandle::new_async(
+                pool_capa,
+                message_capa,
+                channel_capa,
+                overflow_policy,
+                state,
+                format_function,
+            ),
         };
 
         FileLogWriter {
             state_handle,
             max_log_level,
+            o_lazy_creation,
         }
     }
 
@@ -72,6 +98,76 @@ impl FileLogWriter {
         self.state_handle.format_function()
     }
 
+    /// Replaces the output format function.
+    ///
+    /// The new format function is used for all log lines that are written after this call;
+    /// with [`WriteMode::Async`](crate::WriteMode::Async), lines that were already handed over
+    /// to the output thread are not affected.
+    pub fn set_format(&self, format: FormatFunction) {
+        self.state_handle.set_format_function(format);
+    }
+
+    /// Returns the number of log lines that were discarded because the channel to the
+    /// output thread was full.
+    ///
+    /// Is always zero, unless [`WriteMode::AsyncWith`](crate::WriteMode::AsyncWith)
+    /// is used with a bounded channel and a dropping
+    /// [`OverflowPolicy`](crate::OverflowPolicy).
+    #[must_use]
+    pub fn dropped_records(&self) -> usize {
+        self.state_handle.dropped_records()
+    }
+
+    /// Returns a snapshot of the writer's counters.
+    #[must_use]
+    pub fn stats(&self) -> WriterStats {
+        self.state_handle.stats()
+    }
+
+    /// Returns true if the next write will trigger a rotation of the output file.
+    ///
+    /// Returns false if no rotation is configured, or if the output file was not yet opened
+    /// (it is opened with the first write).
+    /// In async mode, records that are still queued for the output thread are not considered.
+    ///
+    /// # Panics
+    ///
+    /// If the internal state is poisoned.
+    #[must_use]
+    pub fn rotation_imminent(&self) -> bool {
+        self.state_handle.rotation_imminent()
+    }
+
+    /// Returns the number of bytes that can still be written before the size limit of the
+    /// configured [`Criterion`](crate::Criterion) is exceeded;
+    /// the rotation then happens with the next write.
+    ///
+    /// Returns `None` if no size-based rotation is configured,
+    /// or if the output file was not yet opened.
+    ///
+    /// # Panics
+    ///
+    /// If the internal state is poisoned.
+    #[must_use]
+    pub fn bytes_until_rotation(&self) -> Option<u64> {
+        self.state_handle.bytes_until_rotation()
+    }
+
+    /// Returns the time until the age limit of the configured [`Criterion`](crate::Criterion)
+    /// is reached; the rotation then happens with the next write.
+    /// A duration of zero means that the age limit is already reached.
+    ///
+    /// Returns `None` if no age-based rotation is configured,
+    /// or if the output file was not yet opened.
+    ///
+    /// # Panics
+    ///
+    /// If the internal state is poisoned.
+    #[must_use]
+    pub fn time_until_rotation(&self) -> Option<std::time::Duration> {
+        self.state_handle.time_until_rotation()
+    }
+
     #[must_use]
     #[doc(hidden)]
     pub fn current_filename(&self) -> PathBuf {
@@ -82,26 +178,147 @@ impl FileLogWriter {
         self.state_handle.plain_write(buffer)
     }
 
+    pub(crate) fn write_record(&self, record: &[u8]) -> std::io::Result<()> {
+        self.state_handle.write_record(record)
+    }
+
     /// Replaces parts of the configuration of the file log writer.
     ///
-    /// Note that the write mode and the format function cannot be reset and
-    /// that the provided `FileLogWriterBuilder` must have the same values for these as the
+    /// The write mode can be switched between [`WriteMode::Direct`](crate::WriteMode::Direct)
+    /// and the buffered write modes;
+    /// the output that was buffered so far is flushed before the switch.
+    /// An asynchronous write mode, however, cannot be switched on or off by a reset.
+    ///
+    /// Note that the format function cannot be reset and
+    /// that the provided `FileLogWriterBuilder` must have the same value for it as the
     /// current `FileLogWriter`.
     ///
     /// # Errors
     ///
     /// `FlexiLoggerError::Reset` if no file log writer is configured,
-    ///  or if a reset was tried with a different write mode.
+    ///  or if a reset was tried from or to an asynchronous write mode.
     /// `FlexiLoggerError::Io` if the specified path doesn't work.
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
     pub fn reset(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
         self.state_handle.reset(flwb)
     }
+
+    /// Moves the output of this writer to the given directory.
+    ///
+    /// The current output file is flushed and closed, and the next log line is written
+    /// into a new file in the given directory, which is created if necessary.
+    /// Rotation starts afresh, the symlink (if configured) is redirected to the new file.
+    /// Files that were written before remain in the previous directory.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the specified path doesn't work.
+    /// `FlexiLoggerError::OutputBadDirectory` if the specified path is not a directory.
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn relocate<P: AsRef<Path>>(&self, directory: P) -> Result<(), FlexiLoggerError> {
+        self.state_handle.relocate(directory.as_ref())
+    }
+
+    /// Replaces the rotation configuration of this writer, e.g. to tighten the rotation
+    /// or the cleanup during an incident, without restarting the program.
+    ///
+    /// The parameters have the same meaning as with [`FileLogWriterBuilder::rotate`].
+    ///
+    /// The current output file is flushed and closed, and the writer continues as if it
+    /// had been started anew with the new configuration:
+    /// unless [`FileLogWriterBuilder::append`] was used, the current file is rotated right away
+    /// (and named according to the new `naming`), and the cleanup is done immediately
+    /// according to the new `cleanup`.
+    /// If the writer did not rotate before, the file that was written so far is left as is,
+    /// and writing continues in the file with infix `_rCURRENT`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputCleanupConflict` if the new cleanup would affect the files
+    /// of another writer.
+    /// `FlexiLoggerError::OutputIo` if rotating or opening the file fails.
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn set_rotation<C: Into<Criterion>>(
+        &self,
+        criterion: C,
+        naming: Naming,
+        cleanup: Cleanup,
+    ) -> Result<(), FlexiLoggerError> {
+        self.state_handle.set_rotation(RotationConfig {
+            criterion: criterion.into(),
+            naming,
+            cleanup,
+        })
+    }
+
+    /// Returns the paths of the log files of this writer that currently exist:
+    /// the file that is currently written, followed by the rotated files,
+    /// including the pinned and the compressed ones.
+    ///
+    /// This allows e.g. collecting all logs of the current run into a diagnostic bundle.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn existing_log_files(&self) -> Result<Vec<PathBuf>, FlexiLoggerError> {
+        self.state_handle.existing_log_files()
+    }
+
+    /// Returns the last `n` log lines of this writer, oldest first,
+    /// e.g. for showing the recent logs in an admin UI.
+    ///
+    /// The lines are read from the file that is currently written, and,
+    /// if it contains fewer lines, from the rotated files, newest first;
+    /// gzip-compressed files are decompressed on the fly
+    /// (this needs the crate feature `compress`, without it such files are skipped).
+    /// Lines that are still buffered are flushed before; with [`WriteMode::Async`](crate::WriteMode::Async),
+    /// this waits for at most a second until the queued lines are written.
+    ///
+    /// Note that the files are read completely, so `n` should be small compared
+    /// to the size of the log files.
+    /// A log record that spans several lines counts as several lines.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if reading a file fails.
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn read_back(&self, n: usize) -> Result<Vec<String>, FlexiLoggerError> {
+        self.state_handle.read_back(n)
+    }
+
+    /// Exempts a rotated log file from the cleanup, e.g. because it is needed
+    /// for an ongoing investigation.
+    ///
+    /// The file is renamed such that it carries the marker `.keep` directly behind the
+    /// rotation infix (e.g. `foo_r00003.log` becomes `foo_r00003.keep.log`),
+    /// and the new path is returned.
+    /// Files with this marker are neither deleted nor compressed by the
+    /// configured [`Cleanup`](crate::Cleanup), and they don't count against its limits.
+    /// Files can also be pinned manually, by renaming them in the same way;
+    /// removing the marker from the file name makes the file subject to the cleanup again.
+    ///
+    /// Pinning an already pinned file is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputNotRotated` if the path does not denote a rotated file
+    /// of this writer.
+    /// `FlexiLoggerError::OutputIo` if renaming the file fails,
+    /// e.g. because the cleanup removed it in the meantime.
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn pin<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, FlexiLoggerError> {
+        self.state_handle.pin(path.as_ref())
+    }
 }
 
 impl LogWriter for FileLogWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        if let Some(ref lazy_creation) = self.o_lazy_creation {
+            if lazy_creation.hold_back(&self.state_handle, now, record) {
+                return Ok(());
+            }
+        }
         self.state_handle.write(now, record)
     }
 
@@ -110,6 +327,10 @@ impl LogWriter for FileLogWriter {
         self.state_handle.flush()
     }
 
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> bool {
+        self.state_handle.flush_and_wait(timeout)
+    }
+
     #[inline]
     fn max_log_level(&self) -> log::LevelFilter {
         self.max_log_level
@@ -133,13 +354,13 @@ impl Drop for FileLogWriter {
 
 #[cfg(test)]
 mod test {
-    use crate::deferred_now::now_local_or_utc;
+    use crate::deferred_now::{now_local_or_utc, test_clock};
     use crate::writers::LogWriter;
-    use crate::{Cleanup, Criterion, DeferredNow, FileSpec, Naming, WriteMode};
+    use crate::{Age, Cleanup, Criterion, DeferredNow, FileSpec, Naming, WriteMode};
     use std::ops::Add;
     use std::path::{Path, PathBuf};
     use std::time::Duration;
-    use time::format_description;
+    use time::{format_description, macros::datetime, UtcOffset};
 
     const DIRECTORY: &str = r"log_files/rotate";
     const ONE: &str = "ONE";
@@ -272,7 +493,7 @@ mod test {
                 .file_stem().unwrap(/*cannot fail*/)
                 .to_string_lossy().to_string(),
         );
-        let naming = Naming::Timestamps;
+        let naming = Naming::local_timestamps();
 
         // ensure we start with -/-/-
         assert!(list_rotated_files(&basename, &ts).is_empty());
@@ -313,7 +534,7 @@ mod test {
                 .file_stem().unwrap(/*cannot fail*/)
                 .to_string_lossy().to_string(),
         );
-        let naming = Naming::Timestamps;
+        let naming = Naming::local_timestamps();
 
         // ensure we start with -/-/-
         assert!(list_rotated_files(&basename, &ts).is_empty());
@@ -336,6 +557,177 @@ mod test {
         assert!(contains("CURRENT", &ts, NINE));
     }
 
+    #[test]
+    fn test_rotate_by_age_with_test_clock() {
+        // we use timestamp as discriminant to allow repeated runs
+        let ts = now_local_or_utc()
+            .format(
+                &format_description::parse(
+                    "age-test-clock-[year]-[month]-[day]_[hour]-[minute]-[second]",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        test_clock::set(datetime!(2021-01-01 23:59:58 UTC));
+        let flw = super::FileLogWriter::builder(
+            FileSpec::default().directory(DIRECTORY).discriminant(&ts),
+        )
+        .rotate(
+            Criterion::Age(Age::Day),
+            Naming::Timestamps(UtcOffset::UTC),
+            Cleanup::Never,
+        )
+        .try_build()
+        .unwrap();
+
+        let write = |text: &'static str| {
+            flw.write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("{}", text))
+                    .level(log::Level::Error)
+                    .build(),
+            )
+            .unwrap();
+        };
+
+        // same day, no rotation
+        write(ONE);
+        test_clock::set(datetime!(2021-01-01 23:59:59 UTC));
+        write(TWO);
+        assert!(contains("CURRENT", &ts, ONE));
+        assert!(contains("CURRENT", &ts, TWO));
+
+        // new day, so the file is rotated and named after its creation
+        test_clock::set(datetime!(2021-01-02 00:00:01 UTC));
+        write(THREE);
+        assert!(contains("2021-01-01T23:59:58+00", &ts, ONE));
+        assert!(contains("2021-01-01T23:59:58+00", &ts, TWO));
+        assert!(not_exists("2021-01-02T00:00:01+00", &ts));
+        assert!(!contains("CURRENT", &ts, TWO));
+        assert!(contains("CURRENT", &ts, THREE));
+
+        test_clock::reset();
+    }
+
+    #[test]
+    fn test_rotate_by_age_with_backwards_clock() {
+        let ts = now_local_or_utc()
+            .format(
+                &format_description::parse(
+                    "backwards-clock-[year]-[month]-[day]_[hour]-[minute]-[second]",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        test_clock::set(datetime!(2021-01-02 00:00:10 UTC));
+        let flw = super::FileLogWriter::builder(
+            FileSpec::default().directory(DIRECTORY).discriminant(&ts),
+        )
+        .rotate(Criterion::Age(Age::Day), Naming::Numbers, Cleanup::Never)
+        .backwards_clock_tolerance(std::time::Duration::from_secs(60))
+        .try_build()
+        .unwrap();
+
+        let write = |text: &'static str| {
+            flw.write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("{}", text))
+                    .level(log::Level::Error)
+                    .build(),
+            )
+            .unwrap();
+        };
+
+        write(ONE);
+        // a small step back into the previous day is ignored
+        test_clock::set(datetime!(2021-01-01 23:59:50 UTC));
+        write(TWO);
+        assert!(contains("CURRENT", &ts, TWO));
+        assert!(not_exists("00000", &ts));
+
+        // when the clock has caught up, it is still the same day as before
+        test_clock::set(datetime!(2021-01-02 00:00:20 UTC));
+        write(THREE);
+        assert!(contains("CURRENT", &ts, THREE));
+        assert!(not_exists("00000", &ts));
+
+        // a large step back causes a single rotation
+        test_clock::set(datetime!(2021-01-01 22:00:00 UTC));
+        write(FOUR);
+        assert!(contains("00000", &ts, THREE));
+        assert!(contains("CURRENT", &ts, FOUR));
+        test_clock::set(datetime!(2021-01-01 22:00:01 UTC));
+        write(FIVE);
+        assert!(contains("CURRENT", &ts, FOUR));
+        assert!(contains("CURRENT", &ts, FIVE));
+
+        test_clock::reset();
+    }
+
+    #[test]
+    fn test_rotate_at_every_new_hour_and_minute_with_test_clock() {
+        let new_ages: [(fn(UtcOffset) -> Age, &str); 2] = [
+            (Age::new_with_splitting_at_every_new_hour, "every-new-hour"),
+            (
+                Age::new_with_splitting_at_every_new_minute,
+                "every-new-minute",
+            ),
+        ];
+        for (new_age, infix) in &new_ages {
+            let ts = now_local_or_utc()
+                .format(
+                    &format_description::parse(&format!(
+                        "{}-test-clock-[year]-[month]-[day]_[hour]-[minute]-[second]",
+                        infix
+                    ))
+                    .unwrap(),
+                )
+                .unwrap();
+
+            // the boundary numbers are initialized with the (fake) clock
+            test_clock::set(datetime!(2021-01-01 10:59:58 UTC));
+            let age = new_age(UtcOffset::UTC);
+            let flw = super::FileLogWriter::builder(
+                FileSpec::default().directory(DIRECTORY).discriminant(&ts),
+            )
+            .rotate(Criterion::Age(age), Naming::Numbers, Cleanup::Never)
+            .try_build()
+            .unwrap();
+
+            let write = |text: &'static str| {
+                flw.write(
+                    &mut DeferredNow::new(),
+                    &log::Record::builder()
+                        .args(format_args!("{}", text))
+                        .level(log::Level::Error)
+                        .build(),
+                )
+                .unwrap();
+            };
+
+            write(ONE);
+            test_clock::set(datetime!(2021-01-01 10:59:59 UTC));
+            write(TWO);
+            assert_eq!(flw.stats().rotations, 0);
+            assert_eq!(
+                flw.time_until_rotation(),
+                Some(std::time::Duration::from_secs(1))
+            );
+
+            test_clock::set(datetime!(2021-01-01 11:00:00 UTC));
+            write(THREE);
+            assert_eq!(flw.stats().rotations, 1);
+            assert!(contains("CURRENT", &ts, THREE));
+            assert!(!contains("CURRENT", &ts, TWO));
+
+            test_clock::reset();
+        }
+    }
+
     #[test]
     fn issue_38() {
         const NUMBER_OF_FILES: usize = 5;
@@ -351,7 +743,7 @@ mod test {
             )
             .rotate(
                 Criterion::Size(500),
-                Naming::Timestamps,
+                Naming::local_timestamps(),
                 Cleanup::KeepLogFiles(NUMBER_OF_FILES),
             )
             .o_append(false);
@@ -362,6 +754,8 @@ mod test {
                 pool_capa: 5,
                 message_capa: 400,
                 flush_interval: Duration::from_secs(0),
+                channel_capa: None,
+                overflow_policy: crate::OverflowPolicy::Block,
             });
 
             let flw = flwb.try_build().unwrap();
@@ -419,6 +813,8 @@ mod test {
             pool_capa: 7,
             message_capa: 8,
             flush_interval: Duration::from_secs(0),
+            channel_capa: None,
+            overflow_policy: crate::OverflowPolicy::Block,
         };
         let flw = super::FileLogWriter::builder(
             FileSpec::default()
@@ -478,21 +874,24 @@ mod test {
         .unwrap();
         println!("FileLogWriter {:?}", flw);
 
-        assert!(flw
-            .reset(
-                &super::FileLogWriter::builder(
-                    FileSpec::default()
-                        .directory(DIRECTORY)
-                        .discriminant("test_reset-3"),
-                )
-                .rotate(
-                    Criterion::Size(28),
-                    Naming::Numbers,
-                    Cleanup::KeepLogFiles(20),
-                )
-                .write_mode(WriteMode::Direct),
+        // switching between synchronous write modes works, switching off async does not
+        let result = flw.reset(
+            &super::FileLogWriter::builder(
+                FileSpec::default()
+                    .directory(DIRECTORY)
+                    .discriminant("test_reset-3"),
             )
-            .is_err());
+            .rotate(
+                Criterion::Size(28),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(20),
+            )
+            .write_mode(WriteMode::Direct),
+        );
+        #[cfg(not(feature = "async"))]
+        assert!(result.is_ok());
+        #[cfg(feature = "async")]
+        assert!(result.is_err());
     }
 
     fn remove(s: &str, discr: &str) {
@@ -1,10 +1,23 @@
+use crate::file_spec::Numbering;
 use crate::flexi_error::FlexiLoggerError;
 use crate::formats::default_format;
-use crate::{Cleanup, Criterion, FileSpec, FormatFunction, Naming, WriteMode};
+use crate::retention_policy::CleanupListener;
+use crate::util::{PLATFORM_LINE_ENDING, UNIX_LINE_ENDING, WINDOWS_LINE_ENDING};
+use crate::{Cleanup, CleanupEvent, Criterion, FileSpec, FormatFunction, Naming, WriteMode};
 use std::path::{Path, PathBuf};
+#[cfg(feature = "compress")]
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
+use std::time::Duration;
 
-use super::{Config, FileLogWriter, LogWriter, RotationConfig, State};
+use super::{
+    manifest::Manifest, CompressionConfig, Config, FileLogWriter, LogFileSystem, LogWriter,
+    RetryPolicy, RotationConfig, State, StdFileSystem,
+};
+
+/// Default tolerance (1 min) for steps of the clock backwards,
+/// see [`FileLogWriterBuilder::backwards_clock_tolerance`].
+pub const DEFAULT_BACKWARDS_CLOCK_TOLERANCE: Duration = Duration::from_secs(60);
 
 /// Builder for [`FileLogWriter`].
 #[allow(clippy::module_name_repetitions)]
@@ -13,12 +26,32 @@ pub struct FileLogWriterBuilder {
     cfg_append: bool,
     cfg_write_mode: WriteMode,
     file_spec: FileSpec,
+    file_system: Arc<dyn LogFileSystem>,
     cfg_o_create_symlink: Option<PathBuf>,
     cfg_line_ending: &'static [u8],
+    rotation_breadcrumbs: bool,
     format: FormatFunction,
     o_rotation_config: Option<RotationConfig>,
     max_log_level: log::LevelFilter,
     cleanup_in_background_thread: bool,
+    o_cleanup_listener: Option<CleanupListener>,
+    write_manifest: bool,
+    o_cleanup_interval: Option<Duration>,
+    o_rotation_check_interval: Option<Duration>,
+    backwards_clock_tolerance: Duration,
+    o_rotated_files_directory: Option<PathBuf>,
+    numbering: Numbering,
+    o_retry_policy: Option<RetryPolicy>,
+    o_max_total_bytes: Option<u64>,
+    duplicate_to_stderr_while_suspended: bool,
+    o_create_file_lazily: Option<log::LevelFilter>,
+    #[cfg(feature = "preallocate")]
+    o_preallocate: Option<u64>,
+    compression: CompressionConfig,
+    #[cfg(feature = "compress")]
+    compress_on_shutdown: bool,
+    #[cfg(feature = "compress")]
+    compress_while_writing: bool,
 }
 
 /// Methods for influencing the behavior of the [`FileLogWriter`].
@@ -28,13 +61,33 @@ impl FileLogWriterBuilder {
             o_rotation_config: None,
             cfg_print_message: false,
             file_spec,
+            file_system: Arc::new(StdFileSystem),
             cfg_append: false,
             cfg_write_mode: WriteMode::Direct,
             cfg_o_create_symlink: None,
-            cfg_line_ending: super::UNIX_LINE_ENDING,
+            cfg_line_ending: UNIX_LINE_ENDING,
+            rotation_breadcrumbs: false,
             format: default_format,
             max_log_level: log::LevelFilter::Trace,
             cleanup_in_background_thread: true,
+            o_cleanup_listener: None,
+            write_manifest: false,
+            o_cleanup_interval: None,
+            o_rotation_check_interval: None,
+            backwards_clock_tolerance: DEFAULT_BACKWARDS_CLOCK_TOLERANCE,
+            o_rotated_files_directory: None,
+            numbering: Numbering::default(),
+            o_retry_policy: None,
+            o_max_total_bytes: None,
+            duplicate_to_stderr_while_suspended: false,
+            o_create_file_lazily: None,
+            #[cfg(feature = "preallocate")]
+            o_preallocate: None,
+            compression: CompressionConfig::default(),
+            #[cfg(feature = "compress")]
+            compress_on_shutdown: false,
+            #[cfg(feature = "compress")]
+            compress_while_writing: false,
         }
     }
 
@@ -73,6 +126,303 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Makes the cleanup additionally run periodically, with the given interval,
+    /// and not only when a rotation happens.
+    ///
+    /// This relieves the disk from old files also when the current log file is not rotated
+    /// for a long time, e.g. because the program has become quiet,
+    /// or when other processes put files into the log directory.
+    ///
+    /// The periodic cleanup is done by the cleanup background thread, so this method has
+    /// no effect if the cleanup is not done in a background thread
+    /// (see [`FileLogWriterBuilder::cleanup_in_background_thread`]).
+    #[must_use]
+    pub fn cleanup_interval(mut self, interval: Duration) -> Self {
+        self.o_cleanup_interval = Some(interval);
+        self
+    }
+
+    /// Makes a background thread check periodically, with the given interval,
+    /// if a rotation is due, and do it.
+    ///
+    /// Without this, the rotation criterion is only checked when a log line is written.
+    /// A program that has become quiet might thus keep writing into (or rather: keep open)
+    /// a log file that should have been rotated long ago, e.g. at midnight
+    /// with [`Criterion::Age`]`(`[`Age::Day`](crate::Age::Day)`)`.
+    ///
+    /// This method has no effect if no rotation is configured.
+    #[must_use]
+    pub fn rotation_check_interval(mut self, interval: Duration) -> Self {
+        self.o_rotation_check_interval = Some(interval);
+        self
+    }
+
+    /// Determines how rotation by age handles a clock that is set backwards
+    /// (e.g. by NTP) behind the creation time of the current log file.
+    ///
+    /// A step backwards by at most the given tolerance is ignored,
+    /// i.e., it does not cause a rotation, and the current file is rotated
+    /// only when the clock has passed the next boundary again.
+    /// A larger step backwards causes a single rotation, and the age of the new file
+    /// is then measured from the new clock time.
+    ///
+    /// By default, [`DEFAULT_BACKWARDS_CLOCK_TOLERANCE`] is used.
+    #[must_use]
+    pub fn backwards_clock_tolerance(mut self, tolerance: Duration) -> Self {
+        self.backwards_clock_tolerance = tolerance;
+        self
+    }
+
+    /// Makes rotated log files be moved into the given directory,
+    /// while the current log file stays in the directory of the [`FileSpec`].
+    ///
+    /// This allows e.g. writing the current log file to a fast local volume,
+    /// and keeping the rotated files on a slower archive volume.
+    /// If the directories are on different file systems, a rotated file is copied
+    /// into the given directory, and then removed from its original place.
+    ///
+    /// The cleanup, and the search for the next index with [`Naming::Numbers`](crate::Naming::Numbers),
+    /// work on the given directory.
+    ///
+    /// This method has no effect if no rotation is configured.
+    #[must_use]
+    pub fn rotated_files_directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.o_rotated_files_directory = Some(directory.into());
+        self
+    }
+
+    /// Sets the string that separates the file stem from the index in the names
+    /// of numbered log files, see [`Naming::Numbers`](crate::Naming::Numbers).
+    ///
+    /// By default, `_r` is used, which results in file names like `foo_r00003.log`;
+    /// with `.` (and a width of 0, see [`FileLogWriterBuilder::number_width`])
+    /// you get `foo.3.log`.
+    ///
+    /// The current log file is still named with `_rCURRENT`,
+    /// and [`Naming::Timestamps`](crate::Naming::Timestamps) is not affected.
+    #[must_use]
+    pub fn number_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.numbering.separator = separator.into();
+        self
+    }
+
+    /// Sets the minimal number of digits of the index in the names of numbered log files;
+    /// shorter indexes are padded with leading zeros.
+    ///
+    /// By default, a width of 5 is used. The cleanup sorts the files by their index,
+    /// so a small width does not confuse the order of the files.
+    #[must_use]
+    pub fn number_width(mut self, width: usize) -> Self {
+        self.numbering.width = width;
+        self
+    }
+
+    /// Sets the index of the first numbered log file, by default 0.
+    #[must_use]
+    pub fn first_number(mut self, start: u32) -> Self {
+        self.numbering.start = start;
+        self
+    }
+
+    /// Makes failed writes be retried according to the given [`RetryPolicy`].
+    ///
+    /// By default, a failed write is not retried, and the log record is lost.
+    /// The policy applies to all write modes, with the asynchronous ones
+    /// the retries are done by the output thread.
+    #[must_use]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.o_retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Limits the disk space that the log files may use.
+    ///
+    /// If the sum of the sizes of the current log file and the rotated log files
+    /// (including the compressed ones) would exceed the given number of bytes,
+    /// even after the [`Cleanup`] has done its work, then writing to the log file is suspended:
+    /// log records are then discarded (and counted in [`WriterStats::suspended`](crate::writers::WriterStats::suspended)),
+    /// or written to stderr, see [`FileLogWriterBuilder::duplicate_to_stderr_while_suspended`].
+    ///
+    /// The disk usage is checked periodically, so writing is resumed automatically
+    /// when disk space was reclaimed, e.g. by the cleanup or by an administrator.
+    ///
+    /// By default, the disk usage is not limited.
+    #[must_use]
+    pub fn max_total_bytes_on_disk(mut self, max_bytes: u64) -> Self {
+        self.o_max_total_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Makes log records be written to stderr while writing to the log file is suspended,
+    /// see [`FileLogWriterBuilder::max_total_bytes_on_disk`].
+    #[must_use]
+    pub fn duplicate_to_stderr_while_suspended(mut self, duplicate: bool) -> Self {
+        self.duplicate_to_stderr_while_suspended = duplicate;
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] create the log file (and the symbolic link to it)
+    /// only when the first record with the given level or a more severe level
+    /// needs to be written.
+    ///
+    /// Until then, the log lines of less severe records are held back in memory;
+    /// they are written, in their original order, when the log file is created,
+    /// and they are discarded if no such record is written at all.
+    /// This avoids littering empty or meaningless log files, e.g. with command line tools
+    /// that only need a log file if something noteworthy happened.
+    ///
+    /// By default, the log file is created with the first record that is written.
+    #[must_use]
+    pub fn create_file_lazily(mut self, level: log::LevelFilter) -> Self {
+        self.o_create_file_lazily = Some(level);
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] reserve the given number of bytes on disk
+    /// for each log file that it creates, e.g. the size of the rotation threshold.
+    ///
+    /// This avoids the fragmentation of log files that grow slowly, and it lets a lack of disk
+    /// space show up when a log file is created, rather than in the middle of the file.
+    /// The reserved space does not change the size of the file,
+    /// so the file still only contains what was written.
+    ///
+    /// The space is reserved with `fallocate` on linux; on other platforms, and on
+    /// file systems that do not support it, this has no effect.
+    /// The space is reserved through the real file system, also if a different
+    /// [`LogFileSystem`] is used.
+    #[cfg_attr(docsrs, doc(cfg(feature = "preallocate")))]
+    #[cfg(feature = "preallocate")]
+    #[must_use]
+    pub fn preallocate(mut self, bytes: u64) -> Self {
+        self.o_preallocate = Some(bytes);
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] manage its files with the given [`LogFileSystem`].
+    ///
+    /// By default, the real file system is used, see [`StdFileSystem`].
+    #[must_use]
+    pub fn file_system(mut self, file_system: Arc<dyn LogFileSystem>) -> Self {
+        self.file_system = file_system;
+        self
+    }
+
+    /// Sets the level with which older log files are compressed,
+    /// from 0 (no compression) to 9 (best compression); higher values are treated as 9.
+    ///
+    /// By default, level 1 is used, which is fast and still gives a decent compression.
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.compression.level = std::cmp::min(level, 9);
+        self
+    }
+
+    /// Sets the maximal number of threads that compress older log files concurrently.
+    ///
+    /// By default, the files are compressed one after the other by the thread that does
+    /// the cleanup. With larger values, the cleanup uses a small pool of additional threads
+    /// when several files are to be compressed, which is useful if a large backlog of
+    /// uncompressed files has accumulated. The pool never has more threads than the given
+    /// number, and than there are files to compress.
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compression_threads(mut self, threads: usize) -> Self {
+        self.compression.threads = std::cmp::max(threads, 1);
+        self
+    }
+
+    /// Makes the shutdown of the [`FileLogWriter`] rotate the current log file
+    /// and compress it immediately.
+    ///
+    /// This lets short-lived programs, like batch jobs, leave only compressed files behind,
+    /// which is consistent with [`Cleanup::KeepCompressedFiles`].
+    /// The shutdown happens with [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown),
+    /// or when the `FileLogWriter` is dropped.
+    ///
+    /// This has no effect if rotation is not used.
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compress_on_shutdown(mut self) -> Self {
+        self.compress_on_shutdown = true;
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] write the log files compressed, with gzip.
+    ///
+    /// This is useful for extremely verbose logs. The suffix of the log files gets an
+    /// additional `.gz`, like in `my_prog_rCURRENT.log.gz`.
+    /// The size criterion of [`FileLogWriterBuilder::rotate`] is then applied to the
+    /// compressed size of the file.
+    /// The gzip stream of a file is finalized when the file is rotated, and at shutdown.
+    ///
+    /// Note that compressed data reach the file in blocks, so a flush
+    /// (see [`WriteMode`]) is needed to see the youngest log lines in the file;
+    /// frequent flushes impair the compression ratio.
+    /// Files that are not finalized, e.g. because the program crashed,
+    /// can mostly still be read with tools like `zcat`.
+    #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+    #[cfg(feature = "compress")]
+    #[must_use]
+    pub fn compress_while_writing(mut self) -> Self {
+        self.compress_while_writing = true;
+        self
+    }
+
+    /// Registers a callback that is called whenever the cleanup compresses, deletes,
+    /// or moves an older log file, see [`CleanupEvent`].
+    ///
+    /// The callback is called by the thread that does the cleanup
+    /// (see [`FileLogWriterBuilder::cleanup_in_background_thread`]),
+    /// or by one of the compression threads, so it should return quickly;
+    /// if you want to do more work, like uploading the file,
+    /// send the event through a channel to some other thread.
+    ///
+    /// The callback must not log; logging from within the callback could block or recurse.
+    #[must_use]
+    pub fn on_cleanup<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&CleanupEvent) + Send + Sync + 'static,
+    {
+        self.o_cleanup_listener = Some(CleanupListener::new(callback));
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] maintain a manifest of the rotated log files,
+    /// a JSON file named like `<basename>.manifest.json` in the directory of the log files.
+    ///
+    /// The manifest lists each rotated file with its path, its time range (the creation
+    /// time and the time of the last modification, in RFC 3339 format), its size,
+    /// whether it is compressed, and its CRC-32 checksum, like in
+    ///
+    /// ```text
+    /// {"files":[
+    /// {"path":"log_files/my_prog_r00000.log.gz","start":"2024-02-11T10:00:04.1+01:00","end":"2024-02-11T11:00:04.2+01:00","size":20754,"compressed":true,"checksum":"crc32:6a3b81c4"},
+    /// {"path":"log_files/my_prog_r00001.log","start":"2024-02-11T11:00:04.2+01:00","end":"2024-02-11T12:00:04.3+01:00","size":208541,"compressed":false,"checksum":"crc32:0e4d1b93"}
+    /// ]}
+    /// ```
+    ///
+    /// A file is added to the manifest when it is rotated, and its entry is updated or removed
+    /// when the cleanup compresses, moves, or deletes the file.
+    /// The current log file is not listed, so tools that ship the log files elsewhere can
+    /// pick up exactly the closed files.
+    /// The manifest is replaced atomically (via a temporary file),
+    /// so it can be read at any time. After a restart, the existing manifest is continued.
+    ///
+    /// The checksum is calculated by a separate thread, which reads the complete file,
+    /// so that the rotation does not wait for it; until then, the entry has an empty checksum.
+    /// Pending checksums are completed when the file log writer is dropped.
+    ///
+    /// This has no effect if rotation is not used.
+    #[must_use]
+    pub fn write_manifest(mut self) -> Self {
+        self.write_manifest = true;
+        self
+    }
+
     /// Use rotation to prevent indefinite growth of log files.
     ///
     /// By default, the log file is fixed while your program is running and will grow indefinitely.
@@ -102,9 +452,14 @@ impl FileLogWriterBuilder {
     /// The cleanup parameter allows defining the strategy for dealing with older files.
     /// See [`Cleanup`] for details.
     #[must_use]
-    pub fn rotate(mut self, criterion: Criterion, naming: Naming, cleanup: Cleanup) -> Self {
+    pub fn rotate<C: Into<Criterion>>(
+        mut self,
+        criterion: C,
+        naming: Naming,
+        cleanup: Cleanup,
+    ) -> Self {
         self.o_rotation_config = Some(RotationConfig {
-            criterion,
+            criterion: criterion.into(),
             naming,
             cleanup,
         });
@@ -137,10 +492,51 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Makes each rotation link the rotated file and the new file with a line at the end of
+    /// the rotated file, like `>>> continued in my_prog_r00004.log`, and a line at the start
+    /// of the new file, like `<<< continuation of my_prog_r00003.log`,
+    /// so that readers of the files can follow the chain.
+    ///
+    /// Since the first line is written before the rotation, the name of the new file is the
+    /// name that it is expected to get with its own rotation.
+    /// It can deviate from the real name if that is taken by then, e.g. with
+    /// [`Naming::Timestamps`](crate::Naming::Timestamps) if the file is rotated
+    /// within the second in which it was created.
+    ///
+    /// This has no effect if rotation is not used.
+    #[must_use]
+    pub fn rotation_breadcrumbs(mut self) -> Self {
+        self.rotation_breadcrumbs = true;
+        self
+    }
+
     /// Use Windows line endings, rather than just `\n`.
+    ///
+    /// The line ending is also applied to the output that is written through the
+    /// [`std::io::Write`] implementation of [`ArcFileLogWriter`],
+    /// where each `\n` that is not preceded by `\r` is replaced.
     #[must_use]
     pub fn use_windows_line_ending(mut self) -> Self {
-        self.cfg_line_ending = super::WINDOWS_LINE_ENDING;
+        self.cfg_line_ending = WINDOWS_LINE_ENDING;
+        self
+    }
+
+    /// Use the line ending that is usual on the target platform,
+    /// i.e., `\r\n` on windows (see [`FileLogWriterBuilder::use_windows_line_ending`])
+    /// and `\n` elsewhere.
+    #[must_use]
+    pub fn use_platform_line_ending(mut self) -> Self {
+        self.cfg_line_ending = PLATFORM_LINE_ENDING;
+        self
+    }
+
+    /// Don't append a line ending to the records.
+    ///
+    /// This is useful with formats that produce binary output,
+    /// like [`cbor_format`](crate::cbor_format).
+    #[must_use]
+    pub fn without_line_ending(mut self) -> Self {
+        self.cfg_line_ending = b"";
         self
     }
 
@@ -153,14 +549,46 @@ impl FileLogWriterBuilder {
         self
     }
 
+    // A reset can switch between the synchronous write modes,
+    // but not from or to an asynchronous write mode.
     pub(crate) fn assert_write_mode(&self, write_mode: WriteMode) -> Result<(), FlexiLoggerError> {
-        if self.cfg_write_mode == write_mode {
+        if self.cfg_write_mode == write_mode
+            || !(self.cfg_write_mode.is_async() || write_mode.is_async())
+        {
             Ok(())
         } else {
             Err(FlexiLoggerError::Reset)
         }
     }
 
+    // A builder with the same settings, but for other files, and without symlink.
+    pub(crate) fn for_other_files(
+        &self,
+        file_spec: FileSpec,
+        max_log_level: log::LevelFilter,
+    ) -> FileLogWriterBuilder {
+        let mut file_spec = file_spec;
+        if self.o_rotation_config.is_some() {
+            file_spec.if_default_use_timestamp(false);
+        }
+        FileLogWriterBuilder {
+            file_spec,
+            file_system: Arc::clone(&self.file_system),
+            cfg_o_create_symlink: None,
+            o_rotation_config: self.o_rotation_config.clone(),
+            max_log_level,
+            o_cleanup_listener: self.o_cleanup_listener.clone(),
+            o_rotated_files_directory: self.o_rotated_files_directory.clone(),
+            numbering: self.numbering.clone(),
+            ..*self
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn get_file_spec(&self) -> &FileSpec {
+        &self.file_spec
+    }
+
     #[must_use]
     pub(crate) fn get_write_mode(&self) -> &WriteMode {
         &self.cfg_write_mode
@@ -173,7 +601,7 @@ impl FileLogWriterBuilder {
     /// `FlexiLoggerError::Io` if the specified path doesn't work.
     pub fn try_build(self) -> Result<FileLogWriter, FlexiLoggerError> {
         Ok(FileLogWriter::new(
-            self.try_build_state()?,
+            self.try_build_state(None)?,
             self.max_log_level,
             self.format,
         ))
@@ -191,48 +619,137 @@ impl FileLogWriterBuilder {
         self,
     ) -> Result<(ArcFileLogWriter, FileLogWriterHandle), FlexiLoggerError> {
         Ok(ArcFileLogWriter::new(FileLogWriter::new(
-            self.try_build_state()?,
+            self.try_build_state(None)?,
             self.max_log_level,
             self.format,
         )))
     }
 
-    pub(crate) fn try_build_state(&self) -> Result<State, FlexiLoggerError> {
+    // A predecessor is a state that is going to be replaced by the new one.
+    pub(crate) fn try_build_state(
+        &self,
+        o_predecessor: Option<&State>,
+    ) -> Result<State, FlexiLoggerError> {
         // make sure the folder exists or create it
         let dir = self.file_spec.get_directory();
         let p_directory = Path::new(&dir);
-        std::fs::create_dir_all(&p_directory)?;
-        if !std::fs::metadata(&p_directory)?.is_dir() {
+        self.file_system.create_dir_all(p_directory)?;
+        if !self.file_system.metadata(p_directory)?.is_dir {
             return Err(FlexiLoggerError::OutputBadDirectory);
         };
+        if let Some(ref rotated_files_directory) = self.o_rotated_files_directory {
+            self.file_system.create_dir_all(rotated_files_directory)?;
+            if !self.file_system.metadata(rotated_files_directory)?.is_dir {
+                return Err(FlexiLoggerError::OutputBadDirectory);
+            }
+        }
 
-        #[cfg(feature = "async")]
-        let cleanup_in_background_thread = if let WriteMode::AsyncWith {
-            bufsize: _,
-            pool_capa: _,
-            message_capa: _,
-            flush_interval: _,
-        } = self.cfg_write_mode
+        if let Some(RotationConfig {
+            criterion:
+                Criterion::AgeAtTime {
+                    time_of_day: (hour, minute, second),
+                    ..
+                },
+            ..
+        }) = self.o_rotation_config
         {
-            false
-        } else {
-            self.cleanup_in_background_thread
+            if hour > 23 || minute > 59 || second > 59 {
+                return Err(FlexiLoggerError::OutputRotationTimeOfDay((
+                    hour, minute, second,
+                )));
+            }
+        }
+
+        #[cfg(feature = "async")]
+        let cleanup_in_background_thread = match self.cfg_write_mode {
+            WriteMode::AsyncWith {
+                bufsize: _,
+                pool_capa: _,
+                message_capa: _,
+                flush_interval: _,
+                channel_capa: _,
+                overflow_policy: _,
+            }
+            | WriteMode::AsyncLossy {
+                channel_capa: _,
+                flush_interval: _,
+            } => false,
+            _ => self.cleanup_in_background_thread,
         };
         #[cfg(not(feature = "async"))]
         let cleanup_in_background_thread = self.cleanup_in_background_thread;
 
-        Ok(State::try_new(
+        #[cfg(feature = "compress")]
+        let mut file_spec = {
+            let mut file_spec = self.file_spec.clone();
+            if self.compress_while_writing {
+                file_spec.add_gz_suffix();
+            }
+            file_spec
+        };
+        #[cfg(not(feature = "compress"))]
+        let mut file_spec = self.file_spec.clone();
+        file_spec
+            .o_rotated_directory
+            .clone_from(&self.o_rotated_files_directory);
+        file_spec.numbering.clone_from(&self.numbering);
+
+        let o_manifest = if self.write_manifest && self.o_rotation_config.is_some() {
+            Some(Arc::new(Manifest::new(
+                file_spec.as_manifest_pathbuf(),
+                Arc::clone(&self.file_system),
+            )))
+        } else {
+            None
+        };
+        // the manifest follows the actions of the cleanup
+        let o_cleanup_listener = match o_manifest {
+            Some(ref manifest) => {
+                let manifest = Arc::clone(manifest);
+                let o_listener = self.o_cleanup_listener.clone();
+                Some(CleanupListener::new(move |event| {
+                    manifest.cleaned_up(event);
+                    if let Some(ref listener) = o_listener {
+                        listener.notify(event);
+                    }
+                }))
+            }
+            None => self.o_cleanup_listener.clone(),
+        };
+
+        State::try_new(
             Config {
                 print_message: self.cfg_print_message,
                 append: self.cfg_append,
                 line_ending: self.cfg_line_ending,
+                rotation_breadcrumbs: self.rotation_breadcrumbs,
                 write_mode: self.cfg_write_mode,
-                file_spec: self.file_spec.clone(),
+                file_spec,
+                file_system: Arc::clone(&self.file_system),
                 o_create_symlink: self.cfg_o_create_symlink.as_ref().map(Clone::clone),
+                o_cleanup_listener,
+                o_manifest,
+                o_cleanup_interval: self.o_cleanup_interval,
+                o_rotation_check_interval: self.o_rotation_check_interval,
+                backwards_clock_tolerance: self.backwards_clock_tolerance,
+                o_retry_policy: self.o_retry_policy,
+                o_max_total_bytes: self.o_max_total_bytes,
+                duplicate_to_stderr_while_suspended: self.duplicate_to_stderr_while_suspended,
+                o_create_file_lazily: self.o_create_file_lazily,
+                #[cfg(feature = "preallocate")]
+                o_preallocate: self.o_preallocate,
+                compression: self.compression,
+                #[cfg(feature = "compress")]
+                compress_on_shutdown: self.compress_on_shutdown,
+                #[cfg(feature = "compress")]
+                compress_while_writing: self.compress_while_writing,
+                #[cfg(feature = "compress")]
+                compressed_size: Arc::new(AtomicU64::new(0)),
             },
             self.o_rotation_config.as_ref().map(Clone::clone),
             cleanup_in_background_thread,
-        ))
+            o_predecessor,
+        )
     }
 }
 
@@ -290,7 +807,40 @@ impl FileLogWriterBuilder {
     }
 }
 
-/// A shareable `FileLogWriter` with a handle.
+/// A shareable `FileLogWriter` with a handle,
+/// see [`FileLogWriterBuilder::try_build_with_handle`].
+///
+/// `ArcFileLogWriter` implements `std::io::Write`, `Send`, and `Clone`, and cloning it is cheap,
+/// so it can be handed to code that only accepts a writer,
+/// like `tracing_subscriber::fmt::MakeWriter`, or the piping of the output of a child process.
+/// The bytes are written to the log file as they are, without a format function being applied,
+/// but the rotation, the cleanup, and the write mode of the `FileLogWriter` are effective.
+///
+/// A rotation never splits a record: if the bytes of a `write` call do not end with a line
+/// break, a due rotation is deferred until a subsequent call completes the line.
+/// Records that consist of several lines can be written with
+/// [`ArcFileLogWriter::write_record`].
+///
+/// ```rust
+/// # use flexi_logger::{writers::FileLogWriter, Cleanup, Criterion, FileSpec, Naming};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (file_writer, _fw_handle) = FileLogWriter::builder(
+///     FileSpec::default()
+/// #       .directory("log_files/arc_file_log_writer_docu")
+///         .basename("child"),
+/// )
+/// .rotate(
+///     Criterion::Size(1_000_000),
+///     Naming::Numbers,
+///     Cleanup::KeepLogFiles(5),
+/// )
+/// .try_build_with_handle()?;
+///
+/// let mut child_output: &[u8] = b"first line\nsecond line\n";
+/// std::io::copy(&mut child_output, &mut std::io::LineWriter::new(file_writer.clone()))?;
+/// # Ok(())
+/// # }
+/// ```
 pub struct ArcFileLogWriter(Arc<FileLogWriter>);
 impl ArcFileLogWriter {
     pub(crate) fn new(flw: FileLogWriter) -> (Self, FileLogWriterHandle) {
@@ -298,6 +848,24 @@ impl ArcFileLogWriter {
         let handle = FileLogWriterHandle(a_flw.clone());
         (Self(a_flw), handle)
     }
+
+    /// Writes the given bytes as one record, i.e., without a rotation in between,
+    /// even if they contain several lines.
+    ///
+    /// The line ending of the `FileLogWriter` is added if the record does not end
+    /// with a line break.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if writing fails.
+    pub fn write_record(&self, record: &[u8]) -> std::io::Result<()> {
+        self.0.write_record(record)
+    }
+}
+impl std::fmt::Debug for ArcFileLogWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ArcFileLogWriter").field(&self.0).finish()
+    }
 }
 impl Clone for ArcFileLogWriter {
     fn clone(&self) -> Self {
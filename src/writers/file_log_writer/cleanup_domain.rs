@@ -0,0 +1,162 @@
+use crate::{FileSpec, FlexiLoggerError};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+// All file log writers that write into the same directory share a cleanup domain.
+//
+// The domain serializes the cleanup scans of its members, so that concurrent scans don't
+// trip over each other, and it rejects new members if the cleanup of one member would
+// delete or compress the files of another member.
+lazy_static::lazy_static! {
+    static ref DOMAINS: Mutex<HashMap<PathBuf, CleanupDomain>> = Mutex::new(HashMap::new());
+}
+static NEXT_MEMBER_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default)]
+struct CleanupDomain {
+    scan_lock: Arc<Mutex<()>>,
+    members: Vec<Member>,
+}
+
+struct Member {
+    id: u64,
+    file_spec: FileSpec,
+    does_cleanup: bool,
+}
+
+// The membership of a file log writer in the cleanup domain of its directory;
+// the writer leaves the domain when the membership is dropped.
+#[derive(Debug)]
+pub(super) struct Membership {
+    id: u64,
+    directory: PathBuf,
+    scan_lock: Arc<Mutex<()>>,
+}
+impl Membership {
+    // Joins the cleanup domain of the directory of the given `FileSpec`.
+    //
+    // A predecessor, i.e. the membership of a writer state that is about to be replaced
+    // by the new one, is ignored in the overlap check.
+    pub(super) fn join(
+        file_spec: &FileSpec,
+        does_cleanup: bool,
+        o_predecessor: Option<&Membership>,
+    ) -> Result<Self, FlexiLoggerError> {
+        let directory = domain_key(&file_spec.get_rotated_directory());
+        let mut domains = DOMAINS.lock().map_err(|_| FlexiLoggerError::Poison)?;
+
+        if let Some(domain) = domains.get(&directory) {
+            for member in domain
+                .members
+                .iter()
+                .filter(|member| Some(member.id) != o_predecessor.map(|pred| pred.id))
+            {
+                if let Some(conflict) = find_conflict(
+                    (&member.file_spec, member.does_cleanup),
+                    (file_spec, does_cleanup),
+                ) {
+                    return Err(FlexiLoggerError::OutputCleanupConflict(conflict));
+                }
+            }
+        }
+
+        let id = NEXT_MEMBER_ID.fetch_add(1, Ordering::Relaxed);
+        let domain = domains.entry(directory.clone()).or_default();
+        domain.members.push(Member {
+            id,
+            file_spec: file_spec.clone(),
+            does_cleanup,
+        });
+        Ok(Self {
+            id,
+            directory,
+            scan_lock: Arc::clone(&domain.scan_lock),
+        })
+    }
+
+    // Cleanup scans must hold this lock.
+    pub(super) fn scan_lock(&self) -> &Arc<Mutex<()>> {
+        &self.scan_lock
+    }
+}
+impl Drop for Membership {
+    fn drop(&mut self) {
+        if let Ok(mut domains) = DOMAINS.lock() {
+            if let Some(domain) = domains.get_mut(&self.directory) {
+                domain.members.retain(|member| member.id != self.id);
+                if domain.members.is_empty() {
+                    domains.remove(&self.directory);
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn lock_scans(scan_lock: &Mutex<()>) -> MutexGuard<'_, ()> {
+    // the lock protects no data, so a poisoned lock can still be used
+    scan_lock.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn domain_key(directory: &Path) -> PathBuf {
+    std::fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf())
+}
+
+fn find_conflict(first: (&FileSpec, bool), second: (&FileSpec, bool)) -> Option<String> {
+    let (first_spec, first_does_cleanup) = first;
+    let (second_spec, second_does_cleanup) = second;
+    if first_does_cleanup {
+        if let Some(conflict) = find_affected_file(first_spec, second_spec) {
+            return Some(conflict);
+        }
+    }
+    if second_does_cleanup {
+        if let Some(conflict) = find_affected_file(second_spec, first_spec) {
+            return Some(conflict);
+        }
+    }
+    None
+}
+
+// Checks if the cleanup of `cleaner` would consider files that are written by `other`.
+fn find_affected_file(cleaner: &FileSpec, other: &FileSpec) -> Option<String> {
+    let infix_patterns = cleaner.rotated_infix_patterns();
+    let patterns: Vec<(String, glob::Pattern)> = infix_patterns
+        .iter()
+        .flat_map(|infix| {
+            [None, Some("gz"), Some("zip")]
+                .iter()
+                .map(move |o_suffix| file_name_of(&cleaner.as_glob_pattern(Some(infix), *o_suffix)))
+        })
+        .filter_map(|pattern| {
+            glob::Pattern::new(&pattern)
+                .ok()
+                .map(|compiled| (pattern, compiled))
+        })
+        .collect();
+
+    // samples for all kinds of files that `other` can produce
+    [
+        None,
+        Some("_rCURRENT".to_string()),
+        Some(other.number_infix(other.numbering.start)),
+        Some("_r2021-01-01T00:00:00+00".to_string()),
+    ]
+    .iter()
+    .map(|o_infix| file_name_of(&other.as_pathbuf(o_infix.as_deref()).to_string_lossy()))
+    .flat_map(|file_name| vec![format!("{}.gz", file_name), file_name])
+    .find_map(|file_name| {
+        patterns
+            .iter()
+            .find(|(_, compiled)| compiled.matches(&file_name))
+            .map(|(pattern, _)| format!("{} would match {}", pattern, file_name))
+    })
+}
+
+fn file_name_of(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
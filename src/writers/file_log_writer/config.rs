@@ -1,5 +1,11 @@
+use super::{manifest::Manifest, LogFileSystem, RetryPolicy};
+use crate::retention_policy::CleanupListener;
 use crate::{Cleanup, Criterion, FileSpec, Naming, WriteMode};
 use std::path::PathBuf;
+#[cfg(feature = "compress")]
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::time::Duration;
 
 // Describes how rotation should work
 #[derive(Clone, Debug)]
@@ -12,6 +18,24 @@ pub(crate) struct RotationConfig {
     pub(crate) cleanup: Cleanup,
 }
 
+// Describes how rotated files are compressed.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "compress"), allow(dead_code))]
+pub(crate) struct CompressionConfig {
+    // The compression level, from 0 (no compression) to 9 (best compression)
+    pub(crate) level: u32,
+    // The maximal number of threads that compress files concurrently
+    pub(crate) threads: usize,
+}
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            threads: 1,
+        }
+    }
+}
+
 // The immutable configuration of a FileLogWriter.
 #[derive(Debug)]
 pub(crate) struct Config {
@@ -19,6 +43,27 @@ pub(crate) struct Config {
     pub(crate) append: bool,
     pub(crate) write_mode: WriteMode,
     pub(crate) file_spec: FileSpec,
+    pub(crate) file_system: Arc<dyn LogFileSystem>,
     pub(crate) o_create_symlink: Option<PathBuf>,
     pub(crate) line_ending: &'static [u8],
+    pub(crate) rotation_breadcrumbs: bool,
+    pub(crate) o_cleanup_listener: Option<CleanupListener>,
+    pub(crate) o_manifest: Option<Arc<Manifest>>,
+    pub(crate) o_cleanup_interval: Option<Duration>,
+    pub(crate) o_rotation_check_interval: Option<Duration>,
+    pub(crate) backwards_clock_tolerance: Duration,
+    pub(crate) o_retry_policy: Option<RetryPolicy>,
+    pub(crate) o_max_total_bytes: Option<u64>,
+    pub(crate) duplicate_to_stderr_while_suspended: bool,
+    pub(crate) o_create_file_lazily: Option<log::LevelFilter>,
+    #[cfg(feature = "preallocate")]
+    pub(crate) o_preallocate: Option<u64>,
+    pub(crate) compression: CompressionConfig,
+    #[cfg(feature = "compress")]
+    pub(crate) compress_on_shutdown: bool,
+    #[cfg(feature = "compress")]
+    pub(crate) compress_while_writing: bool,
+    // with compress_while_writing: the number of compressed bytes in the current file
+    #[cfg(feature = "compress")]
+    pub(crate) compressed_size: Arc<AtomicU64>,
 }
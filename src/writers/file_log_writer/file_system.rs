@@ -0,0 +1,155 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The file system operations that the [`FileLogWriter`](crate::writers::FileLogWriter)
+/// uses for managing its log files.
+///
+/// By default, the [`FileLogWriter`](crate::writers::FileLogWriter) works with the real
+/// file system, see [`StdFileSystem`].
+/// With [`FileLogWriterBuilder::file_system`](crate::writers::FileLogWriterBuilder::file_system),
+/// an alternative implementation can be plugged in, e.g. an in-memory file system for tests,
+/// or one that is backed by an object store.
+///
+/// All paths that are handed to the methods are derived from the
+/// [`FileSpec`](crate::FileSpec) (and from the other path-related settings) of the writer.
+///
+/// Symbolic links to the current log file
+/// (see [`FileLogWriterBuilder::create_symlink`](crate::writers::FileLogWriterBuilder::create_symlink))
+/// are always created in the real file system.
+pub trait LogFileSystem: std::fmt::Debug + Send + Sync {
+    /// Opens the file for writing, and creates it if it does not exist.
+    ///
+    /// With `append == true`, the output is appended to the existing content of the file,
+    /// otherwise the file is truncated.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be opened.
+    fn open(&self, path: &Path, append: bool) -> std::io::Result<Box<dyn Write + Send>>;
+
+    /// Opens an existing file for reading.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be opened.
+    fn open_for_reading(&self, path: &Path) -> std::io::Result<Box<dyn Read + Send>>;
+
+    /// Renames the file; a file that exists with the new name is replaced.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be renamed; `std::io::ErrorKind::NotFound` if the file does not exist.
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()>;
+
+    /// Removes the file.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be removed; `std::io::ErrorKind::NotFound` if the file does not exist.
+    fn remove_file(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Creates the directory and all of its missing parent directories.
+    ///
+    /// # Errors
+    ///
+    /// If the directory cannot be created.
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Removes the directory if it is empty.
+    ///
+    /// # Errors
+    ///
+    /// If the directory cannot be removed, e.g. because it is not empty.
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Returns the paths of the existing files that match the glob pattern,
+    /// in any order.
+    ///
+    /// The patterns use `*` and `?` as wildcards, and `[...]` for character classes,
+    /// wildcards can also appear in directory names.
+    fn glob(&self, pattern: &str) -> Vec<PathBuf>;
+
+    /// Returns the metadata of the file or directory.
+    ///
+    /// # Errors
+    ///
+    /// If the metadata cannot be determined, e.g. because the file does not exist.
+    fn metadata(&self, path: &Path) -> std::io::Result<FileMetadata>;
+
+    /// Returns `true` if the file or directory exists.
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+}
+
+/// The properties of a file or directory that a [`LogFileSystem`] provides.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FileMetadata {
+    /// The size of the file, in bytes.
+    pub len: u64,
+    /// The time of the last modification, if available.
+    pub modified: Option<SystemTime>,
+    /// `true` for a directory.
+    pub is_dir: bool,
+}
+
+/// The default implementation of [`LogFileSystem`], which uses `std::fs`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdFileSystem;
+impl LogFileSystem for StdFileSystem {
+    fn open(&self, path: &Path, append: bool) -> std::io::Result<Box<dyn Write + Send>> {
+        Ok(Box::new(
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .append(append)
+                .truncate(!append)
+                .open(path)?,
+        ))
+    }
+
+    fn open_for_reading(&self, path: &Path) -> std::io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(std::fs::File::open(path)?))
+    }
+
+    // Since rename does not work across file systems, the file is then copied
+    // and the original is removed.
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        match std::fs::rename(from, to) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(e),
+            Err(_) => {
+                std::fs::copy(from, to)?;
+                std::fs::remove_file(from)
+            }
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_dir(path)
+    }
+
+    fn glob(&self, pattern: &str) -> Vec<PathBuf> {
+        glob::glob(pattern)
+            .map(|paths| paths.filter_map(Result::ok).collect())
+            .unwrap_or_default()
+    }
+
+    fn metadata(&self, path: &Path) -> std::io::Result<FileMetadata> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(FileMetadata {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            is_dir: metadata.is_dir(),
+        })
+    }
+}
@@ -0,0 +1,63 @@
+use super::state_handle::StateHandle;
+use crate::util::{eprint_err, sanitize, ERRCODE};
+use crate::DeferredNow;
+use log::{LevelFilter, Record};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+// Holds back the log lines until the first record with a sufficient level arrives,
+// see FileLogWriterBuilder::create_file_lazily.
+#[derive(Debug)]
+pub(super) struct LazyCreation {
+    level: LevelFilter,
+    line_ending: &'static [u8],
+    triggered: AtomicBool,
+    // the formatted lines that are held back; is None once the creation was triggered
+    mo_held_lines: Mutex<Option<Vec<u8>>>,
+}
+impl LazyCreation {
+    pub(super) fn new(level: LevelFilter, line_ending: &'static [u8]) -> Self {
+        Self {
+            level,
+            line_ending,
+            triggered: AtomicBool::new(false),
+            mo_held_lines: Mutex::new(Some(Vec::new())),
+        }
+    }
+
+    // Returns true if the record was held back, and must not be written.
+    pub(super) fn hold_back(
+        &self,
+        state_handle: &StateHandle,
+        now: &mut DeferredNow,
+        record: &Record,
+    ) -> bool {
+        if self.triggered.load(Ordering::Acquire) {
+            return false;
+        }
+        if let Ok(mut guard) = self.mo_held_lines.lock() {
+            if let Some(ref mut held_lines) = *guard {
+                if record.level() > self.level {
+                    let mut line = Vec::with_capacity(200);
+                    (state_handle.format_function())(&mut line, now, record).unwrap_or_else(|e| {
+                        eprint_err(ERRCODE::Format, "formatting failed", &e);
+                    });
+                    sanitize(&mut line);
+                    held_lines.extend_from_slice(&line);
+                    held_lines.extend_from_slice(self.line_ending);
+                    return true;
+                }
+
+                // the held back lines are written while the lock is held, to keep the order
+                if !held_lines.is_empty() {
+                    if let Err(e) = state_handle.plain_write(held_lines) {
+                        eprint_err(ERRCODE::Write, "writing failed", &e);
+                    }
+                }
+                *guard = None;
+                self.triggered.store(true, Ordering::Release);
+            }
+        }
+        false
+    }
+}
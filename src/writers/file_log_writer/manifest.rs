@@ -0,0 +1,368 @@
+use super::{FileMetadata, LogFileSystem};
+use crate::deferred_now::now_local_or_utc;
+use crate::formats::write_json_string;
+use crate::util::{eprint_err, ERRCODE};
+use crate::CleanupEvent;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+// The list of the rotated log files, see FileLogWriterBuilder::write_manifest.
+//
+// The manifest is written with one file per line, so that it can be read back
+// after a restart without a full JSON parser.
+//
+// The checksums are computed by a separate thread, because reading a whole file must not
+// delay the rotation, during which the logging threads have to wait; an entry is thus
+// first written with an empty checksum, and is completed a little later.
+#[derive(Debug)]
+pub(crate) struct Manifest {
+    a_list: Arc<List>,
+    o_checksum_sender: Option<Sender<PathBuf>>,
+    o_checksum_thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+struct List {
+    path: PathBuf,
+    file_system: Arc<dyn LogFileSystem>,
+    m_entries: Mutex<Vec<Entry>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    path: String,
+    start: String,
+    end: String,
+    size: u64,
+    compressed: bool,
+    checksum: String,
+}
+
+impl Manifest {
+    // Continues an existing manifest; entries of files that no longer exist are dropped.
+    pub(crate) fn new(path: PathBuf, file_system: Arc<dyn LogFileSystem>) -> Self {
+        let mut content = String::new();
+        let entries = match file_system
+            .open_for_reading(&path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+        {
+            Ok(_) => content
+                .lines()
+                .filter_map(Entry::parse)
+                .filter(|entry| file_system.exists(Path::new(&entry.path)))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let a_list = Arc::new(List {
+            path,
+            file_system,
+            m_entries: Mutex::new(entries),
+        });
+
+        // if the thread cannot be spawned, the checksums are computed right away
+        let (sender, receiver) = channel::<PathBuf>();
+        let t_list = Arc::clone(&a_list);
+        let builder = std::thread::Builder::new().name("flexi_logger-checksum".to_string());
+        #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+        let builder = builder.stack_size(512 * 1024);
+        let o_checksum_thread = builder
+            .spawn(move || {
+                for path in receiver {
+                    t_list.add_checksum(&path);
+                }
+            })
+            .map_err(|e| eprint_err(ERRCODE::LogFile, "cannot spawn checksum thread", &e))
+            .ok();
+        Self {
+            a_list,
+            o_checksum_sender: o_checksum_thread.as_ref().map(|_| sender),
+            o_checksum_thread,
+        }
+    }
+
+    // Adds a file that was just rotated, and that was started at the given time.
+    pub(crate) fn add_rotated(&self, path: &Path, start: OffsetDateTime) {
+        // if the file does not exist, nothing was rotated
+        if let Ok(metadata) = self.a_list.file_system.metadata(path) {
+            self.add(path, start, &metadata);
+        }
+    }
+
+    fn add(&self, path: &Path, start: OffsetDateTime, metadata: &FileMetadata) {
+        let end = metadata.modified.map_or_else(now_local_or_utc, |modified| {
+            OffsetDateTime::from(modified).to_offset(start.offset())
+        });
+        let entry = Entry {
+            path: path.to_string_lossy().to_string(),
+            start: start.format(&Rfc3339).unwrap_or_default(),
+            end: end.format(&Rfc3339).unwrap_or_default(),
+            size: metadata.len,
+            compressed: is_compressed(path),
+            checksum: String::new(),
+        };
+        self.a_list.update(|entries| {
+            entries.retain(|e| e.path != entry.path);
+            entries.push(entry);
+        });
+        self.request_checksum(path);
+    }
+
+    // Follows the actions of the cleanup.
+    pub(crate) fn cleaned_up(&self, event: &CleanupEvent) {
+        match event {
+            CleanupEvent::Compressed {
+                path,
+                compressed_path,
+            } => {
+                let size = self
+                    .a_list
+                    .file_system
+                    .metadata(compressed_path)
+                    .map(|metadata| metadata.len)
+                    .unwrap_or_default();
+                let new_path = compressed_path.to_string_lossy().to_string();
+                self.a_list.update_entry(path, |entry| {
+                    entry.path = new_path;
+                    entry.size = size;
+                    entry.compressed = true;
+                    entry.checksum = String::new();
+                });
+                self.request_checksum(compressed_path);
+            }
+            CleanupEvent::Moved { path, new_path } => {
+                let new_path = new_path.to_string_lossy().to_string();
+                self.a_list
+                    .update_entry(path, |entry| entry.path = new_path);
+            }
+            CleanupEvent::Deleted { path } => self.a_list.remove_entry(path),
+            #[cfg(feature = "upload")]
+            CleanupEvent::Uploaded { path } => self.a_list.remove_entry(path),
+        }
+    }
+
+    fn request_checksum(&self, path: &Path) {
+        if let Some(ref sender) = self.o_checksum_sender {
+            if sender.send(path.to_path_buf()).is_ok() {
+                return;
+            }
+        }
+        self.a_list.add_checksum(path);
+    }
+}
+
+impl Drop for Manifest {
+    // Lets the checksum thread complete the pending checksums.
+    fn drop(&mut self) {
+        self.o_checksum_sender.take();
+        if let Some(thread_handle) = self.o_checksum_thread.take() {
+            thread_handle.join().ok();
+        }
+    }
+}
+
+impl List {
+    // Computes the checksum of the file and adds it to its entry, if the entry still refers
+    // to the file.
+    fn add_checksum(&self, path: &Path) {
+        let checksum = self.checksum(path);
+        self.update_entry(path, |entry| entry.checksum = checksum);
+    }
+
+    fn update_entry<F: FnOnce(&mut Entry)>(&self, path: &Path, f: F) {
+        let path = path.to_string_lossy();
+        self.update(|entries| {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.path == path) {
+                f(entry);
+            }
+        });
+    }
+
+    fn remove_entry(&self, path: &Path) {
+        let path = path.to_string_lossy();
+        self.update(|entries| entries.retain(|entry| entry.path != path));
+    }
+
+    // Applies the change and replaces the manifest file, via a temporary file,
+    // so that readers never see an incomplete manifest.
+    fn update<F: FnOnce(&mut Vec<Entry>)>(&self, f: F) {
+        if let Ok(mut entries) = self.m_entries.lock() {
+            f(&mut entries);
+            self.write(&entries);
+        }
+    }
+
+    fn write(&self, entries: &[Entry]) {
+        let mut content = Vec::<u8>::with_capacity(200 * (entries.len() + 1));
+        content.extend_from_slice(b"{\"files\":[\n");
+        for (i, entry) in entries.iter().enumerate() {
+            entry.write(&mut content).ok();
+            if i + 1 < entries.len() {
+                content.push(b',');
+            }
+            content.push(b'\n');
+        }
+        content.extend_from_slice(b"]}\n");
+
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let result = self
+            .file_system
+            .open(&tmp_path, false)
+            .and_then(|mut file| {
+                file.write_all(&content)?;
+                file.flush()
+            })
+            .and_then(|()| self.file_system.rename(&tmp_path, &self.path));
+        if let Err(e) = result {
+            eprint_err(ERRCODE::LogFile, "cannot write manifest of log files", &e);
+        }
+    }
+
+    fn checksum(&self, path: &Path) -> String {
+        let mut crc = Crc32::new();
+        let mut buffer = vec![0_u8; 64 * 1024];
+        let result = self
+            .file_system
+            .open_for_reading(path)
+            .and_then(|mut file| loop {
+                let len = file.read(&mut buffer)?;
+                if len == 0 {
+                    return Ok(());
+                }
+                crc.update(&buffer[..len]);
+            });
+        match result {
+            Ok(()) => format!("crc32:{:08x}", crc.finish()),
+            Err(_) => String::new(),
+        }
+    }
+}
+
+impl Entry {
+    fn write(&self, w: &mut Vec<u8>) -> std::io::Result<()> {
+        w.write_all(b"{\"path\":")?;
+        write_json_string(w, &self.path)?;
+        w.write_all(b",\"start\":")?;
+        write_json_string(w, &self.start)?;
+        w.write_all(b",\"end\":")?;
+        write_json_string(w, &self.end)?;
+        write!(
+            w,
+            ",\"size\":{},\"compressed\":{},\"checksum\":",
+            self.size, self.compressed
+        )?;
+        write_json_string(w, &self.checksum)?;
+        w.write_all(b"}")
+    }
+
+    // Reads an entry back that was written with `write`.
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end_matches(',');
+        let line = line.strip_prefix('{')?.strip_suffix('}')?;
+        let (path, rest) = parse_string_field(line, "path")?;
+        let (start, rest) = parse_string_field(rest.strip_prefix(',')?, "start")?;
+        let (end, rest) = parse_string_field(rest.strip_prefix(',')?, "end")?;
+        let (size, rest) = parse_plain_field(rest.strip_prefix(',')?, "size")?;
+        let (compressed, rest) = parse_plain_field(rest.strip_prefix(',')?, "compressed")?;
+        let (checksum, _) = parse_string_field(rest.strip_prefix(',')?, "checksum")?;
+        Some(Self {
+            path,
+            start,
+            end,
+            size: size.parse().ok()?,
+            compressed: compressed.parse().ok()?,
+            checksum,
+        })
+    }
+}
+
+// Parses `"name":"value"` from the start of s, and returns the unescaped value and the rest.
+fn parse_string_field<'a>(s: &'a str, name: &str) -> Option<(String, &'a str)> {
+    let s = s
+        .strip_prefix('"')?
+        .strip_prefix(name)?
+        .strip_prefix("\":\"")?;
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &s[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'u' => {
+                    let hex: String = (0..4)
+                        .filter_map(|_| chars.next())
+                        .map(|(_, c)| c)
+                        .collect();
+                    value.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+// Parses `"name":value` from the start of s, for a number or boolean value.
+fn parse_plain_field<'a>(s: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+    let s = s
+        .strip_prefix('"')?
+        .strip_prefix(name)?
+        .strip_prefix("\":")?;
+    let end = s.find(',').unwrap_or(s.len());
+    Some((&s[..end], &s[end..]))
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension() == Some(std::ffi::OsStr::new("gz"))
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+// CRC-32 as used by gzip and zip (IEEE 802.3, reflected, polynomial 0xEDB88320).
+struct Crc32(u32);
+impl Crc32 {
+    fn new() -> Self {
+        Self(0xFFFF_FFFF)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = CRC32_TABLE[((self.0 ^ u32::from(*byte)) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.0 ^ 0xFFFF_FFFF
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::Write;
+use std::time::Duration;
+
+/// Defines how the [`FileLogWriter`](crate::writers::FileLogWriter) handles
+/// failed writes, so that transient storage hiccups
+/// (like a momentary `EIO` on a network file system) do not lose log records.
+///
+/// Is used in [`FileLogWriterBuilder::retry_policy`](crate::writers::FileLogWriterBuilder::retry_policy).
+///
+/// A failed write is retried up to the given number of times; before each retry,
+/// the writer waits, starting with the given backoff, which is doubled with each further retry.
+/// Note that the writer is blocked while it waits;
+/// with the synchronous write modes, this also blocks the threads that log concurrently.
+///
+/// ## Example
+///
+/// Retry three times, after 10, 20, and 40 milliseconds,
+/// and then write the record to stderr:
+///
+/// ```rust
+/// # use flexi_logger::{FileSpec, Logger};
+/// # use flexi_logger::writers::{RetryExhausted, RetryPolicy};
+/// # use std::time::Duration;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?
+///     .log_to_file(FileSpec::default())
+///     .retry_policy(
+///         RetryPolicy::new(3, Duration::from_millis(10))
+///             .when_exhausted(RetryExhausted::WriteToStderr),
+///     )
+/// #   .log_to_stdout()
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    attempts: u32,
+    backoff: Duration,
+    when_exhausted: RetryExhausted,
+}
+impl RetryPolicy {
+    /// Retries a failed write up to `attempts` times, the first time after `backoff`.
+    ///
+    /// When all retries have failed, the record is discarded.
+    #[must_use]
+    pub fn new(attempts: u32, backoff: Duration) -> Self {
+        Self {
+            attempts,
+            backoff,
+            when_exhausted: RetryExhausted::Discard,
+        }
+    }
+
+    /// Determines what happens with the record when all retries have failed.
+    #[must_use]
+    pub fn when_exhausted(mut self, when_exhausted: RetryExhausted) -> Self {
+        self.when_exhausted = when_exhausted;
+        self
+    }
+
+    // Writes the buffer, retrying according to the policy;
+    // returns the error of the last attempt if all attempts failed.
+    pub(crate) fn write_all(&self, w: &mut dyn Write, buf: &[u8]) -> std::io::Result<()> {
+        let mut backoff = self.backoff;
+        let mut result = w.write_all(buf);
+        for _ in 0..self.attempts {
+            if result.is_ok() {
+                break;
+            }
+            std::thread::sleep(backoff);
+            backoff *= 2;
+            result = w.write_all(buf);
+        }
+        if result.is_err() {
+            if let RetryExhausted::WriteToStderr = self.when_exhausted {
+                crate::util::LockedStderr.write_all(buf).ok();
+            }
+        }
+        result
+    }
+}
+
+/// What happens with a log record when all retries of a [`RetryPolicy`] have failed.
+///
+/// In any case, the failure is reported as an internal error,
+/// see [`Logger::error_channel`](crate::Logger::error_channel).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RetryExhausted {
+    /// The record is discarded.
+    Discard,
+    /// The record is written to stderr.
+    WriteToStderr,
+}
+
+#[cfg(test)]
+mod test {
+    use super::RetryPolicy;
+    use std::io::Write;
+    use std::time::Duration;
+
+    // fails the given number of times, then succeeds
+    struct FlakyWriter {
+        failures: usize,
+        written: Vec<u8>,
+    }
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "EIO"))
+            } else {
+                self.written.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::new(2, Duration::from_millis(1));
+
+        let mut w = FlakyWriter {
+            failures: 2,
+            written: Vec::new(),
+        };
+        policy.write_all(&mut w, b"line\n").unwrap();
+        assert_eq!(w.written, b"line\n");
+
+        let mut w = FlakyWriter {
+            failures: 3,
+            written: Vec::new(),
+        };
+        assert!(policy.write_all(&mut w, b"line\n").is_err());
+        assert!(w.written.is_empty());
+    }
+}
@@ -1,25 +1,42 @@
+#[cfg(feature = "disk_free")]
+use std::cell::Cell;
 use std::cmp::max;
-use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::convert::TryFrom;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::ops::Add;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicI32, Ordering};
+#[cfg(feature = "compress")]
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
-use time::{format_description, macros::offset, OffsetDateTime};
+use time::format_description::well_known::Rfc3339;
+use time::{format_description, Duration, OffsetDateTime, Time, UtcOffset};
 
-use crate::{Age, Cleanup, Criterion, FlexiLoggerError, Naming};
 use crate::deferred_now::now_local_or_utc;
+use crate::retention_policy::CleanupListener;
+use crate::rotation_policy::{SplitAtEveryNewDay, SplitAtEveryNewHour, SplitAtEveryNewMinute};
+use crate::util::{eprint_err, eprint_msg, LockedStderr, ERRCODE};
+#[cfg(feature = "compress")]
+use crate::util::io_err;
 use crate::FileSpec;
-use crate::parameters::SplitAtEveryNewDay;
-use crate::util::{eprint_err, ERRCODE};
+#[cfg(feature = "upload")]
+use crate::Uploader;
+use crate::{Age, Cleanup, CleanupEvent, Criterion, FlexiLoggerError, Naming};
 
-use super::{Config, RotationConfig};
+use super::{
+    cleanup_domain::{self, Membership},
+    stats::Counters,
+    CompressionConfig, Config, LogFileSystem, RotationConfig,
+};
 
 const CURRENT_INFIX: &str = "_rCURRENT";
-
-fn number_infix(idx: u32) -> String {
-    format!("_r{:0>5}", idx)
-}
+const PIN_MARKER: &str = "keep";
+const CREATED_AT_SUFFIX: &str = ".created_at";
+const QUOTA_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+#[cfg(feature = "disk_free")]
+const DISK_FREE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
 
 //  Describes the latest existing numbered log file.
 #[derive(Clone, Copy, Debug)]
@@ -37,6 +54,7 @@ enum IdxState {
 enum NamingState {
     CreatedAt,
     IdxState(IdxState),
+    RoundRobin(u32, u32), // no_of_slots, next_slot
 }
 
 #[derive(Debug)]
@@ -45,6 +63,9 @@ enum RollState {
     // max_size, current_size
     Age(Age),
     AgeOrSize(Age, u64, u64), // age, max_size, current_size
+    AtTime(Time, UtcOffset, AtomicI32), // time_of_day, offset, period_number
+    #[cfg(feature = "disk_free")]
+    DiskFree(u64, PathBuf, Cell<Instant>), // min_free, directory, next_check
 }
 
 enum MessageToCleanupThread {
@@ -63,8 +84,11 @@ struct RotationState {
     naming_state: NamingState,
     roll_state: RollState,
     created_at: OffsetDateTime,
+    // the offset in which the age criterion is evaluated and rotated files are named
+    utc_offset: UtcOffset,
     cleanup: Cleanup,
     o_cleanup_thread_handle: Option<CleanupThreadHandle>,
+    backwards_clock_tolerance: Duration,
 }
 
 impl RotationState {
@@ -72,38 +96,49 @@ impl RotationState {
         current_size > max_size
     }
 
+    fn now(&self) -> OffsetDateTime {
+        now_local_or_utc().to_offset(self.utc_offset)
+    }
+
     fn age_rotation_necessary(&self, age: Age) -> bool {
-        let now = now_local_or_utc();
-        // let now = time::OffsetDateTime::now_local().unwrap();
-        // println!("now is: {}, {:?}", now, now);
+        let now = self.now();
         match age {
-            Age::EveryNewDay(SplitAtEveryNewDay { atomic_day_number, utc_offset }) => {
-                // real
+            Age::EveryNewDay(SplitAtEveryNewDay {
+                atomic_day_number,
+                utc_offset,
+            }) => {
                 let current_date = now.to_offset(utc_offset).date();
-                // //
-                // // // fake data for testing
-                // // // let current_date = time::Date::from_calendar_date(2021 + now.second() as i32 / 10 , time::Month::November, current_date.day()).unwrap();
-                // let current_date = time::Date::from_calendar_date(
-                //     2021 + now.minute() as i32,
-                //     time::Month::November,
-                //     current_date.day(),
-                // ).unwrap();
-                // //
-
-                let number_current = crate::deferred_now::offset_date_time_to_year_month_day_number(current_date);
-
-                // println!("fake current_date is: {}, {:?}", current_date, current_date);
+                let number_current =
+                    crate::deferred_now::offset_date_time_to_year_month_day_number(current_date);
 
                 let d = atomic_day_number.load(Ordering::SeqCst);
                 if d == number_current {
-                    // println!("no day change");
                     return false;
                 } else {
                     atomic_day_number.store(number_current, Ordering::SeqCst);
-                    // println!("day change, old: {}, new: {}", d, number_current);
                     true
                 }
             }
+            Age::EveryNewHour(SplitAtEveryNewHour {
+                atomic_hour_number,
+                utc_offset,
+            }) => {
+                let number_current =
+                    crate::deferred_now::offset_date_time_to_year_month_day_hour_number(
+                        now.to_offset(utc_offset),
+                    );
+                atomic_hour_number.swap(number_current, Ordering::SeqCst) != number_current
+            }
+            Age::EveryNewMinute(SplitAtEveryNewMinute {
+                atomic_minute_number,
+                utc_offset,
+            }) => {
+                let number_current =
+                    crate::deferred_now::offset_date_time_to_year_month_day_hour_minute_number(
+                        now.to_offset(utc_offset),
+                    );
+                atomic_minute_number.swap(number_current, Ordering::SeqCst) != number_current
+            }
             Age::Day => {
                 self.created_at.year() != now.year()
                     || self.created_at.month() != now.month()
@@ -133,19 +168,180 @@ impl RotationState {
         }
     }
 
+    // If the clock was set back behind the creation of the current file:
+    // a small step is ignored (Some(false)), a larger one causes a rotation (Some(true)),
+    // after which the age is measured from the new clock time.
+    fn backwards_clock_step(&self) -> Option<bool> {
+        let step = self.created_at - self.now();
+        if step.is_positive() {
+            Some(step > self.backwards_clock_tolerance)
+        } else {
+            None
+        }
+    }
+
+    // Applies the age check, which also moves the boundary numbers to the current time,
+    // with consideration of backwards clock steps.
+    fn age_check(&self, check: impl FnOnce() -> bool) -> bool {
+        match self.backwards_clock_step() {
+            Some(true) => {
+                check();
+                true
+            }
+            Some(false) => false,
+            None => check(),
+        }
+    }
+
     fn rotation_necessary(&self) -> bool {
         match &self.roll_state {
             RollState::Size(max_size, current_size) => {
                 Self::size_rotation_necessary(*max_size, *current_size)
             }
-            RollState::Age(age) => self.age_rotation_necessary(age.clone()),
+            RollState::Age(age) => self.age_check(|| self.age_rotation_necessary(age.clone())),
             RollState::AgeOrSize(age, max_size, current_size) => {
                 Self::size_rotation_necessary(*max_size, *current_size)
-                    || self.age_rotation_necessary(age.clone())
+                    || self.age_check(|| self.age_rotation_necessary(age.clone()))
+            }
+            RollState::AtTime(time_of_day, offset, period_number) => self.age_check(|| {
+                let current = day_period_number(now_local_or_utc(), *time_of_day, *offset);
+                period_number.swap(current, Ordering::SeqCst) != current
+            }),
+            #[cfg(feature = "disk_free")]
+            RollState::DiskFree(min_free, directory, next_check) => {
+                let now = Instant::now();
+                if now < next_check.get() {
+                    false
+                } else {
+                    next_check.set(now + DISK_FREE_CHECK_INTERVAL);
+                    disk_space_low(directory, *min_free)
+                }
             }
         }
     }
 
+    // Number of bytes that can still be written before the size limit is exceeded.
+    fn bytes_until_rotation(&self) -> Option<u64> {
+        match self.roll_state {
+            RollState::Size(max_size, current_size)
+            | RollState::AgeOrSize(_, max_size, current_size) => {
+                Some(max_size.saturating_sub(current_size))
+            }
+            RollState::Age(_) | RollState::AtTime(_, _, _) => None,
+            #[cfg(feature = "disk_free")]
+            RollState::DiskFree(_, _, _) => None,
+        }
+    }
+
+    // Time until the age limit is reached; unlike age_rotation_necessary(), this has no
+    // side effects.
+    fn time_until_rotation(&self) -> Option<std::time::Duration> {
+        let age = match self.roll_state {
+            RollState::Age(ref age) | RollState::AgeOrSize(ref age, _, _) => age,
+            RollState::Size(_, _) => return None,
+            #[cfg(feature = "disk_free")]
+            RollState::DiskFree(_, _, _) => return None,
+            RollState::AtTime(time_of_day, offset, ref period_number) => {
+                let now = now_local_or_utc().to_offset(offset);
+                if period_number.load(Ordering::SeqCst)
+                    != day_period_number(now, time_of_day, offset)
+                {
+                    return Some(std::time::Duration::from_secs(0));
+                }
+                let time_since_midnight = time_of_day - Time::MIDNIGHT;
+                let period_start =
+                    (now - time_since_midnight).replace_time(Time::MIDNIGHT) + time_since_midnight;
+                return Some(
+                    std::time::Duration::try_from(period_start + Duration::DAY - now)
+                        .unwrap_or_default(),
+                );
+            }
+        };
+        let now = self.now();
+        let next_rotation = match age {
+            Age::EveryNewDay(SplitAtEveryNewDay {
+                atomic_day_number,
+                utc_offset,
+            }) => {
+                let today = now.to_offset(*utc_offset);
+                let number_today =
+                    crate::deferred_now::offset_date_time_to_year_month_day_number(today.date());
+                if atomic_day_number.load(Ordering::SeqCst) != number_today {
+                    return Some(std::time::Duration::from_secs(0));
+                }
+                today.replace_time(Time::MIDNIGHT) + Duration::DAY
+            }
+            Age::EveryNewHour(SplitAtEveryNewHour {
+                atomic_hour_number,
+                utc_offset,
+            }) => {
+                let now = now.to_offset(*utc_offset);
+                let number_now =
+                    crate::deferred_now::offset_date_time_to_year_month_day_hour_number(now);
+                if atomic_hour_number.load(Ordering::SeqCst) != number_now {
+                    return Some(std::time::Duration::from_secs(0));
+                }
+                now.replace_time(Time::from_hms(now.hour(), 0, 0).unwrap(/*ok*/)) + Duration::HOUR
+            }
+            Age::EveryNewMinute(SplitAtEveryNewMinute {
+                atomic_minute_number,
+                utc_offset,
+            }) => {
+                let now = now.to_offset(*utc_offset);
+                let number_now =
+                    crate::deferred_now::offset_date_time_to_year_month_day_hour_minute_number(now);
+                if atomic_minute_number.load(Ordering::SeqCst) != number_now {
+                    return Some(std::time::Duration::from_secs(0));
+                }
+                now.replace_time(Time::from_hms(now.hour(), now.minute(), 0).unwrap(/*ok*/))
+                    + Duration::MINUTE
+            }
+            Age::Day => {
+                let created_at = self.created_at.to_offset(now.offset());
+                created_at.replace_time(Time::MIDNIGHT) + Duration::DAY
+            }
+            Age::Hour => {
+                let created_at = self.created_at.to_offset(now.offset());
+                created_at.replace_time(Time::from_hms(created_at.hour(), 0, 0).unwrap(/*ok*/))
+                    + Duration::HOUR
+            }
+            Age::Minute => {
+                let created_at = self.created_at.to_offset(now.offset());
+                created_at.replace_time(
+                    Time::from_hms(created_at.hour(), created_at.minute(), 0).unwrap(/*ok*/),
+                ) + Duration::MINUTE
+            }
+            Age::Second => {
+                let created_at = self.created_at.to_offset(now.offset());
+                created_at.replace_time(
+                    Time::from_hms(created_at.hour(), created_at.minute(), created_at.second())
+                        .unwrap(/*ok*/),
+                ) + Duration::SECOND
+            }
+        };
+        // a negative duration means that the rotation is overdue
+        Some(std::time::Duration::try_from(next_rotation - now).unwrap_or_default())
+    }
+
+    // Like rotation_necessary(), but without side effects.
+    fn rotation_imminent(&self) -> bool {
+        let size_exceeded = match self.roll_state {
+            RollState::Size(max_size, current_size)
+            | RollState::AgeOrSize(_, max_size, current_size) => {
+                Self::size_rotation_necessary(max_size, current_size)
+            }
+            RollState::Age(_) | RollState::AtTime(_, _, _) => false,
+            #[cfg(feature = "disk_free")]
+            RollState::DiskFree(min_free, ref directory, _) => disk_space_low(directory, min_free),
+        };
+        // time_until_rotation() is only given for age-based rotation
+        let age_exceeded = match self.backwards_clock_step() {
+            Some(rotate) => rotate && self.time_until_rotation().is_some(),
+            None => self.time_until_rotation() == Some(std::time::Duration::from_secs(0)),
+        };
+        size_exceeded || age_exceeded
+    }
+
     fn shutdown(&mut self) {
         // this sets o_cleanup_thread_handle in self.state.o_rotation_state to None:
         let o_cleanup_thread_handle = self.o_cleanup_thread_handle.take();
@@ -159,6 +355,14 @@ impl RotationState {
     }
 }
 
+// The number of the day-long period that contains the given point in time,
+// where the periods start at the given time of day, in the given offset.
+fn day_period_number(now: OffsetDateTime, time_of_day: Time, offset: UtcOffset) -> i32 {
+    (now.to_offset(offset) - (time_of_day - Time::MIDNIGHT))
+        .date()
+        .to_julian_day()
+}
+
 fn try_roll_state_from_criterion(
     criterion: Criterion,
     config: &Config,
@@ -166,9 +370,22 @@ fn try_roll_state_from_criterion(
 ) -> Result<RollState, std::io::Error> {
     Ok(match criterion {
         Criterion::Age(age) => RollState::Age(age),
+        Criterion::AgeAtTime {
+            time_of_day: (hour, minute, second),
+            offset,
+        } => {
+            let time_of_day = Time::from_hms(hour, minute, second).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid time of day")
+            })?;
+            RollState::AtTime(
+                time_of_day,
+                offset,
+                AtomicI32::new(day_period_number(now_local_or_utc(), time_of_day, offset)),
+            )
+        }
         Criterion::Size(size) => {
             let written_bytes = if config.append {
-                std::fs::metadata(p_path)?.len()
+                config.file_system.metadata(p_path)?.len
             } else {
                 0
             };
@@ -176,12 +393,18 @@ fn try_roll_state_from_criterion(
         } // max_size, current_size
         Criterion::AgeOrSize(age, size) => {
             let written_bytes = if config.append {
-                std::fs::metadata(&p_path)?.len()
+                config.file_system.metadata(p_path)?.len
             } else {
                 0
             };
             RollState::AgeOrSize(age, size, written_bytes)
         } // age, max_size, current_size
+        #[cfg(feature = "disk_free")]
+        Criterion::DiskFree(min_free) => RollState::DiskFree(
+            min_free,
+            config.file_spec.get_directory(),
+            Cell::new(Instant::now()),
+        ),
     })
 }
 
@@ -195,7 +418,7 @@ impl std::fmt::Debug for Inner {
         match self {
             Self::Initial(o_rot, b) => f.write_fmt(format_args!("Initial({:?}, {}) ", o_rot, b)),
             Self::Active(o_rot, _) => {
-                f.write_fmt(format_args!("Active({:?}, <some-writer>) ", o_rot, ))
+                f.write_fmt(format_args!("Active({:?}, <some-writer>) ", o_rot,))
             }
         }
     }
@@ -205,51 +428,146 @@ impl std::fmt::Debug for Inner {
 #[derive(Debug)]
 pub(crate) struct State {
     config: Config,
+    // the original rotation settings, are needed again after a relocation
+    o_rotation_config: Option<RotationConfig>,
+    cleanup_in_background_thread: bool,
     inner: Inner,
+    counters: Arc<Counters>,
+    cleanup_domain: Membership,
+    o_quota: Option<Quota>,
+    // is Some while only the first part of a record was written (see `write_chunk`),
+    // with `true` if the remainder is to be discarded since writing is suspended
+    o_partial_record: Option<bool>,
+}
+
+// Tracks the disk usage of the log files, see FileLogWriterBuilder::max_total_bytes_on_disk.
+#[derive(Debug)]
+struct Quota {
+    max_bytes: u64,
+    // the sum of the sizes of the current and the rotated files, as of the last check,
+    // plus what was written since then
+    total_bytes: u64,
+    next_check: Instant,
+    suspended: bool,
+    // the path of the log file without rotation, whose name can contain a timestamp
+    o_log_path: Option<PathBuf>,
 }
 
 impl State {
+    // A predecessor is a state that is going to be replaced by the new one.
     pub fn try_new(
         config: Config,
         o_rotation_config: Option<RotationConfig>,
         cleanup_in_background_thread: bool,
-    ) -> Self {
-        Self {
+        o_predecessor: Option<&State>,
+    ) -> Result<Self, FlexiLoggerError> {
+        let cleanup_domain = Membership::join(
+            &config.file_spec,
+            does_cleanup(o_rotation_config.as_ref()),
+            o_predecessor.map(|predecessor| &predecessor.cleanup_domain),
+        )?;
+        let o_quota = config.o_max_total_bytes.map(|max_bytes| Quota {
+            max_bytes,
+            total_bytes: 0,
+            next_check: Instant::now(),
+            suspended: false,
+            o_log_path: None,
+        });
+        Ok(Self {
             config,
+            o_rotation_config: o_rotation_config.clone(),
+            cleanup_in_background_thread,
             inner: Inner::Initial(o_rotation_config, cleanup_in_background_thread),
-        }
+            counters: Arc::new(Counters::default()),
+            cleanup_domain,
+            o_quota,
+            o_partial_record: None,
+        })
     }
 
     fn initialize(&mut self) -> Result<(), std::io::Error> {
         if let Inner::Initial(o_rotation_config, cleanup_in_background_thread) = &self.inner {
             match o_rotation_config {
                 None => {
-                    let (log_file, _created_at, _p_path) = open_log_file(&self.config, false)?;
+                    let (log_file, _created_at, p_path) = open_log_file(&self.config, false)?;
                     self.inner = Inner::Active(None, log_file);
+                    if let Some(ref mut quota) = self.o_quota {
+                        quota.o_log_path = Some(p_path);
+                        quota.next_check = Instant::now();
+                    }
                 }
                 Some(rotate_config) => {
+                    let utc_offset = rotation_offset(rotate_config.naming);
+                    let current_path = self.config.file_spec.as_pathbuf(Some(CURRENT_INFIX));
+                    // only needed for the manifest
+                    let mut o_rotated = None;
                     // first rotate, then open the log file
                     let naming_state = match rotate_config.naming {
-                        Naming::Timestamps(utf_offset) => {
+                        Naming::Timestamps(_) => {
                             if !self.config.append {
-                                rotate_output_file_to_date(
-                                    &get_creation_date(
-                                        &self.config.file_spec.as_pathbuf(Some(CURRENT_INFIX)),
-                                    ).to_offset(utf_offset),
-                                    &self.config,
-                                )?;
+                                let created_at =
+                                    get_creation_date(&*self.config.file_system, &current_path)
+                                        .to_offset(utc_offset);
+                                let rotated_path =
+                                    rotate_output_file_to_date(&created_at, &self.config)?;
+                                o_rotated = Some((rotated_path, created_at));
                             }
                             NamingState::CreatedAt
                         }
                         Naming::Numbers => {
-                            let mut rotation_state = get_highest_rotate_idx(&self.config.file_spec);
+                            let mut rotation_state = get_highest_rotate_idx(
+                                &*self.config.file_system,
+                                &self.config.file_spec,
+                            );
                             if !self.config.append {
-                                rotation_state =
-                                    rotate_output_file_to_idx(rotation_state, &self.config)?;
+                                let created_at =
+                                    get_creation_date(&*self.config.file_system, &current_path);
+                                if self.config.file_system.exists(&current_path) {
+                                    let idx =
+                                        next_rotate_idx(rotation_state, created_at, &self.config);
+                                    let rotated_path = self.config.file_spec.as_rotated_pathbuf(
+                                        &self.config.file_spec.number_infix(idx),
+                                        created_at,
+                                    );
+                                    o_rotated = Some((rotated_path, created_at));
+                                }
+                                rotation_state = rotate_output_file_to_idx(
+                                    rotation_state,
+                                    created_at,
+                                    &self.config,
+                                )?;
                             }
                             NamingState::IdxState(rotation_state)
                         }
+                        Naming::NumbersRoundRobin(no_of_slots) => {
+                            let no_of_slots = max(no_of_slots, 1);
+                            let mut next_slot = get_next_round_robin_slot(
+                                &*self.config.file_system,
+                                &self.config.file_spec,
+                                no_of_slots,
+                            );
+                            if !self.config.append {
+                                if self.config.file_system.exists(&current_path) {
+                                    let [path, _] =
+                                        round_robin_slot_files(&self.config.file_spec, next_slot);
+                                    let created_at =
+                                        get_creation_date(&*self.config.file_system, &current_path);
+                                    o_rotated = Some((path, created_at));
+                                }
+                                next_slot = rotate_output_file_to_slot(
+                                    next_slot,
+                                    no_of_slots,
+                                    &self.config,
+                                )?;
+                            }
+                            NamingState::RoundRobin(no_of_slots, next_slot)
+                        }
                     };
+                    if let (Some(manifest), Some((rotated_path, created_at))) =
+                        (&self.config.o_manifest, o_rotated)
+                    {
+                        manifest.add_rotated(&rotated_path, created_at);
+                    }
                     let (log_file, created_at, p_path) = open_log_file(&self.config, true)?;
 
                     let roll_state = try_roll_state_from_criterion(
@@ -262,23 +580,38 @@ impl State {
                         remove_or_compress_too_old_logfiles(
                             &None,
                             &rotate_config.cleanup,
+                            &self.config.file_system,
                             &self.config.file_spec,
+                            self.cleanup_domain.scan_lock(),
+                            self.config.o_cleanup_listener.as_ref(),
+                            self.config.compression,
                         )?;
                         if *cleanup_in_background_thread {
-                            let cleanup = rotate_config.cleanup;
+                            let cleanup = rotate_config.cleanup.clone();
+                            let file_system = Arc::clone(&self.config.file_system);
                             let filename_config = self.config.file_spec.clone();
+                            let scan_lock = Arc::clone(self.cleanup_domain.scan_lock());
+                            let o_cleanup_listener = self.config.o_cleanup_listener.clone();
+                            let o_cleanup_interval = self.config.o_cleanup_interval;
+                            let compression = self.config.compression;
                             let (sender, receiver) = std::sync::mpsc::channel();
                             let builder = std::thread::Builder::new()
                                 .name("flexi_logger-cleanup".to_string());
                             #[cfg(not(feature = "dont_minimize_extra_stacks"))]
-                                let builder = builder.stack_size(512 * 1024);
+                            let builder = builder.stack_size(512 * 1024);
                             let join_handle = builder.spawn(move || {
-                                while let Ok(MessageToCleanupThread::Act) = receiver.recv() {
+                                while let Some(MessageToCleanupThread::Act) =
+                                    next_message_to_cleanup_thread(&receiver, o_cleanup_interval)
+                                {
                                     remove_or_compress_too_old_logfiles_impl(
                                         &cleanup,
+                                        &file_system,
                                         &filename_config,
+                                        &scan_lock,
+                                        o_cleanup_listener.as_ref(),
+                                        compression,
                                     )
-                                        .ok();
+                                    .ok();
                                 }
                             })?;
                             o_cleanup_thread_handle = Some(CleanupThreadHandle {
@@ -291,9 +624,14 @@ impl State {
                         Some(RotationState {
                             naming_state,
                             roll_state,
-                            created_at: created_at.to_offset(offset!(+8)),
-                            cleanup: rotate_config.cleanup,
+                            created_at: created_at.to_offset(utc_offset),
+                            utc_offset,
+                            cleanup: rotate_config.cleanup.clone(),
                             o_cleanup_thread_handle,
+                            backwards_clock_tolerance: Duration::try_from(
+                                self.config.backwards_clock_tolerance,
+                            )
+                            .unwrap_or(Duration::MAX),
                         }),
                         log_file,
                     );
@@ -307,9 +645,22 @@ impl State {
         &self.config
     }
 
+    pub(super) fn counters(&self) -> &Arc<Counters> {
+        &self.counters
+    }
+
+    // Lets this state continue the counting of a state that it replaces.
+    pub(super) fn take_over_counters(&mut self, counters: Arc<Counters>) {
+        self.counters = counters;
+    }
+
     pub fn flush(&mut self) -> std::io::Result<()> {
         if let Inner::Active(_, ref mut file) = self.inner {
-            file.flush()
+            let result = file.flush();
+            if result.is_err() {
+                self.counters.io_error();
+            }
+            result
         } else {
             Ok(())
         }
@@ -322,58 +673,250 @@ impl State {
     fn mount_next_linewriter_if_necessary(&mut self) -> Result<(), FlexiLoggerError> {
         if let Inner::Active(Some(ref mut rotation_state), ref mut file) = self.inner {
             if rotation_state.rotation_necessary() {
-                match rotation_state.naming_state {
+                if self.config.rotation_breadcrumbs {
+                    let successor = successor_rotated_path(rotation_state, &self.config);
+                    write_breadcrumb(
+                        &mut **file,
+                        ">>> continued in",
+                        &successor,
+                        self.config.line_ending,
+                    );
+                }
+                #[cfg(feature = "compress")]
+                {
+                    if self.config.compress_while_writing {
+                        // finalizes the gzip stream
+                        *file = Box::new(std::io::sink());
+                    }
+                }
+                let rotated_path = match rotation_state.naming_state {
                     NamingState::CreatedAt => {
-                        rotate_output_file_to_date(&rotation_state.created_at, &self.config)?;
+                        rotate_output_file_to_date(&rotation_state.created_at, &self.config)?
                     }
                     NamingState::IdxState(ref mut idx_state) => {
-                        *idx_state = rotate_output_file_to_idx(*idx_state, &self.config)?;
+                        *idx_state = rotate_output_file_to_idx(
+                            *idx_state,
+                            rotation_state.created_at,
+                            &self.config,
+                        )?;
+                        let file_spec = &self.config.file_spec;
+                        let idx = match *idx_state {
+                            IdxState::Start => file_spec.numbering.start,
+                            IdxState::Idx(idx) => idx,
+                        };
+                        file_spec.as_rotated_pathbuf(
+                            &file_spec.number_infix(idx),
+                            rotation_state.created_at,
+                        )
                     }
+                    NamingState::RoundRobin(no_of_slots, ref mut next_slot) => {
+                        let [path, _] = round_robin_slot_files(&self.config.file_spec, *next_slot);
+                        *next_slot =
+                            rotate_output_file_to_slot(*next_slot, no_of_slots, &self.config)?;
+                        path
+                    }
+                };
+                if let Some(ref manifest) = self.config.o_manifest {
+                    manifest.add_rotated(&rotated_path, rotation_state.created_at);
                 }
 
                 let (line_writer, created_at, _) = open_log_file(&self.config, true)?;
                 *file = line_writer;
-                rotation_state.created_at = created_at.to_offset(offset!(+8));
+                self.counters.rotated();
+                rotation_state.created_at = created_at.to_offset(rotation_state.utc_offset);
+                let breadcrumb_len = if self.config.rotation_breadcrumbs {
+                    write_breadcrumb(
+                        &mut **file,
+                        "<<< continuation of",
+                        &rotated_path,
+                        self.config.line_ending,
+                    )
+                } else {
+                    0
+                };
                 if let RollState::Size(_, ref mut current_size)
                 | RollState::AgeOrSize(_, _, ref mut current_size) = rotation_state.roll_state
                 {
-                    *current_size = 0;
+                    *current_size = breadcrumb_len as u64;
                 }
 
                 remove_or_compress_too_old_logfiles(
                     &rotation_state.o_cleanup_thread_handle,
                     &rotation_state.cleanup,
+                    &self.config.file_system,
                     &self.config.file_spec,
+                    self.cleanup_domain.scan_lock(),
+                    self.config.o_cleanup_listener.as_ref(),
+                    self.config.compression,
                 )?;
+                #[cfg(feature = "disk_free")]
+                {
+                    if let RollState::DiskFree(min_free, ref directory, _) =
+                        rotation_state.roll_state
+                    {
+                        remove_logfiles_until_disk_space_suffices(
+                            min_free,
+                            directory,
+                            &*self.config.file_system,
+                            &self.config.file_spec,
+                            self.cleanup_domain.scan_lock(),
+                            self.config.o_cleanup_listener.as_ref(),
+                        )?;
+                    }
+                }
             }
         }
 
         Ok(())
     }
 
-    pub fn write_buffer(&mut self, buf: &[u8]) -> std::io::Result<()> {
-        if let Inner::Initial(_, _) = self.inner {
-            self.initialize()?;
+    // Does a rotation that is due, also if nothing is written,
+    // unless a record was only partially written.
+    pub(super) fn rotate_if_necessary(&mut self) -> Result<(), FlexiLoggerError> {
+        if self.o_partial_record.is_some() {
+            return Ok(());
+        }
+        let result = self.mount_next_linewriter_if_necessary();
+        if result.is_err() {
+            self.counters.io_error();
+        }
+        result
+    }
+
+    // Writes one or more complete records; a rotation can only happen before them.
+    pub fn write_record(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.write_part(buf, true)
+    }
+
+    // Writes an arbitrary part of the output, e.g. from `ArcFileLogWriter`;
+    // a part that does not end with a line break is continued by the next part,
+    // and a rotation is deferred until the record is complete.
+    pub fn write_chunk(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.write_part(buf, buf.ends_with(b"\n"))
+    }
+
+    fn write_part(&mut self, buf: &[u8], completes_record: bool) -> std::io::Result<()> {
+        let suspended = if let Some(suspended) = self.o_partial_record.take() {
+            // the continuation of a record goes to the same file as its beginning
+            if let (false, Some(ref mut quota)) = (suspended, &mut self.o_quota) {
+                quota.total_bytes += buf.len() as u64;
+            }
+            suspended
+        } else {
+            if let Inner::Initial(_, _) = self.inner {
+                if let Err(e) = self.initialize() {
+                    self.counters.io_error();
+                    return Err(e);
+                }
+            }
+            // rotate if necessary
+            self.mount_next_linewriter_if_necessary()
+                .unwrap_or_else(|e| {
+                    self.counters.io_error();
+                    eprint_err(ERRCODE::LogFile, "can't open file", &e);
+                });
+
+            let suspended = self.quota_exceeded(buf.len());
+            if suspended {
+                self.counters.record_suspended();
+            }
+            suspended
+        };
+        if !completes_record {
+            self.o_partial_record = Some(suspended);
+        }
+
+        if suspended {
+            if self.config.duplicate_to_stderr_while_suspended {
+                LockedStderr.write_all(buf).ok();
+            }
+            return Ok(());
         }
-        // rotate if necessary
-        self.mount_next_linewriter_if_necessary()
-            .unwrap_or_else(|e| {
-                eprint_err(ERRCODE::LogFile, "can't open file", &e);
-            });
 
         if let Inner::Active(ref mut o_rotation_state, ref mut log_file) = self.inner {
-            log_file.write_all(buf)?;
+            let result = match self.config.o_retry_policy {
+                Some(ref retry_policy) => retry_policy.write_all(log_file, buf),
+                None => log_file.write_all(buf),
+            };
+            if let Err(e) = result {
+                self.counters.io_error();
+                return Err(e);
+            }
+            self.counters.record_written(buf.len());
             if let Some(ref mut rotation_state) = o_rotation_state {
                 if let RollState::Size(_, ref mut current_size)
                 | RollState::AgeOrSize(_, _, ref mut current_size) = rotation_state.roll_state
                 {
                     *current_size += buf.len() as u64;
+                    #[cfg(feature = "compress")]
+                    {
+                        if self.config.compress_while_writing {
+                            *current_size = self.config.compressed_size.load(Ordering::Relaxed);
+                        }
+                    }
                 }
             };
         }
         Ok(())
     }
 
+    // Checks if writing the given number of bytes would exceed the quota for the log files;
+    // the disk usage is checked at most once per QUOTA_CHECK_INTERVAL, unless the quota
+    // seems to be reached. Writing is suspended as long as the quota is exceeded.
+    fn quota_exceeded(&mut self, len: usize) -> bool {
+        let current_filename = self.current_filename();
+        let file_system = &*self.config.file_system;
+        let file_spec = &self.config.file_spec;
+        match self.o_quota {
+            None => false,
+            Some(ref mut quota) => {
+                let len = len as u64;
+                let now = Instant::now();
+                if now >= quota.next_check
+                    || (!quota.suspended && quota.total_bytes + len > quota.max_bytes)
+                {
+                    let current_path = quota.o_log_path.as_ref().unwrap_or(&current_filename);
+                    quota.total_bytes = total_bytes_on_disk(file_system, current_path, file_spec);
+                    quota.next_check = now + QUOTA_CHECK_INTERVAL;
+                }
+                let exceeded = quota.total_bytes + len > quota.max_bytes;
+                if exceeded && !quota.suspended {
+                    eprint_msg(
+                        ERRCODE::LogFile,
+                        "writing to the log file is suspended until the log files \
+                         use less than the configured maximum of disk space",
+                    );
+                }
+                quota.suspended = exceeded;
+                if !exceeded {
+                    quota.total_bytes += len;
+                }
+                exceeded
+            }
+        }
+    }
+
+    pub(super) fn rotation_imminent(&self) -> bool {
+        match self.inner {
+            Inner::Active(Some(ref rotation_state), _) => rotation_state.rotation_imminent(),
+            _ => false,
+        }
+    }
+
+    pub(super) fn bytes_until_rotation(&self) -> Option<u64> {
+        match self.inner {
+            Inner::Active(Some(ref rotation_state), _) => rotation_state.bytes_until_rotation(),
+            _ => None,
+        }
+    }
+
+    pub(super) fn time_until_rotation(&self) -> Option<std::time::Duration> {
+        match self.inner {
+            Inner::Active(Some(ref rotation_state), _) => rotation_state.time_until_rotation(),
+            _ => None,
+        }
+    }
+
     pub fn current_filename(&self) -> PathBuf {
         let o_infix = match &self.inner {
             Inner::Initial(o_rotation_config, _) => {
@@ -404,13 +947,17 @@ impl State {
                     .as_ref()
                     .map(|_| super::state::CURRENT_INFIX),
             );
-            let f = File::open(path.clone()).unwrap_or_else(|e| {
-                panic!(
-                    "validate_logs: can't open file {} due to {:?}",
-                    path.display(),
-                    e
-                )
-            });
+            let f = self
+                .config
+                .file_system
+                .open_for_reading(&path)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "validate_logs: can't open file {} due to {:?}",
+                        path.display(),
+                        e
+                    )
+                });
             let mut reader = BufReader::new(f);
             let mut buf = String::new();
             for tuple in expected {
@@ -450,13 +997,229 @@ impl State {
     }
 
     pub fn shutdown(&mut self) {
+        self.close();
+        #[cfg(feature = "compress")]
+        {
+            if self.config.compress_on_shutdown {
+                if let Err(e) = self.rotate_and_compress_current_file() {
+                    eprint_err(ERRCODE::LogFile, "compressing the log file failed", &e);
+                }
+            }
+        }
+    }
+
+    // Stops the cleanup thread and flushes the output.
+    fn close(&mut self) {
         if let Inner::Active(ref mut o_rotation_state, ref mut writer) = self.inner {
             if let Some(ref mut rotation_state) = o_rotation_state {
                 rotation_state.shutdown();
             }
             writer.flush().ok();
+            #[cfg(feature = "compress")]
+            {
+                if self.config.compress_while_writing {
+                    // finalizes the gzip stream; later writes are discarded
+                    *writer = Box::new(std::io::sink());
+                }
+            }
+        }
+    }
+
+    // Closes the current file, rotates it, and compresses the rotated file;
+    // the next write would start afresh.
+    #[cfg(feature = "compress")]
+    fn rotate_and_compress_current_file(&mut self) -> Result<(), std::io::Error> {
+        if let Inner::Active(Some(ref mut rotation_state), ref mut file) = self.inner {
+            let current_path = self.config.file_spec.as_pathbuf(Some(CURRENT_INFIX));
+            if !self.config.file_system.exists(&current_path) {
+                return Ok(());
+            }
+            // closes the current file
+            *file = Box::new(std::io::sink());
+            let rotated_path = match rotation_state.naming_state {
+                NamingState::CreatedAt => {
+                    rotate_output_file_to_date(&rotation_state.created_at, &self.config)?
+                }
+                NamingState::IdxState(ref mut idx_state) => {
+                    *idx_state = rotate_output_file_to_idx(
+                        *idx_state,
+                        rotation_state.created_at,
+                        &self.config,
+                    )?;
+                    match *idx_state {
+                        IdxState::Idx(idx) => self.config.file_spec.as_rotated_pathbuf(
+                            &self.config.file_spec.number_infix(idx),
+                            rotation_state.created_at,
+                        ),
+                        IdxState::Start => return Ok(()),
+                    }
+                }
+                NamingState::RoundRobin(no_of_slots, ref mut next_slot) => {
+                    let slot = *next_slot;
+                    *next_slot = rotate_output_file_to_slot(slot, no_of_slots, &self.config)?;
+                    let [path, _] = round_robin_slot_files(&self.config.file_spec, slot);
+                    path
+                }
+            };
+            if let Some(ref manifest) = self.config.o_manifest {
+                manifest.add_rotated(&rotated_path, rotation_state.created_at);
+            }
+            self.counters.rotated();
+            self.inner = Inner::Initial(
+                self.o_rotation_config.clone(),
+                self.cleanup_in_background_thread,
+            );
+            if !self.config.compress_while_writing {
+                compress_file(
+                    &*self.config.file_system,
+                    rotated_path,
+                    self.config.compression.level,
+                    self.config.o_cleanup_listener.as_ref(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // Closes the current output and lets the next write start afresh in the given directory,
+    // with a new rotation state.
+    pub(super) fn relocate(&mut self, directory: &Path) -> Result<(), FlexiLoggerError> {
+        self.config.file_system.create_dir_all(directory)?;
+        if !self.config.file_system.metadata(directory)?.is_dir {
+            return Err(FlexiLoggerError::OutputBadDirectory);
+        }
+        let file_spec = self.config.file_spec.clone().directory(directory);
+        self.cleanup_domain = Membership::join(
+            &file_spec,
+            does_cleanup(self.o_rotation_config.as_ref()),
+            Some(&self.cleanup_domain),
+        )?;
+        self.close();
+        self.config.file_spec = file_spec;
+        self.inner = Inner::Initial(
+            self.o_rotation_config.clone(),
+            self.cleanup_in_background_thread,
+        );
+        Ok(())
+    }
+
+    // Closes the current output and continues with the given rotation configuration,
+    // which takes effect as if the writer was started anew with it.
+    pub(super) fn set_rotation(
+        &mut self,
+        rotation_config: RotationConfig,
+    ) -> Result<(), FlexiLoggerError> {
+        self.cleanup_domain = Membership::join(
+            &self.config.file_spec,
+            rotation_config.cleanup.do_cleanup(),
+            Some(&self.cleanup_domain),
+        )?;
+        self.close();
+        self.o_rotation_config = Some(rotation_config.clone());
+        self.inner = Inner::Initial(Some(rotation_config), self.cleanup_in_background_thread);
+        self.initialize()?;
+        Ok(())
+    }
+
+    // The current log file, if it exists, followed by the rotated and the compressed log files.
+    pub(super) fn existing_log_files(&self) -> Vec<PathBuf> {
+        let current = self.current_filename();
+        let mut log_files = Vec::new();
+        if self.config.file_system.exists(&current) {
+            log_files.push(current);
         }
+        log_files.extend(list_of_log_and_compressed_files(
+            &*self.config.file_system,
+            &self.config.file_spec,
+        ));
+        log_files
     }
+
+    // The last n lines of the log files, oldest first; the files are read from the newest
+    // (the current file) to the older ones, until enough lines are found.
+    pub(super) fn read_back(&self, n: usize) -> Result<Vec<String>, FlexiLoggerError> {
+        let file_system = &*self.config.file_system;
+        let mut rotated: Vec<PathBuf> =
+            list_of_log_and_compressed_files(file_system, &self.config.file_spec).collect();
+        // list_of_log_and_compressed_files sorts per kind of file, here the order must be global
+        rotated.sort_by_key(|file| {
+            (
+                self.config.file_spec.rotate_idx(file),
+                file.file_name().map(ToOwned::to_owned),
+            )
+        });
+
+        let mut lines = Vec::with_capacity(n);
+        for file in std::iter::once(self.current_filename()).chain(rotated.into_iter().rev()) {
+            if lines.len() >= n {
+                break;
+            }
+            let content = match read_log_file(file_system, &file) {
+                Ok(content) => content,
+                // e.g. removed by the cleanup in the meantime
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            lines.extend(
+                String::from_utf8_lossy(&content)
+                    .lines()
+                    .rev()
+                    .take(n - lines.len())
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<String>>(),
+            );
+        }
+        lines.reverse();
+        Ok(lines)
+    }
+
+    // Renames the given rotated file such that it carries the pin marker,
+    // and thus is ignored by the cleanup.
+    pub(super) fn pin(&self, path: &Path) -> Result<PathBuf, FlexiLoggerError> {
+        if is_pinned(&self.config.file_spec, path) {
+            return Ok(path.to_path_buf());
+        }
+        if !list_of_log_and_compressed_files(&*self.config.file_system, &self.config.file_spec)
+            .any(|file| file == path)
+        {
+            return Err(FlexiLoggerError::OutputNotRotated);
+        }
+        let pinned_path =
+            pinned_path(&self.config.file_spec, path).ok_or(FlexiLoggerError::OutputNotRotated)?;
+        self.config.file_system.rename(path, &pinned_path)?;
+        Ok(pinned_path)
+    }
+}
+
+fn does_cleanup(o_rotation_config: Option<&RotationConfig>) -> bool {
+    matches!(o_rotation_config, Some(rotation_config) if rotation_config.cleanup.do_cleanup())
+}
+
+// Inserts the pin marker directly after the rotation infix,
+// e.g. "foo_r00003.log.gz" becomes "foo_r00003.keep.log.gz".
+fn pinned_path(file_spec: &FileSpec, path: &Path) -> Option<PathBuf> {
+    let filename = path.file_name()?.to_str()?;
+    let infix_start = file_spec.infix_start(filename)?;
+    let insert_at = filename[infix_start..]
+        .find('.')
+        .map_or(filename.len(), |idx| infix_start + idx);
+    let mut pinned_filename = String::with_capacity(filename.len() + PIN_MARKER.len() + 1);
+    pinned_filename.push_str(&filename[..insert_at]);
+    pinned_filename.push('.');
+    pinned_filename.push_str(PIN_MARKER);
+    pinned_filename.push_str(&filename[insert_at..]);
+    Some(path.with_file_name(pinned_filename))
+}
+
+fn is_pinned(file_spec: &FileSpec, path: &Path) -> bool {
+    path.file_name()
+        .map(|filename| filename.to_string_lossy())
+        .and_then(|filename| {
+            file_spec
+                .infix_start(&filename)
+                .map(|idx| filename[idx..].split('.').any(|part| part == PIN_MARKER))
+        })
+        .unwrap_or(false)
 }
 
 #[allow(clippy::type_complexity)]
@@ -477,43 +1240,104 @@ fn open_log_file(
         self::platform::create_symlink_if_possible(link, &p_path);
     }
 
-    let log_file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(config.append)
-        .truncate(!config.append)
-        .open(&p_path)?;
+    let file_system = &*config.file_system;
+    let continued = config.append && file_system.exists(&p_path);
+    let log_file = file_system.open(&p_path, config.append)?;
+    #[cfg(feature = "preallocate")]
+    {
+        if let Some(bytes) = config.o_preallocate {
+            if !continued {
+                self::platform::preallocate(&p_path, bytes);
+            }
+        }
+    }
+    // only a file that is appended to can be continued after a restart,
+    // so only then the creation date needs to be persisted
+    let created_at = if with_rotation && config.append {
+        if continued {
+            read_created_at(file_system, &p_path).unwrap_or_else(|| {
+                // the file was written by an older version, or the sidecar file was lost
+                let created_at = get_creation_date(file_system, &p_path);
+                write_created_at(file_system, &p_path, created_at);
+                created_at
+            })
+        } else {
+            let created_at = now_local_or_utc();
+            write_created_at(file_system, &p_path, created_at);
+            created_at
+        }
+    } else {
+        if with_rotation {
+            // a sidecar file from a former run with append would be stale now
+            file_system.remove_file(&created_at_path(&p_path)).ok();
+        }
+        get_creation_date(file_system, &p_path)
+    };
+
+    #[cfg(feature = "compress")]
+    {
+        if config.compress_while_writing {
+            let compressed_size = Arc::clone(&config.compressed_size);
+            compressed_size.store(file_system.metadata(&p_path)?.len, Ordering::Relaxed);
+            let gz_encoder = flate2::write::GzEncoder::new(
+                CountingWriter {
+                    inner: log_file,
+                    count: compressed_size,
+                },
+                flate2::Compression::new(config.compression.level),
+            );
+            let w: Box<dyn Write + Send> = match config.write_mode.buffersize() {
+                Some(capacity) => Box::new(BufWriter::with_capacity(capacity, gz_encoder)),
+                None => Box::new(gz_encoder),
+            };
+            return Ok((w, created_at, p_path));
+        }
+    }
 
     #[allow(clippy::option_if_let_else)]
-        let w: Box<dyn Write + Send> = if let Some(capacity) = config.write_mode.buffersize() {
+    let w: Box<dyn Write + Send> = if let Some(capacity) = config.write_mode.buffersize() {
         Box::new(BufWriter::with_capacity(capacity, log_file))
     } else {
         Box::new(log_file)
     };
-    Ok((w, get_creation_date(&p_path), p_path))
+    Ok((w, created_at, p_path))
 }
 
-fn get_highest_rotate_idx(file_spec: &FileSpec) -> IdxState {
+// Gaps in the sequence of indexes are not filled, the next index is always
+// above the highest existing one.
+fn get_highest_rotate_idx(file_system: &dyn LogFileSystem, file_spec: &FileSpec) -> IdxState {
     let mut highest_idx = IdxState::Start;
-    for file in list_of_log_and_compressed_files(file_spec) {
-        let filename = file.file_stem().unwrap(/*ok*/).to_string_lossy();
-        let mut it = filename.rsplit("_r");
-        match it.next() {
-            Some(next) => {
-                let idx: u32 = next.parse().unwrap_or(0);
-                highest_idx = match highest_idx {
-                    IdxState::Start => IdxState::Idx(idx),
-                    IdxState::Idx(prev) => IdxState::Idx(max(prev, idx)),
-                };
-            }
-            None => continue, // ignore unexpected files
-        }
+    for idx in list_of_log_and_compressed_files(file_system, file_spec)
+        .filter_map(|file| file_spec.rotate_idx(&file))
+    {
+        highest_idx = match highest_idx {
+            IdxState::Start => IdxState::Idx(idx),
+            IdxState::Idx(prev) => IdxState::Idx(max(prev, idx)),
+        };
     }
     highest_idx
 }
 
+// A numbered file might exist already although its index is not above the highest index
+// that was found, e.g. if another process with the same file spec has rotated meanwhile.
+fn rotate_idx_taken(config: &Config, idx: u32, created_at: OffsetDateTime) -> bool {
+    let path = config
+        .file_spec
+        .as_rotated_pathbuf(&config.file_spec.number_infix(idx), created_at);
+    let mut compressed = path.clone().into_os_string();
+    compressed.push(".gz");
+    let file_system = &*config.file_system;
+    file_system.exists(&path)
+        || file_system.exists(Path::new(&compressed))
+        || matches!(
+            pinned_path(&config.file_spec, &path),
+            Some(pinned) if file_system.exists(&pinned)
+        )
+}
+
 #[allow(clippy::type_complexity)]
 fn list_of_log_and_compressed_files(
+    file_system: &dyn LogFileSystem,
     file_spec: &FileSpec,
 ) -> std::iter::Chain<
     std::iter::Chain<
@@ -522,33 +1346,146 @@ fn list_of_log_and_compressed_files(
     >,
     std::vec::IntoIter<std::path::PathBuf>,
 > {
-    let o_infix = Some("_r[0-9]*");
+    let patterns = |o_suffix| -> Vec<String> {
+        file_spec
+            .rotated_infix_patterns()
+            .iter()
+            .flat_map(|infix| file_spec.as_rotated_glob_patterns(Some(infix), o_suffix))
+            .collect()
+    };
+    let log_patterns = patterns(None);
+    let zip_patterns = patterns(Some("zip"));
+    let gz_patterns = patterns(Some("gz"));
+
+    // with compress_while_writing, the log files are found with the gz_patterns
+    let log_files = if matches!(Path::new(&log_patterns[0]).extension(), Some(ext) if ext == "gz") {
+        Vec::new().into_iter()
+    } else {
+        list_of_files(file_system, file_spec, &log_patterns)
+    };
 
-    let log_pattern = file_spec.as_glob_pattern(o_infix, None);
-    let zip_pattern = file_spec.as_glob_pattern(o_infix, Some("zip"));
-    let gz_pattern = file_spec.as_glob_pattern(o_infix, Some("gz"));
+    log_files
+        .chain(list_of_files(file_system, file_spec, &gz_patterns))
+        .chain(list_of_files(file_system, file_spec, &zip_patterns))
+}
 
-    list_of_files(&log_pattern)
-        .chain(list_of_files(&gz_pattern))
-        .chain(list_of_files(&zip_pattern))
+// Returns the uncompressed content of a log file.
+//
+// A gzip stream that is still being written (with compress_while_writing) is
+// incomplete, its content is returned as far as it can be decoded.
+// Zip files (as written by old versions) are ignored.
+fn read_log_file(file_system: &dyn LogFileSystem, path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut content = Vec::new();
+    match path.extension().and_then(std::ffi::OsStr::to_str) {
+        Some("zip") => {}
+        #[cfg(feature = "compress")]
+        Some("gz") => {
+            let mut decoder =
+                flate2::read::MultiGzDecoder::new(file_system.open_for_reading(path)?);
+            if let Err(e) = decoder.read_to_end(&mut content) {
+                if e.kind() != std::io::ErrorKind::UnexpectedEof {
+                    return Err(e);
+                }
+            }
+        }
+        #[cfg(not(feature = "compress"))]
+        Some("gz") => {}
+        _ => {
+            file_system
+                .open_for_reading(path)?
+                .read_to_end(&mut content)?;
+        }
+    }
+    Ok(content)
 }
 
-fn list_of_files(pattern: &str) -> std::vec::IntoIter<PathBuf> {
-    let mut log_files: Vec<PathBuf> = glob::glob(pattern)
-        .unwrap(/* failure should be impossible */)
-        .filter_map(Result::ok)
+// Numbered files are sorted by their index, other files by their names (not by their paths,
+// so that the order is also correct across daily subdirectories); the newest file comes first.
+fn list_of_files(
+    file_system: &dyn LogFileSystem,
+    file_spec: &FileSpec,
+    patterns: &[String],
+) -> std::vec::IntoIter<PathBuf> {
+    let mut log_files: Vec<PathBuf> = patterns
+        .iter()
+        .flat_map(|pattern| file_system.glob(pattern))
         .collect();
+    log_files.sort_by_key(|file| {
+        (
+            file_spec.rotate_idx(file),
+            file.file_name().map(ToOwned::to_owned),
+        )
+    });
+    log_files.dedup();
     log_files.reverse();
     log_files.into_iter()
 }
 
+// The sum of the sizes of the current and the rotated log files.
+fn total_bytes_on_disk(
+    file_system: &dyn LogFileSystem,
+    current_path: &Path,
+    file_spec: &FileSpec,
+) -> u64 {
+    std::iter::once(current_path.to_path_buf())
+        .chain(list_of_log_and_compressed_files(file_system, file_spec))
+        .filter_map(|file| file_system.metadata(&file).ok())
+        .map(|metadata| metadata.len)
+        .sum()
+}
+
+// Removes the daily subdirectory of a file that was removed or moved, if it is empty now.
+fn remove_empty_daily_subdirectory(
+    file_system: &dyn LogFileSystem,
+    file: &Path,
+    file_spec: &FileSpec,
+) {
+    if file_spec.daily_subdirectories {
+        if let Some(directory) = file.parent() {
+            if directory != file_spec.get_rotated_directory() {
+                // fails if the directory is not empty
+                file_system.remove_dir(directory).ok();
+            }
+        }
+    }
+}
+
+// Waits for the next message to the cleanup thread;
+// if an interval is given, its expiry is treated like MessageToCleanupThread::Act.
+fn next_message_to_cleanup_thread(
+    receiver: &std::sync::mpsc::Receiver<MessageToCleanupThread>,
+    o_interval: Option<std::time::Duration>,
+) -> Option<MessageToCleanupThread> {
+    match o_interval {
+        Some(interval) => match receiver.recv_timeout(interval) {
+            Ok(message) => Some(message),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Some(MessageToCleanupThread::Act),
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => None,
+        },
+        None => receiver.recv().ok(),
+    }
+}
+
 fn remove_or_compress_too_old_logfiles(
     o_cleanup_thread_handle: &Option<CleanupThreadHandle>,
     cleanup_config: &Cleanup,
+    file_system: &Arc<dyn LogFileSystem>,
     file_spec: &FileSpec,
+    scan_lock: &Mutex<()>,
+    o_cleanup_listener: Option<&CleanupListener>,
+    compression: CompressionConfig,
 ) -> Result<(), std::io::Error> {
     o_cleanup_thread_handle.as_ref().map_or_else(
-        || remove_or_compress_too_old_logfiles_impl(cleanup_config, file_spec),
+        || {
+            remove_or_compress_too_old_logfiles_impl(
+                cleanup_config,
+                file_system,
+                file_spec,
+                scan_lock,
+                o_cleanup_listener,
+                compression,
+            )
+        },
         |cleanup_thread_handle| {
             cleanup_thread_handle
                 .sender
@@ -559,55 +1496,283 @@ fn remove_or_compress_too_old_logfiles(
     )
 }
 
+// the compression config is not needed without feature compress
+#[cfg_attr(not(feature = "compress"), allow(unused_variables))]
 fn remove_or_compress_too_old_logfiles_impl(
     cleanup_config: &Cleanup,
+    file_system: &Arc<dyn LogFileSystem>,
     file_spec: &FileSpec,
+    scan_lock: &Mutex<()>,
+    o_cleanup_listener: Option<&CleanupListener>,
+    compression: CompressionConfig,
 ) -> Result<(), std::io::Error> {
-    let (log_limit, compress_limit) = match *cleanup_config {
+    let notify = |event: CleanupEvent| {
+        if let Some(cleanup_listener) = o_cleanup_listener {
+            cleanup_listener.notify(&event);
+        }
+    };
+
+    let (log_limit, compress_limit, disposal) = match *cleanup_config {
         Cleanup::Never => {
             return Ok(());
         }
-        Cleanup::KeepLogFiles(log_limit) => (log_limit, 0),
+        Cleanup::KeepLogFiles(log_limit) => (log_limit, 0, Disposal::Delete),
 
         #[cfg(feature = "compress")]
-        Cleanup::KeepCompressedFiles(compress_limit) => (0, compress_limit),
+        Cleanup::KeepCompressedFiles(compress_limit) => (0, compress_limit, Disposal::Delete),
 
         #[cfg(feature = "compress")]
         Cleanup::KeepLogAndCompressedFiles(log_limit, compress_limit) => {
-            (log_limit, compress_limit)
+            (log_limit, compress_limit, Disposal::Delete)
+        }
+
+        Cleanup::MoveToDirectory(ref archive_dir, log_limit) => {
+            file_system.create_dir_all(archive_dir)?;
+            (log_limit, 0, Disposal::Move(archive_dir))
+        }
+
+        #[cfg(feature = "upload")]
+        Cleanup::UploadThenDelete(ref uploader, log_limit) => {
+            (log_limit, 0, Disposal::Upload(uploader))
         }
     };
 
-    for (index, file) in list_of_log_and_compressed_files(file_spec).enumerate() {
+    let _scan_guard = cleanup_domain::lock_scans(scan_lock);
+    #[cfg(feature = "compress")]
+    let mut files_to_compress = Vec::new();
+    for (index, file) in list_of_log_and_compressed_files(&**file_system, file_spec)
+        .filter(|file| !is_pinned(file_spec, file))
+        .enumerate()
+    {
         if index >= log_limit + compress_limit {
-            // delete (log or log.gz)
-            std::fs::remove_file(&file)?;
+            match disposal {
+                // archive (log or log.gz)
+                Disposal::Move(archive_dir) => {
+                    let new_path = move_to_directory(&**file_system, &file, archive_dir)?;
+                    remove_empty_daily_subdirectory(&**file_system, &file, file_spec);
+                    notify(CleanupEvent::Moved {
+                        path: file,
+                        new_path,
+                    });
+                }
+                // delete (log or log.gz)
+                Disposal::Delete => {
+                    file_system.remove_file(&file)?;
+                    remove_empty_daily_subdirectory(&**file_system, &file, file_spec);
+                    notify(CleanupEvent::Deleted { path: file });
+                }
+                // upload (log or log.gz), and delete only if that succeeded;
+                // otherwise the upload is retried with the next cleanup
+                #[cfg(feature = "upload")]
+                Disposal::Upload(uploader) => match uploader.upload(&file) {
+                    Ok(()) => {
+                        file_system.remove_file(&file)?;
+                        remove_empty_daily_subdirectory(&**file_system, &file, file_spec);
+                        notify(CleanupEvent::Uploaded { path: file });
+                    }
+                    Err(e) => {
+                        eprint_err(ERRCODE::LogFile, "uploading the log file failed", &e);
+                    }
+                },
+            }
         } else if index >= log_limit {
             #[cfg(feature = "compress")]
-                {
-                    // compress, if not yet compressed
-                    if let Some(extension) = file.extension() {
-                        if extension != "gz" {
-                            let mut old_file = File::open(file.clone())?;
-                            let mut compressed_file = file.clone();
-                            compressed_file.set_extension("log.gz");
-                            let mut gz_encoder = flate2::write::GzEncoder::new(
-                                File::create(compressed_file)?,
-                                flate2::Compression::fast(),
-                            );
-                            std::io::copy(&mut old_file, &mut gz_encoder)?;
-                            gz_encoder.finish()?;
-                            std::fs::remove_file(&file)?;
-                        }
+            {
+                // compress, if not yet compressed
+                if let Some(extension) = file.extension() {
+                    if extension != "gz" {
+                        files_to_compress.push(file);
                     }
                 }
+            }
+        }
+    }
+    #[cfg(feature = "compress")]
+    compress_files(
+        file_system,
+        files_to_compress,
+        compression,
+        o_cleanup_listener,
+    )?;
+
+    Ok(())
+}
+
+// What the cleanup does with the files that are not kept.
+enum Disposal<'a> {
+    Delete,
+    Move(&'a Path),
+    #[cfg(feature = "upload")]
+    Upload(&'a Uploader),
+}
+
+// A volume whose free space can't be determined is not considered to be low on space.
+#[cfg(feature = "disk_free")]
+fn disk_space_low(directory: &Path, min_free: u64) -> bool {
+    matches!(platform::available_space(directory), Some(available) if available < min_free)
+}
+
+// Deletes the oldest rotated log files until the free space suffices again,
+// or until no rotated log file is left.
+#[cfg(feature = "disk_free")]
+fn remove_logfiles_until_disk_space_suffices(
+    min_free: u64,
+    directory: &Path,
+    file_system: &dyn LogFileSystem,
+    file_spec: &FileSpec,
+    scan_lock: &Mutex<()>,
+    o_cleanup_listener: Option<&CleanupListener>,
+) -> Result<(), std::io::Error> {
+    let _scan_guard = cleanup_domain::lock_scans(scan_lock);
+    // the newest files come first, compressed files after the uncompressed ones
+    let mut files: Vec<PathBuf> = list_of_log_and_compressed_files(file_system, file_spec)
+        .filter(|file| !is_pinned(file_spec, file))
+        .collect();
+    while disk_space_low(directory, min_free) {
+        match files.pop() {
+            Some(file) => {
+                file_system.remove_file(&file)?;
+                if let Some(cleanup_listener) = o_cleanup_listener {
+                    cleanup_listener.notify(&CleanupEvent::Deleted { path: file });
+                }
+            }
+            None => break,
         }
     }
+    Ok(())
+}
+
+// Compresses the files, with up to compression.threads threads.
+#[cfg(feature = "compress")]
+fn compress_files(
+    file_system: &Arc<dyn LogFileSystem>,
+    mut files: Vec<PathBuf>,
+    compression: CompressionConfig,
+    o_cleanup_listener: Option<&CleanupListener>,
+) -> Result<(), std::io::Error> {
+    let no_of_threads = std::cmp::min(compression.threads, files.len());
+    if no_of_threads <= 1 {
+        return files.drain(..).try_for_each(|file| {
+            compress_file(&**file_system, file, compression.level, o_cleanup_listener)
+        });
+    }
+
+    let files = Arc::new(Mutex::new(files));
+    let mut join_handles = Vec::with_capacity(no_of_threads);
+    for _ in 0..no_of_threads {
+        let files = Arc::clone(&files);
+        let file_system = Arc::clone(file_system);
+        let o_cleanup_listener = o_cleanup_listener.cloned();
+        let builder = std::thread::Builder::new().name("flexi_logger-compress".to_string());
+        #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+        let builder = builder.stack_size(512 * 1024);
+        join_handles.push(builder.spawn(move || loop {
+            let o_file = files.lock().map_or(None, |mut files| files.pop());
+            match o_file {
+                Some(file) => {
+                    compress_file(
+                        &*file_system,
+                        file,
+                        compression.level,
+                        o_cleanup_listener.as_ref(),
+                    )?;
+                }
+                None => return Ok(()),
+            }
+        })?);
+    }
+    // wait for all threads, and report the first error
+    join_handles
+        .into_iter()
+        .map(|join_handle| {
+            join_handle
+                .join()
+                .unwrap_or_else(|_| Err(io_err("compression thread panicked")))
+        })
+        .fold(Ok(()), Result::and)
+}
 
+// Writes a compressed copy of the file with additional suffix .gz, and removes the file.
+#[cfg(feature = "compress")]
+fn compress_file(
+    file_system: &dyn LogFileSystem,
+    file: PathBuf,
+    level: u32,
+    o_cleanup_listener: Option<&CleanupListener>,
+) -> Result<(), std::io::Error> {
+    let mut old_file = file_system.open_for_reading(&file)?;
+    let mut compressed_file = file.clone();
+    compressed_file.set_extension("log.gz");
+    let mut gz_encoder = flate2::write::GzEncoder::new(
+        file_system.open(&compressed_file, false)?,
+        flate2::Compression::new(level),
+    );
+    std::io::copy(&mut old_file, &mut gz_encoder)?;
+    gz_encoder.finish()?.flush()?;
+    file_system.remove_file(&file)?;
+    if let Some(cleanup_listener) = o_cleanup_listener {
+        cleanup_listener.notify(&CleanupEvent::Compressed {
+            path: file,
+            compressed_path: compressed_file,
+        });
+    }
     Ok(())
 }
 
-// Moves the current file to the timestamp of the CURRENT file's creation date.
+// Counts the bytes that are written to the inner writer.
+#[cfg(feature = "compress")]
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: Arc<AtomicU64>,
+}
+#[cfg(feature = "compress")]
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count.fetch_add(written as u64, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Moves the file into the archive directory, without overwriting files that are there already,
+// and returns the new path.
+fn move_to_directory(
+    file_system: &dyn LogFileSystem,
+    file: &Path,
+    archive_dir: &Path,
+) -> Result<PathBuf, std::io::Error> {
+    let file_name = file.file_name().unwrap(/*ok*/).to_string_lossy();
+    let mut target = archive_dir.join(&*file_name);
+    let mut number = 0;
+    while file_system.exists(&target) {
+        target = archive_dir.join(format!("{}.restart-{:04}", file_name, number));
+        number += 1;
+    }
+    file_system.rename(file, &target)?;
+    Ok(target)
+}
+
+// The offset of the timestamps that are used for rotation: the one that is given with
+// Naming::Timestamps, otherwise the local offset.
+fn rotation_offset(naming: Naming) -> UtcOffset {
+    match naming {
+        Naming::Timestamps(utc_offset) => utc_offset,
+        Naming::Numbers | Naming::NumbersRoundRobin(_) => now_local_or_utc().offset(),
+    }
+}
+
+const TS_S: &str = "_r[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]";
+lazy_static::lazy_static! {
+static ref TS: Vec<format_description::FormatItem<'static>>
+= format_description::parse(TS_S).unwrap(/*ok*/);
+}
+
+// Moves the current file to the timestamp of the CURRENT file's creation date,
+// and returns the new path.
 // If the rotation comes very fast, the new timestamp would be equal to the old one.
 // To avoid file collisions, we insert an additional string to the filename (".restart-<number>").
 // The number is incremented in case of repeated collisions.
@@ -616,17 +1781,11 @@ fn remove_or_compress_too_old_logfiles_impl(
 fn rotate_output_file_to_date(
     creation_date: &OffsetDateTime,
     config: &Config,
-) -> Result<(), std::io::Error> {
-    const TS_S: &str = "_r[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]";
-    lazy_static::lazy_static! {
-    static ref TS: Vec<format_description::FormatItem<'static>>
-    = format_description::parse(TS_S).unwrap(/*ok*/);
-    }
-
+) -> Result<PathBuf, std::io::Error> {
     let current_path = config.file_spec.as_pathbuf(Some(CURRENT_INFIX));
     let mut rotated_path = config
         .file_spec
-        .as_pathbuf(Some(&creation_date.format(&TS).unwrap(/*ok*/)));
+        .as_rotated_pathbuf(&creation_date.format(&TS).unwrap(/*ok*/), *creation_date);
 
     // Search for rotated_path as is and for restart-siblings;
     // if any exists, find highest restart and add 1, else continue without restart
@@ -635,11 +1794,11 @@ fn rotate_output_file_to_date(
     let mut pattern = pattern.to_string_lossy().to_string();
     pattern.push_str(".restart-*");
 
-    let file_list = glob::glob(&pattern).unwrap(/*ok*/);
-    let mut vec: Vec<PathBuf> = file_list.map(Result::unwrap).collect();
+    let file_system = &*config.file_system;
+    let mut vec: Vec<PathBuf> = file_system.glob(&pattern);
     vec.sort_unstable();
 
-    if (*rotated_path).exists() || !vec.is_empty() {
+    if file_system.exists(&rotated_path) || !vec.is_empty() {
         let mut number = if vec.is_empty() {
             0
         } else {
@@ -653,23 +1812,25 @@ fn rotate_output_file_to_date(
             file_stem[(index + 9)..].parse::<usize>().unwrap(/*ok*/)
         };
 
-        while (*rotated_path).exists() {
-            rotated_path = config.file_spec.as_pathbuf(Some(
+        while file_system.exists(&rotated_path) {
+            rotated_path = config.file_spec.as_rotated_pathbuf(
                 &creation_date
                     .format(&TS)
                     .unwrap(/*ok*/)
                     .add(&format!(".restart-{:04}", number)),
-            ));
+                *creation_date,
+            );
             number += 1;
         }
     }
 
-    match std::fs::rename(&current_path, &rotated_path) {
-        Ok(()) => Ok(()),
+    create_daily_subdirectory(&current_path, &rotated_path, config)?;
+    match file_system.rename(&current_path, &rotated_path) {
+        Ok(()) => Ok(rotated_path),
         Err(e) => {
             if e.kind() == std::io::ErrorKind::NotFound {
                 // current did not exist, so we had nothing to do
-                Ok(())
+                Ok(rotated_path)
             } else {
                 Err(e)
             }
@@ -677,21 +1838,34 @@ fn rotate_output_file_to_date(
     }
 }
 
+// Creates the daily subdirectory for the rotated file, if needed.
+fn create_daily_subdirectory(
+    current_path: &Path,
+    rotated_path: &Path,
+    config: &Config,
+) -> Result<(), std::io::Error> {
+    if config.file_spec.daily_subdirectories && config.file_system.exists(current_path) {
+        if let Some(directory) = rotated_path.parent() {
+            config.file_system.create_dir_all(directory)?;
+        }
+    }
+    Ok(())
+}
+
 // Moves the current file to the name with the next rotate_idx and returns the next rotate_idx.
 // The current file must be closed already.
 fn rotate_output_file_to_idx(
     idx_state: IdxState,
+    created_at: OffsetDateTime,
     config: &Config,
 ) -> Result<IdxState, std::io::Error> {
-    let new_idx = match idx_state {
-        IdxState::Start => 0,
-        IdxState::Idx(idx) => idx + 1,
-    };
-
-    match std::fs::rename(
-        config.file_spec.as_pathbuf(Some(CURRENT_INFIX)),
-        config.file_spec.as_pathbuf(Some(&number_infix(new_idx))),
-    ) {
+    let new_idx = next_rotate_idx(idx_state, created_at, config);
+    let current_path = config.file_spec.as_pathbuf(Some(CURRENT_INFIX));
+    let rotated_path = config
+        .file_spec
+        .as_rotated_pathbuf(&config.file_spec.number_infix(new_idx), created_at);
+    create_daily_subdirectory(&current_path, &rotated_path, config)?;
+    match config.file_system.rename(&current_path, &rotated_path) {
         Ok(()) => Ok(IdxState::Idx(new_idx)),
         Err(e) => {
             if e.kind() == std::io::ErrorKind::NotFound {
@@ -704,13 +1878,134 @@ fn rotate_output_file_to_idx(
     }
 }
 
+fn next_rotate_idx(idx_state: IdxState, created_at: OffsetDateTime, config: &Config) -> u32 {
+    let mut new_idx = match idx_state {
+        IdxState::Start => config.file_spec.numbering.start,
+        IdxState::Idx(idx) => idx + 1,
+    };
+    while rotate_idx_taken(config, new_idx, created_at) {
+        new_idx += 1;
+    }
+    new_idx
+}
+
+// The path that the file that is created with the imminent rotation will get
+// with the subsequent rotation; it can deviate if that path is taken by then,
+// e.g. by another process, or by a restart within the same second.
+fn successor_rotated_path(rotation_state: &RotationState, config: &Config) -> PathBuf {
+    let file_spec = &config.file_spec;
+    match rotation_state.naming_state {
+        NamingState::CreatedAt => {
+            let created_at = now_local_or_utc().to_offset(rotation_state.utc_offset);
+            file_spec.as_rotated_pathbuf(&created_at.format(&TS).unwrap(/*ok*/), created_at)
+        }
+        NamingState::IdxState(idx_state) => {
+            let created_at = rotation_state.created_at;
+            let idx = next_rotate_idx(idx_state, created_at, config) + 1;
+            file_spec.as_rotated_pathbuf(&file_spec.number_infix(idx), created_at)
+        }
+        NamingState::RoundRobin(no_of_slots, next_slot) => {
+            let [path, _] = round_robin_slot_files(file_spec, (next_slot + 1) % no_of_slots);
+            path
+        }
+    }
+}
+
+// Writes a line that links a log file to its predecessor or successor.
+fn write_breadcrumb(
+    file: &mut dyn Write,
+    direction: &str,
+    path: &Path,
+    line_ending: &[u8],
+) -> usize {
+    let mut line = format!(
+        "{} {}",
+        direction,
+        path.file_name().unwrap_or_default().to_string_lossy()
+    )
+    .into_bytes();
+    line.extend_from_slice(line_ending);
+    match file.write_all(&line) {
+        Ok(()) => line.len(),
+        Err(e) => {
+            eprint_err(ERRCODE::Write, "writing the rotation breadcrumb failed", &e);
+            0
+        }
+    }
+}
+
+// The file of a slot, and its compressed variant.
+fn round_robin_slot_files(file_spec: &FileSpec, slot: u32) -> [PathBuf; 2] {
+    let path = file_spec.get_rotated_directory().join(
+        file_spec
+            .as_pathbuf(Some(
+                &file_spec.number_infix(file_spec.numbering.start + slot),
+            ))
+            .file_name()
+            .unwrap(/*ok*/),
+    );
+    let mut compressed = path.clone().into_os_string();
+    compressed.push(".gz");
+    [path, PathBuf::from(compressed)]
+}
+
+// The slot that is to be used next: the first free slot, or else the one with the oldest file.
+fn get_next_round_robin_slot(
+    file_system: &dyn LogFileSystem,
+    file_spec: &FileSpec,
+    no_of_slots: u32,
+) -> u32 {
+    let mut o_oldest: Option<(u32, std::time::SystemTime)> = None;
+    for slot in 0..no_of_slots {
+        let o_modified = round_robin_slot_files(file_spec, slot)
+            .iter()
+            .filter_map(|file| file_system.metadata(file).ok().and_then(|m| m.modified))
+            .max();
+        match (o_modified, o_oldest) {
+            (None, _) => return slot,
+            (Some(modified), Some((_, oldest))) if modified >= oldest => {}
+            (Some(modified), _) => o_oldest = Some((slot, modified)),
+        }
+    }
+    o_oldest.map_or(0, |(slot, _)| slot)
+}
+
+// Renames the current file into the given slot, overwriting the slot's previous file,
+// and returns the slot that is to be used next.
+fn rotate_output_file_to_slot(
+    slot: u32,
+    no_of_slots: u32,
+    config: &Config,
+) -> Result<u32, std::io::Error> {
+    let file_system = &*config.file_system;
+    let current = config.file_spec.as_pathbuf(Some(CURRENT_INFIX));
+    if !file_system.exists(&current) {
+        // current did not exist, so we had nothing to do
+        return Ok(slot);
+    }
+    let [path, compressed] = round_robin_slot_files(&config.file_spec, slot);
+    for old_file in &[&path, &compressed] {
+        if let Err(e) = file_system.remove_file(old_file) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(e);
+            }
+        }
+    }
+    file_system.rename(&current, &path)?;
+    Ok((slot + 1) % no_of_slots)
+}
+
 // See documentation of Criterion::Age.
 #[allow(unused_variables)]
-fn get_creation_date(path: &Path) -> OffsetDateTime {
+fn get_creation_date(file_system: &dyn LogFileSystem, path: &Path) -> OffsetDateTime {
+    if let Some(created_at) = read_created_at(file_system, path) {
+        return created_at;
+    }
+
     // On windows, we know that try_get_creation_date() returns a result, but it is wrong.
     // On linux, we know that try_get_creation_date() returns an error.
     #[cfg(any(target_os = "windows", target_os = "linux"))]
-        return get_fake_creation_date();
+    return get_fake_creation_date();
 
     // On all others of the many platforms, we give the real creation date a try,
     // and fall back to the fake if it is not available.
@@ -721,6 +2016,43 @@ fn get_creation_date(path: &Path) -> OffsetDateTime {
     }
 }
 
+// The creation date of a rotated log file is persisted in a sidecar file,
+// because the file system does not reliably provide it.
+fn created_at_path(path: &Path) -> PathBuf {
+    let mut filename = path.file_name().unwrap_or_default().to_os_string();
+    filename.push(CREATED_AT_SUFFIX);
+    path.with_file_name(filename)
+}
+
+fn write_created_at(file_system: &dyn LogFileSystem, path: &Path, created_at: OffsetDateTime) {
+    // without the sidecar file, the age of a continued file would be underestimated
+    if let Ok(s) = created_at.format(&Rfc3339) {
+        file_system
+            .open(&created_at_path(path), false)
+            .and_then(|mut file| {
+                file.write_all(s.as_bytes())?;
+                file.flush()
+            })
+            .unwrap_or_else(|e| {
+                eprint_err(
+                    ERRCODE::LogFile,
+                    "cannot write creation date of log file",
+                    &e,
+                );
+            });
+    }
+}
+
+fn read_created_at(file_system: &dyn LogFileSystem, path: &Path) -> Option<OffsetDateTime> {
+    let mut s = String::new();
+    file_system
+        .open_for_reading(&created_at_path(path))
+        .ok()?
+        .read_to_string(&mut s)
+        .ok()?;
+    OffsetDateTime::parse(s.trim(), &Rfc3339).ok()
+}
+
 fn get_fake_creation_date() -> OffsetDateTime {
     now_local_or_utc()
 }
@@ -757,4 +2089,61 @@ mod platform {
 
     #[cfg(not(target_os = "linux"))]
     fn linux_create_symlink(_: &Path, _: &Path) {}
+
+    // The number of bytes that are available for unprivileged users on the volume
+    // of the given path.
+    #[cfg(all(feature = "disk_free", unix))]
+    pub fn available_space(path: &Path) -> Option<u64> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // the field types differ between the unix platforms
+        #[allow(clippy::unnecessary_cast)]
+        // SAFETY: c_path is a valid C string, and stat is only read if statvfs succeeded
+        unsafe {
+            if libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) == 0 {
+                let stat = stat.assume_init();
+                Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+            } else {
+                None
+            }
+        }
+    }
+
+    #[cfg(all(feature = "disk_free", not(unix)))]
+    pub fn available_space(_: &Path) -> Option<u64> {
+        None
+    }
+
+    // Reserves the space without changing the size of the file;
+    // file systems that don't support this are silently accepted.
+    #[cfg(all(feature = "preallocate", target_os = "linux"))]
+    pub fn preallocate(path: &Path, bytes: u64) {
+        use std::convert::TryFrom;
+        use std::os::unix::io::AsRawFd;
+
+        let result = std::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| {
+                let len = libc::off_t::try_from(bytes).unwrap_or(libc::off_t::MAX);
+                // SAFETY: the file descriptor is valid while file is alive
+                let rc =
+                    unsafe { libc::fallocate(file.as_raw_fd(), libc::FALLOC_FL_KEEP_SIZE, 0, len) };
+                if rc == 0 {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::last_os_error())
+                }
+            });
+        if let Err(e) = result {
+            if e.raw_os_error() != Some(libc::EOPNOTSUPP) {
+                eprint_err(ERRCODE::LogFile, "cannot preallocate log file", &e);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "preallocate", not(target_os = "linux")))]
+    pub fn preallocate(_: &Path, _: u64) {}
 }
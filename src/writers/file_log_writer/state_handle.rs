@@ -1,22 +1,34 @@
-use super::{builder::FileLogWriterBuilder, state::State};
+use super::{
+    builder::FileLogWriterBuilder,
+    config::RotationConfig,
+    state::State,
+    stats::{Counters, WriterStats},
+};
 #[cfg(feature = "async")]
 use crate::util::eprint_msg;
-use crate::util::{buffer_with, eprint_err, io_err, ERRCODE};
+use crate::util::{
+    buffer_with, eprint_err, io_err, sanitize, SwappableFormat, ERRCODE, WINDOWS_LINE_ENDING,
+};
 #[cfg(feature = "async")]
-use crate::util::{ASYNC_FLUSH, ASYNC_SHUTDOWN};
+use crate::util::{AsyncSender, ASYNC_FLUSH, ASYNC_SHUTDOWN};
 use crate::DeferredNow;
 use crate::FlexiLoggerError;
 use crate::FormatFunction;
 #[cfg(feature = "async")]
-use crossbeam::{
-    channel::{self, Sender},
-    queue::ArrayQueue,
-};
+use crate::OverflowPolicy;
+#[cfg(feature = "async")]
+use crossbeam::queue::ArrayQueue;
 use log::Record;
+use std::borrow::Cow;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::{mpsc, Arc, Mutex};
 #[cfg(feature = "async")]
 use std::thread::JoinHandle;
+use std::time::Duration;
+
+// How long read_back and set_rotation wait for queued lines to be written.
+const QUEUE_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[derive(Debug)]
 pub(super) enum StateHandle {
@@ -27,50 +39,151 @@ pub(super) enum StateHandle {
 
 pub(super) struct SyncHandle {
     am_state: Arc<Mutex<State>>,
-    format_function: FormatFunction,
+    counters: Arc<Counters>,
+    format_function: SwappableFormat,
     line_ending: &'static [u8],
+    // dropping the sender stops the flusher-thread
+    mo_flusher: Mutex<Option<mpsc::Sender<()>>>,
+    // dropping the sender stops the rotation-timer-thread
+    mo_rotation_timer: Mutex<Option<mpsc::Sender<()>>>,
 }
 impl SyncHandle {
     fn new(state: State, format_function: FormatFunction) -> Self {
         let line_ending = state.config().line_ending;
         let flush_interval = state.config().write_mode.get_flush_interval();
+        let o_rotation_check_interval = state.config().o_rotation_check_interval;
+        let counters = Arc::clone(state.counters());
         let am_state = Arc::new(Mutex::new(state));
-        // Create a flusher if needed
-        if flush_interval != std::time::Duration::from_secs(0) {
-            let t_am_state = Arc::clone(&am_state);
-            let builder = std::thread::Builder::new().name("flexi_logger-flusher".to_string());
-            #[cfg(not(feature = "dont_minimize_extra_stacks"))]
-            let builder = builder.stack_size(128);
-            builder.spawn(move || {
-                    let (_sender, receiver): (
-                        mpsc::Sender<()>,
-                        mpsc::Receiver<()>,
-                    ) = mpsc::channel();
-                    loop {
-                        receiver.recv_timeout(flush_interval).ok();
-                        (*t_am_state).lock().map_or_else(
-                            |_e| (),
-                            |mut state| {
-                                state.flush().ok();
-                            },
-                        );
-                    }
-                })
-                .unwrap(/* yes, let's panic if the thread can't be spawned */);
-        }
+        let mo_flusher = Mutex::new(start_flusher(&am_state, flush_interval));
+        let mo_rotation_timer =
+            Mutex::new(start_rotation_timer(&am_state, o_rotation_check_interval));
         Self {
             am_state,
-            format_function,
+            counters,
+            format_function: SwappableFormat::new(format_function),
             line_ending,
+            mo_flusher,
+            mo_rotation_timer,
         }
     }
+
+    // Replaces the state, and the flusher-thread if the flush interval changes.
+    fn reset(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
+        let mut state = self.am_state.lock().map_err(|_| FlexiLoggerError::Poison)?;
+        let old_flush_interval = state.config().write_mode.get_flush_interval();
+        let old_rotation_check_interval = state.config().o_rotation_check_interval;
+        replace_state(&mut state, flwb)?;
+        let flush_interval = state.config().write_mode.get_flush_interval();
+        let o_rotation_check_interval = state.config().o_rotation_check_interval;
+        drop(state);
+
+        if flush_interval != old_flush_interval {
+            *self
+                .mo_flusher
+                .lock()
+                .map_err(|_| FlexiLoggerError::Poison)? =
+                start_flusher(&self.am_state, flush_interval);
+        }
+        reset_rotation_timer(
+            &self.am_state,
+            &self.mo_rotation_timer,
+            old_rotation_check_interval,
+            o_rotation_check_interval,
+        )
+    }
+}
+
+// Creates a flusher-thread if needed; it stops when the returned sender is dropped.
+fn start_flusher(
+    am_state: &Arc<Mutex<State>>,
+    flush_interval: std::time::Duration,
+) -> Option<mpsc::Sender<()>> {
+    if flush_interval == std::time::Duration::from_secs(0) {
+        return None;
+    }
+    let (sender, receiver) = mpsc::channel::<()>();
+    let t_am_state = Arc::clone(am_state);
+    let builder = std::thread::Builder::new().name("flexi_logger-flusher".to_string());
+    #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+    let builder = builder.stack_size(128);
+    builder
+        .spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) = receiver.recv_timeout(flush_interval)
+            {
+                (*t_am_state).lock().map_or_else(
+                    |_e| (),
+                    |mut state| {
+                        state.flush().ok();
+                    },
+                );
+            }
+        })
+        .unwrap(/* yes, let's panic if the thread can't be spawned */);
+    Some(sender)
+}
+
+// Creates a thread that does due rotations also if nothing is logged, if needed;
+// it stops when the returned sender is dropped.
+fn start_rotation_timer(
+    am_state: &Arc<Mutex<State>>,
+    o_rotation_check_interval: Option<std::time::Duration>,
+) -> Option<mpsc::Sender<()>> {
+    let rotation_check_interval = o_rotation_check_interval?;
+    let (sender, receiver) = mpsc::channel::<()>();
+    let t_am_state = Arc::clone(am_state);
+    let builder = std::thread::Builder::new().name("flexi_logger-rotation_timer".to_string());
+    #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+    let builder = builder.stack_size(512 * 1024);
+    builder
+        .spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                receiver.recv_timeout(rotation_check_interval)
+            {
+                if let Ok(mut state) = t_am_state.lock() {
+                    state.rotate_if_necessary().unwrap_or_else(|e| {
+                        eprint_err(ERRCODE::LogFile, "can't open file", &e);
+                    });
+                }
+            }
+        })
+        .unwrap(/* yes, let's panic if the thread can't be spawned */);
+    Some(sender)
+}
+
+// Replaces the rotation-timer-thread if the rotation check interval changes.
+fn reset_rotation_timer(
+    am_state: &Arc<Mutex<State>>,
+    mo_rotation_timer: &Mutex<Option<mpsc::Sender<()>>>,
+    old_rotation_check_interval: Option<std::time::Duration>,
+    o_rotation_check_interval: Option<std::time::Duration>,
+) -> Result<(), FlexiLoggerError> {
+    if o_rotation_check_interval != old_rotation_check_interval {
+        *mo_rotation_timer
+            .lock()
+            .map_err(|_| FlexiLoggerError::Poison)? =
+            start_rotation_timer(am_state, o_rotation_check_interval);
+    }
+    Ok(())
+}
+
+// Flushes the current state and replaces it with a new one that continues its counting.
+fn replace_state(state: &mut State, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
+    flwb.assert_write_mode(state.config().write_mode)?;
+    let mut new_state = flwb.try_build_state(Some(state))?;
+    state.flush()?;
+    new_state.take_over_counters(Arc::clone(state.counters()));
+    *state = new_state;
+    Ok(())
 }
 impl std::fmt::Debug for SyncHandle {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         f.debug_struct("SyncHandle")
             .field("am_state", &self.am_state)
+            .field("counters", &self.counters)
             .field("format", &"<..>")
             .field("line_ending", &self.line_ending)
+            .field("mo_flusher", &self.mo_flusher)
+            .field("mo_rotation_timer", &self.mo_rotation_timer)
             .finish()
     }
 }
@@ -78,11 +191,14 @@ impl std::fmt::Debug for SyncHandle {
 #[cfg(feature = "async")]
 pub(super) struct AsyncHandle {
     am_state: Arc<Mutex<State>>,
-    sender: Sender<Vec<u8>>,
+    counters: Arc<Counters>,
+    sender: AsyncSender,
     mo_thread_handle: Mutex<Option<JoinHandle<()>>>,
+    // dropping the sender stops the rotation-timer-thread
+    mo_rotation_timer: Mutex<Option<mpsc::Sender<()>>>,
     a_pool: Arc<ArrayQueue<Vec<u8>>>,
     message_capa: usize,
-    format_function: FormatFunction,
+    format_function: SwappableFormat,
     line_ending: &'static [u8],
 }
 #[cfg(feature = "async")]
@@ -90,17 +206,31 @@ impl AsyncHandle {
     fn new(
         pool_capa: usize,
         message_capa: usize,
+        channel_capa: Option<usize>,
+        overflow_policy: OverflowPolicy,
         state: State,
         format_function: FormatFunction,
     ) -> Self {
         let flush_interval = state.config().write_mode.get_flush_interval();
+        let o_rotation_check_interval = state.config().o_rotation_check_interval;
         let line_ending = state.config().line_ending;
+        let counters = Arc::clone(state.counters());
         let am_state = Arc::new(Mutex::new(state));
-        let (async_sender, receiver) = channel::unbounded::<Vec<u8>>();
+        let mo_rotation_timer =
+            Mutex::new(start_rotation_timer(&am_state, o_rotation_check_interval));
         let a_pool = Arc::new(ArrayQueue::new(pool_capa));
+        let (async_sender, mut receiver) = AsyncSender::new(
+            channel_capa,
+            overflow_policy,
+            format_function,
+            line_ending,
+            &a_pool,
+            message_capa,
+        );
 
         let t_state = Arc::clone(&am_state);
         let t_pool = Arc::clone(&a_pool);
+        let t_flushes = async_sender.flush_tracker();
 
         let mo_thread_handle = Mutex::new(Some(
             std::thread::Builder::new()
@@ -115,17 +245,20 @@ impl AsyncHandle {
                                     state.flush().unwrap_or_else(|e| {
                                         eprint_err(ERRCODE::Flush, "flushing failed", &e);
                                     });
+                                    t_flushes.processed();
                                 }
                                 ASYNC_SHUTDOWN => {
                                     state.shutdown();
                                     break;
                                 }
                                 _ => {
-                                    state.write_buffer(&message).unwrap_or_else(|e| {
+                                    state.write_chunk(&message).unwrap_or_else(|e| {
                                         eprint_err(ERRCODE::Write, "writing failed", &e);
                                     });
                                 }
                             }
+                            // buffers that had to grow beyond the configured capacity are freed
+                            // rather than pooled, to keep the memory consumption of the pool bounded
                             if message.capacity() <= message_capa {
                                 message.clear();
                                 t_pool.push(message).ok();
@@ -154,7 +287,7 @@ impl AsyncHandle {
                             break;
                         }
 
-                        cloned_async_sender.send(ASYNC_FLUSH.to_vec()).ok();
+                        cloned_async_sender.send_control(ASYNC_FLUSH.to_vec()).ok();
                     }
                 })
                 .unwrap(/* yes, let's panic if the thread can't be spawned */);
@@ -162,27 +295,42 @@ impl AsyncHandle {
 
         Self {
             am_state,
+            counters,
             sender: async_sender,
             mo_thread_handle,
+            mo_rotation_timer,
             a_pool,
             message_capa,
-            format_function,
+            format_function: SwappableFormat::new(format_function),
             line_ending,
         }
     }
 
     fn write(&self, now: &mut DeferredNow, record: &Record) -> Result<(), std::io::Error> {
         let mut buffer = self.pop_buffer();
-        (self.format_function)(&mut buffer, now, record).map_err(|e| {
+        (self.format_function.get())(&mut buffer, now, record).map_err(|e| {
             eprint_err(ERRCODE::Format, "formatting failed", &e);
             e
         })?;
-        buffer.write_all(self.line_ending).map_err(|e| {
-            eprint_err(ERRCODE::Write, "writing failed", &e);
-            e
-        })?;
-        self.sender.send(buffer).map_err(|_e| io_err("Send"))
+        sanitize(&mut buffer);
+        self.sender.send_line(buffer).map_err(|_e| io_err("Send"))
+    }
+    // Replaces the state, and the rotation-timer-thread if the rotation check interval changes.
+    fn reset(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
+        let mut state = self.am_state.lock().map_err(|_| FlexiLoggerError::Poison)?;
+        let old_rotation_check_interval = state.config().o_rotation_check_interval;
+        replace_state(&mut state, flwb)?;
+        let o_rotation_check_interval = state.config().o_rotation_check_interval;
+        drop(state);
+
+        reset_rotation_timer(
+            &self.am_state,
+            &self.mo_rotation_timer,
+            old_rotation_check_interval,
+            o_rotation_check_interval,
+        )
     }
+
     fn pop_buffer(&self) -> Vec<u8> {
         self.a_pool
             .pop()
@@ -194,8 +342,10 @@ impl std::fmt::Debug for AsyncHandle {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         f.debug_struct("AsyncHandle")
             .field("am_state", &self.am_state)
+            .field("counters", &self.counters)
             .field("sender", &self.sender)
             .field("mo_thread_handle", &self.mo_thread_handle)
+            .field("mo_rotation_timer", &self.mo_rotation_timer)
             .field("a_pool", &self.a_pool)
             .field("message_capa", &self.message_capa)
             .field("format", &"<..>")
@@ -215,12 +365,16 @@ impl StateHandle {
     pub(super) fn new_async(
         pool_capa: usize,
         message_capa: usize,
+        channel_capa: Option<usize>,
+        overflow_policy: OverflowPolicy,
         state: State,
         format_function: FormatFunction,
     ) -> Self {
         Self::Async(AsyncHandle::new(
             pool_capa,
             message_capa,
+            channel_capa,
+            overflow_policy,
             state,
             format_function,
         ))
@@ -236,42 +390,130 @@ impl StateHandle {
         .current_filename()
     }
 
+    pub(super) fn rotation_imminent(&self) -> bool {
+        match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .expect("state_handle.am_state is poisoned")
+        .rotation_imminent()
+    }
+
+    pub(super) fn bytes_until_rotation(&self) -> Option<u64> {
+        match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .expect("state_handle.am_state is poisoned")
+        .bytes_until_rotation()
+    }
+
+    pub(super) fn time_until_rotation(&self) -> Option<std::time::Duration> {
+        match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .expect("state_handle.am_state is poisoned")
+        .time_until_rotation()
+    }
+
+    pub(super) fn dropped_records(&self) -> usize {
+        match self {
+            StateHandle::Sync(_) => 0,
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.sender.dropped(),
+        }
+    }
+
+    pub(super) fn stats(&self) -> WriterStats {
+        let counters = match self {
+            StateHandle::Sync(handle) => &handle.counters,
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => &handle.counters,
+        };
+        counters.snapshot(self.dropped_records() as u64)
+    }
+
     pub(super) fn format_function(&self) -> FormatFunction {
         match self {
-            StateHandle::Sync(handle) => handle.format_function,
+            StateHandle::Sync(handle) => handle.format_function.get(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.format_function.get(),
+        }
+    }
+
+    pub(super) fn set_format_function(&self, format_function: FormatFunction) {
+        match self {
+            StateHandle::Sync(handle) => handle.format_function.set(format_function),
             #[cfg(feature = "async")]
-            StateHandle::Async(handle) => handle.format_function,
+            StateHandle::Async(handle) => handle.format_function.set(format_function),
+        }
+    }
+
+    fn line_ending(&self) -> &'static [u8] {
+        match self {
+            StateHandle::Sync(handle) => handle.line_ending,
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.line_ending,
         }
     }
 
     pub(super) fn plain_write(&self, buffer: &[u8]) -> std::result::Result<usize, std::io::Error> {
+        let converted = with_line_ending(buffer, self.line_ending());
         match self {
             StateHandle::Sync(handle) => {
                 let mut state_guard = handle.am_state.lock().map_err(|_e| io_err("Poison"))?;
                 let state = &mut *state_guard;
-                state.write_buffer(buffer).map(|_| buffer.len())
+                state.write_chunk(&converted).map(|()| buffer.len())
             }
             #[cfg(feature = "async")]
             StateHandle::Async(handle) => {
                 handle
                     .sender
-                    .send(buffer.to_owned())
+                    .send(converted.into_owned())
                     .map_err(|_e| io_err("Send"))?;
                 Ok(buffer.len())
             }
         }
     }
 
+    // The line ending is added if the record does not end with a line break,
+    // so that the async output thread can recognize the end of the record.
+    pub(super) fn write_record(&self, record: &[u8]) -> std::io::Result<()> {
+        let line_ending = self.line_ending();
+        let record = if record.ends_with(b"\n") {
+            with_line_ending(record, line_ending)
+        } else {
+            Cow::Owned([&with_line_ending(record, line_ending), line_ending].concat())
+        };
+        match self {
+            StateHandle::Sync(handle) => {
+                let mut state_guard = handle.am_state.lock().map_err(|_e| io_err("Poison"))?;
+                state_guard.write_record(&record)
+            }
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle
+                .sender
+                .send(record.into_owned())
+                .map_err(|_e| io_err("Send")),
+        }
+    }
+
     #[allow(clippy::unnecessary_wraps)]
     #[inline]
     pub(super) fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
         match &self {
             StateHandle::Sync(handle) => {
+                let format_function = handle.format_function.get();
                 buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
                     Ok(mut buffer) => {
-                        (handle.format_function)(&mut *buffer, now, record).unwrap_or_else(|e| {
+                        format_function(&mut *buffer, now, record).unwrap_or_else(|e| {
                             eprint_err(ERRCODE::Format, "formatting failed", &e);
                         });
+                        sanitize(&mut buffer);
                         buffer
                             .write_all(handle.line_ending)
                             .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing failed", &e));
@@ -279,7 +521,7 @@ impl StateHandle {
                             .am_state
                             .lock()
                             .expect("state_handle.am_state is poisoned"))
-                            .write_buffer(&*buffer)
+                            .write_record(&*buffer)
                             .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing failed", &e));
                         buffer.clear();
                     }
@@ -289,9 +531,10 @@ impl StateHandle {
                         // we print the inner calls, in chronological order, before finally the
                         // outer most message is printed
                         let mut tmp_buf = Vec::<u8>::with_capacity(200);
-                        (handle.format_function)(&mut tmp_buf, now, record).unwrap_or_else(|e| {
+                        format_function(&mut tmp_buf, now, record).unwrap_or_else(|e| {
                             eprint_err(ERRCODE::Format, "formatting failed", &e);
                         });
+                        sanitize(&mut tmp_buf);
                         let mut state_guard = handle
                             .am_state
                             .lock()
@@ -301,7 +544,7 @@ impl StateHandle {
                             .write_all(state.config().line_ending)
                             .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing failed", &e));
                         state
-                            .write_buffer(&tmp_buf)
+                            .write_record(&tmp_buf)
                             .unwrap_or_else(|e| eprint_err(ERRCODE::Write, "writing failed", &e));
                     }
                 });
@@ -324,23 +567,89 @@ impl StateHandle {
             StateHandle::Async(handle) => {
                 let mut buffer = handle.pop_buffer();
                 buffer.extend(ASYNC_FLUSH);
-                handle.sender.send(buffer).ok();
+                handle.sender.send_control(buffer).ok();
             }
         }
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "async"), allow(unused_variables))]
+    pub(super) fn flush_and_wait(&self, timeout: std::time::Duration) -> bool {
+        match &self {
+            StateHandle::Sync(_) => self.flush().is_ok(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => {
+                let mut buffer = handle.pop_buffer();
+                buffer.extend(ASYNC_FLUSH);
+                handle.sender.flush_and_wait(buffer, timeout)
+            }
+        }
+    }
+
     // Replaces parts of the configuration of the file log writer.
     pub(super) fn reset(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
-        let mut state = match self {
+        match self {
+            StateHandle::Sync(handle) => handle.reset(flwb),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.reset(flwb),
+        }
+    }
+
+    pub(super) fn relocate(&self, directory: &Path) -> Result<(), FlexiLoggerError> {
+        match self {
             StateHandle::Sync(handle) => handle.am_state.lock(),
             #[cfg(feature = "async")]
             StateHandle::Async(handle) => handle.am_state.lock(),
         }
-        .map_err(|_| FlexiLoggerError::Poison)?;
-        flwb.assert_write_mode((*state).config().write_mode)?;
-        *state = flwb.try_build_state()?;
-        Ok(())
+        .map_err(|_| FlexiLoggerError::Poison)?
+        .relocate(directory)
+    }
+
+    pub(super) fn existing_log_files(&self) -> Result<Vec<PathBuf>, FlexiLoggerError> {
+        Ok(match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?
+        .existing_log_files())
+    }
+
+    pub(super) fn set_rotation(
+        &self,
+        rotation_config: RotationConfig,
+    ) -> Result<(), FlexiLoggerError> {
+        // lets the lines that are still queued be written with the old rotation
+        self.flush_and_wait(QUEUE_FLUSH_TIMEOUT);
+        match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?
+        .set_rotation(rotation_config)
+    }
+
+    pub(super) fn read_back(&self, n: usize) -> Result<Vec<String>, FlexiLoggerError> {
+        // lets the lines that are still buffered (or, with async, queued) arrive in the files
+        self.flush_and_wait(QUEUE_FLUSH_TIMEOUT);
+        match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?
+        .read_back(n)
+    }
+
+    pub(super) fn pin(&self, path: &Path) -> Result<PathBuf, FlexiLoggerError> {
+        match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?
+        .pin(path)
     }
 
     #[doc(hidden)]
@@ -366,7 +675,7 @@ impl StateHandle {
             StateHandle::Async(handle) => {
                 let mut buffer = handle.pop_buffer();
                 buffer.extend(ASYNC_SHUTDOWN);
-                handle.sender.send(buffer).ok();
+                handle.sender.send_control(buffer).ok();
                 if let Ok(ref mut o_th) = handle.mo_thread_handle.lock() {
                     o_th.take().and_then(|th| th.join().ok());
                 }
@@ -374,3 +683,19 @@ impl StateHandle {
         }
     }
 }
+
+// Output that bypasses the formatting gets the configured line ending, too:
+// with windows line endings, each `\n` that is not preceded by `\r` is replaced.
+fn with_line_ending<'a>(buffer: &'a [u8], line_ending: &[u8]) -> Cow<'a, [u8]> {
+    if line_ending != WINDOWS_LINE_ENDING || !buffer.contains(&b'\n') {
+        return Cow::Borrowed(buffer);
+    }
+    let mut converted = Vec::<u8>::with_capacity(buffer.len() + 16);
+    for (i, byte) in buffer.iter().enumerate() {
+        if *byte == b'\n' && (i == 0 || buffer[i - 1] != b'\r') {
+            converted.push(b'\r');
+        }
+        converted.push(*byte);
+    }
+    Cow::Owned(converted)
+}
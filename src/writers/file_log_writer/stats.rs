@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A snapshot of the counters of a [`FileLogWriter`](crate::writers::FileLogWriter).
+///
+/// Is provided by [`FileLogWriter::stats`](crate::writers::FileLogWriter::stats) and by
+/// [`LoggerHandle::writer_stats`](crate::LoggerHandle::writer_stats).
+/// All values are accumulated since the creation of the writer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WriterStats {
+    /// Number of records that were written.
+    pub records: u64,
+    /// Number of bytes that were written.
+    pub bytes: u64,
+    /// Number of rotations of the output file.
+    pub rotations: u64,
+    /// Number of records that were discarded because the channel to the output thread was full.
+    pub dropped: u64,
+    /// Number of I/O errors that occurred when writing, flushing, or rotating.
+    pub io_errors: u64,
+    /// Number of records that were not written to the file because the log files
+    /// exceeded their maximum disk usage,
+    /// see [`FileLogWriterBuilder::max_total_bytes_on_disk`](crate::writers::FileLogWriterBuilder::max_total_bytes_on_disk).
+    pub suspended: u64,
+}
+
+// The counters behind `WriterStats`; they are shared between the `State` and the `StateHandle`,
+// so that they can be read without locking the state.
+#[derive(Debug, Default)]
+pub(super) struct Counters {
+    records: AtomicU64,
+    bytes: AtomicU64,
+    rotations: AtomicU64,
+    io_errors: AtomicU64,
+    suspended: AtomicU64,
+}
+impl Counters {
+    pub(super) fn record_written(&self, bytes: usize) {
+        self.records.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+    pub(super) fn rotated(&self) {
+        self.rotations.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(super) fn io_error(&self) {
+        self.io_errors.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(super) fn record_suspended(&self) {
+        self.suspended.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(super) fn snapshot(&self, dropped: u64) -> WriterStats {
+        WriterStats {
+            records: self.records.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            rotations: self.rotations.load(Ordering::Relaxed),
+            dropped,
+            io_errors: self.io_errors.load(Ordering::Relaxed),
+            suspended: self.suspended.load(Ordering::Relaxed),
+        }
+    }
+}
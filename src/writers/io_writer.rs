@@ -0,0 +1,72 @@
+use crate::deferred_now::DeferredNow;
+use crate::util::{io_err, sanitize};
+use crate::writers::LogWriter;
+use crate::{default_format, FormatFunction};
+use log::Record;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// A [`LogWriter`] that writes the formatted log lines into an arbitrary implementation
+/// of [`std::io::Write`], e.g. a text pane of a GUI, or a stream to a remote service.
+///
+/// Is used by [`Logger::log_to_io_writer`](crate::Logger::log_to_io_writer),
+/// and can also be used in [`Logger::add_writer`](crate::Logger::add_writer).
+///
+/// Each log line is formatted and redacted like a log line for a file,
+/// and is handed over to the wrapped `Write` with a single call of `write_all`.
+/// The wrapped `Write` is not buffered additionally; if that is desired,
+/// wrap it into a [`BufWriter`](std::io::BufWriter) and let the logger flush it regularly,
+/// by choosing a [`WriteMode`](crate::WriteMode) with flush interval.
+pub struct IoWriter {
+    m_write: Mutex<Box<dyn Write + Send>>,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+}
+impl IoWriter {
+    /// Creates an `IoWriter` that uses the default format and accepts all levels.
+    #[must_use]
+    pub fn new(write: Box<dyn Write + Send>) -> Self {
+        Self {
+            m_write: Mutex::new(write),
+            format: default_format,
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    /// Lets the writer ignore records with a lower level than the given one,
+    /// when it is used as additional writer.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+}
+
+impl LogWriter for IoWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let mut line = Vec::<u8>::with_capacity(200);
+        (self.format)(&mut line, now, record)?;
+        sanitize(&mut line);
+        line.push(b'\n');
+        self.m_write
+            .lock()
+            .map_err(|_e| io_err("Poison"))?
+            .write_all(&line)
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.m_write.lock().map_err(|_e| io_err("Poison"))?.flush()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    fn shutdown(&self) {
+        self.flush().ok();
+    }
+}
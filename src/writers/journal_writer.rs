@@ -0,0 +1,133 @@
+use crate::deferred_now::DeferredNow;
+use crate::formats::sd_daemon_priority;
+use crate::writers::log_writer::LogWriter;
+use std::io::Result as IoResult;
+use std::io::Write;
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// A [`LogWriter`] implementation that sends log messages to systemd-journald,
+/// using the native journal protocol.
+///
+/// Only available on linux, with optional crate feature `journald`.
+///
+/// Each log record is sent as a journal entry with the fields
+/// `MESSAGE`, `PRIORITY`, `SYSLOG_IDENTIFIER`, `TARGET`, and, if available,
+/// `CODE_MODULE`, `CODE_FILE`, and `CODE_LINE`.
+/// The journal can then be queried by these fields, e.g. with `journalctl PRIORITY=3`
+/// or `journalctl TARGET=my_crate::my_module`.
+///
+/// The message is sent without further formatting, since timestamp, level and location
+/// are stored in the journal entry anyway.
+/// Records that exceed the maximum datagram size of the socket are not sent,
+/// the write fails with an I/O error.
+///
+/// See the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::JournalWriter, Logger};
+///
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .log_to_writer(JournalWriter::try_new(log::LevelFilter::Trace).unwrap())
+///     .start()
+///     .unwrap();
+/// ```
+pub struct JournalWriter {
+    socket: UnixDatagram,
+    syslog_identifier: String,
+    max_log_level: log::LevelFilter,
+}
+impl JournalWriter {
+    /// Returns a boxed instance that is connected to the standard socket of journald,
+    /// `/run/systemd/journal/socket`.
+    ///
+    /// The file name of the program is used as `SYSLOG_IDENTIFIER`.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if the socket cannot be connected,
+    /// e.g. because journald is not running.
+    pub fn try_new(max_log_level: log::LevelFilter) -> IoResult<Box<Self>> {
+        Self::try_with_path(JOURNALD_SOCKET, max_log_level)
+    }
+
+    /// Returns a boxed instance that is connected to the specified socket.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if the socket cannot be connected.
+    pub fn try_with_path<P: AsRef<Path>>(
+        path: P,
+        max_log_level: log::LevelFilter,
+    ) -> IoResult<Box<Self>> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        let arg0 = std::env::args().next().unwrap_or_else(|| "rs".to_owned());
+        Ok(Box::new(Self {
+            socket,
+            syslog_identifier: Path::new(&arg0)
+                .file_name()
+                .map_or_else(|| arg0.clone(), |name| name.to_string_lossy().to_string()),
+            max_log_level,
+        }))
+    }
+}
+
+impl LogWriter for JournalWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut message = Vec::<u8>::with_capacity(200);
+        write!(message, "{}", record.args())?;
+
+        let mut entry = Vec::<u8>::with_capacity(message.len() + 200);
+        add_field(&mut entry, "MESSAGE", &message);
+        add_field(
+            &mut entry,
+            "PRIORITY",
+            sd_daemon_priority(record.level()).to_string().as_bytes(),
+        );
+        add_field(
+            &mut entry,
+            "SYSLOG_IDENTIFIER",
+            self.syslog_identifier.as_bytes(),
+        );
+        add_field(&mut entry, "TARGET", record.target().as_bytes());
+        if let Some(module_path) = record.module_path() {
+            add_field(&mut entry, "CODE_MODULE", module_path.as_bytes());
+        }
+        if let Some(file) = record.file() {
+            add_field(&mut entry, "CODE_FILE", file.as_bytes());
+        }
+        if let Some(line) = record.line() {
+            add_field(&mut entry, "CODE_LINE", line.to_string().as_bytes());
+        }
+
+        self.socket.send(&entry).map(|_| ())
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+}
+
+// Values that contain a newline must be sent in the binary form of the protocol:
+// the field name, a newline, the length of the value as little-endian u64, and the value.
+fn add_field(entry: &mut Vec<u8>, name: &str, value: &[u8]) {
+    entry.extend_from_slice(name.as_bytes());
+    if value.contains(&b'\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value);
+    entry.push(b'\n');
+}
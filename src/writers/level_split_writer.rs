@@ -0,0 +1,97 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::{FileLogWriter, FileLogWriterBuilder, LogWriter};
+use crate::FlexiLoggerError;
+use log::{Level, Record};
+
+// in the order of their numeric values, starting with 1
+const LEVELS: [Level; 5] = [
+    Level::Error,
+    Level::Warn,
+    Level::Info,
+    Level::Debug,
+    Level::Trace,
+];
+
+/// A [`LogWriter`] that writes the records of each log level into separate files,
+/// like `error.log`, `warn.log`, `info.log`, `debug.log`, and `trace.log`.
+///
+/// For each level, a [`FileLogWriter`] is used that takes its settings
+/// (format, rotation, write mode, etc.) from the given [`FileLogWriterBuilder`];
+/// in the file spec of the builder, the basename is replaced with the name of the level.
+/// A file is only created when the first record of its level is written.
+///
+/// ## Example
+///
+/// ```rust
+/// # use flexi_logger::{writers::{FileLogWriter, LevelSplitWriter}, FileSpec, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?
+///     .log_to_writer(Box::new(LevelSplitWriter::try_new(
+///         &FileLogWriter::builder(
+///             FileSpec::default()
+/// #               .directory("log_files/level_split_writer_docu")
+///                 .suppress_timestamp(),
+///         ),
+///     )?))
+///     .start()?;
+/// log::warn!("This goes into warn.log");
+/// # Ok(())
+/// # }
+/// ```
+pub struct LevelSplitWriter {
+    writers: Vec<FileLogWriter>,
+}
+impl LevelSplitWriter {
+    /// Creates the file log writers for all levels.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Io` if the specified directory doesn't work.
+    pub fn try_new(flwb: &FileLogWriterBuilder) -> Result<Self, FlexiLoggerError> {
+        let mut writers = Vec::with_capacity(LEVELS.len());
+        for level in &LEVELS {
+            let file_spec = flwb
+                .get_file_spec()
+                .clone()
+                .basename(level.as_str().to_lowercase());
+            writers.push(
+                flwb.for_other_files(file_spec, log::LevelFilter::Trace)
+                    .try_build()?,
+            );
+        }
+        Ok(Self { writers })
+    }
+
+    /// The file log writer for the given level.
+    #[must_use]
+    pub fn writer(&self, level: Level) -> &FileLogWriter {
+        &self.writers[level as usize - 1]
+    }
+}
+
+impl LogWriter for LevelSplitWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.writer(record.level()).write(now, record)
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        let mut result = Ok(());
+        for writer in &self.writers {
+            let r = writer.flush();
+            if result.is_ok() {
+                result = r;
+            }
+        }
+        result
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+
+    fn shutdown(&self) {
+        for writer in &self.writers {
+            writer.shutdown();
+        }
+    }
+}
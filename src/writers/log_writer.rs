@@ -1,6 +1,7 @@
 use crate::deferred_now::DeferredNow;
 use crate::FormatFunction;
 use log::Record;
+use std::time::Duration;
 
 /// Writes to a single log output stream.
 ///
@@ -21,6 +22,18 @@ pub trait LogWriter: Sync + Send {
     /// [`std::io::Error`]
     fn flush(&self) -> std::io::Result<()>;
 
+    /// Flushes any buffered records, and waits until they are written out,
+    /// but not longer than the given timeout.
+    ///
+    /// Returns `false` if the records could not be written out within the timeout.
+    ///
+    /// Writers that write out records asynchronously should override this method;
+    /// the default implementation calls [`LogWriter::flush`].
+    fn flush_and_wait(&self, timeout: Duration) -> bool {
+        let _ = timeout;
+        self.flush().is_ok()
+    }
+
     /// Provides the maximum log level that is to be written.
     fn max_log_level(&self) -> log::LevelFilter {
         log::LevelFilter::Trace
@@ -37,6 +50,16 @@ pub trait LogWriter: Sync + Send {
         let _ = format;
     }
 
+    /// Tells whether the writer is ready to write out records,
+    /// e.g. whether it has established its network connection.
+    ///
+    /// Is used by [`Logger::start_with_readiness`](crate::Logger::start_with_readiness).
+    ///
+    /// The default implementation returns `true`.
+    fn ready(&self) -> bool {
+        true
+    }
+
     /// Cleanup open resources, if necessary.
     fn shutdown(&self) {}
 
@@ -0,0 +1,264 @@
+use crate::deferred_now::DeferredNow;
+use crate::util::{io_err, sanitize};
+use crate::writers::log_writer::LogWriter;
+use crate::{default_format, FlexiLoggerError, FormatFunction};
+use memmap2::MmapMut;
+use std::ffi::OsString;
+use std::fs::OpenOptions;
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+// Layout of the file: a header of HEADER_LEN bytes, followed by the ring of `capacity` bytes.
+// The header consists of MAGIC, the capacity, and the total number of bytes that were written
+// so far (in native byte order); the current write offset in the ring is derived from the latter.
+const MAGIC: &[u8; 8] = b"FLXRING1";
+const CAPACITY_OFFSET: usize = 8;
+const POSITION_OFFSET: usize = 16;
+const HEADER_LEN: usize = 64;
+
+/// A [`LogWriter`] implementation that mirrors the latest formatted log lines
+/// into a memory-mapped file, which serves as a ring buffer.
+///
+/// Only available with optional crate feature `mmap_ring`.
+///
+/// Since the memory of the mapping belongs to the operating system,
+/// its content survives a hard crash of the program (e.g. by `SIGKILL`, or by the OOM killer),
+/// also if buffered output of other writers was lost.
+/// After such a crash, [`MmapRingWriter::recover`] returns the last moments of logging
+/// from the file.
+/// The content does not necessarily survive a crash of the operating system.
+///
+/// Each log line is formatted outside of any lock; only the copy into the mapping is serialized
+/// with a short lock, so that concurrent log calls never write into the same part of the ring.
+/// When the ring is full, the oldest lines are overwritten.
+/// Lines that are longer than the ring are truncated to its tail.
+/// A line whose copying was interrupted by the crash is recovered in a garbled form.
+///
+/// If the file exists already when the writer is built, e.g. after a crash,
+/// it is renamed by appending `.prev` to its name (an older such file is overwritten),
+/// so that it can still be recovered after the program was restarted.
+///
+/// Use [`MmapRingWriter::builder`] to create an instance,
+/// and see the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::MmapRingWriter, FileSpec, Logger};
+///
+/// let ring_writer = MmapRingWriter::builder("/var/tmp/my_prog.ring")
+///     .capacity(256 * 1024)
+///     .try_build()
+///     .unwrap();
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .log_to_file_and_writer(FileSpec::default(), Box::new(ring_writer))
+///     .start()
+///     .unwrap();
+/// ```
+///
+/// and, e.g. in a tool that runs after the crash:
+///
+/// ```rust,no_run
+/// use flexi_logger::writers::MmapRingWriter;
+///
+/// let last_lines = MmapRingWriter::recover("/var/tmp/my_prog.ring.prev").unwrap();
+/// print!("{}", String::from_utf8_lossy(&last_lines));
+/// ```
+pub struct MmapRingWriter {
+    // is written only through `base`
+    mmap: MmapMut,
+    base: *mut u8,
+    // serializes the updates of the position and the copies into the ring
+    copy_lock: Mutex<()>,
+    capacity: usize,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+}
+
+// `base` points into `mmap`, which lives as long as the writer and is never moved or remapped.
+// The ring is only written while `copy_lock` is held, so concurrent calls of `write` cannot
+// overlap, also if a line wraps around the ring onto the range of a line that is being copied.
+unsafe impl Send for MmapRingWriter {}
+unsafe impl Sync for MmapRingWriter {}
+
+impl MmapRingWriter {
+    /// Returns a builder for a `MmapRingWriter` that maps the given file.
+    #[must_use]
+    pub fn builder<P: Into<PathBuf>>(path: P) -> MmapRingWriterBuilder {
+        MmapRingWriterBuilder {
+            path: path.into(),
+            capacity: DEFAULT_CAPACITY,
+            format: default_format,
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    /// Reads a file that was written by a `MmapRingWriter`,
+    /// and returns the log lines it contains, oldest first.
+    ///
+    /// If the ring had been overwritten, the first, incomplete line is omitted.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if the file cannot be read or was not written by a `MmapRingWriter`.
+    pub fn recover<P: AsRef<Path>>(path: P) -> IoResult<Vec<u8>> {
+        let content = std::fs::read(path)?;
+        if content.len() < HEADER_LEN || &content[0..MAGIC.len()] != MAGIC {
+            return Err(io_err("not a ring buffer file of flexi_logger"));
+        }
+        let capacity = read_u64(&content, CAPACITY_OFFSET);
+        let position = read_u64(&content, POSITION_OFFSET);
+        if capacity == 0 || content.len() as u64 != HEADER_LEN as u64 + capacity {
+            return Err(io_err("ring buffer file has an unexpected size"));
+        }
+        let ring = &content[HEADER_LEN..];
+
+        #[allow(clippy::cast_possible_truncation)]
+        let len = std::cmp::min(position, capacity) as usize;
+        #[allow(clippy::cast_possible_truncation)]
+        let start = ((position - len as u64) % capacity) as usize;
+        let mut lines = Vec::<u8>::with_capacity(len);
+        let first = std::cmp::min(len, ring.len() - start);
+        lines.extend_from_slice(&ring[start..start + first]);
+        lines.extend_from_slice(&ring[0..len - first]);
+
+        if position > capacity {
+            let skip = lines
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(lines.len(), |i| i + 1);
+            lines.drain(0..skip);
+        }
+        Ok(lines)
+    }
+
+    fn position(&self) -> &AtomicU64 {
+        // the mapping is page-aligned, so the position is properly aligned
+        #[allow(clippy::cast_ptr_alignment)]
+        unsafe {
+            &*(self.base.add(POSITION_OFFSET).cast::<AtomicU64>())
+        }
+    }
+}
+
+impl LogWriter for MmapRingWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut line = Vec::<u8>::with_capacity(200);
+        (self.format)(&mut line, now, record)?;
+        sanitize(&mut line);
+        line.push(b'\n');
+        let bytes = if line.len() > self.capacity {
+            &line[line.len() - self.capacity..]
+        } else {
+            &line[..]
+        };
+
+        let _guard = self.copy_lock.lock().map_err(|_e| io_err("Poison"))?;
+        let start = self
+            .position()
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = (start % self.capacity as u64) as usize;
+        let first = std::cmp::min(bytes.len(), self.capacity - offset);
+        unsafe {
+            let ring = self.base.add(HEADER_LEN);
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), ring.add(offset), first);
+            std::ptr::copy_nonoverlapping(bytes[first..].as_ptr(), ring, bytes.len() - first);
+        }
+        Ok(())
+    }
+
+    // The operating system writes the mapping to the file anyway,
+    // flushing only makes that happen earlier.
+    fn flush(&self) -> IoResult<()> {
+        self.mmap.flush_async()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+}
+
+/// Builder for [`MmapRingWriter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct MmapRingWriterBuilder {
+    path: PathBuf,
+    capacity: usize,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+}
+impl MmapRingWriterBuilder {
+    /// Sets the number of bytes of formatted output that are kept, defaults to 64 KB.
+    ///
+    /// Values below 1 KB are raised to 1 KB.
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = std::cmp::max(capacity, 1024);
+        self
+    }
+
+    /// Sets the format function, defaults to [`default_format`](crate::default_format).
+    ///
+    /// Note that [`Logger::format_for_writer`](crate::Logger::format_for_writer)
+    /// overrides this value.
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the maximum log level that is to be written, defaults to `Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Produces the `MmapRingWriter`, after renaming an existing file,
+    /// and creates and maps the file.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the file cannot be renamed, created, or mapped.
+    pub fn try_build(self) -> Result<MmapRingWriter, FlexiLoggerError> {
+        if self.path.exists() {
+            let mut prev_path = OsString::from(self.path.as_os_str());
+            prev_path.push(".prev");
+            std::fs::rename(&self.path, prev_path)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&self.path)?;
+        file.set_len((HEADER_LEN + self.capacity) as u64)?;
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        mmap[0..MAGIC.len()].copy_from_slice(MAGIC);
+        mmap[CAPACITY_OFFSET..CAPACITY_OFFSET + 8]
+            .copy_from_slice(&(self.capacity as u64).to_ne_bytes());
+        let base = mmap.as_mut_ptr();
+        Ok(MmapRingWriter {
+            mmap,
+            base,
+            copy_lock: Mutex::new(()),
+            capacity: self.capacity,
+            format: self.format,
+            max_log_level: self.max_log_level,
+        })
+    }
+}
+
+fn read_u64(content: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&content[offset..offset + 8]);
+    u64::from_ne_bytes(bytes)
+}
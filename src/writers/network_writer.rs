@@ -0,0 +1,491 @@
+use crate::deferred_now::DeferredNow;
+use crate::util::{eprint_err, eprint_msg, io_err, sanitize, ERRCODE};
+use crate::writers::log_writer::LogWriter;
+use crate::{default_format, FlexiLoggerError, FormatFunction};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const DEFAULT_BUFFER_CAPACITY: usize = 10_000;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+// upper limit for the bytes that are sent in one go
+const MAX_BATCH_SIZE: usize = 64 * 1024;
+
+/// Signature of a function that wraps a freshly connected `TcpStream`,
+/// e.g. into a TLS stream.
+///
+/// See [`NetworkWriterBuilder::wrap_stream`].
+pub type StreamWrapper =
+    Box<dyn Fn(TcpStream) -> IoResult<Box<dyn Write + Send>> + Send + Sync + 'static>;
+
+enum Message {
+    Line(Vec<u8>),
+    Flush,
+    Shutdown,
+}
+
+/// A [`LogWriter`] implementation that streams formatted log lines to a TCP endpoint,
+/// optionally via TLS.
+///
+/// The records are formatted in the logging thread and handed over to an output thread
+/// through a bounded buffer, so that logging never waits for the network.
+/// The output thread connects right away (see [`LogWriter::ready`]),
+/// and if the connection fails or drops,
+/// it reconnects with exponential backoff and then sends what was buffered in the meantime,
+/// including the lines whose transmission was interrupted (these may thus arrive twice).
+/// Only if the buffer runs full during an outage, records are dropped;
+/// they are counted (see [`NetworkWriter::dropped_records`]), and their number is reported
+/// once on stderr when the connection is up again, or with the next flush.
+/// A write that does not complete within the write timeout is treated like a lost connection,
+/// so that a collector that stops reading cannot block the shutdown of the writer.
+///
+/// Use [`NetworkWriter::builder`] to create an instance,
+/// and see the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::NetworkWriter, Logger};
+///
+/// let network_writer = NetworkWriter::builder("logs.example.com:5170")
+///     .max_log_level(log::LevelFilter::Info)
+///     .try_build()
+///     .unwrap();
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .add_writer("Remote", Box::new(network_writer))
+///     .start()
+///     .unwrap();
+/// ```
+pub struct NetworkWriter {
+    sender: SyncSender<Message>,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    dropped: Arc<AtomicU64>,
+    connected: Arc<AtomicBool>,
+    shutdown_requested: Arc<AtomicBool>,
+    mo_thread_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl NetworkWriter {
+    /// Returns a builder for a `NetworkWriter` that sends to the given address,
+    /// e.g. `"localhost:5170"`.
+    #[must_use]
+    pub fn builder<S: Into<String>>(address: S) -> NetworkWriterBuilder {
+        NetworkWriterBuilder {
+            address: address.into(),
+            format: default_format,
+            max_log_level: log::LevelFilter::Trace,
+            buffer_capacity: DEFAULT_BUFFER_CAPACITY,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            o_wrapper: None,
+        }
+    }
+
+    /// Returns the number of records that were dropped because the buffer was full,
+    /// or because they could not be sent before the writer was shut down.
+    #[must_use]
+    pub fn dropped_records(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl LogWriter for NetworkWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut line = Vec::<u8>::with_capacity(200);
+        (self.format)(&mut line, now, record)?;
+        sanitize(&mut line);
+        line.push(b'\n');
+        match self.sender.try_send(Message::Line(line)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                // is reported by the output thread
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                Err(IoError::new(
+                    ErrorKind::BrokenPipe,
+                    "network writer is shut down, record is dropped",
+                ))
+            }
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        // if the buffer is full, the output thread is busy anyway
+        self.sender.try_send(Message::Flush).ok();
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    // ready as long as the connection is up
+    fn ready(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    fn shutdown(&self) {
+        self.shutdown_requested.store(true, Ordering::Relaxed);
+        // fails if the output thread is already gone, or if the buffer is full;
+        // in the latter case, the output thread finds the flag when the buffer is empty
+        self.sender.try_send(Message::Shutdown).ok();
+        if let Ok(ref mut o_thread_handle) = self.mo_thread_handle.lock() {
+            if let Some(thread_handle) = o_thread_handle.take() {
+                thread_handle.join().ok();
+            }
+        }
+    }
+}
+
+impl Drop for NetworkWriter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Builder for [`NetworkWriter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct NetworkWriterBuilder {
+    address: String,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    buffer_capacity: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    connect_timeout: Duration,
+    write_timeout: Duration,
+    o_wrapper: Option<StreamWrapper>,
+}
+impl NetworkWriterBuilder {
+    /// Sets the format function, defaults to [`default_format`](crate::default_format).
+    ///
+    /// Note that [`Logger::format_for_writer`](crate::Logger::format_for_writer)
+    /// overrides this value.
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the maximum log level that is to be written, defaults to `Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Sets the number of records that can be buffered while the connection is down,
+    /// defaults to 10,000.
+    #[must_use]
+    pub fn buffer_capacity(mut self, buffer_capacity: usize) -> Self {
+        self.buffer_capacity = buffer_capacity;
+        self
+    }
+
+    /// Sets the waiting time before the first reconnection attempt, and the maximum
+    /// waiting time between reconnection attempts;
+    /// the waiting time is doubled after each failed attempt.
+    ///
+    /// Defaults to 100 milliseconds and 30 seconds.
+    #[must_use]
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the timeout for a single connection attempt, defaults to 5 seconds.
+    #[must_use]
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets the timeout for sending to the endpoint, defaults to 10 seconds.
+    ///
+    /// If a write does not complete in time, e.g. because the endpoint stopped reading,
+    /// the connection is dropped and re-established.
+    #[must_use]
+    pub fn write_timeout(mut self, write_timeout: Duration) -> Self {
+        self.write_timeout = write_timeout;
+        self
+    }
+
+    /// Provides a function that wraps each freshly connected `TcpStream`, which can be used
+    /// to add TLS.
+    ///
+    /// `flexi_logger` does not depend on a TLS implementation;
+    /// with e.g. the `native-tls` crate, the wrapper could look like this:
+    ///
+    /// ```rust,ignore
+    /// let connector = native_tls::TlsConnector::new()?;
+    /// let builder = NetworkWriter::builder("logs.example.com:6514").wrap_stream(
+    ///     move |stream| {
+    ///         connector
+    ///             .connect("logs.example.com", stream)
+    ///             .map(|tls_stream| Box::new(tls_stream) as Box<dyn std::io::Write + Send>)
+    ///             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn wrap_stream<F>(mut self, wrapper: F) -> Self
+    where
+        F: Fn(TcpStream) -> IoResult<Box<dyn Write + Send>> + Send + Sync + 'static,
+    {
+        self.o_wrapper = Some(Box::new(wrapper));
+        self
+    }
+
+    /// Produces the `NetworkWriter` and starts its output thread.
+    ///
+    /// The output thread starts connecting immediately.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the output thread cannot be spawned.
+    pub fn try_build(self) -> Result<NetworkWriter, FlexiLoggerError> {
+        let (sender, receiver) = sync_channel(self.buffer_capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let connected = Arc::new(AtomicBool::new(false));
+        let shutdown_requested = Arc::new(AtomicBool::new(false));
+        let connector = Connector {
+            address: self.address,
+            o_wrapper: self.o_wrapper,
+            initial_backoff: self.initial_backoff,
+            max_backoff: self.max_backoff,
+            connect_timeout: self.connect_timeout,
+            write_timeout: self.write_timeout,
+            connected: Arc::clone(&connected),
+            shutdown_requested: Arc::clone(&shutdown_requested),
+        };
+        let t_dropped = Arc::clone(&dropped);
+        let builder = std::thread::Builder::new().name("flexi_logger-network_writer".to_string());
+        let thread_handle =
+            builder.spawn(move || run_output_thread(&receiver, &connector, &t_dropped))?;
+
+        Ok(NetworkWriter {
+            sender,
+            format: self.format,
+            max_log_level: self.max_log_level,
+            dropped,
+            connected,
+            shutdown_requested,
+            mo_thread_handle: Mutex::new(Some(thread_handle)),
+        })
+    }
+}
+
+struct Connector {
+    address: String,
+    o_wrapper: Option<StreamWrapper>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    connect_timeout: Duration,
+    write_timeout: Duration,
+    connected: Arc<AtomicBool>,
+    shutdown_requested: Arc<AtomicBool>,
+}
+impl Connector {
+    fn connect(&self) -> IoResult<Box<dyn Write + Send>> {
+        let stream = self.connect_tcp()?;
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(self.write_timeout))?;
+        match self.o_wrapper {
+            Some(ref wrap) => wrap(stream),
+            None => Ok(Box::new(stream)),
+        }
+    }
+
+    // Tries the resolved addresses one after the other, each with the connect timeout.
+    fn connect_tcp(&self) -> IoResult<TcpStream> {
+        let mut last_error = io_err("address could not be resolved");
+        for addr in self.address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.connect_timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    // Tries to connect until it succeeds, with exponential backoff;
+    // returns None if the shutdown was requested in the meantime.
+    fn connect_with_backoff(&self) -> Option<Box<dyn Write + Send>> {
+        let mut backoff = self.initial_backoff;
+        let mut reported = false;
+        loop {
+            match self.connect() {
+                Ok(stream) => {
+                    self.connected.store(true, Ordering::Relaxed);
+                    return Some(stream);
+                }
+                Err(e) => {
+                    if !reported {
+                        eprint_err(
+                            ERRCODE::Write,
+                            &format!("network writer cannot connect to {}", self.address),
+                            &e,
+                        );
+                        reported = true;
+                    }
+                }
+            }
+            if !self.sleep_unless_shutdown(backoff) {
+                return None;
+            }
+            backoff = std::cmp::min(backoff * 2, self.max_backoff);
+        }
+    }
+
+    // Returns false if the shutdown was requested.
+    fn sleep_unless_shutdown(&self, duration: Duration) -> bool {
+        const SLICE: Duration = Duration::from_millis(50);
+        let mut remaining = duration;
+        while remaining > Duration::from_millis(0) {
+            if self.shutdown_requested.load(Ordering::Relaxed) {
+                return false;
+            }
+            let slice = std::cmp::min(remaining, SLICE);
+            std::thread::sleep(slice);
+            remaining -= slice;
+        }
+        !self.shutdown_requested.load(Ordering::Relaxed)
+    }
+}
+
+// Collects the lines in a batch, and sends the batch when no further lines are waiting;
+// a batch is only discarded after it was sent successfully.
+fn run_output_thread(receiver: &Receiver<Message>, connector: &Connector, dropped: &AtomicU64) {
+    let mut o_stream = connector.connect_with_backoff();
+    let mut batch = Vec::<u8>::with_capacity(MAX_BATCH_SIZE);
+    let mut lines_in_batch: u64 = 0;
+    let mut reported_drops: u64 = 0;
+    // shutdown was requested while the connection was down
+    let mut shutting_down = o_stream.is_none();
+
+    while !shutting_down {
+        let message = match receiver.try_recv() {
+            Ok(message) => message,
+            Err(TryRecvError::Empty) => {
+                if lines_in_batch > 0 {
+                    Message::Flush
+                } else if connector.shutdown_requested.load(Ordering::Relaxed) {
+                    // the shutdown message did not fit into the full buffer
+                    Message::Shutdown
+                } else {
+                    match receiver.recv() {
+                        Ok(message) => message,
+                        Err(_) => Message::Shutdown,
+                    }
+                }
+            }
+            Err(TryRecvError::Disconnected) => Message::Shutdown,
+        };
+        match message {
+            Message::Line(line) => {
+                batch.extend_from_slice(&line);
+                lines_in_batch += 1;
+                if batch.len() < MAX_BATCH_SIZE {
+                    continue;
+                }
+            }
+            Message::Flush => report_drops(dropped, &mut reported_drops),
+            Message::Shutdown => shutting_down = true,
+        }
+
+        if lines_in_batch > 0 {
+            if send_batch(&mut o_stream, &batch, connector) {
+                batch.clear();
+                lines_in_batch = 0;
+                // the connection is (again) up
+                report_drops(dropped, &mut reported_drops);
+            } else {
+                // shutdown was requested while the connection was down
+                shutting_down = true;
+            }
+        }
+    }
+
+    // whatever could not be sent is lost
+    let mut lost = lines_in_batch;
+    while let Ok(message) = receiver.try_recv() {
+        if let Message::Line(_) = message {
+            lost += 1;
+        }
+    }
+    if lost > 0 {
+        dropped.fetch_add(lost, Ordering::Relaxed);
+        eprint_msg(
+            ERRCODE::Write,
+            &format!(
+                "network writer was shut down with {} records that could not be sent",
+                lost
+            ),
+        );
+    }
+}
+
+// Reports the records that were dropped due to the full buffer since the previous report.
+fn report_drops(dropped: &AtomicU64, reported_drops: &mut u64) {
+    let dropped = dropped.load(Ordering::Relaxed);
+    if dropped > *reported_drops {
+        eprint_msg(
+            ERRCODE::Write,
+            &format!(
+                "network writer dropped {} records because its buffer was full",
+                dropped - *reported_drops
+            ),
+        );
+        *reported_drops = dropped;
+    }
+}
+
+// Returns false if the batch could not be sent because the shutdown was requested.
+fn send_batch(
+    o_stream: &mut Option<Box<dyn Write + Send>>,
+    batch: &[u8],
+    connector: &Connector,
+) -> bool {
+    loop {
+        if o_stream.is_none() {
+            *o_stream = connector.connect_with_backoff();
+        }
+        if let Some(stream) = o_stream {
+            match stream.write_all(batch).and_then(|()| stream.flush()) {
+                Ok(()) => return true,
+                Err(e) => {
+                    eprint_err(
+                        ERRCODE::Write,
+                        "network writer lost its connection, will reconnect",
+                        &e,
+                    );
+                    connector.connected.store(false, Ordering::Relaxed);
+                    *o_stream = None;
+                    if connector.shutdown_requested.load(Ordering::Relaxed) {
+                        return false;
+                    }
+                }
+            }
+        } else {
+            return false;
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use crate::deferred_now::DeferredNow;
+use crate::util::{io_err, sanitize};
+use crate::writers::log_writer::LogWriter;
+use crate::FlexiLoggerError;
+use rusqlite::{params, Connection};
+use std::io::Result as IoResult;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use time::format_description::well_known::Rfc3339;
+
+const DEFAULT_TABLE: &str = "log";
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// A [`LogWriter`] implementation that inserts the log records into a table
+/// of an `SQLite` database, so that they can be queried with SQL.
+///
+/// Only available with optional crate feature `sqlite`.
+///
+/// The table is created if it does not exist yet, with the columns
+///
+/// | column    | content                                                         |
+/// |-----------|-----------------------------------------------------------------|
+/// | `id`      | the row id, increasing with each record                          |
+/// | `ts`      | the timestamp, as RFC 3339 string                                |
+/// | `level`   | the level, like `ERROR` or `INFO`                                |
+/// | `target`  | the target of the record                                         |
+/// | `module`  | the module path, if available                                    |
+/// | `file`    | the source file, if available                                    |
+/// | `line`    | the line in the source file, if available                        |
+/// | `message` | the message                                                      |
+/// | `kv`      | the key-value pairs as JSON object (with crate feature `kv`), if there are any |
+///
+/// The records are collected and inserted in batches, each in a single transaction,
+/// when the batch is full, and when the writer is flushed or shut down.
+/// Records that are not yet inserted are lost if the program crashes.
+///
+/// With [`SqliteWriterBuilder::max_size`], the database file can be kept from growing
+/// without bound: when it exceeds the given size, the older half of the records is deleted,
+/// and the database file is compacted with `VACUUM`.
+///
+/// Use [`SqliteWriter::builder`] to create an instance,
+/// and see the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::SqliteWriter, Logger};
+///
+/// let sqlite_writer = SqliteWriter::builder("logs.db")
+///     .max_size(100 * 1024 * 1024)
+///     .try_build()
+///     .unwrap();
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .log_to_writer(Box::new(sqlite_writer))
+///     .start()
+///     .unwrap();
+/// ```
+///
+/// The records can then be queried e.g. with
+/// `sqlite3 logs.db "SELECT ts, message FROM log WHERE level = 'ERROR'"`.
+pub struct SqliteWriter {
+    m_state: Mutex<State>,
+    max_log_level: log::LevelFilter,
+}
+impl SqliteWriter {
+    /// Returns a builder for a `SqliteWriter` that writes into the database file
+    /// with the given path; the file is created if it does not exist.
+    #[must_use]
+    pub fn builder<P: Into<PathBuf>>(path: P) -> SqliteWriterBuilder {
+        SqliteWriterBuilder {
+            path: path.into(),
+            table: DEFAULT_TABLE.to_string(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            o_max_size: None,
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+}
+
+impl LogWriter for SqliteWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut message = record.args().to_string().into_bytes();
+        sanitize(&mut message);
+        let row = Row {
+            ts: now.now().format(&Rfc3339).map_err(io_err)?,
+            level: record.level().as_str(),
+            target: record.target().to_string(),
+            o_module: record.module_path().map(ToString::to_string),
+            o_file: record.file().map(ToString::to_string),
+            o_line: record.line(),
+            message: String::from_utf8_lossy(&message).into_owned(),
+            o_kv: key_values(record)?,
+        };
+
+        let mut state = self.lock_state()?;
+        state.pending.push(row);
+        if state.pending.len() >= state.batch_size {
+            state.insert_pending()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        self.lock_state()?.insert_pending()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn shutdown(&self) {
+        self.flush().ok();
+    }
+}
+impl SqliteWriter {
+    fn lock_state(&self) -> IoResult<std::sync::MutexGuard<'_, State>> {
+        self.m_state.lock().map_err(|_e| io_err("Poison"))
+    }
+}
+impl Drop for SqliteWriter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Builder for [`SqliteWriter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct SqliteWriterBuilder {
+    path: PathBuf,
+    table: String,
+    batch_size: usize,
+    o_max_size: Option<u64>,
+    max_log_level: log::LevelFilter,
+}
+impl SqliteWriterBuilder {
+    /// Sets the name of the table, defaults to `log`.
+    #[must_use]
+    pub fn table<S: Into<String>>(mut self, table: S) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Sets the number of records that are inserted together in one transaction,
+    /// defaults to 100.
+    ///
+    /// With 1, each record is inserted immediately.
+    #[must_use]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = std::cmp::max(batch_size, 1);
+        self
+    }
+
+    /// Sets the size in bytes above which the older half of the records is deleted
+    /// and the database file is compacted.
+    ///
+    /// By default, the database file grows without bound.
+    #[must_use]
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.o_max_size = Some(max_size);
+        self
+    }
+
+    /// Sets the maximum log level that is to be written, defaults to `Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Opens the database and creates the table, if necessary.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Sqlite` if the database cannot be opened or the table cannot be created.
+    pub fn try_build(self) -> Result<SqliteWriter, FlexiLoggerError> {
+        let connection = Connection::open(&self.path).map_err(FlexiLoggerError::Sqlite)?;
+        let table = quote_identifier(&self.table);
+        connection
+            .execute_batch(&format!(
+                "PRAGMA journal_mode = WAL;
+                 CREATE TABLE IF NOT EXISTS {} (
+                     id INTEGER PRIMARY KEY,
+                     ts TEXT NOT NULL,
+                     level TEXT NOT NULL,
+                     target TEXT NOT NULL,
+                     module TEXT,
+                     file TEXT,
+                     line INTEGER,
+                     message TEXT NOT NULL,
+                     kv TEXT
+                 );",
+                table
+            ))
+            .map_err(FlexiLoggerError::Sqlite)?;
+        Ok(SqliteWriter {
+            m_state: Mutex::new(State {
+                connection,
+                insert: format!(
+                    "INSERT INTO {} (ts, level, target, module, file, line, message, kv) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    table
+                ),
+                table,
+                batch_size: self.batch_size,
+                o_max_size: self.o_max_size,
+                pending: Vec::with_capacity(self.batch_size),
+            }),
+            max_log_level: self.max_log_level,
+        })
+    }
+}
+
+struct State {
+    connection: Connection,
+    // the quoted table name
+    table: String,
+    insert: String,
+    batch_size: usize,
+    o_max_size: Option<u64>,
+    pending: Vec<Row>,
+}
+impl State {
+    // Inserts the pending records in a single transaction;
+    // if this fails, the records are discarded, and the error is returned.
+    fn insert_pending(&mut self) -> IoResult<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let rows = std::mem::take(&mut self.pending);
+        self.insert(&rows)
+            .and_then(|()| self.limit_size())
+            .map_err(io_err)
+    }
+
+    fn insert(&mut self, rows: &[Row]) -> rusqlite::Result<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(&self.insert)?;
+            for row in rows {
+                statement.execute(params![
+                    row.ts,
+                    row.level,
+                    row.target,
+                    row.o_module,
+                    row.o_file,
+                    row.o_line,
+                    row.message,
+                    row.o_kv,
+                ])?;
+            }
+        }
+        transaction.commit()
+    }
+
+    // Deletes the older half of the records and compacts the database file,
+    // if the file is larger than allowed.
+    fn limit_size(&mut self) -> rusqlite::Result<()> {
+        if let Some(max_size) = self.o_max_size {
+            let size: i64 = self.connection.query_row(
+                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                [],
+                |row| row.get(0),
+            )?;
+            #[allow(clippy::cast_sign_loss)]
+            let size = size as u64;
+            if size > max_size {
+                self.connection.execute(
+                    &format!(
+                        "DELETE FROM {0} WHERE id <= (SELECT (MIN(id) + MAX(id)) / 2 FROM {0})",
+                        self.table
+                    ),
+                    [],
+                )?;
+                self.connection.execute_batch("VACUUM;")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Row {
+    ts: String,
+    level: &'static str,
+    target: String,
+    o_module: Option<String>,
+    o_file: Option<String>,
+    o_line: Option<u32>,
+    message: String,
+    o_kv: Option<String>,
+}
+
+// Quotes a table name for the use in SQL statements.
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(feature = "kv")]
+fn key_values(record: &log::Record) -> IoResult<Option<String>> {
+    if record.key_values().count() == 0 {
+        return Ok(None);
+    }
+    let mut buffer = Vec::<u8>::with_capacity(100);
+    crate::formats::write_json_key_value_object(&mut buffer, record)?;
+    Ok(Some(String::from_utf8_lossy(&buffer).into_owned()))
+}
+
+#[cfg(not(feature = "kv"))]
+#[allow(clippy::unnecessary_wraps)]
+fn key_values(_record: &log::Record) -> IoResult<Option<String>> {
+    Ok(None)
+}
@@ -0,0 +1,105 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::LogWriter;
+use crate::FormatFunction;
+use log::Record;
+
+/// A [`LogWriter`] that hands every record over to several other log writers.
+///
+/// This allows using a group of writers wherever a single writer is expected,
+/// e.g. as additional writer in [`Logger::add_writer`](crate::Logger::add_writer):
+///
+/// ```rust
+/// # use flexi_logger::{writers::{FileLogWriter, TeeWriter}, FileSpec, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let alert_file = FileLogWriter::builder(
+///     FileSpec::default()
+/// #       .directory("log_files/tee_writer_docu")
+///         .discriminant("alerts"),
+/// )
+/// .try_build()?;
+/// let audit_file = FileLogWriter::builder(
+///     FileSpec::default()
+/// #       .directory("log_files/tee_writer_docu")
+///         .discriminant("audit"),
+/// )
+/// .try_build()?;
+/// let _logger = Logger::try_with_str("info")?
+///     .add_writer(
+///         "Alert",
+///         Box::new(TeeWriter::new(vec![Box::new(alert_file), Box::new(audit_file)])),
+///     )
+///     .start()?;
+/// log::error!(target: "{Alert}", "This goes into both files");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A record is written by each writer whose
+/// [`max_log_level`](LogWriter::max_log_level) admits it.
+/// If some writers fail, the other writers are nevertheless served, and the first error
+/// is returned.
+pub struct TeeWriter {
+    writers: Vec<Box<dyn LogWriter>>,
+}
+impl TeeWriter {
+    /// Creates a `TeeWriter` that hands the records over to all the given writers.
+    #[must_use]
+    pub fn new(writers: Vec<Box<dyn LogWriter>>) -> Self {
+        Self { writers }
+    }
+
+    // Applies the function to all writers, and returns the first error, if any.
+    fn for_all<F>(&self, mut f: F) -> std::io::Result<()>
+    where
+        F: FnMut(&dyn LogWriter) -> std::io::Result<()>,
+    {
+        let mut result = Ok(());
+        for writer in &self.writers {
+            let r = f(writer.as_ref());
+            if result.is_ok() {
+                result = r;
+            }
+        }
+        result
+    }
+}
+
+impl LogWriter for TeeWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.for_all(|writer| {
+            if record.level() <= writer.max_log_level() {
+                writer.write(now, record)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.for_all(|writer| writer.flush())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.writers
+            .iter()
+            .map(|writer| writer.max_log_level())
+            .max()
+            .unwrap_or(log::LevelFilter::Off)
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        for writer in &mut self.writers {
+            writer.format(format);
+        }
+    }
+
+    fn ready(&self) -> bool {
+        self.writers.iter().all(|writer| writer.ready())
+    }
+
+    fn shutdown(&self) {
+        for writer in &self.writers {
+            writer.shutdown();
+        }
+    }
+}
@@ -0,0 +1,513 @@
+use crate::deferred_now::DeferredNow;
+use crate::formats::write_json_string;
+use crate::util::{eprint_err, io_err, sanitize, ERRCODE};
+use crate::writers::log_writer::LogWriter;
+use crate::{default_format, json_format, FlexiLoggerError, FormatFunction};
+use std::io::{BufRead, BufReader, Read, Result as IoResult, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_BATCH_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_RECORDS: usize = 20;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A bidirectional stream over which the [`WebhookWriter`] sends its HTTP requests.
+///
+/// Is implemented for all types that implement `Read`, `Write`, and `Send`.
+pub trait HttpStream: Read + Write + Send {}
+impl<T: Read + Write + Send> HttpStream for T {}
+
+/// Signature of a function that wraps a freshly connected `TcpStream`,
+/// e.g. into a TLS stream; the first parameter is the host name of the endpoint.
+///
+/// See [`WebhookWriterBuilder::wrap_stream`].
+pub type HttpStreamWrapper =
+    Box<dyn Fn(&str, TcpStream) -> IoResult<Box<dyn HttpStream>> + Send + Sync + 'static>;
+
+/// The structure of the JSON payloads that the [`WebhookWriter`] posts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookPayload {
+    /// `{"text":"..."}`, with the log lines formatted with the format function of the writer,
+    /// separated by line breaks.
+    ///
+    /// This is understood by the incoming webhooks of Slack and of Microsoft Teams.
+    Text,
+    /// `{"records":[...],"omitted":0}`, with the records formatted with
+    /// [`json_format`](crate::json_format), and the number of records that were
+    /// omitted because the batch was full.
+    Records,
+}
+
+enum Message {
+    Line(String),
+    Shutdown,
+}
+
+/// A [`LogWriter`] implementation that posts the records of a high level
+/// (by default: errors) as JSON payload to a webhook, e.g. of Slack or Microsoft Teams.
+///
+/// To avoid flooding the receiver, the records are collected in batches:
+/// a batch is posted when the first of its records is a second old
+/// (see [`WebhookWriterBuilder::batch_delay`]), but not sooner than
+/// ten seconds after the previous post (see [`WebhookWriterBuilder::min_interval`]);
+/// of a burst of errors, only the first records are sent with their text
+/// (see [`WebhookWriterBuilder::max_records`]), the others are only counted.
+/// Pending records are posted when the writer is shut down.
+///
+/// The posts are done by an output thread, so that logging never waits for the network.
+/// Failed posts are reported, but not retried.
+///
+/// The requests are sent with HTTP/1.1. `flexi_logger` does not depend on a TLS
+/// implementation, so for `https` endpoints you need to provide a wrapper with
+/// [`WebhookWriterBuilder::wrap_stream`].
+///
+/// Use [`WebhookWriter::builder`] to create an instance,
+/// and see the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::WebhookWriter, Logger};
+///
+/// let webhook_writer = WebhookWriter::builder("http://alerts.example.com/hooks/logs")
+///     .min_level(log::Level::Warn)
+///     .try_build()
+///     .unwrap();
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .log_to_stderr()
+///     .add_writer("Alert", Box::new(webhook_writer))
+///     .also_send_to(&["Alert"], log::LevelFilter::Warn)
+///     .start()
+///     .unwrap();
+/// ```
+pub struct WebhookWriter {
+    sender: Mutex<Sender<Message>>,
+    format: FormatFunction,
+    min_level: log::Level,
+    mo_thread_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl WebhookWriter {
+    /// Returns a builder for a `WebhookWriter` that posts to the given URL,
+    /// e.g. `"https://hooks.slack.com/services/T000/B000/XXXX"`.
+    #[must_use]
+    pub fn builder<S: Into<String>>(url: S) -> WebhookWriterBuilder {
+        WebhookWriterBuilder {
+            url: url.into(),
+            format: default_format,
+            payload: WebhookPayload::Text,
+            min_level: log::Level::Error,
+            min_interval: DEFAULT_MIN_INTERVAL,
+            batch_delay: DEFAULT_BATCH_DELAY,
+            max_records: DEFAULT_MAX_RECORDS,
+            timeout: DEFAULT_TIMEOUT,
+            headers: Vec::new(),
+            o_wrapper: None,
+        }
+    }
+}
+
+impl LogWriter for WebhookWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        if record.level() > self.min_level {
+            return Ok(());
+        }
+        let mut line = Vec::<u8>::with_capacity(200);
+        (self.format)(&mut line, now, record)?;
+        sanitize(&mut line);
+        self.sender
+            .lock()
+            .map_err(|_e| io_err("Poison"))?
+            .send(Message::Line(String::from_utf8_lossy(&line).into_owned()))
+            .map_err(|_e| io_err("webhook writer is shut down"))
+    }
+
+    // The records are posted according to the batching rules.
+    fn flush(&self) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.min_level.to_level_filter()
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    fn shutdown(&self) {
+        if let Ok(sender) = self.sender.lock() {
+            // fails if the output thread is already gone
+            sender.send(Message::Shutdown).ok();
+        }
+        if let Ok(ref mut o_thread_handle) = self.mo_thread_handle.lock() {
+            if let Some(thread_handle) = o_thread_handle.take() {
+                thread_handle.join().ok();
+            }
+        }
+    }
+}
+
+impl Drop for WebhookWriter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Builder for [`WebhookWriter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct WebhookWriterBuilder {
+    url: String,
+    format: FormatFunction,
+    payload: WebhookPayload,
+    min_level: log::Level,
+    min_interval: Duration,
+    batch_delay: Duration,
+    max_records: usize,
+    timeout: Duration,
+    headers: Vec<(String, String)>,
+    o_wrapper: Option<HttpStreamWrapper>,
+}
+impl WebhookWriterBuilder {
+    /// Sets the format function for the payload [`WebhookPayload::Text`],
+    /// defaults to [`default_format`](crate::default_format).
+    ///
+    /// Note that [`Logger::format_for_writer`](crate::Logger::format_for_writer)
+    /// overrides this value.
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the structure of the payloads, defaults to [`WebhookPayload::Text`].
+    #[must_use]
+    pub fn payload(mut self, payload: WebhookPayload) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Sets the minimum level of the records that are posted, defaults to `Error`.
+    #[must_use]
+    pub fn min_level(mut self, min_level: log::Level) -> Self {
+        self.min_level = min_level;
+        self
+    }
+
+    /// Sets the minimum time between two posts, defaults to 10 seconds.
+    #[must_use]
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Sets how long the first record of a batch waits for further records,
+    /// defaults to one second.
+    #[must_use]
+    pub fn batch_delay(mut self, batch_delay: Duration) -> Self {
+        self.batch_delay = batch_delay;
+        self
+    }
+
+    /// Sets the maximum number of records whose text is sent in one post,
+    /// defaults to 20; further records of the batch are only counted.
+    #[must_use]
+    pub fn max_records(mut self, max_records: usize) -> Self {
+        self.max_records = std::cmp::max(max_records, 1);
+        self
+    }
+
+    /// Sets the timeout for connecting, sending, and receiving the response,
+    /// defaults to 10 seconds.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Adds a header to the requests, e.g. for authorization.
+    #[must_use]
+    pub fn header<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Provides a function that wraps each freshly connected `TcpStream`, which is
+    /// necessary for `https` endpoints.
+    ///
+    /// `flexi_logger` does not depend on a TLS implementation;
+    /// with e.g. the `native-tls` crate, the wrapper could look like this:
+    ///
+    /// ```rust,ignore
+    /// let connector = native_tls::TlsConnector::new()?;
+    /// let builder = WebhookWriter::builder("https://hooks.slack.com/services/T000/B000/XXXX")
+    ///     .wrap_stream(move |host, stream| {
+    ///         connector
+    ///             .connect(host, stream)
+    ///             .map(|tls_stream| Box::new(tls_stream) as Box<dyn HttpStream>)
+    ///             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+    ///     });
+    /// ```
+    #[must_use]
+    pub fn wrap_stream<F>(mut self, wrapper: F) -> Self
+    where
+        F: Fn(&str, TcpStream) -> IoResult<Box<dyn HttpStream>> + Send + Sync + 'static,
+    {
+        self.o_wrapper = Some(Box::new(wrapper));
+        self
+    }
+
+    /// Produces the `WebhookWriter` and starts its output thread.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::WebhookUrl` if the URL is not valid,
+    /// or if it uses `https` and no wrapper is provided.
+    ///
+    /// `FlexiLoggerError::OutputIo` if the output thread cannot be spawned.
+    pub fn try_build(self) -> Result<WebhookWriter, FlexiLoggerError> {
+        let endpoint = Endpoint::parse(&self.url)?;
+        if endpoint.https && self.o_wrapper.is_none() {
+            return Err(FlexiLoggerError::WebhookUrl(format!(
+                "{} (https needs a stream wrapper)",
+                self.url
+            )));
+        }
+        let poster = Poster {
+            endpoint,
+            headers: self.headers,
+            timeout: self.timeout,
+            o_wrapper: self.o_wrapper,
+        };
+        let batching = Batching {
+            payload: self.payload,
+            min_interval: self.min_interval,
+            batch_delay: self.batch_delay,
+            max_records: self.max_records,
+        };
+        let (sender, receiver) = channel();
+        let builder = std::thread::Builder::new().name("flexi_logger-webhook_writer".to_string());
+        let thread_handle =
+            builder.spawn(move || run_output_thread(&receiver, &batching, &poster))?;
+
+        Ok(WebhookWriter {
+            sender: Mutex::new(sender),
+            format: match self.payload {
+                WebhookPayload::Text => self.format,
+                WebhookPayload::Records => json_format,
+            },
+            min_level: self.min_level,
+            mo_thread_handle: Mutex::new(Some(thread_handle)),
+        })
+    }
+}
+
+struct Batching {
+    payload: WebhookPayload,
+    min_interval: Duration,
+    batch_delay: Duration,
+    max_records: usize,
+}
+
+#[derive(Default)]
+struct Batch {
+    lines: Vec<String>,
+    omitted: usize,
+    o_start: Option<Instant>,
+}
+impl Batch {
+    fn add(&mut self, line: String, max_records: usize) {
+        if self.o_start.is_none() {
+            self.o_start = Some(Instant::now());
+        }
+        if self.lines.len() < max_records {
+            self.lines.push(line);
+        } else {
+            self.omitted += 1;
+        }
+    }
+
+    fn payload(&self, payload: WebhookPayload) -> IoResult<Vec<u8>> {
+        let mut body = Vec::<u8>::with_capacity(200 * (self.lines.len() + 1));
+        match payload {
+            WebhookPayload::Text => {
+                let mut text = self.lines.join("\n");
+                if self.omitted > 0 {
+                    text = format!("{text}\n... and {} more records", self.omitted);
+                }
+                body.write_all(b"{\"text\":")?;
+                write_json_string(&mut body, &text)?;
+                body.write_all(b"}")?;
+            }
+            WebhookPayload::Records => {
+                write!(body, "{{\"records\":[{}],", self.lines.join(","))?;
+                write!(body, "\"omitted\":{}}}", self.omitted)?;
+            }
+        }
+        Ok(body)
+    }
+}
+
+// Collects the lines into batches, and posts them according to the batching rules.
+fn run_output_thread(receiver: &Receiver<Message>, batching: &Batching, poster: &Poster) {
+    let mut batch = Batch::default();
+    let mut o_last_post: Option<Instant> = None;
+    loop {
+        let message = match batch.o_start {
+            None => receiver.recv().unwrap_or(Message::Shutdown),
+            Some(start) => {
+                let mut due = start + batching.batch_delay;
+                if let Some(last_post) = o_last_post {
+                    due = std::cmp::max(due, last_post + batching.min_interval);
+                }
+                match receiver.recv_timeout(due.saturating_duration_since(Instant::now())) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => {
+                        post(&std::mem::take(&mut batch), batching.payload, poster);
+                        o_last_post = Some(Instant::now());
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => Message::Shutdown,
+                }
+            }
+        };
+        match message {
+            Message::Line(line) => batch.add(line, batching.max_records),
+            Message::Shutdown => {
+                if batch.o_start.is_some() {
+                    post(&batch, batching.payload, poster);
+                }
+                return;
+            }
+        }
+    }
+}
+
+fn post(batch: &Batch, payload: WebhookPayload, poster: &Poster) {
+    batch
+        .payload(payload)
+        .and_then(|body| poster.post(&body))
+        .unwrap_or_else(|e| {
+            eprint_err(
+                ERRCODE::Write,
+                &format!(
+                    "webhook writer failed to post {} records",
+                    batch.lines.len() + batch.omitted
+                ),
+                &e,
+            );
+        });
+}
+
+struct Endpoint {
+    https: bool,
+    host: String,
+    port: u16,
+    // path and query
+    path: String,
+}
+impl Endpoint {
+    fn parse(url: &str) -> Result<Self, FlexiLoggerError> {
+        let invalid = || FlexiLoggerError::WebhookUrl(url.to_string());
+        let (https, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(invalid());
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rfind(':') {
+            Some(index) => (
+                &authority[..index],
+                authority[index + 1..].parse().map_err(|_e| invalid())?,
+            ),
+            None => (authority, if https { 443 } else { 80 }),
+        };
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            https,
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    // The value of the Host header, with the port if it is not the default port of the scheme.
+    fn host_header(&self) -> String {
+        if self.port == if self.https { 443 } else { 80 } {
+            self.host.clone()
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+struct Poster {
+    endpoint: Endpoint,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+    o_wrapper: Option<HttpStreamWrapper>,
+}
+impl Poster {
+    // Sends the body with a POST request, and checks that the response has a success status.
+    fn post(&self, body: &[u8]) -> IoResult<()> {
+        let endpoint = &self.endpoint;
+        let stream = self.connect()?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let mut stream: Box<dyn HttpStream> = match self.o_wrapper {
+            Some(ref wrap) => wrap(&endpoint.host, stream)?,
+            None => Box::new(stream),
+        };
+
+        let mut request = Vec::<u8>::with_capacity(200 + body.len());
+        write!(
+            request,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n",
+            endpoint.path,
+            endpoint.host_header(),
+            body.len()
+        )?;
+        for (name, value) in &self.headers {
+            write!(request, "{name}: {value}\r\n")?;
+        }
+        request.write_all(b"\r\n")?;
+        request.write_all(body)?;
+        stream.write_all(&request)?;
+        stream.flush()?;
+
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line)?;
+        match status_line.split_whitespace().nth(1) {
+            Some(status) if status.starts_with('2') => Ok(()),
+            _ => Err(io_err(format!(
+                "webhook responded with \"{}\"",
+                status_line.trim_end()
+            ))),
+        }
+    }
+
+    // Connects with the timeout, trying each address that the host resolves to.
+    fn connect(&self) -> IoResult<TcpStream> {
+        let endpoint = &self.endpoint;
+        let mut last_error = io_err("address could not be resolved");
+        for addr in (endpoint.host.as_str(), endpoint.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+}
@@ -0,0 +1,68 @@
+mod test_utils;
+
+#[cfg(all(feature = "admin_socket", unix))]
+mod a {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{AdminSocket, DeferredNow, Logger};
+    use log::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_admin_socket() {
+        let socket = super::test_utils::file("logctl.sock");
+        std::fs::create_dir_all(socket.parent().unwrap()).unwrap();
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_writer(Box::new(SharedWriter(Arc::clone(&output))))
+            .admin_socket(AdminSocket::Unix(socket.clone()))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        debug!("debug-0");
+        assert_eq!(request(&socket, ""), "info");
+
+        assert_eq!(
+            request(&socket, "debug, test_admin_socket::b=trace"),
+            "debug,test_admin_socket::b=trace"
+        );
+        debug!("debug-1");
+        trace!("trace-1");
+
+        // a malformed spec is rejected, the previous one stays active
+        assert!(request(&socket, "debug, foo=bar").starts_with("error:"));
+        assert_eq!(request(&socket, ""), "debug,test_admin_socket::b=trace");
+
+        // an overlong request is rejected, too
+        let long_spec = format!("trace, {}", "x=info, ".repeat(2000));
+        assert!(request(&socket, &long_spec).starts_with("error:"));
+        assert_eq!(request(&socket, ""), "debug,test_admin_socket::b=trace");
+        debug!("debug-2");
+
+        assert_eq!(*output.lock().unwrap(), vec!["debug-1", "debug-2"]);
+    }
+
+    fn request(socket: &Path, line: &str) -> String {
+        let mut stream = UnixStream::connect(socket).unwrap();
+        stream.write_all(format!("{}\n", line).as_bytes()).unwrap();
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).unwrap();
+        response.trim_end().to_string()
+    }
+
+    struct SharedWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for SharedWriter {
+        fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(record.args().to_string());
+            Ok(())
+        }
+
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,39 @@
+mod test_utils;
+
+use flexi_logger::{Age, Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_age_append_restart() {
+    let dir = self::test_utils::dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let current = dir.join("restart_rCURRENT.log");
+    let sidecar = dir.join("restart_rCURRENT.log.created_at");
+
+    // a left-over file from a previous run, which was created long ago
+    std::fs::write(&current, "line from the previous run\n").unwrap();
+    std::fs::write(&sidecar, "2020-01-01T00:00:00Z").unwrap();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).basename("restart"))
+        .rotate(Criterion::Age(Age::Day), Naming::Numbers, Cleanup::Never)
+        .append()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    info!("line from this run");
+    logger.flush();
+
+    // the stale file was rotated, although the program was just started
+    assert_eq!(
+        std::fs::read_to_string(dir.join("restart_r00000.log")).unwrap(),
+        "line from the previous run\n"
+    );
+    assert!(!std::fs::read_to_string(&current)
+        .unwrap()
+        .contains("previous run"));
+    assert!(!std::fs::read_to_string(&sidecar)
+        .unwrap()
+        .starts_with("2020"));
+    logger.shutdown();
+}
@@ -0,0 +1,83 @@
+mod test_utils;
+
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, DeferredNow, FileSpec, FlexiLoggerError, Naming};
+use std::time::Duration;
+use time::{OffsetDateTime, UtcOffset};
+
+#[test]
+fn test_age_at_time() {
+    // a time of day that is reached soon
+    let time_of_day = (OffsetDateTime::now_utc() + Duration::from_secs(2)).time();
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(self::test_utils::dir())
+            .discriminant("at_time"),
+    )
+    .rotate(
+        Criterion::AgeAtTime {
+            time_of_day: (
+                time_of_day.hour(),
+                time_of_day.minute(),
+                time_of_day.second(),
+            ),
+            offset: UtcOffset::UTC,
+        },
+        Naming::Numbers,
+        Cleanup::Never,
+    )
+    .try_build()
+    .unwrap();
+
+    write(&flw, "This is the first line");
+    assert_eq!(flw.bytes_until_rotation(), None);
+    let time_until_rotation = flw.time_until_rotation().unwrap();
+    assert!(time_until_rotation <= Duration::from_secs(2));
+    write(&flw, "This is the second line");
+    assert_eq!(flw.stats().rotations, 0);
+
+    std::thread::sleep(time_until_rotation + Duration::from_millis(100));
+    assert!(flw.rotation_imminent());
+    write(&flw, "This line goes into a fresh file");
+    assert_eq!(flw.stats().rotations, 1);
+
+    // the next rotation happens a day later
+    let time_until_rotation = flw.time_until_rotation().unwrap();
+    assert!(time_until_rotation > Duration::from_secs(24 * 60 * 60 - 10));
+    assert!(time_until_rotation <= Duration::from_secs(24 * 60 * 60));
+    write(&flw, "This is another line");
+    assert_eq!(flw.stats().rotations, 1);
+}
+
+#[test]
+fn test_age_at_time_invalid() {
+    let result = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(self::test_utils::dir())
+            .discriminant("invalid"),
+    )
+    .rotate(
+        Criterion::AgeAtTime {
+            time_of_day: (24, 0, 0),
+            offset: UtcOffset::UTC,
+        },
+        Naming::Numbers,
+        Cleanup::Never,
+    )
+    .try_build();
+    assert!(matches!(
+        result,
+        Err(FlexiLoggerError::OutputRotationTimeOfDay((24, 0, 0)))
+    ));
+}
+
+fn write(flw: &FileLogWriter, message: &str) {
+    flw.write(
+        &mut DeferredNow::new(),
+        &log::Record::builder()
+            .args(format_args!("{}", message))
+            .level(log::Level::Info)
+            .build(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,42 @@
+mod test_utils;
+
+use flexi_logger::{existing_handle, FileSpec, FlexiLoggerError, Logger};
+use log::*;
+
+#[test]
+fn test_already_initialized() {
+    assert!(existing_handle().is_none());
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(self::test_utils::dir()))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This is an info message");
+
+    // the failed initialization must not change the settings of the running logger
+    match Logger::try_with_str("trace")
+        .unwrap()
+        .redact(|line| line.clear())
+        .max_record_len(5)
+        .start()
+    {
+        Err(FlexiLoggerError::AlreadyInitialized) => {}
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Second initialization must fail"),
+    }
+
+    {
+        // the existing handle can be used and dropped without shutting down the logger
+        let mut handle = existing_handle().expect("existing handle is not available");
+        handle.parse_new_spec("warn").unwrap();
+    }
+    info!("This is an info message - you must not see it!");
+    warn!("This is a warning");
+
+    logger.validate_logs(&[
+        ("INFO", "test_already_initialized", "info message"),
+        ("WARN", "test_already_initialized", "warning"),
+    ]);
+}
@@ -0,0 +1,55 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::PathBuf;
+
+#[test]
+fn test_also_log_errors_to() {
+    let dir = self::test_utils::dir();
+    let logger = Logger::try_with_str("debug")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir))
+        .also_log_errors_to(FileSpec::default().directory(&dir).discriminant("ERR"))
+        .rotate(Criterion::Size(500), Naming::Numbers, Cleanup::Never)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..10 {
+        error!("error {}", i);
+        warn!("warning {}", i);
+        info!("info {}", i);
+        debug!("debug {}", i);
+    }
+    logger.flush();
+
+    let main_files = files(&dir, |name| !name.contains("_ERR_"));
+    let error_files = files(&dir, |name| name.contains("_ERR_"));
+    // both writers rotate on their own
+    assert!(main_files.len() > error_files.len());
+    assert!(error_files.len() > 1);
+
+    let main_content = content(&main_files);
+    let error_content = content(&error_files);
+    assert_eq!(main_content.lines().count(), 40);
+    assert_eq!(error_content.lines().count(), 20);
+    assert!(error_content.contains("error 9"));
+    assert!(error_content.contains("warning 9"));
+    assert!(!error_content.contains("info"));
+    assert!(!error_content.contains("debug"));
+}
+
+fn files<F: Fn(&str) -> bool>(dir: &PathBuf, predicate: F) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| predicate(&path.file_name().unwrap().to_string_lossy()))
+        .collect()
+}
+
+fn content(files: &[PathBuf]) -> String {
+    files
+        .iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect()
+}
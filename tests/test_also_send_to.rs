@@ -0,0 +1,61 @@
+use flexi_logger::writers::{LogWriter, TeeWriter};
+use flexi_logger::{DeferredNow, FlexiLoggerError, LevelFilter, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_also_send_to() {
+    // a broadcast to an unknown writer is rejected
+    assert!(matches!(
+        Logger::try_with_str("info")
+            .unwrap()
+            .also_send_to(&["alerts"], LevelFilter::Warn)
+            .build(),
+        Err(FlexiLoggerError::UnknownWriter(ref writer_name)) if writer_name == "alerts"
+    ));
+
+    let main = Arc::new(Mutex::new(Vec::new()));
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let audit_1 = Arc::new(Mutex::new(Vec::new()));
+    let audit_2 = Arc::new(Mutex::new(Vec::new()));
+    let _logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(SharedWriter(Arc::clone(&main))))
+        .add_writer("alerts", Box::new(SharedWriter(Arc::clone(&alerts))))
+        .add_writer(
+            "audit",
+            Box::new(TeeWriter::new(vec![
+                Box::new(SharedWriter(Arc::clone(&audit_1))),
+                Box::new(SharedWriter(Arc::clone(&audit_2))),
+            ])),
+        )
+        .also_send_to(&["alerts", "audit"], LevelFilter::Warn)
+        .also_send_to(&["audit"], LevelFilter::Info)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("error-1");
+    info!("info-1");
+    debug!("debug-1");
+    error!(target: "{alerts}", "explicitly addressed");
+
+    assert_eq!(*main.lock().unwrap(), vec!["error-1", "info-1"]);
+    assert_eq!(
+        *alerts.lock().unwrap(),
+        vec!["error-1", "explicitly addressed"]
+    );
+    assert_eq!(*audit_1.lock().unwrap(), vec!["error-1", "info-1"]);
+    assert_eq!(*audit_2.lock().unwrap(), vec!["error-1", "info-1"]);
+}
+
+struct SharedWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for SharedWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0.lock().unwrap().push(record.args().to_string());
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+mod test_utils;
+
+#[cfg(feature = "async")]
+mod d {
+    use flexi_logger::writers::{FileLogWriter, LogWriter};
+    use flexi_logger::{DeferredNow, FileSpec, OverflowPolicy, WriteMode};
+    use std::io::{BufRead, BufReader};
+    use std::time::Duration;
+
+    const MESSAGE_CAPA: usize = 16;
+    // the hard limit for the length of a log line in async mode
+    const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+    #[test]
+    fn test_async_long_messages() {
+        let flw = FileLogWriter::builder(
+            FileSpec::default()
+                .directory(super::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .write_mode(WriteMode::AsyncWith {
+            bufsize: 64,
+            pool_capa: 2,
+            message_capa: MESSAGE_CAPA,
+            flush_interval: Duration::from_secs(0),
+            channel_capa: None,
+            overflow_policy: OverflowPolicy::Block,
+        })
+        .try_build()
+        .unwrap();
+
+        let lengths = [
+            1,
+            MESSAGE_CAPA - 1,
+            MESSAGE_CAPA,
+            1_000,
+            100_000,
+            3,
+            100_000,
+            2 * MAX_MESSAGE_LEN,
+        ];
+        let messages: Vec<String> = lengths
+            .iter()
+            .enumerate()
+            .map(|(i, len)| {
+                std::iter::repeat((b'a' + i as u8) as char)
+                    .take(*len)
+                    .collect()
+            })
+            .collect();
+
+        for message in &messages {
+            flw.write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("{}", message))
+                    .level(log::Level::Info)
+                    .build(),
+            )
+            .unwrap();
+        }
+        flw.shutdown();
+
+        let lines: Vec<String> =
+            BufReader::new(std::fs::File::open(flw.current_filename()).unwrap())
+                .lines()
+                .map(Result::unwrap)
+                .collect();
+        assert_eq!(lines.len(), messages.len());
+        for (line, message) in lines.iter().zip(messages.iter()) {
+            if message.len() > MAX_MESSAGE_LEN {
+                assert!(line.len() < MAX_MESSAGE_LEN + 100);
+                assert!(
+                    line.ends_with(" bytes]") && line.contains("…[truncated "),
+                    "cut log line has no marker"
+                );
+                continue;
+            }
+            assert!(
+                line.ends_with(&format!(" {}", message)),
+                "log line of length {} is not complete",
+                message.len()
+            );
+        }
+    }
+}
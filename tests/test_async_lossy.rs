@@ -0,0 +1,56 @@
+mod test_utils;
+
+#[cfg(feature = "async")]
+mod d {
+    use flexi_logger::writers::{FileLogWriter, LogWriter};
+    use flexi_logger::{DeferredNow, FileSpec, WriteMode};
+    use std::time::Duration;
+
+    const NO_OF_LOGLINES: usize = 20_000;
+    const REPORT: &str = "log lines were discarded because the output could not keep up";
+
+    #[test]
+    fn test_async_lossy() {
+        let flw = FileLogWriter::builder(
+            FileSpec::default()
+                .directory(super::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .write_mode(WriteMode::AsyncLossy {
+            channel_capa: 2,
+            flush_interval: Duration::from_secs(0),
+        })
+        .try_build()
+        .unwrap();
+
+        for i in 0..NO_OF_LOGLINES {
+            flw.write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("line {}", i))
+                    .level(log::Level::Info)
+                    .build(),
+            )
+            .unwrap();
+        }
+        // a flush lets the discarded lines be reported;
+        // if the channel is full, they are reported with the next flush
+        assert!(flw.flush_and_wait(Duration::from_secs(5)));
+        flw.flush().unwrap();
+        flw.shutdown();
+
+        let content = std::fs::read_to_string(flw.current_filename()).unwrap();
+        let dropped = flw.dropped_records();
+        let written = content.lines().filter(|l| l.contains("line ")).count();
+        assert_eq!(written + dropped, NO_OF_LOGLINES);
+
+        let reports: Vec<&str> = content.lines().filter(|l| l.contains(REPORT)).collect();
+        if dropped > 0 {
+            assert_eq!(reports.len(), 1);
+            assert!(reports[0].starts_with("WARN [flexi_logger]"));
+            assert!(reports[0].contains(&format!("{} {}", dropped, REPORT)));
+        } else {
+            assert!(reports.is_empty());
+        }
+    }
+}
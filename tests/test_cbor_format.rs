@@ -0,0 +1,107 @@
+mod test_utils;
+
+use flexi_logger::{cbor_format, FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_cbor_format() {
+    let dir = self::test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).suppress_timestamp())
+        .format_for_files(cbor_format)
+        .without_line_ending()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!(target: "some_target", "This is a warning with a long text: {}", "x".repeat(300));
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+    logger.flush();
+
+    let path = std::fs::read_dir(&dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let bytes = std::fs::read(path).unwrap();
+
+    let mut records = Vec::new();
+    let mut rest = &bytes[..];
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let mut decoder = Decoder(&rest[4..4 + len]);
+        records.push(decoder.record());
+        assert!(decoder.0.is_empty());
+        rest = &rest[4 + len..];
+    }
+
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].level, "ERROR");
+    assert_eq!(records[0].target, "test_cbor_format");
+    assert_eq!(records[0].message, "This is an error message");
+    assert_eq!(records[1].level, "WARN");
+    assert_eq!(records[1].target, "some_target");
+    assert!(records[1].message.ends_with(&"x".repeat(300)));
+    assert_eq!(records[2].level, "INFO");
+    assert_eq!(records[2].message, "This is an info message");
+    assert!(records[0].timestamp > 1_600_000_000_000_000_000);
+    assert!(records[0].timestamp <= records[2].timestamp);
+}
+
+struct CborRecord {
+    timestamp: u64,
+    level: String,
+    target: String,
+    message: String,
+}
+
+// A minimal decoder for the data items that cbor_format produces without key-value pairs
+struct Decoder<'a>(&'a [u8]);
+impl<'a> Decoder<'a> {
+    fn head(&mut self, expected_major_type: u8) -> u64 {
+        let initial = self.take(1)[0];
+        assert_eq!(initial >> 5, expected_major_type);
+        match initial & 0x1f {
+            n @ 0..=23 => u64::from(n),
+            24 => u64::from(self.take(1)[0]),
+            25 => self.big_endian(2),
+            26 => self.big_endian(4),
+            27 => self.big_endian(8),
+            n => panic!("unexpected additional information {}", n),
+        }
+    }
+    fn big_endian(&mut self, n: usize) -> u64 {
+        self.take(n)
+            .iter()
+            .fold(0, |acc, b| acc << 8 | u64::from(*b))
+    }
+    fn text(&mut self) -> String {
+        let len = self.head(3) as usize;
+        String::from_utf8(self.take(len).to_vec()).unwrap()
+    }
+    fn take(&mut self, n: usize) -> &'a [u8] {
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        head
+    }
+    fn record(&mut self) -> CborRecord {
+        assert_eq!(self.head(5), 4);
+        assert_eq!(self.text(), "timestamp");
+        let timestamp = self.head(0);
+        assert_eq!(self.text(), "level");
+        let level = self.text();
+        assert_eq!(self.text(), "target");
+        let target = self.text();
+        assert_eq!(self.text(), "message");
+        let message = self.text();
+        CborRecord {
+            timestamp,
+            level,
+            target,
+            message,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+mod test_utils;
+
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{Cleanup, Criterion, FileSpec, FlexiLoggerError, Naming};
+
+#[test]
+fn test_cleanup_domain() {
+    let directory = self::test_utils::dir();
+
+    let writer_with_cleanup = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .basename("app")
+            .suppress_timestamp(),
+    )
+    .rotate(
+        Criterion::Size(1000),
+        Naming::Numbers,
+        Cleanup::KeepLogFiles(2),
+    )
+    .try_build()
+    .unwrap();
+
+    // the cleanup of the first writer would delete files like "app_r1_r00000.log"
+    let overlapping_spec = FileSpec::default()
+        .directory(&directory)
+        .basename("app")
+        .discriminant("r1")
+        .suppress_timestamp();
+    match FileLogWriter::builder(overlapping_spec.clone()).try_build() {
+        Err(FlexiLoggerError::OutputCleanupConflict(_)) => {}
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(_) => panic!("overlapping file specs were not detected"),
+    }
+
+    // a different discriminant separates the files
+    let separate_writer = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .basename("app")
+            .discriminant("alert")
+            .suppress_timestamp(),
+    )
+    .rotate(
+        Criterion::Size(1000),
+        Naming::Numbers,
+        Cleanup::KeepLogFiles(2),
+    )
+    .try_build()
+    .unwrap();
+
+    // a different directory is a different cleanup domain
+    FileLogWriter::builder(overlapping_spec.clone().directory(directory.join("other")))
+        .try_build()
+        .unwrap();
+
+    // once the first writer is gone, the overlap does not matter anymore
+    drop(writer_with_cleanup);
+    FileLogWriter::builder(overlapping_spec)
+        .try_build()
+        .unwrap();
+    drop(separate_writer);
+}
@@ -0,0 +1,48 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, CleanupEvent, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::sync::mpsc::channel;
+use std::sync::Mutex;
+
+#[test]
+fn test_cleanup_events() {
+    let dir = self::test_utils::dir();
+    let (sender, receiver) = channel();
+    let sender = Mutex::new(sender);
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir))
+        .rotate(
+            Criterion::Size(100),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(2),
+        )
+        .cleanup_in_background_thread(false)
+        .on_cleanup(move |event: &CleanupEvent| {
+            sender.lock().unwrap().send(event.clone()).ok();
+        })
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..20 {
+        info!("this is line number {} of the log", i);
+    }
+    logger.shutdown();
+
+    let events: Vec<CleanupEvent> = receiver.try_iter().collect();
+    assert!(!events.is_empty());
+    for event in &events {
+        match event {
+            CleanupEvent::Deleted { path } => {
+                assert!(path.starts_with(&dir));
+                assert!(!path.exists());
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    let remaining = std::fs::read_dir(&dir).unwrap().count();
+    // the current file, two rotated files
+    assert_eq!(remaining, 3);
+}
@@ -0,0 +1,45 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn test_cleanup_interval() {
+    let dir = self::test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).basename("periodic"))
+        .rotate(
+            Criterion::Size(1_000_000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(2),
+        )
+        .cleanup_interval(Duration::from_millis(100))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    info!("this line does not cause a rotation");
+    logger.flush();
+
+    // rotated files that appear without a rotation, e.g. from another instance of the program
+    for i in 0..5 {
+        std::fs::write(dir.join(format!("periodic_r{:05}.log", i)), "old\n").unwrap();
+    }
+    assert_eq!(rotated_files(&dir), 5);
+
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(rotated_files(&dir), 2);
+    logger.shutdown();
+}
+
+fn rotated_files(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("periodic_r") && !name.contains("CURRENT")
+        })
+        .count()
+}
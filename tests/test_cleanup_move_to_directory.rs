@@ -0,0 +1,67 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_cleanup_move_to_directory() {
+    let dir = self::test_utils::dir();
+    let archive_dir = dir.join("archive");
+    {
+        let logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&dir))
+            .rotate(
+                Criterion::Size(100),
+                Naming::Numbers,
+                Cleanup::MoveToDirectory(archive_dir.clone(), 2),
+            )
+            .cleanup_in_background_thread(false)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..20 {
+            info!("this is line number {} of the log", i);
+        }
+        logger.shutdown();
+    }
+
+    let live_files = rotated_files(&dir);
+    let archived_files = rotated_files(&archive_dir);
+    assert_eq!(live_files.len(), 2, "{:?}", live_files);
+    assert!(!archived_files.is_empty());
+
+    // nothing was lost
+    let mut lines: Vec<String> = live_files
+        .iter()
+        .chain(archived_files.iter())
+        .chain(std::iter::once(&current_file(&dir)))
+        .flat_map(|path| {
+            std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    lines.sort();
+    lines.dedup();
+    assert_eq!(lines.len(), 20);
+}
+
+fn rotated_files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file() && !path.to_string_lossy().contains("_rCURRENT"))
+        .collect()
+}
+
+fn current_file(dir: &Path) -> PathBuf {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().contains("_rCURRENT"))
+        .unwrap()
+}
@@ -0,0 +1,96 @@
+#[cfg(feature = "upload")]
+mod test_utils;
+
+#[cfg(feature = "upload")]
+#[test]
+fn test_cleanup_upload() {
+    use flexi_logger::{
+        Cleanup, CleanupEvent, Criterion, ErrorChannel, FileSpec, Logger, Naming, Uploader,
+    };
+    use log::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    let dir = self::test_utils::dir();
+    let remote_dir = dir.join("remote");
+    std::fs::create_dir_all(&remote_dir).unwrap();
+
+    let fail = Arc::new(AtomicBool::new(true));
+    let uploader = {
+        let fail = Arc::clone(&fail);
+        let remote_dir = remote_dir.clone();
+        Uploader::new(move |path| {
+            if fail.load(Ordering::SeqCst) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "remote storage not reachable",
+                ));
+            }
+            std::fs::copy(path, remote_dir.join(path.file_name().unwrap())).map(|_| ())
+        })
+    };
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    {
+        let events_clone = Arc::clone(&events);
+        let handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&dir).basename("up"))
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .rotate(
+                Criterion::Size(0),
+                Naming::Numbers,
+                Cleanup::UploadThenDelete(uploader, 2),
+            )
+            .cleanup_in_background_thread(false)
+            .on_cleanup(move |event| events_clone.lock().unwrap().push(event.clone()))
+            .error_channel(ErrorChannel::DevNull)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        // while the uploads fail, the files are kept
+        for i in 0..5 {
+            info!("{}", i);
+        }
+        assert_eq!(file_names(&dir).len(), 5 + 1);
+        assert!(events.lock().unwrap().is_empty());
+
+        // the failed uploads are retried with the next cleanup
+        fail.store(false, Ordering::SeqCst);
+        info!("5");
+        handle.shutdown();
+    }
+
+    assert_eq!(
+        file_names(&dir),
+        vec![
+            "remote",
+            "up_r00003.log",
+            "up_r00004.log",
+            "up_rCURRENT.log"
+        ]
+    );
+    assert_eq!(
+        file_names(&remote_dir),
+        vec!["up_r00000.log", "up_r00001.log", "up_r00002.log"]
+    );
+    assert_eq!(
+        std::fs::read_to_string(remote_dir.join("up_r00002.log")).unwrap(),
+        "2\n"
+    );
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 3);
+    assert!(events
+        .iter()
+        .all(|event| matches!(event, CleanupEvent::Uploaded { .. })));
+}
+
+#[cfg(feature = "upload")]
+fn file_names(dir: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
@@ -0,0 +1,86 @@
+#[cfg(feature = "atty")]
+mod d {
+    use flexi_logger::{AdaptiveFormat, ColorChoice, DeferredNow, Logger};
+    use log::*;
+    use std::process::Command;
+
+    const CHILD_CHOICE: &str = "FLEXI_LOGGER_TEST_COLOR_CHOICE";
+
+    // The test re-runs itself in child processes, with different environments,
+    // to capture what is written to stderr (which is then not a tty).
+    #[test]
+    fn test_color_choice() {
+        if let Ok(choice) = std::env::var(CHILD_CHOICE) {
+            let _logger = Logger::try_with_str("info")
+                .unwrap()
+                .log_to_stderr()
+                .adaptive_format_for_stderr(AdaptiveFormat::Custom(plain_format, colored_format))
+                .color_choice(match choice.as_str() {
+                    "always" => ColorChoice::Always,
+                    "never" => ColorChoice::Never,
+                    _ => ColorChoice::Auto,
+                })
+                .start()
+                .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+            info!("message");
+            return;
+        }
+
+        assert_eq!(run_child("auto", &[]), "plain: message");
+        assert_eq!(
+            run_child("auto", &[("CLICOLOR_FORCE", "1")]),
+            "colored: message"
+        );
+        assert_eq!(
+            run_child("auto", &[("CLICOLOR_FORCE", "0")]),
+            "plain: message"
+        );
+        assert_eq!(
+            run_child("auto", &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]),
+            "plain: message"
+        );
+        assert_eq!(
+            run_child("always", &[("NO_COLOR", "1")]),
+            "colored: message"
+        );
+        assert_eq!(
+            run_child("never", &[("CLICOLOR_FORCE", "1")]),
+            "plain: message"
+        );
+    }
+
+    fn run_child(choice: &str, env: &[(&str, &str)]) -> String {
+        let mut command = Command::new(std::env::current_exe().unwrap());
+        command
+            .args(&["--exact", "d::test_color_choice", "--nocapture", "-q"])
+            .env(CHILD_CHOICE, choice)
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .env_remove("CLICOLOR");
+        for (key, value) in env {
+            command.env(key, value);
+        }
+        let output = command.output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .trim_end()
+            .to_string()
+    }
+
+    fn plain_format(
+        w: &mut dyn std::io::Write,
+        _now: &mut DeferredNow,
+        record: &Record,
+    ) -> std::io::Result<()> {
+        write!(w, "plain: {}", record.args())
+    }
+
+    fn colored_format(
+        w: &mut dyn std::io::Write,
+        _now: &mut DeferredNow,
+        record: &Record,
+    ) -> std::io::Result<()> {
+        write!(w, "colored: {}", record.args())
+    }
+}
@@ -0,0 +1,54 @@
+mod test_utils;
+
+#[cfg(feature = "compress")]
+mod a {
+    use flate2::read::GzDecoder;
+    use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+    use log::*;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_compress_on_shutdown() {
+        let dir = super::test_utils::dir();
+        let logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&dir))
+            .rotate(
+                Criterion::Size(200),
+                Naming::Numbers,
+                Cleanup::KeepCompressedFiles(10),
+            )
+            .cleanup_in_background_thread(false)
+            .compress_on_shutdown()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..10 {
+            info!("this is line number {} of the log", i);
+        }
+        logger.shutdown();
+
+        let files: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert!(files.len() > 1);
+        assert!(
+            files
+                .iter()
+                .all(|path| path.to_string_lossy().ends_with(".log.gz")),
+            "{:?}",
+            files
+        );
+
+        let mut content = String::new();
+        for path in &files {
+            GzDecoder::new(std::fs::File::open(path).unwrap())
+                .read_to_string(&mut content)
+                .unwrap();
+        }
+        assert_eq!(content.lines().count(), 10);
+        assert!(content.contains("this is line number 9 of the log"));
+    }
+}
@@ -0,0 +1,51 @@
+mod test_utils;
+
+#[cfg(feature = "compress")]
+mod a {
+    use flate2::read::GzDecoder;
+    use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+    use log::*;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    const NO_OF_LINES: usize = 20_000;
+
+    #[test]
+    fn test_compress_while_writing() {
+        let dir = super::test_utils::dir();
+        let logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&dir))
+            .rotate(Criterion::Size(5_000), Naming::Numbers, Cleanup::Never)
+            .compress_while_writing()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..NO_OF_LINES {
+            info!("this is line number {} of the log", i);
+        }
+        assert!(logger
+            .existing_log_files()
+            .unwrap()
+            .iter()
+            .any(|path| path.to_string_lossy().ends_with("_rCURRENT.log.gz")));
+        logger.shutdown();
+
+        let files: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        // the size criterion is applied to the compressed size
+        assert!(files.len() > 1, "{:?}", files);
+
+        let mut content = String::new();
+        for path in &files {
+            assert!(path.to_string_lossy().ends_with(".log.gz"), "{:?}", path);
+            GzDecoder::new(std::fs::File::open(path).unwrap())
+                .read_to_string(&mut content)
+                .unwrap();
+        }
+        assert_eq!(content.lines().count(), NO_OF_LINES);
+        assert!(content.contains("this is line number 19999 of the log"));
+    }
+}
@@ -0,0 +1,64 @@
+mod test_utils;
+
+#[cfg(feature = "compress")]
+mod a {
+    use flate2::read::GzDecoder;
+    use flexi_logger::{Cleanup, CleanupEvent, Criterion, FileSpec, Logger, Naming};
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    const NO_OF_FILES: usize = 12;
+
+    #[test]
+    fn test_compression_threads() {
+        let dir = super::test_utils::dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        // a backlog of uncompressed rotated files
+        for i in 0..NO_OF_FILES {
+            std::fs::write(
+                dir.join(format!("backlog_r{:05}.log", i)),
+                format!("content of file {}\n", i).repeat(1000),
+            )
+            .unwrap();
+        }
+
+        let compressed = Arc::new(Mutex::new(Vec::new()));
+        let compressed2 = Arc::clone(&compressed);
+        let logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&dir).basename("backlog"))
+            .rotate(
+                Criterion::Size(1_000_000),
+                Naming::Numbers,
+                Cleanup::KeepCompressedFiles(100),
+            )
+            .compression_level(9)
+            .compression_threads(4)
+            .on_cleanup(move |event: &CleanupEvent| {
+                if let CleanupEvent::Compressed {
+                    compressed_path, ..
+                } = event
+                {
+                    compressed2.lock().unwrap().push(compressed_path.clone());
+                }
+            })
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        // the output file is opened, and the cleanup is triggered, with the first write
+        log::info!("first line");
+        logger.shutdown();
+
+        let compressed = compressed.lock().unwrap();
+        assert_eq!(compressed.len(), NO_OF_FILES);
+        for i in 0..NO_OF_FILES {
+            assert!(!dir.join(format!("backlog_r{:05}.log", i)).exists());
+            let mut content = String::new();
+            GzDecoder::new(
+                std::fs::File::open(dir.join(format!("backlog_r{:05}.log.gz", i))).unwrap(),
+            )
+            .read_to_string(&mut content)
+            .unwrap();
+            assert_eq!(content, format!("content of file {}\n", i).repeat(1000));
+        }
+    }
+}
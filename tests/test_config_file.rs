@@ -0,0 +1,88 @@
+mod test_utils;
+
+#[cfg(feature = "config_file")]
+mod a {
+    use flexi_logger::{FlexiLoggerError, Logger};
+    use log::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_config_file() {
+        let dir = super::test_utils::dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_file = dir.join("logger.toml");
+        write_config(&config_file, &dir, "info", "detailed");
+
+        let mut handle = Logger::try_from_config_file(&config_file)
+            .unwrap()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("an error");
+        info!(target: "{_Default,alerts}", "an info for the alerts");
+        info!("an info, not for the alerts");
+        debug!("a debug message - you must not see it!");
+
+        write_config(&config_file, &dir, "debug", "detailed");
+        handle.reload_config(&config_file).unwrap();
+        debug!("a debug message after the reload");
+
+        // an invalid config is rejected and changes nothing
+        std::fs::write(&config_file, "write_mode = 'sometimes'\n").unwrap();
+        match handle.reload_config(&config_file) {
+            Err(FlexiLoggerError::ConfigFileValue(s)) => assert!(s.contains("sometimes")),
+            other => panic!("unexpected result {:?}", other),
+        }
+        std::fs::write(&config_file, "unknown_key = 1\n").unwrap();
+        assert!(matches!(
+            handle.reload_config(&config_file),
+            Err(FlexiLoggerError::ConfigFileToml(_))
+        ));
+        trace!("a trace message - you must not see it!");
+        handle.flush();
+
+        let main = std::fs::read_to_string(dir.join("config_test_main.log")).unwrap();
+        let lines: Vec<&str> = main.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", main);
+        assert!(lines[0].contains("ERROR [test_config_file::a]"));
+        assert!(lines[0].contains("tests/test_config_file.rs:"));
+        assert!(lines[1].ends_with("an info for the alerts"));
+        assert!(lines[3].ends_with("a debug message after the reload"));
+
+        let alerts = std::fs::read_to_string(dir.join("config_test_alerts.log")).unwrap();
+        let lines: Vec<&str> = alerts.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", alerts);
+        assert!(lines[0].ends_with("an error"));
+        assert!(lines[1].ends_with("an info for the alerts"));
+    }
+
+    fn write_config(path: &Path, dir: &Path, spec: &str, format: &str) {
+        std::fs::write(
+            path,
+            format!(
+                r#"
+spec = "{spec}"
+format_for_files = "{format}"
+write_mode = "buffer_and_flush"
+
+[file]
+directory = '{dir}'
+basename = "config_test"
+discriminant = "main"
+use_timestamp = false
+
+[writers.alerts]
+directory = '{dir}'
+basename = "config_test"
+discriminant = "alerts"
+use_timestamp = false
+also_send = "warn"
+"#,
+                spec = spec,
+                format = format,
+                dir = dir.display(),
+            ),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,41 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, LevelFilter, Logger};
+use log::*;
+
+#[test]
+fn test_create_file_lazily() {
+    let dir = self::test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).suppress_timestamp())
+        .format(|w, _now, record| write!(w, "{} {}", record.level(), record.args()))
+        .create_file_lazily(LevelFilter::Warn)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("starting");
+    debug!("not enabled");
+    info!("nothing noteworthy");
+    handle.flush();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    warn!("something noteworthy");
+    info!("done");
+    handle.shutdown();
+
+    let files = handle.existing_log_files().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(&files[0])
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>(),
+        vec![
+            "INFO starting",
+            "INFO nothing noteworthy",
+            "WARN something noteworthy",
+            "INFO done",
+        ]
+    );
+}
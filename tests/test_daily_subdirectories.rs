@@ -0,0 +1,65 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+
+#[test]
+fn test_daily_subdirectories() {
+    let dir = self::test_utils::dir();
+
+    // a rotated file from an earlier day, which the cleanup has to find
+    let old_subdirectory = dir.join("2000-01-01");
+    std::fs::create_dir_all(&old_subdirectory).unwrap();
+    std::fs::write(old_subdirectory.join("dd_r00000.log"), "0\n").unwrap();
+
+    {
+        let _handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(
+                FileSpec::default()
+                    .directory(&dir)
+                    .basename("dd")
+                    .daily_subdirectories(true),
+            )
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .rotate(
+                Criterion::Size(0),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(2),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 1..=5 {
+            info!("{}", i);
+        }
+    }
+
+    assert!(dir.join("dd_rCURRENT.log").exists());
+    assert!(
+        !old_subdirectory.exists(),
+        "the emptied subdirectory should have been removed"
+    );
+
+    let subdirectories = subdirectories(&dir);
+    assert_eq!(subdirectories.len(), 1, "{:?}", subdirectories);
+    let name = subdirectories[0].file_name().unwrap().to_string_lossy();
+    assert_eq!(name.len(), 10);
+    assert_eq!(name.matches('-').count(), 2);
+
+    let mut rotated: Vec<String> = std::fs::read_dir(&subdirectories[0])
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    rotated.sort();
+    assert_eq!(rotated, vec!["dd_r00003.log", "dd_r00004.log"]);
+}
+
+fn subdirectories(dir: &Path) -> Vec<std::path::PathBuf> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
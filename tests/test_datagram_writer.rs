@@ -0,0 +1,63 @@
+mod test_utils;
+
+#[cfg(unix)]
+mod a {
+    use flexi_logger::writers::{DatagramWriter, LogWriter};
+    use flexi_logger::Logger;
+    use log::*;
+    use std::os::unix::net::UnixDatagram;
+    use std::time::Duration;
+
+    #[test]
+    fn test_datagram_writer() {
+        let directory = super::test_utils::dir();
+        std::fs::create_dir_all(&directory).unwrap();
+        let socket_path = directory.join("collector.socket");
+        let collector = UnixDatagram::bind(&socket_path).unwrap();
+        collector
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+
+        let writer = DatagramWriter::builder(&socket_path)
+            .max_log_level(LevelFilter::Info)
+            .try_build()
+            .unwrap();
+        assert_eq!(writer.max_log_level(), LevelFilter::Info);
+
+        let _handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_writer(Box::new(writer))
+            .format(|w, _now, record| write!(w, "{} {}", record.level(), record.args()))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("first record");
+        warn!("second record\nwith two lines");
+        debug!("not enabled");
+        assert_eq!(receive(&collector), "ERROR first record");
+        assert_eq!(receive(&collector), "WARN second record\nwith two lines");
+
+        // the writer reconnects when the collector was restarted
+        drop(collector);
+        std::fs::remove_file(&socket_path).unwrap();
+        let collector = UnixDatagram::bind(&socket_path).unwrap();
+        collector
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        info!("third record");
+        assert_eq!(receive(&collector), "INFO third record");
+    }
+
+    #[test]
+    fn test_datagram_writer_without_collector() {
+        let socket_path = super::test_utils::dir().join("missing.socket");
+        assert!(DatagramWriter::builder(socket_path).try_build().is_err());
+    }
+
+    fn receive(collector: &UnixDatagram) -> String {
+        let mut buffer = vec![0_u8; 4096];
+        let len = collector.recv(&mut buffer).unwrap();
+        buffer.truncate(len);
+        String::from_utf8(buffer).unwrap()
+    }
+}
@@ -0,0 +1,65 @@
+mod test_utils;
+
+#[cfg(all(feature = "disk_free", unix))]
+mod a {
+    use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+    use std::path::Path;
+
+    #[test]
+    fn test_disk_free() {
+        // there is enough space on the disk
+        let dir = super::test_utils::dir().join("ok");
+        write_old_files(&dir);
+        log_a_line(&dir, 1);
+        assert_eq!(rotated_files(&dir).len(), 4);
+
+        // the disk is too full (no disk has that much free space),
+        // so the log files are rotated and deleted
+        let dir = super::test_utils::dir().join("low");
+        write_old_files(&dir);
+        log_a_line(&dir, u64::MAX);
+        assert_eq!(
+            rotated_files(&dir),
+            vec!["space_r00003.keep.log".to_string()]
+        );
+        assert!(dir.join("space_rCURRENT.log").exists());
+    }
+
+    fn log_a_line(dir: &Path, min_free: u64) {
+        let (logger, handle) = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(dir).basename("space"))
+            .rotate(
+                Criterion::DiskFree(min_free),
+                Naming::Numbers,
+                Cleanup::Never,
+            )
+            .build()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        logger.log(
+            &log::Record::builder()
+                .args(format_args!("a line"))
+                .level(log::Level::Info)
+                .build(),
+        );
+        handle.shutdown();
+    }
+
+    fn write_old_files(dir: &Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        for i in 0..3 {
+            std::fs::write(dir.join(format!("space_r{:05}.log", i)), "old\n").unwrap();
+        }
+        std::fs::write(dir.join("space_r00003.keep.log"), "pinned\n").unwrap();
+    }
+
+    fn rotated_files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("space_r") && !name.contains("CURRENT"))
+            .collect();
+        files.sort();
+        files
+    }
+}
@@ -0,0 +1,79 @@
+mod test_utils;
+
+use flexi_logger::{detailed_format, DeferredNow, Duplicate, FileSpec, Logger};
+use log::*;
+use std::process::Command;
+
+const CHILD_DIR: &str = "FLEXI_LOGGER_TEST_DUPLICATE_DIR";
+
+// The test re-runs itself in a child process, to capture what is duplicated to stdout and stderr,
+// and verifies that each output uses its own format.
+#[test]
+fn test_duplicate_formats() {
+    if let Ok(directory) = std::env::var(CHILD_DIR) {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(directory))
+            .format_for_files(detailed_format)
+            .duplicate_to_stdout(Duplicate::Info)
+            .format_for_stdout(compact_format)
+            .duplicate_to_stderr(Duplicate::Warn)
+            .format_for_stderr(plain_format)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        warn!("This is a warning");
+        info!("This is an info message");
+        return;
+    }
+
+    let directory = self::test_utils::dir();
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(&["--exact", "test_duplicate_formats", "--nocapture", "-q"])
+        .env(CHILD_DIR, &directory)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // stdout also contains the output of the test harness
+    let duplicated: Vec<&str> = stdout.lines().filter(|l| l.contains('|')).collect();
+    assert_eq!(
+        duplicated,
+        vec!["W|This is a warning", "I|This is an info message"]
+    );
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.lines().collect::<Vec<_>>(),
+        vec!["This is a warning"]
+    );
+
+    let logfile = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let content = std::fs::read_to_string(logfile).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("WARN [test_duplicate_formats] tests/test_duplicate_formats.rs:"));
+    assert!(lines[0].ends_with("This is a warning"));
+    assert!(lines[1].ends_with("This is an info message"));
+}
+
+fn compact_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(w, "{}|{}", &record.level().as_str()[..1], record.args())
+}
+
+fn plain_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(w, "{}", record.args())
+}
@@ -0,0 +1,69 @@
+mod test_utils;
+
+use flexi_logger::{DeferredNow, Duplicate, FileSpec, Logger, RateLimit};
+use log::*;
+use std::process::Command;
+
+const CHILD_DIR: &str = "FLEXI_LOGGER_TEST_DUPLICATE_RATE_LIMIT_DIR";
+
+// The test re-runs itself in a child process, to capture what is duplicated to stderr.
+#[test]
+fn test_duplicate_rate_limit() {
+    if let Ok(directory) = std::env::var(CHILD_DIR) {
+        let logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(directory))
+            .duplicate_to_stderr(Duplicate::Info)
+            .rate_limit_duplication_to_stderr(RateLimit::per_second(5))
+            .format_for_stderr(plain_format)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        for i in 0..20 {
+            info!("line {}", i);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        logger.flush();
+        return;
+    }
+
+    let directory = self::test_utils::dir();
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(&["--exact", "test_duplicate_rate_limit", "--nocapture", "-q"])
+        .env(CHILD_DIR, &directory)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.lines().collect::<Vec<_>>(),
+        vec![
+            "line 0",
+            "line 1",
+            "line 2",
+            "line 3",
+            "line 4",
+            "15 log records were not duplicated to stderr due to the rate limit",
+        ]
+    );
+
+    // the file gets everything
+    let logfile = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    assert_eq!(
+        std::fs::read_to_string(logfile).unwrap().lines().count(),
+        20
+    );
+}
+
+fn plain_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    write!(w, "{}", record.args())
+}
@@ -0,0 +1,60 @@
+mod test_utils;
+
+use flexi_logger::{DeferredNow, FileSpec, LogSpecification, Logger};
+use log::*;
+use std::process::Command;
+
+const CHILD_DIR: &str = "FLEXI_LOGGER_TEST_DUPLICATE_SPEC_DIR";
+
+// The test re-runs itself in a child process, to capture what is duplicated to stderr.
+#[test]
+fn test_duplicate_spec() {
+    if let Ok(directory) = std::env::var(CHILD_DIR) {
+        let _logger = Logger::try_with_str("debug")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(directory))
+            .duplicate_to_stderr_with_spec(
+                LogSpecification::parse("warn, my_app::api = info").unwrap(),
+            )
+            .format_for_stderr(plain_format)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        warn!(target: "chatty_dependency", "dependency warning");
+        info!(target: "chatty_dependency", "dependency info");
+        info!(target: "my_app::api::handler", "api info");
+        debug!(target: "my_app::api::handler", "api debug");
+        info!(target: "my_app::db", "db info");
+        return;
+    }
+
+    let directory = self::test_utils::dir();
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(&["--exact", "test_duplicate_spec", "--nocapture", "-q"])
+        .env(CHILD_DIR, &directory)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.lines().collect::<Vec<_>>(),
+        vec!["dependency warning", "api info"]
+    );
+
+    // the file gets everything
+    let logfile = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    assert_eq!(std::fs::read_to_string(logfile).unwrap().lines().count(), 5);
+}
+
+fn plain_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    write!(w, "{}", record.args())
+}
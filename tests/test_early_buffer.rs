@@ -0,0 +1,48 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, FlexiLoggerError, Logger};
+use log::*;
+
+#[test]
+fn test_early_buffer() {
+    Logger::buffer_until_started(3).unwrap();
+    assert!(matches!(
+        Logger::buffer_until_started(3),
+        Err(FlexiLoggerError::AlreadyInitialized)
+    ));
+
+    info!("early 1");
+    debug!("early 2");
+    warn!("early 3");
+    error!("early 4");
+
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(self::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .format(|w, _now, record| write!(w, "{} {}", record.level(), record.args()))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("late 1");
+    debug!("late 2");
+    assert!(matches!(
+        Logger::try_with_str("info").unwrap().start(),
+        Err(FlexiLoggerError::AlreadyInitialized)
+    ));
+
+    handle.shutdown();
+    let content = std::fs::read_to_string(handle.existing_log_files().unwrap().remove(0)).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec![
+            "INFO early 1",
+            "WARN early 3",
+            "WARN 1 log records were discarded because the early buffer was full",
+            "INFO late 1",
+        ]
+    );
+}
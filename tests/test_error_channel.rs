@@ -0,0 +1,66 @@
+mod test_utils;
+
+use flexi_logger::{DeferredNow, ErrCode, ErrorChannel, FileSpec, Logger};
+use log::Record;
+use std::sync::{Arc, Mutex};
+
+fn failing_format(
+    _w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    _record: &Record,
+) -> Result<(), std::io::Error> {
+    Err(std::io::Error::new(std::io::ErrorKind::Other, "test error"))
+}
+
+fn log_once(logger: &dyn log::Log) {
+    logger.log(
+        &Record::builder()
+            .args(format_args!("something"))
+            .level(log::Level::Error)
+            .build(),
+    );
+}
+
+#[test]
+fn test_error_channel() {
+    let dir = self::test_utils::dir();
+
+    // callback
+    let errors: Arc<Mutex<Vec<(ErrCode, String)>>> = Arc::new(Mutex::new(Vec::new()));
+    let errors_clone = Arc::clone(&errors);
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).basename("callback"))
+        .format(failing_format)
+        .error_channel(ErrorChannel::Callback(Arc::new(move |errcode, msg| {
+            errors_clone.lock().unwrap().push((errcode, msg));
+        })))
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    log_once(&*logger);
+    handle.shutdown();
+    {
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1, "{:?}", *errors);
+        assert_eq!(errors[0].0, ErrCode::Format);
+        assert!(errors[0].1.contains("test error"), "{}", errors[0].1);
+    }
+
+    // file
+    let error_file = dir.join("errors.txt");
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).basename("file"))
+        .format(failing_format)
+        .error_channel(ErrorChannel::File(error_file.clone()))
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    log_once(&*logger);
+    log_once(&*logger);
+    handle.shutdown();
+    let content = std::fs::read_to_string(&error_file).unwrap();
+    assert_eq!(
+        content.matches("[flexi_logger][ERRCODE::Format]").count(),
+        2
+    );
+}
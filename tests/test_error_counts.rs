@@ -0,0 +1,65 @@
+use flexi_logger::{DeferredNow, ErrCode, ErrorChannel, Logger};
+use log::Record;
+
+struct FailingWriter;
+impl std::io::Write for FailingWriter {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn failing_format(
+    _w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    _record: &Record,
+) -> Result<(), std::io::Error> {
+    Err(std::io::Error::new(std::io::ErrorKind::Other, "test error"))
+}
+
+fn log_once(logger: &dyn log::Log) {
+    logger.log(
+        &Record::builder()
+            .args(format_args!("something"))
+            .level(log::Level::Error)
+            .build(),
+    );
+}
+
+#[test]
+fn test_error_counts() {
+    // formatting errors are counted, but don't affect the health
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_stderr()
+        .format(failing_format)
+        .error_channel(ErrorChannel::DevNull)
+        .build()
+        .unwrap();
+    assert!(handle.error_counts().is_empty());
+    for _ in 0..3 {
+        log_once(&*logger);
+    }
+    assert_eq!(handle.error_counts().get(&ErrCode::Format), Some(&3));
+    assert!(handle.is_healthy());
+    handle.shutdown();
+
+    // write errors make the logger unhealthy
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_io_writer(Box::new(FailingWriter))
+        .error_channel(ErrorChannel::DevNull)
+        .build()
+        .unwrap();
+    log_once(&*logger);
+    let counts = handle.error_counts();
+    assert!(
+        counts.get(&ErrCode::Write).copied().unwrap_or(0) >= 1,
+        "{:?}",
+        counts
+    );
+    assert!(!handle.is_healthy());
+    handle.shutdown();
+}
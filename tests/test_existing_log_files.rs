@@ -0,0 +1,32 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_existing_log_files() {
+    let directory = self::test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..5 {
+        info!(
+            "This is line {} of a log that is rotated after every line",
+            i
+        );
+    }
+    logger.flush();
+
+    let existing_log_files = logger.existing_log_files().unwrap();
+    let files_in_directory = std::fs::read_dir(&directory).unwrap().count();
+    assert_eq!(existing_log_files.len(), files_in_directory);
+    assert!(existing_log_files.len() > 1);
+    assert!(existing_log_files[0]
+        .to_string_lossy()
+        .contains("_rCURRENT"));
+    assert!(existing_log_files.iter().all(|path| path.exists()));
+}
@@ -0,0 +1,164 @@
+use flexi_logger::writers::{FileMetadata, LogFileSystem};
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+#[test]
+fn test_file_system() {
+    let file_system = Arc::new(MemFileSystem::default());
+    let directory = PathBuf::from("in_memory/test_file_system");
+    {
+        let _handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&directory).basename("mem"))
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .rotate(
+                Criterion::Size(0),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(2),
+            )
+            .cleanup_in_background_thread(false)
+            .file_system(Arc::clone(&file_system) as Arc<dyn LogFileSystem>)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..5 {
+            info!("{}", i);
+        }
+    }
+
+    assert!(!directory.exists(), "the real file system was used");
+    let path = |filename: &str| directory.join(filename);
+    assert_eq!(
+        file_system.file_names(),
+        vec![
+            path("mem_r00002.log"),
+            path("mem_r00003.log"),
+            path("mem_rCURRENT.log")
+        ]
+    );
+    assert_eq!(file_system.content(&path("mem_r00003.log")), "3");
+    assert_eq!(file_system.content(&path("mem_rCURRENT.log")), "4");
+}
+
+// Keeps the files in memory; knows only files, and the directories that were created explicitly.
+#[derive(Debug, Default)]
+struct MemFileSystem {
+    files: Arc<Mutex<BTreeMap<PathBuf, Vec<u8>>>>,
+    directories: Mutex<BTreeSet<PathBuf>>,
+}
+impl MemFileSystem {
+    fn file_names(&self) -> Vec<PathBuf> {
+        self.files.lock().unwrap().keys().cloned().collect()
+    }
+    fn content(&self, path: &Path) -> String {
+        String::from_utf8(self.files.lock().unwrap()[path].clone())
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+}
+impl LogFileSystem for MemFileSystem {
+    fn open(&self, path: &Path, append: bool) -> std::io::Result<Box<dyn Write + Send>> {
+        let mut files = self.files.lock().unwrap();
+        let content = files.entry(path.to_path_buf()).or_default();
+        if !append {
+            content.clear();
+        }
+        Ok(Box::new(MemFile {
+            files: Arc::clone(&self.files),
+            path: path.to_path_buf(),
+        }))
+    }
+
+    fn open_for_reading(&self, path: &Path) -> std::io::Result<Box<dyn Read + Send>> {
+        let content = self.files.lock().unwrap().get(path).cloned();
+        content
+            .map(|content| Box::new(std::io::Cursor::new(content)) as Box<dyn Read + Send>)
+            .ok_or_else(not_found)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        let content = files.remove(from).ok_or_else(not_found)?;
+        files.insert(to.to_path_buf(), content);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(not_found)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        self.directories.lock().unwrap().insert(path.to_path_buf());
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        self.directories.lock().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn glob(&self, pattern: &str) -> Vec<PathBuf> {
+        let pattern = glob::Pattern::new(pattern).unwrap();
+        self.files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|path| pattern.matches_path(path))
+            .cloned()
+            .collect()
+    }
+
+    fn metadata(&self, path: &Path) -> std::io::Result<FileMetadata> {
+        if self.directories.lock().unwrap().contains(path) {
+            return Ok(FileMetadata {
+                len: 0,
+                modified: None,
+                is_dir: true,
+            });
+        }
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|content| FileMetadata {
+                len: content.len() as u64,
+                modified: Some(SystemTime::now()),
+                is_dir: false,
+            })
+            .ok_or_else(not_found)
+    }
+}
+
+struct MemFile {
+    files: Arc<Mutex<BTreeMap<PathBuf, Vec<u8>>>>,
+    path: PathBuf,
+}
+impl Write for MemFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.files
+            .lock()
+            .unwrap()
+            .get_mut(&self.path)
+            .ok_or_else(not_found)?
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn not_found() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotFound, "no such file")
+}
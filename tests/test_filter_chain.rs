@@ -0,0 +1,76 @@
+mod test_utils;
+
+use flexi_logger::{
+    filter::{LogLineFilter, LogLineWriter},
+    DeferredNow, FileSpec, Logger,
+};
+use log::*;
+
+// drops the logs of health check requests
+struct NoHealthChecks;
+impl LogLineFilter for NoHealthChecks {
+    fn write(
+        &self,
+        now: &mut DeferredNow,
+        record: &Record,
+        log_line_writer: &dyn LogLineWriter,
+    ) -> std::io::Result<()> {
+        if record.args().to_string().contains("/health") {
+            Ok(())
+        } else {
+            log_line_writer.write(now, record)
+        }
+    }
+}
+
+// masks passwords
+struct MaskPasswords;
+impl LogLineFilter for MaskPasswords {
+    fn write(
+        &self,
+        now: &mut DeferredNow,
+        record: &Record,
+        log_line_writer: &dyn LogLineWriter,
+    ) -> std::io::Result<()> {
+        let msg = record.args().to_string();
+        match msg.find("password=") {
+            Some(idx) => log_line_writer.write(
+                now,
+                &Record::builder()
+                    .args(format_args!("{}password=***", &msg[..idx]))
+                    .level(record.level())
+                    .target(record.target())
+                    .module_path(record.module_path())
+                    .file(record.file())
+                    .line(record.line())
+                    .build(),
+            ),
+            None => log_line_writer.write(now, record),
+        }
+    }
+}
+
+#[test]
+fn test_filter_chain() {
+    let dir = self::test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).suppress_timestamp())
+        .format(|w, _now, record| write!(w, "{}", record.args()))
+        .filter(Box::new(NoHealthChecks))
+        .filter(Box::new(MaskPasswords))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("GET /health");
+    info!("GET /orders");
+    info!("POST /login password=secret");
+    info!("GET /health password=secret");
+    handle.shutdown();
+
+    let content = std::fs::read_to_string(handle.existing_log_files().unwrap().remove(0)).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec!["GET /orders", "POST /login password=***"]
+    );
+}
@@ -0,0 +1,71 @@
+mod test_utils;
+
+#[cfg(feature = "async")]
+mod d {
+    use flexi_logger::{FileSpec, Logger, WriteMode};
+    use log::*;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::Thread;
+    use std::time::Duration;
+
+    const NO_OF_LOGLINES: usize = 1_000;
+
+    #[test]
+    fn test_flush_and_wait() {
+        let handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(
+                FileSpec::default()
+                    .directory(super::test_utils::dir())
+                    .suppress_timestamp(),
+            )
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .write_mode(WriteMode::Async)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..NO_OF_LOGLINES {
+            info!("line {}", i);
+        }
+        assert!(handle.flush_and_wait(Duration::from_secs(5)));
+        let path = handle.existing_log_files().unwrap().remove(0);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().lines().count(),
+            NO_OF_LOGLINES
+        );
+
+        info!("last line");
+        assert!(block_on(
+            handle.flush_and_wait_async(Duration::from_secs(5))
+        ));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().lines().last(),
+            Some("last line")
+        );
+
+        handle.shutdown();
+    }
+
+    // a minimal executor, to avoid depending on an async runtime
+    struct ThreadWaker(Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match Pin::as_mut(&mut future).poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+}
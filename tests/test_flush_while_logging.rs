@@ -0,0 +1,108 @@
+mod test_utils;
+
+#[cfg(feature = "async")]
+mod d {
+    use flexi_logger::writers::{FileMetadata, LogFileSystem, StdFileSystem};
+    use flexi_logger::{FileSpec, Logger, OverflowPolicy, WriteMode};
+    use log::*;
+    use std::io::{Read, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const NO_OF_THREADS: usize = 4;
+    const MAX_LINES_PER_THREAD: usize = 100_000;
+    const NO_OF_FLUSHES: usize = 10;
+
+    // A flush must not wait for the log lines that other threads log after it was requested,
+    // also if the output is slower than the logging threads.
+    #[test]
+    fn test_flush_while_logging() {
+        let handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(
+                FileSpec::default()
+                    .directory(super::test_utils::dir())
+                    .suppress_timestamp(),
+            )
+            .file_system(Arc::new(SlowFileSystem))
+            .write_mode(WriteMode::AsyncWith {
+                bufsize: 0,
+                pool_capa: 100,
+                message_capa: 200,
+                flush_interval: Duration::from_secs(0),
+                channel_capa: Some(100),
+                overflow_policy: OverflowPolicy::Block,
+            })
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let workers: Vec<_> = (0..NO_OF_THREADS)
+            .map(|t| {
+                let stop = Arc::clone(&stop);
+                std::thread::spawn(move || {
+                    for i in 0..MAX_LINES_PER_THREAD {
+                        if stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        info!("thread {}, line {}", t, i);
+                    }
+                })
+            })
+            .collect();
+
+        std::thread::sleep(Duration::from_millis(100));
+        for _ in 0..NO_OF_FLUSHES {
+            assert!(handle.flush_and_wait(Duration::from_secs(5)));
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        handle.shutdown();
+    }
+
+    // Uses the real file system, but lets each write take a while.
+    #[derive(Debug)]
+    struct SlowFileSystem;
+    impl LogFileSystem for SlowFileSystem {
+        fn open(&self, path: &Path, append: bool) -> std::io::Result<Box<dyn Write + Send>> {
+            Ok(Box::new(SlowFile(StdFileSystem.open(path, append)?)))
+        }
+        fn open_for_reading(&self, path: &Path) -> std::io::Result<Box<dyn Read + Send>> {
+            StdFileSystem.open_for_reading(path)
+        }
+        fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+            StdFileSystem.rename(from, to)
+        }
+        fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+            StdFileSystem.remove_file(path)
+        }
+        fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+            StdFileSystem.create_dir_all(path)
+        }
+        fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+            StdFileSystem.remove_dir(path)
+        }
+        fn glob(&self, pattern: &str) -> Vec<PathBuf> {
+            StdFileSystem.glob(pattern)
+        }
+        fn metadata(&self, path: &Path) -> std::io::Result<FileMetadata> {
+            StdFileSystem.metadata(path)
+        }
+    }
+
+    struct SlowFile(Box<dyn Write + Send>);
+    impl Write for SlowFile {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            std::thread::sleep(Duration::from_micros(100));
+            self.0.write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.0.flush()
+        }
+    }
+}
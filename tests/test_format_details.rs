@@ -0,0 +1,84 @@
+use flexi_logger::{json_format, opt_format, FormatDetails, Logger};
+use log::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_format_details() {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_io_writer(Box::new(SharedBuffer(Arc::clone(&output))))
+        .format_for_writer(opt_format)
+        .format_details(
+            FormatDetails::default()
+                .process_id()
+                .thread_id()
+                .thread_name(),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    std::thread::Builder::new()
+        .name("worker".to_string())
+        .spawn(|| info!("from the worker"))
+        .unwrap()
+        .join()
+        .unwrap();
+    info!("from the test thread");
+    logger.flush();
+
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let pid = format!("P[{}] ", std::process::id());
+    assert!(lines[0].contains(&format!("{}TID[", pid)));
+    assert!(lines[0].contains("] T[worker] INFO ["));
+    assert!(lines[1].contains(&pid));
+    assert_ne!(thread_id(lines[0]), thread_id(lines[1]));
+
+    output_of_json_format();
+}
+
+fn output_of_json_format() {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_io_writer(Box::new(SharedBuffer(Arc::clone(&output))))
+        .format_for_writer(json_format)
+        .format_details(FormatDetails::default().process_id().thread_name())
+        .build()
+        .unwrap()
+        .0;
+    logger.log(
+        &Record::builder()
+            .level(Level::Info)
+            .args(format_args!("json"))
+            .build(),
+    );
+    logger.flush();
+
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    assert!(output.contains(&format!(",\"pid\":{},", std::process::id())));
+    assert!(output.contains(",\"thread\":\"test_format_details\","));
+    assert!(!output.contains("\"tid\""));
+}
+
+fn thread_id(line: &str) -> &str {
+    line.split("TID[")
+        .nth(1)
+        .unwrap()
+        .split(']')
+        .next()
+        .unwrap()
+}
+
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FormatFunction, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_format_for_additional_writer() {
+    let console_output = Arc::new(Mutex::new(Vec::new()));
+    let machine_output = Arc::new(Mutex::new(Vec::new()));
+
+    let _logger = Logger::try_with_str("info")
+        .unwrap()
+        .do_not_log()
+        .format_for_additional_writer("Machine", machine_format)
+        .add_writer("Console", Box::new(SharedWriter::new(&console_output)))
+        .add_writer("Machine", Box::new(SharedWriter::new(&machine_output)))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!(target: "{Console,Machine}", "This is an error message");
+    warn!(target: "{Console,Machine}", "This is a warning");
+
+    assert_eq!(
+        String::from_utf8(console_output.lock().unwrap().clone()).unwrap(),
+        "This is an error message\nThis is a warning\n"
+    );
+    assert_eq!(
+        String::from_utf8(machine_output.lock().unwrap().clone()).unwrap(),
+        "level=ERROR msg=\"This is an error message\"\nlevel=WARN msg=\"This is a warning\"\n"
+    );
+}
+
+struct SharedWriter {
+    output: Arc<Mutex<Vec<u8>>>,
+    format: FormatFunction,
+}
+impl SharedWriter {
+    fn new(output: &Arc<Mutex<Vec<u8>>>) -> Self {
+        Self {
+            output: Arc::clone(output),
+            format: console_format,
+        }
+    }
+}
+impl LogWriter for SharedWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let mut output = self.output.lock().unwrap();
+        (self.format)(&mut *output, now, record)?;
+        output.push(b'\n');
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+}
+
+fn console_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(w, "{}", record.args())
+}
+
+fn machine_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(w, "level={} msg=\"{}\"", record.level(), record.args())
+}
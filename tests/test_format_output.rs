@@ -0,0 +1,72 @@
+mod test_utils;
+
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FileSpec, Logger, Output};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_format_output() {
+    let db_output = Arc::new(Mutex::new(Vec::new()));
+
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(self::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .format(tagged_format)
+        .add_writer("db", Box::new(SharedWriter(Arc::clone(&db_output))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!(target: "{db,_Default}", "to both");
+    info!("to the file");
+    handle.flush();
+
+    let content = std::fs::read_to_string(handle.existing_log_files().unwrap().remove(0)).unwrap();
+    assert_eq!(content, "[file] to both\n[file] to the file\n");
+    assert_eq!(
+        String::from_utf8(db_output.lock().unwrap().clone()).unwrap(),
+        "[db] to both\n"
+    );
+
+    // outside the logger, the output is not known
+    let mut buffer = Vec::new();
+    tagged_format(
+        &mut buffer,
+        &mut DeferredNow::new(),
+        &Record::builder().args(format_args!("direct")).build(),
+    )
+    .unwrap();
+    assert_eq!(buffer, b"[?] direct");
+}
+
+fn tagged_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    match now.output() {
+        Some(Output::File) => write!(w, "[file] ")?,
+        Some(Output::AdditionalWriter(name)) => write!(w, "[{}] ", name)?,
+        Some(_) => write!(w, "[other] ")?,
+        None => write!(w, "[?] ")?,
+    }
+    write!(w, "{}", record.args())
+}
+
+struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+impl LogWriter for SharedWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let mut output = self.0.lock().unwrap();
+        tagged_format(&mut *output, now, record)?;
+        output.push(b'\n');
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
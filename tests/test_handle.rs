@@ -0,0 +1,31 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger, WriteMode};
+use log::*;
+
+#[test]
+fn test_handle() {
+    assert!(flexi_logger::handle().is_none());
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(self::test_utils::dir()))
+        .write_mode(WriteMode::BufferDontFlush)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    deeply_nested_code();
+    debug!("This is a debug message");
+
+    logger.validate_logs(&[
+        ("ERROR", "test_handle", "error message"),
+        ("DEBUG", "test_handle", "debug message"),
+    ]);
+}
+
+fn deeply_nested_code() {
+    let mut handle = flexi_logger::handle().expect("no active logger");
+    handle.flush();
+    handle.parse_new_spec("debug").unwrap();
+}
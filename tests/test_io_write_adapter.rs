@@ -0,0 +1,52 @@
+mod test_utils;
+
+use flexi_logger::writers::{ArcFileLogWriter, FileLogWriter};
+use flexi_logger::{Cleanup, Criterion, FileSpec, Naming};
+use std::io::{LineWriter, Write};
+
+fn assert_send_clone_write<W: Write + Send + Clone + 'static>(_: &W) {}
+
+#[test]
+fn test_io_write_adapter() {
+    let dir = self::test_utils::dir();
+    let (file_writer, fw_handle): (ArcFileLogWriter, _) =
+        FileLogWriter::builder(FileSpec::default().directory(&dir).basename("io"))
+            .rotate(Criterion::Size(20), Naming::Numbers, Cleanup::Never)
+            .try_build_with_handle()
+            .unwrap();
+    assert_send_clone_write(&file_writer);
+
+    // several threads write through their own clones
+    let workers: Vec<_> = (0..3)
+        .map(|t| {
+            let file_writer = file_writer.clone();
+            std::thread::spawn(move || {
+                let mut line_writer = LineWriter::new(file_writer);
+                for i in 0..10 {
+                    writeln!(line_writer, "thread {} line {}", t, i).unwrap();
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    // output of a "child process" is piped in chunks that do not respect line boundaries
+    let mut child_output: &[u8] = b"child line 0\nchild line 1\nchild line 2\n";
+    std::io::copy(&mut child_output, &mut LineWriter::new(file_writer)).unwrap();
+    drop(fw_handle);
+
+    let mut lines = Vec::new();
+    let mut no_of_files = 0;
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        no_of_files += 1;
+        let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        lines.extend(content.lines().map(ToString::to_string));
+    }
+    assert!(no_of_files > 1, "no rotation happened");
+    assert_eq!(lines.len(), 33);
+    assert!(lines
+        .iter()
+        .all(|line| line.starts_with("thread ") || line.starts_with("child line ")));
+}
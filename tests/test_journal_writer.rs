@@ -0,0 +1,66 @@
+mod test_utils;
+
+#[cfg(all(feature = "journald", target_os = "linux"))]
+mod a {
+    use flexi_logger::writers::{JournalWriter, LogWriter};
+    use flexi_logger::DeferredNow;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn test_journal_writer() {
+        let directory = super::test_utils::dir();
+        std::fs::create_dir_all(&directory).unwrap();
+        let socket_path = directory.join("journal.socket");
+        let journal = UnixDatagram::bind(&socket_path).unwrap();
+        let writer = JournalWriter::try_with_path(&socket_path, log::LevelFilter::Info).unwrap();
+
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("This is a warning"))
+                    .level(log::Level::Warn)
+                    .target("my_target")
+                    .module_path(Some("my_module"))
+                    .file(Some("src/my_module.rs"))
+                    .line(Some(42))
+                    .build(),
+            )
+            .unwrap();
+        let entry = receive(&journal);
+        assert!(entry.starts_with(b"MESSAGE=This is a warning\nPRIORITY=4\nSYSLOG_IDENTIFIER="));
+        assert!(ends_with(
+            &entry,
+            b"\nTARGET=my_target\nCODE_MODULE=my_module\nCODE_FILE=src/my_module.rs\nCODE_LINE=42\n"
+        ));
+
+        // multi-line messages use the binary form
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("first line\nsecond line"))
+                    .level(log::Level::Error)
+                    .target("my_target")
+                    .build(),
+            )
+            .unwrap();
+        let entry = receive(&journal);
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&22_u64.to_le_bytes());
+        expected.extend_from_slice(b"first line\nsecond line\nPRIORITY=3\n");
+        assert!(entry.starts_with(&expected));
+        assert!(ends_with(&entry, b"\nTARGET=my_target\n"));
+    }
+
+    fn receive(journal: &UnixDatagram) -> Vec<u8> {
+        let mut buffer = vec![0_u8; 4096];
+        let len = journal.recv(&mut buffer).unwrap();
+        buffer.truncate(len);
+        buffer
+    }
+
+    fn ends_with(entry: &[u8], suffix: &[u8]) -> bool {
+        entry.len() >= suffix.len() && &entry[entry.len() - suffix.len()..] == suffix
+    }
+}
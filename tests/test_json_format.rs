@@ -0,0 +1,36 @@
+mod test_utils;
+
+use flexi_logger::{json_format, FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_json_format() {
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(self::test_utils::dir()))
+        .format(json_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("He said \"stop\",\nand left\t\\o/");
+    info!(target: "my_target", "control char: {}", '\u{1}');
+
+    logger.validate_logs(&[
+        (
+            r#"{"timestamp":""#,
+            r#"","level":"ERROR","target":"test_json_format","module":"test_json_format","file":"tests/test_json_format.rs","line":15,"#,
+            r#""message":"This is an error message"}"#,
+        ),
+        (
+            r#""level":"WARN""#,
+            r#""line":16,"#,
+            r#""message":"He said \"stop\",\nand left\t\\o/"}"#,
+        ),
+        (
+            r#""level":"INFO","target":"my_target""#,
+            r#""line":17,"#,
+            r#""message":"control char: \u0001"}"#,
+        ),
+    ]);
+}
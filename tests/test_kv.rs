@@ -0,0 +1,56 @@
+#[cfg(feature = "kv")]
+mod a {
+    use flexi_logger::{default_format, json_format, logfmt_format, DeferredNow, FormatFunction};
+
+    #[test]
+    fn test_kv() {
+        for (format, expected) in &[
+            (
+                default_format as FormatFunction,
+                r#"INFO [my_module] logged in user=alice attempts=3 admin=false"#,
+            ),
+            (
+                json_format,
+                r#""message":"logged in","kv":{"user":"alice","attempts":3,"admin":false}}"#,
+            ),
+            (
+                logfmt_format,
+                r#" msg="logged in" user=alice attempts=3 admin=false"#,
+            ),
+        ] {
+            let key_values: &[(&str, &dyn log::kv::ToValue)] =
+                &[("user", &"alice"), ("attempts", &3_u32), ("admin", &false)];
+            let mut buffer = Vec::<u8>::new();
+            format(
+                &mut buffer,
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("logged in"))
+                    .level(log::Level::Info)
+                    .module_path(Some("my_module"))
+                    .key_values(&key_values)
+                    .build(),
+            )
+            .unwrap();
+            let line = String::from_utf8(buffer).unwrap();
+            assert!(line.ends_with(expected), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_json_without_kv() {
+        let mut buffer = Vec::<u8>::new();
+        json_format(
+            &mut buffer,
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("no pairs"))
+                .level(log::Level::Info)
+                .build(),
+        )
+        .unwrap();
+        assert!(String::from_utf8(buffer)
+            .unwrap()
+            .ends_with(r#""message":"no pairs"}"#));
+    }
+}
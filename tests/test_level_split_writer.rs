@@ -0,0 +1,36 @@
+mod test_utils;
+
+use flexi_logger::writers::{FileLogWriter, LevelSplitWriter};
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_level_split_writer() {
+    let dir = self::test_utils::dir();
+    let logger = Logger::try_with_str("debug")
+        .unwrap()
+        .log_to_writer(Box::new(
+            LevelSplitWriter::try_new(&FileLogWriter::builder(
+                FileSpec::default().directory(&dir).suppress_timestamp(),
+            ))
+            .unwrap(),
+        ))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("error-1");
+    warn!("warn-1");
+    info!("info-1");
+    info!("info-2");
+    debug!("debug-1");
+    trace!("trace-1 is not enabled");
+    logger.flush();
+
+    let content = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+    assert!(content("error.log").contains("error-1"));
+    assert_eq!(content("warn.log").lines().count(), 1);
+    assert_eq!(content("info.log").lines().count(), 2);
+    assert!(content("debug.log").contains("debug-1"));
+    // files are only created for levels that are written
+    assert!(!dir.join("trace.log").exists());
+}
@@ -0,0 +1,50 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_lifecycle_records() {
+    let handle = Logger::try_with_str("info, flexi_logger=off")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(self::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .format(|w, _now, record| write!(w, "{} {}", record.level(), record.args()))
+        .startup_banner("1.2.3")
+        .shutdown_summary(true)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("one");
+    debug!("not enabled");
+    warn!("two");
+    info!("three");
+    handle.shutdown();
+    // the summary is written only once
+    handle.shutdown();
+
+    let content = std::fs::read_to_string(handle.existing_log_files().unwrap().remove(0)).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 5, "unexpected content: {}", content);
+
+    let banner = lines[0];
+    assert!(banner.starts_with("INFO startup: binary=test_lifecycle_records"));
+    assert!(banner.contains(" version=1.2.3 "));
+    assert!(banner.contains(&format!(" pid={} ", std::process::id())));
+    assert!(banner.contains(" hostname="));
+    assert!(banner.ends_with(" spec=\"info,flexi_logger=off\""));
+
+    assert_eq!(&lines[1..4], &["INFO one", "WARN two", "INFO three"]);
+
+    let bytes_before_summary = content.len() - lines[4].len() - 1;
+    assert_eq!(
+        lines[4],
+        format!(
+            "INFO shutdown: error=0 warn=1 info=2 debug=0 trace=0 bytes={}",
+            bytes_before_summary
+        )
+    );
+}
@@ -0,0 +1,96 @@
+mod test_utils;
+
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{DeferredNow, Duplicate, FileSpec, Logger};
+use log::*;
+use std::io::Write;
+use std::process::Command;
+
+const CHILD_DIR: &str = "FLEXI_LOGGER_TEST_LINE_ENDINGS_DIR";
+
+// The test re-runs itself in a child process, to capture what is duplicated to stdout.
+#[test]
+fn test_windows_line_ending_everywhere() {
+    if let Ok(directory) = std::env::var(CHILD_DIR) {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(directory))
+            .format(plain_format)
+            .duplicate_to_stdout(Duplicate::Info)
+            .use_windows_line_ending()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        info!("first line");
+        info!("second line");
+        return;
+    }
+
+    let directory = self::test_utils::dir().join("everywhere");
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(&[
+            "--exact",
+            "test_windows_line_ending_everywhere",
+            "--nocapture",
+            "-q",
+        ])
+        .env(CHILD_DIR, &directory)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // stdout also contains the output of the test harness
+    assert!(stdout.contains("first line\r\nsecond line\r\n"));
+
+    let logfile = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    assert_eq!(
+        std::fs::read_to_string(logfile).unwrap(),
+        "first line\r\nsecond line\r\n"
+    );
+}
+
+#[test]
+fn test_windows_line_ending_with_io_write() {
+    let directory = self::test_utils::dir().join("io_write");
+    let (mut writer, handle) = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .suppress_timestamp(),
+    )
+    .format(plain_format)
+    .use_windows_line_ending()
+    .try_build_with_handle()
+    .unwrap();
+
+    writer.write_all(b"unix\nwindows\r\n").unwrap();
+    writer.write_record(b"one\nrecord").unwrap();
+    writer.write_all(b"multi\nline").unwrap();
+    writer.write_all(b"\n").unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    drop(handle);
+
+    let logfile = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    assert_eq!(
+        std::fs::read_to_string(logfile).unwrap(),
+        "unix\r\nwindows\r\none\r\nrecord\r\nmulti\r\nline\r\n"
+    );
+}
+
+fn plain_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    write!(w, "{}", record.args())
+}
@@ -0,0 +1,33 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger, WriteMode};
+use log::*;
+
+#[test]
+fn test_log_panics() {
+    let dir = self::test_utils::dir();
+    let _logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).suppress_timestamp())
+        .write_mode(WriteMode::BufferDontFlush)
+        .log_panics(true)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("before the panic");
+    let result = std::thread::Builder::new()
+        .name("doomed".to_string())
+        .spawn(|| panic!("something went wrong: {}", 42))
+        .unwrap()
+        .join();
+    assert!(result.is_err());
+
+    // the panic hook has flushed the buffered output
+    let content: String = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(content.contains("before the panic"));
+    assert!(content.contains("thread 'doomed' panicked at 'something went wrong: 42'"));
+    assert!(content.contains("test_log_panics.rs:"));
+}
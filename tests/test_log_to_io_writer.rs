@@ -0,0 +1,37 @@
+use flexi_logger::{detailed_format, Logger};
+use log::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_log_to_io_writer() {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_io_writer(Box::new(SharedBuffer(Arc::clone(&output))))
+        .format_for_writer(detailed_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+    logger.flush();
+
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("ERROR [test_log_to_io_writer] tests/test_log_to_io_writer.rs:"));
+    assert!(lines[0].ends_with("This is an error message"));
+    assert!(lines[1].ends_with("This is an info message"));
+}
+
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
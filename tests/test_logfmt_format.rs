@@ -0,0 +1,30 @@
+use flexi_logger::{logfmt_format, DeferredNow};
+use log::Level;
+
+#[test]
+fn test_logfmt_format() {
+    for (message, expected) in &[
+        ("text", " level=warn target=my_target msg=text"),
+        (
+            "He said \"stop\",\nand left",
+            r#" level=warn target=my_target msg="He said \"stop\",\nand left""#,
+        ),
+        ("a=b", r#" level=warn target=my_target msg="a=b""#),
+        ("", r#" level=warn target=my_target msg="""#),
+    ] {
+        let mut buffer = Vec::<u8>::new();
+        logfmt_format(
+            &mut buffer,
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("{}", message))
+                .level(Level::Warn)
+                .target("my_target")
+                .build(),
+        )
+        .unwrap();
+        let line = String::from_utf8(buffer).unwrap();
+        assert!(line.starts_with("ts="), "{}", line);
+        assert!(line.ends_with(expected), "{}", line);
+    }
+}
@@ -0,0 +1,61 @@
+mod test_utils;
+
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{Cleanup, Criterion, FileSpec, Naming};
+use std::io::Write;
+use std::path::Path;
+
+#[test]
+fn test_manifest() {
+    let directory = self::test_utils::dir();
+    // the second run continues the manifest of the first run
+    for run in 0..2 {
+        let (mut file_writer, fw_handle) = FileLogWriter::builder(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("listed")
+                .suppress_timestamp(),
+        )
+        .rotate(
+            Criterion::Size(200),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(3),
+        )
+        .cleanup_in_background_thread(false)
+        .write_manifest()
+        .try_build_with_handle()
+        .unwrap();
+        for i in 0..30 {
+            writeln!(file_writer, "This is line number {} of run {}", i, run).unwrap();
+        }
+        drop(fw_handle);
+    }
+
+    let manifest = std::fs::read_to_string(directory.join("listed.manifest.json")).unwrap();
+    let lines: Vec<&str> = manifest.lines().collect();
+    assert_eq!(lines[0], r#"{"files":["#);
+    assert_eq!(lines[lines.len() - 1], "]}");
+    let entries = &lines[1..lines.len() - 1];
+
+    // only the remaining rotated files are listed
+    let mut rotated: Vec<String> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path().to_string_lossy().to_string())
+        .filter(|path| path.contains("listed_r0"))
+        .collect();
+    rotated.sort();
+    assert_eq!(rotated.len(), 3);
+    assert_eq!(entries.len(), 3);
+
+    for (entry, path) in entries.iter().zip(rotated.iter()) {
+        let escaped_path = path.replace('\\', "\\\\");
+        assert!(
+            entry.starts_with(&format!(r#"{{"path":"{}","start":""#, escaped_path)),
+            "unexpected entry {}",
+            entry
+        );
+        let size = std::fs::metadata(Path::new(path)).unwrap().len();
+        assert!(entry.contains(&format!(r#","size":{},"compressed":false,"#, size)));
+        assert!(entry.contains(r#""checksum":"crc32:"#));
+    }
+}
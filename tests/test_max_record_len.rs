@@ -0,0 +1,35 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_max_record_len() {
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(self::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .format(|w, _now, record| write!(w, "{}", record.args()))
+        .max_record_len(20)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("short line");
+    info!("{}", "x".repeat(40_000_000));
+    // the limit falls into the two-byte encoding of "ä"
+    info!("{}äöü", "y".repeat(19));
+    info!("exactly twenty bytes");
+
+    assert_eq!(
+        handle.read_back(10).unwrap(),
+        vec![
+            "short line".to_string(),
+            format!("{}…[truncated 39999980 bytes]", "x".repeat(20)),
+            format!("{}…[truncated 6 bytes]", "y".repeat(19)),
+            "exactly twenty bytes".to_string(),
+        ]
+    );
+}
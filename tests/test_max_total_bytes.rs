@@ -0,0 +1,50 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::Record;
+use std::time::Duration;
+
+fn log_line(logger: &dyn log::Log, line: &str) {
+    logger.log(
+        &Record::builder()
+            .args(format_args!("{}", line))
+            .level(log::Level::Info)
+            .build(),
+    );
+}
+
+#[test]
+fn test_max_total_bytes() {
+    let dir = self::test_utils::dir();
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).basename("quota"))
+        .format(|w, _now, record| write!(w, "{}", record.args()))
+        .rotate(Criterion::Size(44), Naming::Numbers, Cleanup::Never)
+        .max_total_bytes_on_disk(100)
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    // each line has 11 bytes, so only 9 lines fit
+    for _ in 0..20 {
+        log_line(&*logger, "0123456789");
+    }
+    let stats = handle.writer_stats().unwrap();
+    assert_eq!(stats.records, 9);
+    assert_eq!(stats.suspended, 11);
+
+    // writing is resumed when space is reclaimed
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if !path.to_string_lossy().contains("CURRENT") {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+    std::thread::sleep(Duration::from_millis(1100));
+    log_line(&*logger, "resumed");
+    let stats = handle.writer_stats().unwrap();
+    assert_eq!(stats.records, 10);
+    assert_eq!(stats.suspended, 11);
+
+    handle.shutdown();
+}
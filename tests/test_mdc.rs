@@ -0,0 +1,72 @@
+use flexi_logger::{json_format, mdc, opt_format, Logger};
+use log::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_mdc() {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_io_writer(Box::new(SharedBuffer(Arc::clone(&output))))
+        .format_for_writer(opt_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("without context");
+    mdc::insert("user", "anna");
+    {
+        let _guard = mdc::insert_scoped("request_id", 4711);
+        assert_eq!(mdc::get("request_id"), Some("4711".to_string()));
+        info!("within request");
+        std::thread::spawn(|| info!("other thread")).join().unwrap();
+    }
+    assert_eq!(mdc::get("request_id"), None);
+    info!("after request");
+    assert_eq!(mdc::remove("user"), Some("anna".to_string()));
+    info!("without context again");
+    logger.flush();
+
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].ends_with("without context"));
+    assert!(lines[1].ends_with("within request request_id=4711 user=anna"));
+    assert!(lines[2].ends_with("other thread"));
+    assert!(lines[3].ends_with("after request user=anna"));
+    assert!(lines[4].ends_with("without context again"));
+}
+
+#[test]
+fn test_mdc_json() {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_io_writer(Box::new(SharedBuffer(Arc::clone(&output))))
+        .format_for_writer(json_format)
+        .build()
+        .unwrap()
+        .0;
+
+    let _guard = mdc::insert_scoped("request_id", "a\"b");
+    logger.log(
+        &Record::builder()
+            .level(Level::Info)
+            .args(format_args!("json"))
+            .build(),
+    );
+    logger.flush();
+
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    assert!(output.ends_with(",\"message\":\"json\",\"mdc\":{\"request_id\":\"a\\\"b\"}}\n"));
+}
+
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+mod test_utils;
+
+#[cfg(feature = "mmap_ring")]
+mod a {
+    use flexi_logger::writers::{LogWriter, MmapRingWriter};
+    use flexi_logger::DeferredNow;
+
+    #[test]
+    fn test_mmap_ring_writer() {
+        let directory = super::test_utils::dir();
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("crash.ring");
+
+        let writer = MmapRingWriter::builder(&path)
+            .capacity(1024)
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .try_build()
+            .unwrap();
+        for i in 0..5 {
+            write(&writer, i);
+        }
+        // the content is available without flushing or dropping the writer
+        let expected: String = (0..5)
+            .map(|i| format!("record number {:03}\n", i))
+            .collect();
+        assert_eq!(
+            String::from_utf8(MmapRingWriter::recover(&path).unwrap()).unwrap(),
+            expected
+        );
+
+        // when the ring is full, the oldest lines are overwritten
+        for i in 5..200 {
+            write(&writer, i);
+        }
+        let recovered = String::from_utf8(MmapRingWriter::recover(&path).unwrap()).unwrap();
+        assert!(recovered.len() <= 1024);
+        assert!(recovered.ends_with("record number 199\n"));
+        let lines: Vec<&str> = recovered.lines().collect();
+        let first: usize = lines[0]["record number ".len()..].parse().unwrap();
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(*line, format!("record number {:03}", first + i));
+        }
+        assert_eq!(first + lines.len(), 200);
+
+        // a restart preserves the previous content
+        drop(writer);
+        let writer = MmapRingWriter::builder(&path).try_build().unwrap();
+        let mut prev_path = path.clone().into_os_string();
+        prev_path.push(".prev");
+        assert_eq!(
+            MmapRingWriter::recover(&prev_path).unwrap(),
+            recovered.as_bytes()
+        );
+        assert!(MmapRingWriter::recover(&path).unwrap().is_empty());
+        drop(writer);
+    }
+
+    fn write(writer: &MmapRingWriter, i: usize) {
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("record number {:03}", i))
+                    .level(log::Level::Info)
+                    .build(),
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use flexi_logger::writers::{LogWriter, NetworkWriter};
+use flexi_logger::DeferredNow;
+use std::io::{BufRead, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+#[test]
+fn test_network_writer() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let writer = NetworkWriter::builder(listener.local_addr().unwrap().to_string())
+        .try_build()
+        .unwrap();
+
+    for i in 0..3 {
+        write(&writer, i);
+    }
+    writer.shutdown();
+
+    let (stream, _) = listener.accept().unwrap();
+    let lines: Vec<String> = BufReader::new(stream).lines().map(Result::unwrap).collect();
+    assert_eq!(lines.len(), 3);
+    for (i, line) in lines.iter().enumerate() {
+        assert!(line.ends_with(&format!("This is record number {}", i)));
+    }
+    assert_eq!(writer.dropped_records(), 0);
+}
+
+#[test]
+fn test_network_writer_reconnect() {
+    // find a free port, and keep it unused until the records were written
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let writer = NetworkWriter::builder(address.to_string())
+        .backoff(Duration::from_millis(10), Duration::from_millis(50))
+        .try_build()
+        .unwrap();
+
+    for i in 0..5 {
+        write(&writer, i);
+    }
+    std::thread::sleep(Duration::from_millis(200));
+
+    let listener = TcpListener::bind(address).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let lines = read_lines(stream, 5);
+    for (i, line) in lines.iter().enumerate() {
+        assert!(line.ends_with(&format!("This is record number {}", i)));
+    }
+    writer.shutdown();
+    assert_eq!(writer.dropped_records(), 0);
+}
+
+#[test]
+fn test_network_writer_full_buffer() {
+    // find a free port, and keep it unused
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let writer = NetworkWriter::builder(address.to_string())
+        .buffer_capacity(2)
+        .connect_timeout(Duration::from_millis(100))
+        .backoff(Duration::from_millis(10), Duration::from_millis(50))
+        .try_build()
+        .unwrap();
+
+    // records that don't fit into the buffer are dropped without an error
+    for i in 0..20 {
+        write(&writer, i);
+    }
+    assert_eq!(writer.dropped_records(), 18);
+
+    // the buffered records are lost with the shutdown
+    writer.shutdown();
+    assert_eq!(writer.dropped_records(), 20);
+}
+
+#[test]
+fn test_network_writer_stalled_endpoint() {
+    // the endpoint accepts the connection, but never reads
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let writer = NetworkWriter::builder(listener.local_addr().unwrap().to_string())
+        .buffer_capacity(100)
+        .write_timeout(Duration::from_millis(200))
+        .backoff(Duration::from_millis(10), Duration::from_millis(50))
+        .try_build()
+        .unwrap();
+    let _stream = listener.accept().unwrap();
+
+    // fill the socket buffers and the buffer of the writer
+    let long_text = "x".repeat(100_000);
+    for _ in 0..1_000 {
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("{}", long_text))
+                    .level(log::Level::Info)
+                    .build(),
+            )
+            .unwrap();
+    }
+    assert!(writer.dropped_records() > 0);
+
+    // the shutdown does not wait for the endpoint
+    let start = std::time::Instant::now();
+    writer.shutdown();
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+fn write(writer: &NetworkWriter, i: usize) {
+    writer
+        .write(
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("This is record number {}", i))
+                .level(log::Level::Info)
+                .build(),
+        )
+        .unwrap();
+}
+
+fn read_lines(stream: TcpStream, count: usize) -> Vec<String> {
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    BufReader::new(stream)
+        .lines()
+        .take(count)
+        .map(Result::unwrap)
+        .collect()
+}
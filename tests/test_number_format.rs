@@ -0,0 +1,55 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+
+#[test]
+fn test_number_format() {
+    let dir = self::test_utils::dir();
+
+    {
+        let _handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&dir).basename("app"))
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .rotate(
+                Criterion::Size(0),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(3),
+            )
+            .number_separator(".")
+            .number_width(0)
+            .first_number(1)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 1..=12 {
+            info!("{}", i);
+        }
+    }
+
+    // the cleanup must keep the files with the highest numbers, not the "highest" names
+    assert_eq!(
+        file_names(&dir),
+        vec!["app.10.log", "app.11.log", "app.9.log", "app_rCURRENT.log"]
+    );
+    assert_eq!(read(&dir, "app.11.log"), "11");
+    assert_eq!(read(&dir, "app_rCURRENT.log"), "12");
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+fn read(dir: &Path, filename: &str) -> String {
+    std::fs::read_to_string(dir.join(filename))
+        .unwrap()
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,63 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn test_numbers_round_robin() {
+    let dir = self::test_utils::dir();
+
+    log_lines(&dir, &["1", "2", "3", "4", "5", "6"]);
+    assert_eq!(rotated_files(&dir).len(), 3);
+    assert_eq!(read(&dir, "rr_r00000.log"), "4");
+    assert_eq!(read(&dir, "rr_r00001.log"), "5");
+    assert_eq!(read(&dir, "rr_r00002.log"), "3");
+    assert_eq!(read(&dir, "rr_rCURRENT.log"), "6");
+
+    // after a restart, the oldest file is overwritten first
+    log_lines(&dir, &["7"]);
+    assert_eq!(rotated_files(&dir).len(), 3);
+    assert_eq!(read(&dir, "rr_r00002.log"), "6");
+    assert_eq!(read(&dir, "rr_rCURRENT.log"), "7");
+}
+
+fn log_lines(dir: &Path, lines: &[&str]) {
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(dir).basename("rr"))
+        .format(|w, _now, record| write!(w, "{}", record.args()))
+        .rotate(
+            Criterion::Size(0),
+            Naming::NumbersRoundRobin(3),
+            Cleanup::Never,
+        )
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    for line in lines {
+        logger.log(
+            &log::Record::builder()
+                .args(format_args!("{}", line))
+                .level(log::Level::Info)
+                .build(),
+        );
+        // the files must be distinguishable by their modification time
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    handle.shutdown();
+}
+
+fn read(dir: &Path, filename: &str) -> String {
+    std::fs::read_to_string(dir.join(filename))
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+fn rotated_files(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| !name.contains("CURRENT"))
+        .collect()
+}
@@ -0,0 +1,63 @@
+mod test_utils;
+
+#[cfg(feature = "async")]
+mod d {
+    use flexi_logger::writers::{FileLogWriter, LogWriter};
+    use flexi_logger::{DeferredNow, FileSpec, OverflowPolicy, WriteMode};
+    use std::io::{BufRead, BufReader};
+    use std::time::Duration;
+
+    const NO_OF_LOGLINES: usize = 20_000;
+
+    #[test]
+    fn test_overflow_policy() {
+        assert_eq!(
+            write_and_count(OverflowPolicy::Block, "block"),
+            (NO_OF_LOGLINES, 0)
+        );
+        for (policy, discriminant) in &[
+            (OverflowPolicy::DropNewest, "drop_newest"),
+            (OverflowPolicy::DropOldestWithCounter, "drop_oldest"),
+        ] {
+            let (written, dropped) = write_and_count(*policy, discriminant);
+            assert_eq!(written + dropped, NO_OF_LOGLINES);
+        }
+    }
+
+    // returns the number of written and the number of dropped lines
+    fn write_and_count(overflow_policy: OverflowPolicy, discriminant: &str) -> (usize, usize) {
+        let flw = FileLogWriter::builder(
+            FileSpec::default()
+                .directory(super::test_utils::dir())
+                .discriminant(discriminant)
+                .suppress_timestamp(),
+        )
+        .write_mode(WriteMode::AsyncWith {
+            bufsize: 1024,
+            pool_capa: 10,
+            message_capa: 100,
+            flush_interval: Duration::from_secs(0),
+            channel_capa: Some(2),
+            overflow_policy,
+        })
+        .try_build()
+        .unwrap();
+
+        for i in 0..NO_OF_LOGLINES {
+            flw.write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("line {}", i))
+                    .level(log::Level::Info)
+                    .build(),
+            )
+            .unwrap();
+        }
+        flw.shutdown();
+
+        let written = BufReader::new(std::fs::File::open(flw.current_filename()).unwrap())
+            .lines()
+            .count();
+        (written, flw.dropped_records())
+    }
+}
@@ -0,0 +1,72 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, FlexiLoggerError, Logger, Naming};
+use log::*;
+use std::path::PathBuf;
+
+#[test]
+fn test_pin() {
+    let directory = self::test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .rotate(
+            Criterion::Size(100),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(2),
+        )
+        .cleanup_in_background_thread(false)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..5 {
+        info!("This is log line number {}", i);
+    }
+    let rotated = rotated_files(&directory);
+    assert!(!rotated.is_empty());
+    let first_rotated = rotated[0].clone();
+
+    // the current output file cannot be pinned
+    match logger.pin(current_file(&directory)) {
+        Err(FlexiLoggerError::OutputNotRotated) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let pinned = logger.pin(&first_rotated).unwrap();
+    assert!(pinned.to_string_lossy().ends_with("_r00000.keep.log"));
+    assert!(!first_rotated.exists());
+    assert_eq!(logger.pin(&pinned).unwrap(), pinned);
+
+    for i in 5..50 {
+        info!("This is log line number {}", i);
+    }
+    logger.flush();
+
+    assert!(pinned.exists());
+    let rotated = rotated_files(&directory);
+    assert!(rotated.contains(&pinned));
+    assert_eq!(rotated.len(), 3);
+}
+
+fn files(directory: &std::path::Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    files
+}
+
+fn rotated_files(directory: &std::path::Path) -> Vec<PathBuf> {
+    files(directory)
+        .into_iter()
+        .filter(|path| !path.to_string_lossy().contains("_rCURRENT"))
+        .collect()
+}
+
+fn current_file(directory: &std::path::Path) -> PathBuf {
+    files(directory)
+        .into_iter()
+        .find(|path| path.to_string_lossy().contains("_rCURRENT"))
+        .unwrap()
+}
@@ -0,0 +1,52 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+
+#[test]
+fn test_pipe_reader() {
+    let dir = self::test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).suppress_timestamp())
+        .format(|w, _now, record| {
+            write!(
+                w,
+                "{} [{}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            )
+        })
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let stdout: &'static [u8] = b"first line\nsecond line\r\n\nbroken \xF0\x90 utf8\nno line break";
+    handle
+        .pipe_reader(log::Level::Info, "child", stdout)
+        .unwrap()
+        .join()
+        .unwrap()
+        .unwrap();
+
+    // lines with a disabled level are dropped
+    let stderr: &'static [u8] = b"debug output\n";
+    handle
+        .pipe_reader(log::Level::Debug, "child", stderr)
+        .unwrap()
+        .join()
+        .unwrap()
+        .unwrap();
+
+    handle.shutdown();
+    let content = std::fs::read_to_string(handle.existing_log_files().unwrap().remove(0)).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec![
+            "INFO [child] first line",
+            "INFO [child] second line",
+            "INFO [child] ",
+            "INFO [child] broken \u{FFFD} utf8",
+            "INFO [child] no line break",
+        ]
+    );
+}
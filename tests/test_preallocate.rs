@@ -0,0 +1,43 @@
+mod test_utils;
+
+#[cfg(all(feature = "preallocate", target_os = "linux"))]
+mod a {
+    use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+    use log::*;
+    use std::os::unix::fs::MetadataExt;
+
+    const PREALLOCATED: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_preallocate() {
+        let directory = super::test_utils::dir();
+        let handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&directory))
+            .rotate(
+                Criterion::Size(PREALLOCATED),
+                Naming::Numbers,
+                Cleanup::Never,
+            )
+            .preallocate(PREALLOCATED)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        info!("This is a log line");
+        handle.flush();
+
+        let path = handle.existing_log_files().unwrap().remove(0);
+        let metadata = std::fs::metadata(&path).unwrap();
+        // the size of the file is not changed
+        assert!(metadata.len() < 1000, "unexpected size {}", metadata.len());
+        // not all file systems support preallocation
+        if metadata.blocks() * 512 < PREALLOCATED {
+            println!(
+                "file system of {} does not preallocate",
+                directory.display()
+            );
+        }
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.ends_with("This is a log line\n"));
+    }
+}
@@ -0,0 +1,67 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, Logger, RateLimit};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+const LIMIT: usize = 5;
+
+#[test]
+fn test_rate_limit() {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let _logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(SharedWriter(Arc::clone(&output))))
+        .rate_limit(RateLimit::per_second(LIMIT).per_target())
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..20 {
+        info!(target: "noisy", "noisy {}", i);
+        info!(target: "quiet", "quiet {}", i);
+    }
+    debug!(target: "noisy", "this line is not counted, it is not enabled");
+    assert_eq!(
+        take_lines(&output),
+        (0..LIMIT)
+            .flat_map(|i| vec![format!("noisy noisy {}", i), format!("quiet quiet {}", i)])
+            .collect::<Vec<_>>()
+    );
+
+    // the summary comes with the next record after the end of the second
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    info!(target: "noisy", "noisy again");
+    assert_eq!(
+        take_lines(&output),
+        vec![
+            "noisy 15 log records were suppressed by the rate limit".to_string(),
+            "noisy noisy again".to_string()
+        ]
+    );
+
+    // or when the logger is flushed
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    log::logger().flush();
+    assert_eq!(
+        take_lines(&output),
+        vec!["quiet 15 log records were suppressed by the rate limit".to_string()]
+    );
+}
+
+fn take_lines(output: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    std::mem::take(&mut *output.lock().unwrap())
+}
+
+struct SharedWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for SharedWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", record.target(), record.args()));
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_read_back() {
+    #[cfg(feature = "compress")]
+    let cleanup = Cleanup::KeepLogAndCompressedFiles(1, 100);
+    #[cfg(not(feature = "compress"))]
+    let cleanup = Cleanup::KeepLogFiles(100);
+
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(self::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .format(|w, _now, record| write!(w, "{}", record.args()))
+        .rotate(Criterion::Size(300), Naming::Numbers, cleanup)
+        .cleanup_in_background_thread(false)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..100 {
+        info!("This is line number {:03}", i);
+    }
+    assert!(handle.existing_log_files().unwrap().len() > 5);
+
+    let expected = |range: std::ops::Range<usize>| -> Vec<String> {
+        range
+            .map(|i| format!("This is line number {:03}", i))
+            .collect()
+    };
+    assert_eq!(handle.read_back(0).unwrap(), expected(0..0));
+    assert_eq!(handle.read_back(3).unwrap(), expected(97..100));
+    // spans several files, some of which are compressed
+    assert_eq!(handle.read_back(50).unwrap(), expected(50..100));
+    assert_eq!(handle.read_back(1000).unwrap(), expected(0..100));
+}
@@ -0,0 +1,58 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, LevelCounters, Logger};
+use log::*;
+
+#[test]
+fn test_record_counters() {
+    let handle = Logger::try_with_str("info, sqlx=debug")
+        .unwrap()
+        .log_to_writer(Box::new(NullWriter))
+        .add_writer("db", Box::new(NullWriter))
+        .route("sqlx", "db")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    assert_eq!(handle.counters().primary.total(), 0);
+
+    error!("error-1");
+    warn!("warn-1");
+    info!("info-1");
+    info!("info-2");
+    debug!("not enabled by the log specification");
+    debug!(target: "sqlx::query", "db-1");
+    error!(target: "{db}", "explicitly addressed");
+
+    let counters = handle.counters();
+    assert_eq!(
+        counters.primary,
+        LevelCounters {
+            error: 1,
+            warn: 1,
+            info: 2,
+            debug: 0,
+            trace: 0,
+        }
+    );
+    assert_eq!(counters.primary.get(Level::Info), 2);
+    assert_eq!(counters.primary.total(), 4);
+
+    assert_eq!(counters.writers.len(), 1);
+    let db = counters.writers["db"];
+    assert_eq!(db.debug, 1);
+    assert_eq!(db.error, 1);
+    assert_eq!(db.total(), 2);
+
+    // the difference of two snapshots gives the number of records in between
+    error!("error-2");
+    assert_eq!(handle.counters().primary.error - counters.primary.error, 1);
+}
+
+struct NullWriter;
+impl LogWriter for NullWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
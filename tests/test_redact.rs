@@ -0,0 +1,74 @@
+mod test_utils;
+
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_redact() {
+    let directory = self::test_utils::dir();
+    let file_spec_main = FileSpec::default()
+        .directory(&directory)
+        .suppress_timestamp()
+        .discriminant("main");
+    let file_spec_audit = file_spec_main.clone().discriminant("audit");
+
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec_main)
+            .add_writer(
+                "Audit",
+                Box::new(FileLogWriter::builder(file_spec_audit).try_build().unwrap()),
+            )
+            .redact(mask_tokens)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        info!("login with token=abc123 succeeded");
+        warn!(target: "{_Default,Audit}", "token=xyz789");
+        info!("nothing to hide");
+    }
+
+    let main = read_log_file(&directory, "main");
+    assert_eq!(
+        main.lines().collect::<Vec<_>>(),
+        vec![
+            "INFO [test_redact] login with token=****** succeeded",
+            "WARN [test_redact] token=******",
+            "INFO [test_redact] nothing to hide",
+        ]
+    );
+    let audit = read_log_file(&directory, "audit");
+    assert_eq!(
+        audit.lines().collect::<Vec<_>>(),
+        vec!["WARN [test_redact] token=******"]
+    );
+}
+
+fn read_log_file(directory: &std::path::Path, discriminant: &str) -> String {
+    let path = std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .contains(discriminant)
+        })
+        .unwrap();
+    std::fs::read_to_string(path).unwrap()
+}
+
+// replaces the value of every `token=` with asterisks
+fn mask_tokens(line: &mut Vec<u8>) {
+    const KEY: &[u8] = b"token=";
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].windows(KEY.len()).position(|w| w == KEY) {
+        pos += offset + KEY.len();
+        while pos < line.len() && line[pos] != b' ' {
+            line[pos] = b'*';
+            pos += 1;
+        }
+    }
+}
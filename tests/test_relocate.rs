@@ -0,0 +1,54 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+#[test]
+fn test_relocate() {
+    let dir = self::test_utils::dir();
+    let first_dir = dir.join("first");
+    let second_dir = dir.join("second");
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&first_dir)
+                .suppress_timestamp(),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+
+    logger.relocate(&second_dir).unwrap();
+
+    info!("This is an info message");
+    error!("This is another error message");
+
+    logger.validate_logs(&[
+        ("INFO", "test_relocate", "info message"),
+        ("ERROR", "test_relocate", "another error message"),
+    ]);
+
+    let lines = lines_of_single_file(&first_dir);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("error message"));
+    assert!(lines[1].contains("warning"));
+    assert_eq!(lines_of_single_file(&second_dir).len(), 2);
+}
+
+fn lines_of_single_file(dir: &Path) -> Vec<String> {
+    let files: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1, "unexpected files in {}", dir.display());
+    BufReader::new(std::fs::File::open(&files[0]).unwrap())
+        .lines()
+        .map(Result::unwrap)
+        .collect()
+}
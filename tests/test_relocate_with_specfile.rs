@@ -0,0 +1,76 @@
+mod test_utils;
+
+#[cfg(feature = "specfile_without_notification")]
+mod a {
+    use flexi_logger::{FileSpec, Logger};
+    use log::*;
+    use std::io::Write;
+    use std::time::Duration;
+
+    #[test]
+    fn test_relocate_with_specfile() {
+        let dir = super::test_utils::dir();
+        let first_dir = dir.join("first");
+        let second_dir = dir.join("second");
+        let specfile = first_dir.join("logspec.toml");
+
+        let logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(
+                FileSpec::default()
+                    .directory(&first_dir)
+                    .suppress_timestamp(),
+            )
+            .start_with_specfile(&specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        assert!(specfile.exists());
+
+        error!("This is an error message");
+
+        logger.relocate_with_specfile(&second_dir).unwrap();
+        assert!(!specfile.exists());
+        assert!(second_dir.join("logspec.toml").exists());
+
+        info!("This is an info message");
+
+        // with a specfile in the new directory, the previous specfile remains untouched
+        let third_dir = dir.join("third");
+        std::fs::create_dir_all(&third_dir).unwrap();
+        std::fs::write(
+            third_dir.join("logspec.toml"),
+            "global_level = 'info'\n[modules]\n",
+        )
+        .unwrap();
+        logger.relocate_with_specfile(&third_dir).unwrap();
+        let second_specfile = second_dir.join("logspec.toml");
+        assert!(second_specfile.exists());
+
+        // the watcher of the previous specfile is gone nevertheless
+        #[cfg(all(feature = "specfile", target_os = "linux"))]
+        {
+            std::thread::sleep(Duration::from_millis(200));
+            assert_eq!(specfile_watcher_threads(), 1);
+        }
+
+        // and changes of the previous specfile are ignored
+        std::fs::File::create(&second_specfile)
+            .unwrap()
+            .write_all(b"global_level = 'error'\n[modules]\n")
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(2500));
+        info!("This is an info message after the change");
+
+        logger.validate_logs(&[("INFO", "test_relocate_with_specfile", "after the change")]);
+    }
+
+    // Counts the threads of this process whose (truncated) name
+    // is that of a specfile watcher.
+    #[cfg(all(feature = "specfile", target_os = "linux"))]
+    fn specfile_watcher_threads() -> usize {
+        std::fs::read_dir("/proc/self/task")
+            .unwrap()
+            .filter_map(|task| std::fs::read_to_string(task.unwrap().path().join("comm")).ok())
+            .filter(|name| name.trim_end() == "flexi_logger-sp")
+            .count()
+    }
+}
@@ -0,0 +1,60 @@
+mod test_utils;
+
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{FileSpec, Logger, WriteMode};
+use log::*;
+use std::path::Path;
+
+#[test]
+fn test_reset_write_mode() {
+    let dir = self::test_utils::dir();
+    let file_spec = || FileSpec::default().directory(&dir).suppress_timestamp();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(file_spec())
+        .write_mode(WriteMode::BufferDontFlush)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("buffered-1");
+    assert!(!content(&dir).contains("buffered-1"));
+
+    // switching to direct output flushes what was buffered
+    logger
+        .reset_flw(
+            &FileLogWriter::builder(file_spec())
+                .append()
+                .write_mode(WriteMode::Direct),
+        )
+        .unwrap();
+    assert!(content(&dir).contains("buffered-1"));
+    info!("direct-1");
+    assert!(content(&dir).contains("direct-1"));
+
+    // and back
+    logger
+        .reset_flw(
+            &FileLogWriter::builder(file_spec())
+                .append()
+                .write_mode(WriteMode::BufferDontFlush),
+        )
+        .unwrap();
+    info!("buffered-2");
+    assert!(!content(&dir).contains("buffered-2"));
+    logger.flush();
+    assert!(content(&dir).contains("buffered-2"));
+
+    logger.validate_logs(&[
+        ("INFO", "test_reset_write_mode", "buffered-1"),
+        ("INFO", "test_reset_write_mode", "direct-1"),
+        ("INFO", "test_reset_write_mode", "buffered-2"),
+    ]);
+}
+
+fn content(dir: &Path) -> String {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect()
+}
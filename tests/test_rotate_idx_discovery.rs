@@ -0,0 +1,43 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+
+#[test]
+fn test_rotate_idx_discovery() {
+    let dir = self::test_utils::dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    // rotated files with a gap, and foreign files that also match the glob pattern
+    std::fs::write(dir.join("idx_r00002.log"), "rotated\n").unwrap();
+    std::fs::write(dir.join("idx_r00007.log.gz"), "compressed\n").unwrap();
+    std::fs::write(dir.join("idx_r9-backup.log"), "foreign\n").unwrap();
+    std::fs::write(dir.join("idx_r2021-10-01_10-00-00.log"), "foreign\n").unwrap();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).basename("idx"))
+        .rotate(Criterion::Size(10), Naming::Numbers, Cleanup::Never)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("first line");
+    info!("second line");
+    logger.flush();
+    assert_eq!(read(&dir, "idx_r00008.log"), "first line");
+
+    // another process has rotated meanwhile
+    std::fs::write(dir.join("idx_r00009.log"), "from another process\n").unwrap();
+    info!("third line");
+    logger.flush();
+    assert_eq!(read(&dir, "idx_r00009.log"), "from another process");
+    assert_eq!(read(&dir, "idx_r00010.log"), "second line");
+    assert_eq!(read(&dir, "idx_r9-backup.log"), "foreign");
+    logger.shutdown();
+}
+
+fn read(dir: &Path, filename: &str) -> String {
+    let content = std::fs::read_to_string(dir.join(filename)).unwrap();
+    let line = content.lines().next().unwrap();
+    line[line.find(']').map_or(0, |idx| idx + 2)..].to_string()
+}
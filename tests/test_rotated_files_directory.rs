@@ -0,0 +1,53 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+
+#[test]
+fn test_rotated_files_directory() {
+    let dir = self::test_utils::dir();
+    let live_dir = dir.join("live");
+    let archive_dir = dir.join("archive");
+
+    {
+        let _handle = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&live_dir).basename("rfd"))
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .rotate(
+                Criterion::Size(0),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(2),
+            )
+            .rotated_files_directory(&archive_dir)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 1..=5 {
+            info!("{}", i);
+        }
+    }
+
+    assert_eq!(file_names(&live_dir), vec!["rfd_rCURRENT.log"]);
+    let rotated = file_names(&archive_dir);
+    assert_eq!(rotated.len(), 2, "{:?}", rotated);
+    assert_eq!(read(&archive_dir, &rotated[1]), "4");
+    assert_eq!(read(&live_dir, "rfd_rCURRENT.log"), "5");
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+fn read(dir: &Path, filename: &str) -> String {
+    std::fs::read_to_string(dir.join(filename))
+        .unwrap()
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,64 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_rotation_breadcrumbs() {
+    let directory = self::test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("chain")
+                .suppress_timestamp(),
+        )
+        .format(|w, _now, record| write!(w, "{}", record.args()))
+        .rotate(Criterion::Size(50), Naming::Numbers, Cleanup::Never)
+        .rotation_breadcrumbs()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..12 {
+        info!("This is line number {:02}", i);
+    }
+    handle.shutdown();
+
+    let name = |idx: u32| format!("chain_r{:05}.log", idx);
+    let read = |name: &str| std::fs::read_to_string(directory.join(name)).unwrap();
+    let no_of_rotated = (0..)
+        .find(|idx| !directory.join(name(*idx)).exists())
+        .unwrap();
+    assert!(no_of_rotated > 2, "too few rotations");
+    let current = read("chain_rCURRENT.log");
+    assert!(current.starts_with(&format!(
+        "<<< continuation of {}\n",
+        name(no_of_rotated - 1)
+    )));
+    assert!(!current.contains(">>>"));
+
+    let mut numbers = Vec::new();
+    for idx in 0..no_of_rotated {
+        let content = read(&name(idx));
+        let lines: Vec<&str> = content.lines().collect();
+        let mut records = &lines[..];
+        if idx > 0 {
+            assert_eq!(records[0], format!("<<< continuation of {}", name(idx - 1)));
+            records = &records[1..];
+        }
+        assert_eq!(
+            records[records.len() - 1],
+            format!(">>> continued in {}", name(idx + 1))
+        );
+        records = &records[..records.len() - 1];
+        numbers.extend(
+            records
+                .iter()
+                .map(|line| line["This is line number ".len()..].parse::<u32>().unwrap()),
+        );
+    }
+    // no record is lost
+    assert_eq!(numbers, (0..numbers.len() as u32).collect::<Vec<_>>());
+    assert_eq!(current.lines().count() - 1 + numbers.len(), 12);
+}
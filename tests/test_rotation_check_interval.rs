@@ -0,0 +1,36 @@
+mod test_utils;
+
+use flexi_logger::{Age, Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn test_rotation_check_interval() {
+    let dir = self::test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).basename("idle"))
+        .rotate(Criterion::Age(Age::Second), Naming::Numbers, Cleanup::Never)
+        .rotation_check_interval(Duration::from_millis(100))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    info!("this is the only line that is logged");
+    logger.flush();
+
+    // the program is quiet, but the rotation happens nevertheless
+    std::thread::sleep(Duration::from_millis(2500));
+    assert!(rotated_files(&dir) >= 1);
+    logger.shutdown();
+}
+
+fn rotated_files(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("idle_r") && !name.contains("CURRENT")
+        })
+        .count()
+}
@@ -0,0 +1,66 @@
+mod test_utils;
+
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Age, Cleanup, Criterion, DeferredNow, FileSpec, Naming};
+use std::time::Duration;
+
+#[test]
+fn test_rotation_imminent_by_size() {
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(self::test_utils::dir())
+            .discriminant("size"),
+    )
+    .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+    .try_build()
+    .unwrap();
+
+    // the output file is opened with the first write
+    assert_eq!(flw.bytes_until_rotation(), None);
+    assert!(!flw.rotation_imminent());
+
+    write(&flw, "This is the first line");
+    let bytes_until_rotation = flw.bytes_until_rotation().unwrap();
+    assert!(bytes_until_rotation < 100);
+    assert!(!flw.rotation_imminent());
+    assert_eq!(flw.time_until_rotation(), None);
+
+    while !flw.rotation_imminent() {
+        write(&flw, "This is another line");
+    }
+    assert_eq!(flw.bytes_until_rotation(), Some(0));
+
+    // this line goes into a fresh file
+    write(&flw, "This is the first line");
+    assert!(!flw.rotation_imminent());
+    assert_eq!(flw.bytes_until_rotation(), Some(bytes_until_rotation));
+    assert_eq!(flw.stats().rotations, 1);
+}
+
+#[test]
+fn test_rotation_imminent_by_age() {
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(self::test_utils::dir())
+            .discriminant("age"),
+    )
+    .rotate(Age::Day, Naming::Numbers, Cleanup::Never)
+    .try_build()
+    .unwrap();
+
+    write(&flw, "This is the first line");
+    assert_eq!(flw.bytes_until_rotation(), None);
+    let time_until_rotation = flw.time_until_rotation().unwrap();
+    assert!(time_until_rotation <= Duration::from_secs(24 * 60 * 60));
+}
+
+fn write(flw: &FileLogWriter, message: &str) {
+    flw.write(
+        &mut DeferredNow::new(),
+        &log::Record::builder()
+            .args(format_args!("{}", message))
+            .level(log::Level::Info)
+            .build(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,106 @@
+mod test_utils;
+
+use flexi_logger::writers::{ArcFileLogWriter, FileLogWriter, FileLogWriterHandle};
+use flexi_logger::{Cleanup, Criterion, FileSpec, Naming, WriteMode};
+use std::io::Write;
+use std::path::Path;
+
+#[test]
+fn test_rotation_record_boundaries_direct() {
+    run("direct", WriteMode::Direct);
+}
+
+#[test]
+fn test_rotation_record_boundaries_buffered() {
+    run("buffered", WriteMode::BufferDontFlush);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_rotation_record_boundaries_async() {
+    run("async", WriteMode::Async);
+}
+
+fn run(name: &str, write_mode: WriteMode) {
+    let dir = self::test_utils::dir().join(name);
+    let (mut file_writer, fw_handle) = file_writer(&dir, write_mode);
+
+    // each line is written in many small chunks, and the size limit
+    // is exceeded by the first chunk of most of the lines
+    for i in 0..20 {
+        write!(file_writer, "chunked {:02}:", i).unwrap();
+        for _ in 0..10 {
+            file_writer.write_all(b" abc").unwrap();
+        }
+        file_writer.write_all(b"\n").unwrap();
+    }
+    // records with several lines are kept together
+    for i in 0..20 {
+        file_writer
+            .write_record(
+                format!("record {:02} first line\nrecord {:02} second line", i, i).as_bytes(),
+            )
+            .unwrap();
+    }
+    drop(fw_handle);
+
+    let files = read_files(&dir);
+    assert!(files.len() > 10, "too few rotations: {}", files.len());
+    let mut chunked = 0;
+    let mut records = 0;
+    for content in &files {
+        assert!(content.ends_with('\n'), "record is split: {:?}", content);
+        let lines: Vec<&str> = content.lines().collect();
+        let mut idx = 0;
+        while idx < lines.len() {
+            let line = lines[idx];
+            if line.starts_with("chunked ") {
+                assert_eq!(
+                    line,
+                    format!("chunked {:02}:{}", chunked, " abc".repeat(10)),
+                    "record is split: {:?}",
+                    content
+                );
+                chunked += 1;
+                idx += 1;
+            } else {
+                assert_eq!(line, format!("record {:02} first line", records));
+                assert_eq!(
+                    lines.get(idx + 1).copied(),
+                    Some(format!("record {:02} second line", records).as_str()),
+                    "record is split: {:?}",
+                    content
+                );
+                records += 1;
+                idx += 2;
+            }
+        }
+    }
+    assert_eq!(chunked, 20);
+    assert_eq!(records, 20);
+}
+
+fn file_writer(dir: &Path, write_mode: WriteMode) -> (ArcFileLogWriter, FileLogWriterHandle) {
+    FileLogWriter::builder(FileSpec::default().directory(dir).basename("records"))
+        .rotate(Criterion::Size(30), Naming::Numbers, Cleanup::Never)
+        .write_mode(write_mode)
+        .try_build_with_handle()
+        .unwrap()
+}
+
+// the contents of the log files, in the order in which they were written
+fn read_files(dir: &Path) -> Vec<String> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    // the numbered files first, the current file last
+    paths.sort_by_key(|path| {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        (name.contains("CURRENT"), name)
+    });
+    paths
+        .iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect()
+}
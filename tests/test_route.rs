@@ -0,0 +1,60 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FlexiLoggerError, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_route() {
+    // a route to an unknown writer is rejected
+    assert!(matches!(
+        Logger::try_with_str("info")
+            .unwrap()
+            .route("sqlx", "db")
+            .build(),
+        Err(FlexiLoggerError::UnknownWriter(ref writer_name)) if writer_name == "db"
+    ));
+
+    let main = Arc::new(Mutex::new(Vec::new()));
+    let db = Arc::new(Mutex::new(Vec::new()));
+    let pool = Arc::new(Mutex::new(Vec::new()));
+    let _logger = Logger::try_with_str("info, sqlx=debug")
+        .unwrap()
+        .log_to_writer(Box::new(SharedWriter(Arc::clone(&main))))
+        .add_writer("db", Box::new(SharedWriter(Arc::clone(&db))))
+        .add_writer("pool", Box::new(SharedWriter(Arc::clone(&pool))))
+        .route("sqlx", "db")
+        .route("sqlx::pool", "pool")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("main-1");
+    debug!(target: "sqlx::query", "db-1");
+    trace!(target: "sqlx::query", "not enabled by the log specification");
+    info!(target: "sqlx::pool", "pool-1");
+    info!(target: "{db,_Default}", "explicitly addressed");
+
+    assert_eq!(
+        *main.lock().unwrap(),
+        vec!["test_route main-1", "{db,_Default} explicitly addressed"]
+    );
+    assert_eq!(
+        *db.lock().unwrap(),
+        vec!["sqlx::query db-1", "{db,_Default} explicitly addressed"]
+    );
+    assert_eq!(*pool.lock().unwrap(), vec!["sqlx::pool pool-1"]);
+}
+
+struct SharedWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for SharedWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", record.target(), record.args()));
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
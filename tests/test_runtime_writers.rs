@@ -0,0 +1,61 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FlexiLoggerError, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_runtime_writers() {
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .do_not_log()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!(target: "{Plugin}", "before the writer is added");
+
+    let first = Arc::new(Mutex::new(Sink::default()));
+    handle.add_writer("Plugin", Box::new(SinkWriter(Arc::clone(&first))));
+    // the max level of the writer is respected
+    trace!(target: "{Plugin}", "trace 1");
+    info!(target: "{Plugin}", "info 1");
+
+    // replacing shuts the previous writer down
+    let second = Arc::new(Mutex::new(Sink::default()));
+    handle.add_writer("Plugin", Box::new(SinkWriter(Arc::clone(&second))));
+    trace!(target: "{Plugin}", "trace 2");
+    assert!(first.lock().unwrap().shut_down);
+
+    handle.remove_writer("Plugin").unwrap();
+    assert!(second.lock().unwrap().shut_down);
+    error!(target: "{Plugin}", "after the writer is removed");
+    assert!(matches!(
+        handle.remove_writer("Plugin"),
+        Err(FlexiLoggerError::UnknownWriter(_))
+    ));
+
+    assert_eq!(first.lock().unwrap().lines, vec!["trace 1", "info 1"]);
+    assert_eq!(second.lock().unwrap().lines, vec!["trace 2"]);
+}
+
+#[derive(Default)]
+struct Sink {
+    lines: Vec<String>,
+    shut_down: bool,
+}
+
+struct SinkWriter(Arc<Mutex<Sink>>);
+impl LogWriter for SinkWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0.lock().unwrap().lines.push(record.args().to_string());
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> LevelFilter {
+        LevelFilter::Trace
+    }
+    fn shutdown(&self) {
+        self.0.lock().unwrap().shut_down = true;
+    }
+}
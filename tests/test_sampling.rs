@@ -0,0 +1,56 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, LevelFilter, Logger};
+use log::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_sampling() {
+    let counts = Arc::new(Mutex::new(HashMap::<String, usize>::new()));
+    let _logger = Logger::try_with_str("trace")
+        .unwrap()
+        .log_to_writer(Box::new(CountingWriter(Arc::clone(&counts))))
+        .sample(LevelFilter::Trace, 0.1)
+        .sample_target("noisy", LevelFilter::Debug, 0.0)
+        .sample_target("important", LevelFilter::Trace, 1.0)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for _ in 0..10_000 {
+        trace!("trace");
+        debug!("debug");
+        debug!(target: "noisy::module", "noisy debug");
+        trace!(target: "noisy::module", "noisy trace");
+        info!(target: "noisy::module", "noisy info");
+        trace!(target: "important", "important trace");
+    }
+
+    let counts = counts.lock().unwrap();
+    let trace = counts["trace"];
+    assert!(
+        trace > 700 && trace < 1300,
+        "unexpected number of samples: {}",
+        trace
+    );
+    assert_eq!(counts["debug"], 10_000);
+    assert!(!counts.contains_key("noisy debug"));
+    assert!(!counts.contains_key("noisy trace"));
+    assert_eq!(counts["noisy info"], 10_000);
+    assert_eq!(counts["important trace"], 10_000);
+}
+
+struct CountingWriter(Arc<Mutex<HashMap<String, usize>>>);
+impl LogWriter for CountingWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        *self
+            .0
+            .lock()
+            .unwrap()
+            .entry(record.args().to_string())
+            .or_default() += 1;
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
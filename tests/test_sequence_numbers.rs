@@ -0,0 +1,59 @@
+mod test_utils;
+
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{detailed_format, FileSpec, Logger};
+use log::*;
+use std::path::PathBuf;
+
+#[test]
+fn test_sequence_numbers() {
+    let dir = self::test_utils::dir();
+    let logger = Logger::try_with_str("info, second=info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&dir).discriminant("main"))
+        .format_for_files(detailed_format)
+        .add_writer(
+            "second",
+            Box::new(
+                FileLogWriter::builder(FileSpec::default().directory(&dir).discriminant("second"))
+                    .format(detailed_format)
+                    .try_build()
+                    .unwrap(),
+            ),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..5 {
+        info!("main only {}", i);
+        info!(target: "{_Default,second}", "both {}", i);
+    }
+    logger.flush();
+
+    let main = sequence_numbers(&dir, "_main_");
+    let second = sequence_numbers(&dir, "_second_");
+    assert_eq!(main.len(), 10);
+    assert_eq!(second.len(), 5);
+    // strictly increasing
+    assert!(main.windows(2).all(|pair| pair[0] < pair[1]));
+    // a record has the same number in all outputs
+    for (i, number) in second.iter().enumerate() {
+        assert_eq!(*number, main[2 * i + 1]);
+    }
+}
+
+fn sequence_numbers(dir: &PathBuf, discriminant: &str) -> Vec<u64> {
+    let path = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().contains(discriminant))
+        .unwrap();
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let number = line.split(" #").nth(1).unwrap().split(' ').next().unwrap();
+            number.parse().unwrap()
+        })
+        .collect()
+}
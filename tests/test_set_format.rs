@@ -0,0 +1,105 @@
+mod test_utils;
+
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FileSpec, FlexiLoggerError, FormatFunction, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_set_format() {
+    let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(self::test_utils::dir())
+                .suppress_timestamp(),
+        )
+        .format(human_format)
+        .add_writer("Capture", Box::new(CaptureWriter::new(&lines)))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("first");
+    info!(target: "{Capture}", "first");
+    handle.set_format(machine_format);
+    handle
+        .set_format_for_additional_writer("Capture", machine_format)
+        .unwrap();
+    info!("second");
+    info!(target: "{Capture}", "second");
+    handle.set_format_for_files(human_format).unwrap();
+    info!("third");
+
+    assert!(matches!(
+        handle.set_format_for_additional_writer("Unknown", machine_format),
+        Err(FlexiLoggerError::UnknownWriter(_))
+    ));
+
+    assert_eq!(
+        handle.read_back(10).unwrap(),
+        vec![
+            "INFO first".to_string(),
+            r#"{"level":"INFO","text":"second"}"#.to_string(),
+            "INFO third".to_string(),
+        ]
+    );
+    assert_eq!(
+        *lines.lock().unwrap(),
+        vec![
+            "INFO first".to_string(),
+            r#"{"level":"INFO","text":"second"}"#.to_string(),
+        ]
+    );
+}
+
+fn human_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    write!(w, "{} {}", record.level(), record.args())
+}
+
+fn machine_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    write!(
+        w,
+        r#"{{"level":"{}","text":"{}"}}"#,
+        record.level(),
+        record.args()
+    )
+}
+
+struct CaptureWriter {
+    format: FormatFunction,
+    lines: Arc<Mutex<Vec<String>>>,
+}
+impl CaptureWriter {
+    fn new(lines: &Arc<Mutex<Vec<String>>>) -> Self {
+        Self {
+            format: human_format,
+            lines: Arc::clone(lines),
+        }
+    }
+}
+impl LogWriter for CaptureWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let mut buffer = Vec::<u8>::new();
+        (self.format)(&mut buffer, now, record)?;
+        self.lines
+            .lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(&buffer).to_string());
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+}
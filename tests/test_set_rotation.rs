@@ -0,0 +1,56 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_set_rotation() {
+    let directory = self::test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("reconfigured")
+                .suppress_timestamp(),
+        )
+        .format(|w, _now, record| write!(w, "{}", record.args()))
+        .rotate(Criterion::Size(100_000), Naming::Numbers, Cleanup::Never)
+        .cleanup_in_background_thread(false)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..10 {
+        info!("This is line number {:02}", i);
+    }
+    assert_eq!(handle.existing_log_files().unwrap().len(), 1);
+
+    // the current file is rotated right away, and the new rotation and cleanup apply
+    handle
+        .set_rotation(
+            Criterion::Size(100),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(2),
+        )
+        .unwrap();
+    let rotated = directory.join("reconfigured_r00000.log");
+    assert_eq!(
+        std::fs::read_to_string(&rotated).unwrap().lines().count(),
+        10
+    );
+
+    for i in 10..60 {
+        info!("This is line number {:02}", i);
+    }
+    handle.flush();
+    assert!(!rotated.exists());
+    let log_files = handle.existing_log_files().unwrap();
+    assert_eq!(log_files.len(), 3, "unexpected files: {:?}", log_files);
+    for file in &log_files {
+        assert!(std::fs::metadata(file).unwrap().len() < 150);
+    }
+    assert_eq!(
+        handle.read_back(1).unwrap(),
+        vec!["This is line number 59".to_string()]
+    );
+}
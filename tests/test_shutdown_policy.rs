@@ -0,0 +1,64 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger, ShutdownPolicy, WriteMode};
+use log::*;
+use std::io::{BufRead, BufReader};
+
+const NO_OF_THREADS: usize = 4;
+const NO_OF_LOGLINES_PER_THREAD: usize = 20_000;
+const NO_OF_LATE_LOGLINES: usize = 10;
+const QUEUE_CAPACITY: usize = 5;
+
+#[test]
+fn test_shutdown_policy() {
+    let directory = self::test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .write_mode(WriteMode::BufferAndFlush)
+        .shutdown_policy(ShutdownPolicy::QueueAndAbandon(QUEUE_CAPACITY))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let worker_handles: Vec<_> = (0..NO_OF_THREADS)
+        .map(|thread_number| {
+            std::thread::spawn(move || {
+                for line_number in 0..NO_OF_LOGLINES_PER_THREAD {
+                    info!("line {} of thread {}", line_number, thread_number);
+                }
+            })
+        })
+        .collect();
+
+    // shut down while the workers are logging
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    logger.shutdown();
+    for worker_handle in worker_handles {
+        worker_handle.join().unwrap();
+    }
+    for line_number in 0..NO_OF_LATE_LOGLINES {
+        info!("late line {}", line_number);
+    }
+    debug!("this line is not counted, it is not enabled");
+
+    // every record is either in the file or counted as late
+    let written_lines: usize = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| {
+            BufReader::new(std::fs::File::open(entry.unwrap().path()).unwrap())
+                .lines()
+                .count()
+        })
+        .sum();
+    assert_eq!(
+        written_lines + logger.records_after_shutdown(),
+        NO_OF_THREADS * NO_OF_LOGLINES_PER_THREAD + NO_OF_LATE_LOGLINES
+    );
+    assert!(logger.records_after_shutdown() >= NO_OF_LATE_LOGLINES);
+
+    let abandoned_records = logger.abandoned_records();
+    assert_eq!(abandoned_records.len(), QUEUE_CAPACITY);
+    assert!(abandoned_records
+        .iter()
+        .all(|record| record.contains("line ")));
+}
@@ -0,0 +1,61 @@
+use flexi_logger::{writers::LogWriter, DeferredNow, Logger};
+use log::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_shutdown_with_timeout() {
+    // a writer that shuts down quickly
+    let written = Arc::new(AtomicUsize::new(0));
+    let (_logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(SlowWriter {
+            written: Arc::clone(&written),
+            shutdown_duration: Duration::from_millis(0),
+        }))
+        .build()
+        .unwrap();
+    assert!(handle.shutdown_with_timeout(Duration::from_secs(5)));
+
+    // a writer that hangs in its shutdown
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(SlowWriter {
+            written: Arc::clone(&written),
+            shutdown_duration: Duration::from_secs(3),
+        }))
+        .build()
+        .unwrap();
+    logger.log(
+        &Record::builder()
+            .level(Level::Info)
+            .args(format_args!("before shutdown"))
+            .build(),
+    );
+    let start = Instant::now();
+    assert!(!handle.shutdown_with_timeout(Duration::from_millis(100)));
+    // dropping the handle does not wait for the hanging writer either
+    drop(handle);
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert_eq!(written.load(Ordering::SeqCst), 1);
+}
+
+struct SlowWriter {
+    written: Arc<AtomicUsize>,
+    shutdown_duration: Duration,
+}
+impl LogWriter for SlowWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        self.written.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) {
+        std::thread::sleep(self.shutdown_duration);
+    }
+}
@@ -0,0 +1,62 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, LogSpecification, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_spec_guard() {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(SinkWriter(Arc::clone(&lines))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    let spec = |s: &str| LogSpecification::parse(s).unwrap();
+
+    debug!("debug 1");
+    {
+        let _guard = handle.raise_level_scoped(spec("debug"));
+        debug!("debug 2");
+        {
+            let _inner_guard = handle.raise_level_scoped(spec("trace"));
+            trace!("trace 1");
+        }
+        debug!("debug 3");
+        trace!("trace 2");
+    }
+    debug!("debug 4");
+
+    // guards that are dropped out of order
+    let outer_guard = handle.raise_level_scoped(spec("debug"));
+    let inner_guard = handle.raise_level_scoped(spec("trace"));
+    drop(outer_guard);
+    trace!("trace 3");
+    drop(inner_guard);
+    debug!("debug 5");
+
+    // the spec is restored when the scope is left with a panic
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _guard = handle.raise_level_scoped(spec("debug"));
+        debug!("debug 6");
+        panic!("test panic");
+    }));
+    assert!(result.is_err());
+    debug!("debug 7");
+    info!("info");
+
+    assert_eq!(
+        *lines.lock().unwrap(),
+        vec!["debug 2", "trace 1", "debug 3", "trace 3", "debug 6", "info"]
+    );
+}
+
+struct SinkWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for SinkWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0.lock().unwrap().push(record.args().to_string());
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
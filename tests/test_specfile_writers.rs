@@ -0,0 +1,65 @@
+mod test_utils;
+
+#[cfg(feature = "specfile_without_notification")]
+mod a {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{DeferredNow, Logger};
+    use log::*;
+    use std::sync::{Arc, Mutex};
+
+    #[cfg(not(feature = "specfile_yaml"))]
+    const SPECFILE: &str = "logspec.toml";
+    #[cfg(not(feature = "specfile_yaml"))]
+    const SPEC: &str = "
+        global_level = 'info'
+        [writers]
+        'Sec' = 'warn'
+        ";
+
+    #[cfg(feature = "specfile_yaml")]
+    const SPECFILE: &str = "logspec.yaml";
+    #[cfg(feature = "specfile_yaml")]
+    const SPEC: &str = "
+        global_level: 'info'
+        writers:
+          'Sec': 'warn'
+        ";
+
+    // The level of an additional writer is taken from the specfile,
+    // whose format is determined by its extension
+    #[test]
+    fn test_specfile_writers() {
+        let specfile = super::test_utils::file(SPECFILE);
+        std::fs::create_dir_all(specfile.parent().unwrap()).unwrap();
+        std::fs::write(&specfile, SPEC).unwrap();
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .do_not_log()
+            .add_writer("Sec", Box::new(SharedWriter(Arc::clone(&output))))
+            .start_with_specfile(&specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+
+        info!(target: "{Sec}", "info is filtered");
+        warn!(target: "{Sec}", "warning is written");
+        error!(target: "{Sec}", "error is written");
+
+        assert_eq!(
+            *output.lock().unwrap(),
+            vec!["warning is written", "error is written"]
+        );
+    }
+
+    struct SharedWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for SharedWriter {
+        fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(record.args().to_string());
+            Ok(())
+        }
+
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
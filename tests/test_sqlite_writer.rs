@@ -0,0 +1,107 @@
+#![cfg(feature = "sqlite")]
+
+mod test_utils;
+
+use flexi_logger::writers::{LogWriter, SqliteWriter};
+use flexi_logger::{DeferredNow, Logger};
+use log::*;
+
+#[test]
+fn test_sqlite_writer() {
+    let dir = self::test_utils::dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("logs.db");
+
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(
+            SqliteWriter::builder(&path)
+                .batch_size(10)
+                .try_build()
+                .unwrap(),
+        ))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("an error with 'quotes'");
+    for i in 0..24 {
+        info!("info {}", i);
+    }
+    debug!("not enabled");
+    #[cfg(feature = "kv")]
+    warn!(user = "alice", attempts = 3; "login failed");
+
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    let count = |connection: &rusqlite::Connection| -> i64 {
+        connection
+            .query_row("SELECT COUNT(*) FROM log", [], |row| row.get(0))
+            .unwrap()
+    };
+    // only the full batches are inserted so far
+    assert_eq!(count(&connection), 20);
+
+    handle.flush();
+    #[cfg(feature = "kv")]
+    assert_eq!(count(&connection), 26);
+    #[cfg(not(feature = "kv"))]
+    assert_eq!(count(&connection), 25);
+
+    let (level, module, message): (String, String, String) = connection
+        .query_row(
+            "SELECT level, module, message FROM log WHERE level = 'ERROR'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(level, "ERROR");
+    assert_eq!(module, "test_sqlite_writer");
+    assert_eq!(message, "an error with 'quotes'");
+
+    #[cfg(feature = "kv")]
+    {
+        let kv: String = connection
+            .query_row("SELECT kv FROM log WHERE level = 'WARN'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(kv, r#"{"user":"alice","attempts":3}"#);
+    }
+}
+
+#[test]
+fn test_sqlite_writer_max_size() {
+    let dir = self::test_utils::dir().join("max_size");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("logs.db");
+
+    let writer = SqliteWriter::builder(&path)
+        .table("my log")
+        .batch_size(100)
+        .max_size(64 * 1024)
+        .try_build()
+        .unwrap();
+    let payload = "x".repeat(200);
+    for _ in 0..2000 {
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &Record::builder()
+                    .args(format_args!("{}", payload))
+                    .level(Level::Info)
+                    .target("test")
+                    .build(),
+            )
+            .unwrap();
+    }
+    writer.flush().unwrap();
+
+    assert!(std::fs::metadata(&path).unwrap().len() < 2 * 64 * 1024);
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    let (count, max_id): (i64, i64) = connection
+        .query_row("SELECT COUNT(*), MAX(id) FROM \"my log\"", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(max_id, 2000);
+    assert!(count < 1000, "unexpected number of records: {}", count);
+}
@@ -0,0 +1,50 @@
+use flexi_logger::{writers::NetworkWriter, FlexiLoggerError, Logger};
+use log::*;
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
+use std::time::Duration;
+
+#[test]
+fn test_start_with_readiness() {
+    // find a free port, and keep it unused for the first attempt
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    match logger(&address.to_string()).start_with_readiness(Duration::from_millis(300)) {
+        Err(FlexiLoggerError::NotReady(unready_writers)) => {
+            assert_eq!(unready_writers, vec!["Remote".to_string()]);
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("the network writer cannot be ready without a listener"),
+    }
+
+    let listener = TcpListener::bind(address).unwrap();
+    let _logger = logger(&address.to_string())
+        .start_with_readiness(Duration::from_secs(10))
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    info!(target: "{Remote}", "This is the first record");
+
+    let (stream, _) = listener.accept().unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let line = BufReader::new(stream).lines().next().unwrap().unwrap();
+    assert!(line.ends_with("This is the first record"));
+}
+
+fn logger(address: &str) -> Logger {
+    Logger::try_with_str("info")
+        .unwrap()
+        .log_to_stdout()
+        .add_writer(
+            "Remote",
+            Box::new(
+                NetworkWriter::builder(address)
+                    .backoff(Duration::from_millis(10), Duration::from_millis(50))
+                    .try_build()
+                    .unwrap(),
+            ),
+        )
+}
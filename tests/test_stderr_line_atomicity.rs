@@ -0,0 +1,92 @@
+mod test_utils;
+
+use flexi_logger::{DeferredNow, Duplicate, FileSpec, Logger, WriteMode};
+use log::*;
+use std::process::Command;
+
+const CHILD_MODE: &str = "FLEXI_LOGGER_TEST_STDERR_MODE";
+const NO_OF_THREADS: usize = 8;
+const NO_OF_LOGLINES_PER_THREAD: usize = 200;
+const PAYLOAD_LEN: usize = 20_000;
+
+// The test re-runs itself in child processes that log with many threads to stderr,
+// and verifies that the captured stderr of the children consists only of complete lines.
+#[test]
+fn test_stderr_line_atomicity() {
+    if let Ok(mode) = std::env::var(CHILD_MODE) {
+        log_concurrently(&mode);
+        return;
+    }
+
+    for mode in &["duplicate", "buffered"] {
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(&["--exact", "test_stderr_line_atomicity", "--nocapture"])
+            .env(CHILD_MODE, mode)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "child process failed ({})", mode);
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let mut no_of_loglines = 0;
+        for line in stderr.lines() {
+            if line == "interjection" {
+                continue;
+            }
+            assert!(
+                line.starts_with('<') && line.ends_with('>') && line.matches('<').count() == 1,
+                "torn line in mode {}: {}...",
+                mode,
+                &line[..line.len().min(100)]
+            );
+            assert_eq!(line.matches('x').count(), PAYLOAD_LEN);
+            no_of_loglines += 1;
+        }
+        assert_eq!(no_of_loglines, NO_OF_THREADS * NO_OF_LOGLINES_PER_THREAD);
+    }
+}
+
+fn log_concurrently(mode: &str) {
+    let logger = Logger::try_with_str("info").unwrap();
+    let logger = match mode {
+        "duplicate" => logger
+            .log_to_file(FileSpec::default().directory(self::test_utils::dir()))
+            .duplicate_to_stderr(Duplicate::Info)
+            .format_for_stderr(payload_only),
+        "buffered" => logger
+            .log_to_stderr()
+            .write_mode(WriteMode::BufferDontFlush)
+            .format(payload_only),
+        _ => panic!("unknown mode {}", mode),
+    };
+    let handle = logger
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let payload = "x".repeat(PAYLOAD_LEN);
+    let mut worker_handles = Vec::with_capacity(NO_OF_THREADS + 1);
+    for thread_number in 0..NO_OF_THREADS {
+        let payload = payload.clone();
+        worker_handles.push(std::thread::spawn(move || {
+            for line_number in 0..NO_OF_LOGLINES_PER_THREAD {
+                info!("<{}-{}:{}>", thread_number, line_number, payload);
+            }
+        }));
+    }
+    worker_handles.push(std::thread::spawn(|| {
+        for _ in 0..NO_OF_LOGLINES_PER_THREAD {
+            eprintln!("interjection");
+        }
+    }));
+    for worker_handle in worker_handles {
+        worker_handle.join().unwrap();
+    }
+    handle.flush();
+}
+
+fn payload_only(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(w, "{}", record.args())
+}
@@ -0,0 +1,26 @@
+use flexi_logger::{systemd_format, DeferredNow};
+use log::Level;
+
+#[test]
+fn test_systemd_format() {
+    for (level, expected) in &[
+        (Level::Error, "<3>[my_module] text"),
+        (Level::Warn, "<4>[my_module] text"),
+        (Level::Info, "<6>[my_module] text"),
+        (Level::Debug, "<7>[my_module] text"),
+        (Level::Trace, "<7>[my_module] text"),
+    ] {
+        let mut buffer = Vec::<u8>::new();
+        systemd_format(
+            &mut buffer,
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("text"))
+                .level(*level)
+                .module_path(Some("my_module"))
+                .build(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), *expected);
+    }
+}
@@ -0,0 +1,55 @@
+use flexi_logger::{DeferredNow, Logger, TimestampConfig};
+use time::format_description;
+
+// The timestamp config is global, so the variants are checked one after the other
+// in a single test, by building loggers without installing them.
+#[test]
+fn test_timestamp_cache() {
+    // the cached rendering must not change the output
+    check(
+        TimestampConfig::default().cache_seconds(),
+        "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:6] \
+         [offset_hour sign:mandatory]:[offset_minute]",
+        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6]\
+         [offset_hour sign:mandatory]:[offset_minute]",
+    );
+    check(
+        TimestampConfig::default()
+            .rfc3339()
+            .utc()
+            .subsecond_digits(3)
+            .cache_seconds(),
+        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]\
+         [offset_hour sign:mandatory]:[offset_minute]",
+        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]\
+         [offset_hour sign:mandatory]:[offset_minute]",
+    );
+    check(
+        TimestampConfig::default()
+            .subsecond_digits(0)
+            .cache_seconds(),
+        "[year]-[month]-[day] [hour]:[minute]:[second] \
+         [offset_hour sign:mandatory]:[offset_minute]",
+        "[year]-[month]-[day]T[hour]:[minute]:[second]\
+         [offset_hour sign:mandatory]:[offset_minute]",
+    );
+}
+
+fn check(timestamp_config: TimestampConfig, text: &str, rfc3339: &str) {
+    Logger::try_with_str("info")
+        .unwrap()
+        .timestamp_config(timestamp_config)
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    let text = format_description::parse_owned::<1>(text).unwrap();
+    let rfc3339 = format_description::parse_owned::<1>(rfc3339).unwrap();
+
+    // covers some changes of the second
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_millis(1200) {
+        let mut now = DeferredNow::new();
+        assert_eq!(now.format_timestamp(), now.format(&text));
+        assert_eq!(now.format_rfc3339(), now.format(&rfc3339));
+        std::thread::sleep(std::time::Duration::from_micros(300));
+    }
+}
@@ -0,0 +1,94 @@
+use flexi_logger::{json_format, opt_format, DeferredNow, Logger, TimestampConfig};
+use log::*;
+use time::UtcOffset;
+
+// The timestamp config is global, so the variants are checked one after the other
+// in a single test, by building loggers without installing them.
+#[test]
+fn test_timestamp_config() {
+    // default: local time (or UTC), six subsecond digits
+    let text = format_with(TimestampConfig::default(), opt_format);
+    assert_text_timestamp(&text, 6);
+
+    // UTC, with milliseconds
+    let text = format_with(
+        TimestampConfig::default().utc().subsecond_digits(3),
+        opt_format,
+    );
+    assert_text_timestamp(&text, 3);
+    assert!(text.starts_with('[') && text[1..].split(']').next().unwrap().ends_with("+00:00"));
+
+    // RFC 3339, without subseconds, with a fixed offset, in all formats
+    let config = TimestampConfig::default()
+        .rfc3339()
+        .subsecond_digits(0)
+        .fixed_offset(UtcOffset::from_hms(5, 30, 0).unwrap());
+    let text = format_with(config.clone(), opt_format);
+    let timestamp = text[1..].split(']').next().unwrap();
+    assert_eq!(timestamp.len(), "2021-01-01T00:00:00+05:30".len());
+    assert_eq!(&timestamp[10..11], "T");
+    assert!(timestamp.ends_with("+05:30"));
+    let json = format_with(config, json_format);
+    assert!(json.starts_with(&format!("{{\"timestamp\":\"{}", &timestamp[..10])));
+    assert!(json.contains("+05:30\","));
+
+    // custom format description
+    let text = format_with(
+        TimestampConfig::default()
+            .utc()
+            .custom_format("[hour]h[minute]m")
+            .unwrap(),
+        opt_format,
+    );
+    let timestamp = text[1..].split(']').next().unwrap();
+    assert_eq!(timestamp.len(), "12h34m".len());
+    assert_eq!(&timestamp[2..3], "h");
+
+    // DeferredNow follows the config, too
+    Logger::try_with_str("info")
+        .unwrap()
+        .timestamp_config(TimestampConfig::default().utc())
+        .build()
+        .unwrap();
+    assert_eq!(DeferredNow::new().now().offset(), UtcOffset::UTC);
+
+    assert!(TimestampConfig::default()
+        .custom_format("[hour]h[minutes]m")
+        .is_err());
+}
+
+fn format_with(timestamp_config: TimestampConfig, format: flexi_logger::FormatFunction) -> String {
+    Logger::try_with_str("info")
+        .unwrap()
+        .timestamp_config(timestamp_config)
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let mut buffer = Vec::new();
+    format(
+        &mut buffer,
+        &mut DeferredNow::new(),
+        &Record::builder()
+            .args(format_args!("This is a message"))
+            .level(Level::Info)
+            .target("test_timestamp_config")
+            .file(Some("tests/test_timestamp_config.rs"))
+            .line(Some(1))
+            .build(),
+    )
+    .unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+fn assert_text_timestamp(text: &str, subsecond_digits: usize) {
+    // like [2021-01-01 00:00:00.123456 +01:00]
+    let timestamp = text[1..].split(']').next().unwrap();
+    assert_eq!(
+        timestamp.len(),
+        "2021-01-01 00:00:00. +01:00".len() + subsecond_digits,
+        "unexpected timestamp {}",
+        timestamp
+    );
+    assert_eq!(&timestamp[10..11], " ");
+    assert_eq!(&timestamp[19..20], ".");
+}
@@ -0,0 +1,48 @@
+#[cfg(feature = "tracing_bridge")]
+mod a {
+    use flexi_logger::{tracing_bridge::FlexiLoggerLayer, Logger};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_tracing_bridge() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let (logger, handle) = Logger::try_with_str("info, noisy=error")
+            .unwrap()
+            .log_to_io_writer(Box::new(SharedBuffer(Arc::clone(&output))))
+            .build()
+            .unwrap();
+
+        let subscriber = tracing_subscriber::registry().with(FlexiLoggerLayer::with_logger(logger));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!("an error");
+            tracing::info!(user = "alice", attempts = 3, "logged in");
+            tracing::debug!("a debug message - you must not see it!");
+            tracing::warn!(target: "noisy", "a warning you must not see!");
+            tracing::error!(target: "noisy", "an error from noisy");
+        });
+        handle.flush();
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "ERROR [test_tracing_bridge::a] an error",
+                "INFO [test_tracing_bridge::a] logged in user=alice attempts=3",
+                "ERROR [test_tracing_bridge::a] an error from noisy",
+            ]
+        );
+    }
+
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use flexi_logger::writers::{WebhookPayload, WebhookWriter};
+use flexi_logger::{FlexiLoggerError, Logger};
+use log::*;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+#[test]
+fn test_webhook_writer() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut reader = BufReader::new(stream.unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            let mut authorization = String::new();
+            let mut host = String::new();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header == "\r\n" {
+                    break;
+                }
+                let (name, value) = header.split_at(header.find(':').unwrap());
+                let value = value[1..].trim().to_string();
+                match name {
+                    "Content-Length" => content_length = value.parse().unwrap(),
+                    "Authorization" => authorization = value,
+                    "Host" => host = value,
+                    _ => {}
+                }
+            }
+            let mut body = vec![0_u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            sender
+                .send((
+                    request_line,
+                    host,
+                    authorization,
+                    String::from_utf8(body).unwrap(),
+                ))
+                .unwrap();
+        }
+    });
+
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .do_not_log()
+        .add_writer(
+            "Alert",
+            Box::new(
+                WebhookWriter::builder(format!("http://127.0.0.1:{}/hooks/logs?x=1", port))
+                    .format(|w, _now, record| write!(w, "{} {}", record.level(), record.args()))
+                    .header("Authorization", "Bearer secret")
+                    .min_level(Level::Warn)
+                    .batch_delay(Duration::from_millis(300))
+                    .min_interval(Duration::from_secs(60))
+                    .max_records(3)
+                    .try_build()
+                    .unwrap(),
+            ),
+        )
+        .also_send_to(&["Alert"], LevelFilter::Info)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    // a burst of errors becomes one post
+    info!("not posted");
+    for i in 0..10 {
+        error!("error \"{}\"", i);
+    }
+    warn!("a warning");
+    let (request_line, host, authorization, body) = receiver.recv().unwrap();
+    assert_eq!(request_line, "POST /hooks/logs?x=1 HTTP/1.1\r\n");
+    assert_eq!(host, format!("127.0.0.1:{}", port));
+    assert_eq!(authorization, "Bearer secret");
+    assert_eq!(
+        body,
+        r#"{"text":"ERROR error \"0\"\nERROR error \"1\"\nERROR error \"2\"\n... and 8 more records"}"#
+    );
+
+    // the next post waits for the minimum interval, or for the shutdown
+    error!("after the burst");
+    assert!(receiver.recv_timeout(Duration::from_secs(1)).is_err());
+    handle.shutdown();
+    let (_, _, _, body) = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body, r#"{"text":"ERROR after the burst"}"#);
+
+    // payload with JSON records
+    let (_, _, _, body) = {
+        let writer = WebhookWriter::builder(format!("http://127.0.0.1:{}/", port))
+            .payload(WebhookPayload::Records)
+            .try_build()
+            .unwrap();
+        flexi_logger::writers::LogWriter::write(
+            &writer,
+            &mut flexi_logger::DeferredNow::new(),
+            &Record::builder()
+                .args(format_args!("failed"))
+                .level(Level::Error)
+                .target("test")
+                .build(),
+        )
+        .unwrap();
+        drop(writer);
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap()
+    };
+    assert!(
+        body.starts_with(r#"{"records":[{"timestamp":""#),
+        "{}",
+        body
+    );
+    assert!(
+        body.ends_with(r#""message":"failed"}],"omitted":0}"#),
+        "{}",
+        body
+    );
+
+    assert!(matches!(
+        WebhookWriter::builder("https://hooks.example.com/x").try_build(),
+        Err(FlexiLoggerError::WebhookUrl(_))
+    ));
+    assert!(matches!(
+        WebhookWriter::builder("hooks.example.com/x").try_build(),
+        Err(FlexiLoggerError::WebhookUrl(_))
+    ));
+}
@@ -0,0 +1,59 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FlexiLoggerError, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_writer_levels() {
+    let primary = Arc::new(Mutex::new(Vec::new()));
+    let audit = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(SinkWriter(Arc::clone(&primary))))
+        .add_writer("Audit", Box::new(SinkWriter(Arc::clone(&audit))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    debug!("primary 1");
+    debug!(target: "{Audit}", "audit 1");
+
+    handle
+        .set_writer_level("Audit", LevelFilter::Debug)
+        .unwrap();
+    debug!("primary 2");
+    debug!(target: "{Audit}", "audit 2");
+    trace!(target: "{Audit}", "audit 3");
+
+    handle
+        .set_writer_level("_Default", LevelFilter::Trace)
+        .unwrap();
+    trace!("primary 3");
+
+    handle.reset_writer_level("Audit").unwrap();
+    handle.reset_writer_level("_Default").unwrap();
+    debug!("primary 4");
+    debug!(target: "{Audit}", "audit 4");
+    info!("primary 5");
+    info!(target: "{Audit}", "audit 5");
+
+    assert!(matches!(
+        handle.set_writer_level("Unknown", LevelFilter::Debug),
+        Err(FlexiLoggerError::UnknownWriter(_))
+    ));
+    assert_eq!(*primary.lock().unwrap(), vec!["primary 3", "primary 5"]);
+    assert_eq!(*audit.lock().unwrap(), vec!["audit 2", "audit 5"]);
+}
+
+struct SinkWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for SinkWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0.lock().unwrap().push(record.args().to_string());
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> LevelFilter {
+        LevelFilter::Info
+    }
+}
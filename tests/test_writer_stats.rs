@@ -0,0 +1,34 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+const NO_OF_LOGLINES: u64 = 100;
+
+#[test]
+fn test_writer_stats() {
+    let directory = self::test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..NO_OF_LOGLINES {
+        info!("This is log line number {}", i);
+    }
+    logger.flush();
+
+    let stats = logger.writer_stats().unwrap();
+    assert_eq!(stats.records, NO_OF_LOGLINES);
+    assert_eq!(stats.dropped, 0);
+    assert_eq!(stats.io_errors, 0);
+
+    let files: Vec<std::fs::Metadata> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().metadata().unwrap())
+        .collect();
+    assert_eq!(stats.rotations as usize, files.len() - 1);
+    assert_eq!(stats.bytes, files.iter().map(std::fs::Metadata::len).sum::<u64>());
+}